@@ -0,0 +1,143 @@
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Read from the working directory unless `--config` names another file.
+const CONFIG_FILE: &str = "Antenna.toml";
+
+/// `wasm-pack --target`, deciding how the generated client loads the module.
+#[derive(Clone, Copy, PartialEq, Eq, Default, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Target {
+    Bundler,
+    #[default]
+    Web,
+    Nodejs,
+}
+
+impl Target {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Target::Bundler => "bundler",
+            Target::Web => "web",
+            Target::Nodejs => "nodejs",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Profile {
+    #[default]
+    Release,
+    Dev,
+}
+
+impl Profile {
+    pub fn as_flag(self) -> &'static str {
+        match self {
+            Profile::Release => "--release",
+            Profile::Dev => "--dev",
+        }
+    }
+}
+
+/// `Antenna.toml`. Paths are relative to the file, every key is optional.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub build: BuildConfig,
+    pub dev: DevConfig,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BuildConfig {
+    pub shared: Option<PathBuf>,
+    pub client: Option<PathBuf>,
+    pub out: Option<PathBuf>,
+    pub target: Option<Target>,
+    pub profile: Option<Profile>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DevConfig {
+    pub server: Option<PathBuf>,
+    pub serve: Option<PathBuf>,
+    pub port: Option<u16>,
+}
+
+/// Flags naming the crates and output, each overriding `[build]` in `Antenna.toml`.
+#[derive(clap::Args)]
+pub struct ProjectArgs {
+    /// Crate exporting the message types [default: ./shared]
+    #[arg(long)]
+    pub shared: Option<PathBuf>,
+
+    /// Crate with the `#[antenna_client]` wrapper [default: ./client]
+    #[arg(long)]
+    pub client: Option<PathBuf>,
+
+    /// Directory receiving the generated files [default: ./frontend/src/antenna]
+    #[arg(short, long)]
+    pub out: Option<PathBuf>,
+
+    /// Forwarded to `wasm-pack build --target` [default: web]
+    #[arg(long, value_enum)]
+    pub target: Option<Target>,
+}
+
+/// Crates and output of a project after applying flags, config and defaults.
+pub struct Project {
+    pub shared: PathBuf,
+    pub client: PathBuf,
+    pub out: PathBuf,
+    pub target: Target,
+}
+
+impl Config {
+    /// Loads `path`, or `Antenna.toml` when present, and resolves its paths against its directory.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let path = match path {
+            Some(path) => path,
+            None if Path::new(CONFIG_FILE).is_file() => Path::new(CONFIG_FILE),
+            None => return Ok(Self::default()),
+        };
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let mut config: Config =
+            toml::from_str(&text).with_context(|| format!("Invalid {}", path.display()))?;
+
+        let base = path.parent().unwrap_or(Path::new(""));
+        for dir in [
+            &mut config.build.shared,
+            &mut config.build.client,
+            &mut config.build.out,
+            &mut config.dev.server,
+            &mut config.dev.serve,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            *dir = base.join(&*dir);
+        }
+        Ok(config)
+    }
+
+    pub fn project(&self, args: ProjectArgs) -> Project {
+        let build = &self.build;
+        let pick = |arg: Option<PathBuf>, config: &Option<PathBuf>, default: &str| {
+            arg.or_else(|| config.clone())
+                .unwrap_or_else(|| PathBuf::from(default))
+        };
+        Project {
+            shared: pick(args.shared, &build.shared, "./shared"),
+            client: pick(args.client, &build.client, "./client"),
+            out: pick(args.out, &build.out, "./frontend/src/antenna"),
+            target: args.target.or(build.target).unwrap_or_default(),
+        }
+    }
+}
@@ -0,0 +1,426 @@
+use anyhow::{Context, Result};
+use colored::*;
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+use crate::config::{Profile, Project};
+use crate::output::{self, Staging};
+
+/// Edits arriving this soon after another are handled in the same rebuild.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+pub struct DevOptions {
+    pub project: Project,
+    pub server: PathBuf,
+    /// Directory served over HTTP, none when the frontend has its own dev server
+    pub serve: Option<PathBuf>,
+    pub port: u16,
+}
+
+/// Crates with edits since the last rebuild.
+#[derive(Default, Clone, Copy)]
+struct Changes {
+    shared: bool,
+    client: bool,
+    server: bool,
+}
+
+impl Changes {
+    const ALL: Changes = Changes {
+        shared: true,
+        client: true,
+        server: true,
+    };
+
+    fn any(self) -> bool {
+        self.shared || self.client || self.server
+    }
+}
+
+/// Builds everything once, then rebuilds only what depends on each edited crate until interrupted.
+/// Types and the wasm package follow `shared` and `client`, the server restarts after `shared` or
+/// `server` change.
+pub fn run(options: DevOptions) -> Result<()> {
+    let project = &options.project;
+    let canonical = |dir: &Path| {
+        fs::canonicalize(dir).with_context(|| format!("{} does not exist", dir.display()))
+    };
+    let shared_dir = canonical(&project.shared)?;
+    let client_dir = canonical(&project.client)?;
+    let server_dir = canonical(&options.server)?;
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    for dir in [&shared_dir, &client_dir, &server_dir] {
+        watcher.watch(&dir.join("src"), RecursiveMode::Recursive)?;
+        watcher.watch(&dir.join("Cargo.toml"), RecursiveMode::NonRecursive)?;
+    }
+
+    if let Some(serve) = &options.serve {
+        let listener = TcpListener::bind(("127.0.0.1", options.port))
+            .with_context(|| format!("Failed to listen on port {}", options.port))?;
+        serve_dir(listener, serve.clone());
+        println!(
+            "{} http://localhost:{} {}",
+            "🌐 Serving".green().bold(),
+            options.port,
+            serve.display()
+        );
+    }
+
+    // Steps write into the staging directory, which is synced to the output once it holds a
+    // complete build, so a failed step never removes the files of the last good one.
+    let staging = Staging::new()?;
+    let mut types_built = false;
+    let mut server = None;
+    let mut changes = Changes::ALL;
+    loop {
+        if changes.shared {
+            println!("{}", "📦 Generating TypeScript definitions...".cyan());
+            types_built = report(crate::type_export::run(&project.shared, &staging.types()));
+        }
+        if changes.shared || changes.client {
+            println!("{}", "📦 Compiling WebAssembly...".cyan());
+            let wasm_built = report(
+                crate::run_wasm_pack(
+                    &project.client,
+                    &staging.wasm(),
+                    project.target,
+                    Profile::Dev,
+                )
+                .and_then(|()| {
+                    crate::ts_client::generate(
+                        staging.path(),
+                        &staging.wasm(),
+                        &staging.types(),
+                        project.target,
+                    )
+                })
+                .map(drop),
+            );
+            if types_built && wasm_built {
+                report(sync(project, staging.path()));
+            }
+        }
+        if changes.shared || changes.server {
+            println!("{}", "📦 Building the server...".cyan());
+            // The running server stays up when the new one does not build
+            if let Some(executable) = report_value(build_server(&server_dir)) {
+                if let Some(mut old) = server.take() {
+                    stop(&mut old);
+                }
+                server = report_value(start_server(&executable, &server_dir));
+            }
+        }
+        println!("{}", "👀 Watching for changes...".green().bold());
+
+        changes = wait_for_changes(&rx, &shared_dir, &client_dir, &server_dir)?;
+    }
+}
+
+fn sync(project: &Project, staging: &Path) -> Result<()> {
+    let inputs = output::hash_inputs(
+        &[&project.shared, &project.client],
+        &format!("{} {}", project.target.as_str(), Profile::Dev.as_flag()),
+    )?;
+    let report = output::sync(staging, &project.out, &inputs)?;
+    println!(
+        "   📝 {} written, {} unchanged, {} removed",
+        report.written, report.unchanged, report.removed
+    );
+    Ok(())
+}
+
+/// Blocks until a crate is edited, then collects edits until they stop for [`DEBOUNCE`].
+fn wait_for_changes(
+    rx: &Receiver<notify::Result<notify::Event>>,
+    shared_dir: &Path,
+    client_dir: &Path,
+    server_dir: &Path,
+) -> Result<Changes> {
+    let mut changes = Changes::default();
+    let mut event = rx.recv().context("The file watcher stopped")?;
+    loop {
+        match event {
+            Ok(event) if !matches!(event.kind, EventKind::Access(_)) => {
+                for path in &event.paths {
+                    changes.shared |= path.starts_with(shared_dir);
+                    changes.client |= path.starts_with(client_dir);
+                    changes.server |= path.starts_with(server_dir);
+                }
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("{} {}", "⚠️  Watch error:".yellow(), e),
+        }
+        match rx.recv_timeout(DEBOUNCE) {
+            Ok(next) => event = next,
+            Err(_) if changes.any() => return Ok(changes),
+            Err(_) => event = rx.recv().context("The file watcher stopped")?,
+        }
+    }
+}
+
+/// Builds the server crate, returning the binary cargo reports.
+fn build_server(server_dir: &Path) -> Result<PathBuf> {
+    let output = Command::new("cargo")
+        .args(["build", "--message-format=json-render-diagnostics"])
+        .current_dir(server_dir)
+        .stderr(Stdio::inherit())
+        .output()
+        .context("Failed to run cargo build for the server")?;
+    if !output.status.success() {
+        anyhow::bail!("Server build failed");
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter(|message| message["reason"] == "compiler-artifact")
+        .filter_map(|message| message["executable"].as_str().map(PathBuf::from))
+        .next_back()
+        .context("The server crate has no binary")
+}
+
+/// Runs the server binary from the crate directory, like `cargo run` would.
+fn start_server(executable: &Path, server_dir: &Path) -> Result<Child> {
+    println!("{} {}", "🚀 Starting".green().bold(), executable.display());
+    Command::new(executable)
+        .current_dir(server_dir)
+        .spawn()
+        .with_context(|| format!("Failed to start {}", executable.display()))
+}
+
+fn stop(server: &mut Child) {
+    let _ = server.kill();
+    let _ = server.wait();
+}
+
+/// Prints a failed step and keeps watching, so the next edit can fix it.
+fn report(result: Result<()>) -> bool {
+    report_value(result).is_some()
+}
+
+fn report_value<T>(result: Result<T>) -> Option<T> {
+    result.map_err(|e| eprintln!("{} {:#}", "❌".red(), e)).ok()
+}
+
+/// Serves the files of `dir` to connections of `listener` from a background thread.
+fn serve_dir(listener: TcpListener, dir: PathBuf) {
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let dir = dir.clone();
+            thread::spawn(move || {
+                let _ = respond(stream, &dir);
+            });
+        }
+    });
+}
+
+fn respond(mut stream: TcpStream, dir: &Path) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut parts = request.split_whitespace();
+    let (method, target) = (
+        parts.next().unwrap_or_default(),
+        parts.next().unwrap_or("/"),
+    );
+    if method != "GET" {
+        return write_response(&mut stream, "405 Method Not Allowed", "text/plain", b"");
+    }
+
+    let path = target.split(['?', '#']).next().unwrap_or_default();
+    let relative = Path::new(path.trim_start_matches('/'));
+    if relative
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_)))
+    {
+        return write_response(&mut stream, "400 Bad Request", "text/plain", b"");
+    }
+    let mut file = dir.join(relative);
+    if file.is_dir() {
+        file.push("index.html");
+    }
+
+    match fs::read(&file) {
+        Ok(body) => write_response(&mut stream, "200 OK", content_type(&file), &body),
+        Err(_) => write_response(&mut stream, "404 Not Found", "text/plain", b"Not found"),
+    }
+}
+
+fn write_response(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &[u8],
+) -> std::io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    stream.write_all(body)
+}
+
+/// `application/wasm` matters most, browsers refuse to stream-compile anything else.
+fn content_type(file: &Path) -> &'static str {
+    match file
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default()
+    {
+        "html" => "text/html; charset=utf-8",
+        "js" | "mjs" => "text/javascript",
+        "css" => "text/css",
+        "json" | "map" => "application/json",
+        "wasm" => "application/wasm",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "ico" => "image/x-icon",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{AccessKind, CreateKind, ModifyKind};
+    use std::io::Read;
+    use std::time::Instant;
+    use tempfile::TempDir;
+
+    /// Sends `request_line` to a server of `dir` and returns the whole response.
+    fn request(dir: &Path, request_line: &str) -> String {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let address = listener.local_addr().unwrap();
+        serve_dir(listener, dir.to_path_buf());
+
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(stream, "{}\r\nHost: localhost\r\n\r\n", request_line).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    fn event(kind: EventKind, path: &Path) -> notify::Result<notify::Event> {
+        Ok(notify::Event::new(kind).add_path(path.to_path_buf()))
+    }
+
+    #[test]
+    fn test_respond_serves_files_and_index() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join("pkg")).unwrap();
+        fs::write(dir.path().join("index.html"), "<h1>hi</h1>").unwrap();
+        fs::write(dir.path().join("pkg/app_bg.wasm"), "wasm").unwrap();
+
+        let index = request(dir.path(), "GET /?v=1 HTTP/1.1");
+        assert!(index.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(index.contains("Content-Type: text/html; charset=utf-8\r\n"));
+        assert!(index.ends_with("\r\n\r\n<h1>hi</h1>"));
+
+        let wasm = request(dir.path(), "GET /pkg/app_bg.wasm HTTP/1.1");
+        assert!(wasm.contains("Content-Type: application/wasm\r\n"));
+        assert!(request(dir.path(), "GET /missing.js HTTP/1.1").starts_with("HTTP/1.1 404"));
+        assert!(request(dir.path(), "POST / HTTP/1.1").starts_with("HTTP/1.1 405"));
+    }
+
+    #[test]
+    fn test_respond_rejects_paths_outside_dir() {
+        let root = TempDir::new().unwrap();
+        let dir = root.path().join("public");
+        fs::create_dir(&dir).unwrap();
+        fs::write(root.path().join("secret.txt"), "secret").unwrap();
+
+        for target in [
+            "/../secret.txt",
+            "/./../secret.txt",
+            "/pkg/../../secret.txt",
+        ] {
+            let response = request(&dir, &format!("GET {} HTTP/1.1", target));
+            assert!(
+                response.starts_with("HTTP/1.1 400"),
+                "{}: {}",
+                target,
+                response
+            );
+        }
+        // Leading slashes are stripped, the path stays inside the directory
+        let response = request(&dir, "GET //secret.txt HTTP/1.1");
+        assert!(response.starts_with("HTTP/1.1 404"));
+    }
+
+    #[test]
+    fn test_content_type() {
+        assert_eq!(content_type(Path::new("app_bg.wasm")), "application/wasm");
+        assert_eq!(content_type(Path::new("index.js")), "text/javascript");
+        assert_eq!(content_type(Path::new("a/b/style.css")), "text/css");
+        assert_eq!(
+            content_type(Path::new("logo.SVG")),
+            "application/octet-stream"
+        );
+        assert_eq!(
+            content_type(Path::new("LICENSE")),
+            "application/octet-stream"
+        );
+    }
+
+    #[test]
+    fn test_wait_for_changes_collects_edits_until_quiet() {
+        let (shared, client, server) = (
+            Path::new("/p/shared"),
+            Path::new("/p/client"),
+            Path::new("/p/server"),
+        );
+        let (tx, rx) = mpsc::channel();
+        tx.send(event(
+            EventKind::Access(AccessKind::Any),
+            &client.join("src/lib.rs"),
+        ))
+        .unwrap();
+        tx.send(event(
+            EventKind::Modify(ModifyKind::Any),
+            &shared.join("src/lib.rs"),
+        ))
+        .unwrap();
+        let sender = thread::spawn(move || {
+            thread::sleep(DEBOUNCE / 3);
+            tx.send(event(
+                EventKind::Create(CreateKind::File),
+                &server.join("src/room.rs"),
+            ))
+            .unwrap();
+            // Keep the channel open past the debounce
+            thread::sleep(DEBOUNCE * 3);
+            tx.send(event(
+                EventKind::Modify(ModifyKind::Any),
+                &client.join("src/lib.rs"),
+            ))
+            .unwrap();
+        });
+
+        let started = Instant::now();
+        let changes = wait_for_changes(&rx, shared, client, server).unwrap();
+        assert!(changes.shared && changes.server && !changes.client);
+        assert!(started.elapsed() >= DEBOUNCE + DEBOUNCE / 3);
+        assert!(started.elapsed() < DEBOUNCE * 3);
+
+        let changes = wait_for_changes(&rx, shared, client, server).unwrap();
+        assert!(changes.client && !changes.shared && !changes.server);
+        sender.join().unwrap();
+    }
+}
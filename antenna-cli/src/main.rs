@@ -2,9 +2,21 @@ use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
 use colored::*;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 
+mod config;
+mod dev;
+mod new_project;
+mod output;
+mod ts_client;
+mod type_export;
+
+use config::{Config, Profile, Project, ProjectArgs, Target};
+use dev::DevOptions;
+use new_project::{NewOptions, Template};
+use output::Staging;
+
 #[derive(Parser)]
 #[command(name = "cargo-antenna")]
 #[command(bin_name = "cargo-antenna")]
@@ -14,6 +26,10 @@ enum Cli {
 
 #[derive(clap::Args)]
 struct AntennaArgs {
+    /// Project config to read instead of `./Antenna.toml`
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -21,14 +37,56 @@ struct AntennaArgs {
 #[derive(Subcommand)]
 enum Commands {
     Build {
-        #[arg(long, default_value = "./shared")]
-        shared: String,
+        #[command(flatten)]
+        project: ProjectArgs,
+
+        /// Optimized wasm, the default
+        #[arg(long, conflicts_with = "dev")]
+        release: bool,
+
+        /// Unoptimized wasm with debug assertions
+        #[arg(long)]
+        dev: bool,
+
+        /// Fail instead of writing when the output is not current
+        #[arg(long)]
+        check: bool,
+
+        /// Rebuild even when the crates did not change
+        #[arg(long)]
+        force: bool,
+    },
+    /// Builds, then rebuilds on every change and restarts the server
+    Dev {
+        #[command(flatten)]
+        project: ProjectArgs,
+
+        /// Server crate to run [default: ./server]
+        #[arg(long)]
+        server: Option<PathBuf>,
+
+        /// Directory served over HTTP, e.g. the frontend build output `./frontend/dist`
+        #[arg(long)]
+        serve: Option<PathBuf>,
+
+        /// Port `--serve` listens on [default: 8080]
+        #[arg(short, long)]
+        port: Option<u16>,
+    },
+    /// Creates a project with shared, server, wasm-lib and frontend crates
+    New {
+        name: String,
 
-        #[arg(long, default_value = "./client")]
-        client: String,
+        #[arg(short, long, value_enum)]
+        template: Option<Template>,
 
-        #[arg(short, long, default_value = "./frontend/src/antenna")]
-        out: String,
+        /// Directory of the `antenna` crate the project depends on
+        #[arg(long)]
+        antenna_path: Option<PathBuf>,
+
+        /// Use the defaults instead of asking
+        #[arg(short, long)]
+        yes: bool,
     },
 }
 
@@ -37,58 +95,141 @@ fn main() -> Result<()> {
 
     match args.command {
         Commands::Build {
-            shared,
-            client,
-            out,
+            project,
+            release,
+            dev,
+            check,
+            force,
         } => {
-            println!("{}", "🚀 Starting Antenna Build...".green().bold());
-
-            let out_path = Path::new(&out);
-            let types_path = out_path.join("types");
-            let wasm_path = out_path.join("wasm");
-
-            if out_path.exists() {
-                fs::remove_dir_all(out_path)?;
-            }
-            fs::create_dir_all(&types_path)?;
-            fs::create_dir_all(&wasm_path)?;
-
-            println!("{}", "📦 Generating TypeScript definitions...".cyan());
-            run_type_gen(&shared, &types_path)?;
-
-            println!("{}", "📦 Compiling WebAssembly...".cyan());
-            run_wasm_pack(&client, &wasm_path)?;
-
-            println!("{}", "✨ Build completed successfully!".green().bold());
-            println!("   📂 Types: {}", types_path.display());
-            println!("   📂 WASM:  {}", wasm_path.display());
+            let config = Config::load(args.config.as_deref())?;
+            let profile = match (release, dev) {
+                (true, _) => Profile::Release,
+                (_, true) => Profile::Dev,
+                _ => config.build.profile.unwrap_or_default(),
+            };
+            build(&config.project(project), profile, check, force)?;
+        }
+        Commands::Dev {
+            project,
+            server,
+            serve,
+            port,
+        } => {
+            let config = Config::load(args.config.as_deref())?;
+            println!("{}", "🚀 Starting Antenna Dev...".green().bold());
+            dev::run(DevOptions {
+                server: server
+                    .or(config.dev.server.clone())
+                    .unwrap_or_else(|| PathBuf::from("./server")),
+                serve: serve.or(config.dev.serve.clone()),
+                port: port.or(config.dev.port).unwrap_or(8080),
+                project: config.project(project),
+            })?;
+        }
+        Commands::New {
+            name,
+            template,
+            antenna_path,
+            yes,
+        } => {
+            let root = new_project::run(NewOptions {
+                name,
+                template,
+                antenna_path,
+                yes,
+            })?;
+
+            println!(
+                "{} {}",
+                "✨ Created".green().bold(),
+                root.display().to_string().bold()
+            );
+            println!("   cd {}", root.display());
+            println!("   cargo run -p server");
+            println!("   cargo antenna build");
+            println!("   cd frontend && npm install && npm run dev");
         }
     }
 
     Ok(())
 }
 
-fn run_type_gen(shared_path: &str, out_dir: &Path) -> Result<()> {
-    let out_abs = fs::canonicalize(out_dir).unwrap_or(out_dir.to_path_buf());
-    let status = Command::new("cargo")
-        .arg("test")
-        .env("TS_RS_EXPORT_DIR", out_abs)
-        .current_dir(shared_path)
-        .stdout(Stdio::inherit())
-        .stderr(Stdio::inherit())
-        .status()
-        .context("Failed to run cargo test for type generation")?;
+/// Builds into a staging directory, then replaces only the output files that changed, or with
+/// `check` only compares them.
+fn build(project: &Project, profile: Profile, check: bool, force: bool) -> Result<()> {
+    println!("{}", "🚀 Starting Antenna Build...".green().bold());
+
+    let out_path = project.out.as_path();
+    let inputs = output::hash_inputs(
+        &[&project.shared, &project.client],
+        &format!("{} {}", project.target.as_str(), profile.as_flag()),
+    )?;
+    if !check && !force && output::up_to_date(out_path, &inputs)? {
+        println!(
+            "{}",
+            "✨ Up to date, nothing changed since the last build"
+                .green()
+                .bold()
+        );
+        return Ok(());
+    }
 
-    if !status.success() {
-        anyhow::bail!("Type generation failed");
+    let staging = Staging::new()?;
+
+    println!("{}", "📦 Generating TypeScript definitions...".cyan());
+    type_export::run(&project.shared, &staging.types())?;
+
+    println!("{}", "📦 Compiling WebAssembly...".cyan());
+    run_wasm_pack(&project.client, &staging.wasm(), project.target, profile)?;
+
+    println!("{}", "📦 Generating the TypeScript client...".cyan());
+    ts_client::generate(
+        staging.path(),
+        &staging.wasm(),
+        &staging.types(),
+        project.target,
+    )?;
+
+    if check {
+        let outdated = output::check(staging.path(), out_path)?;
+        if !outdated.is_empty() {
+            anyhow::bail!(
+                "{} is not current, run `cargo antenna build`:\n  {}",
+                out_path.display(),
+                outdated.join("\n  ")
+            );
+        }
+        println!("{}", "✨ Generated files are current".green().bold());
+        return Ok(());
     }
+
+    let report = output::sync(staging.path(), out_path, &inputs)?;
+    println!("{}", "✨ Build completed successfully!".green().bold());
+    println!(
+        "   📝 {} written, {} unchanged, {} removed",
+        report.written, report.unchanged, report.removed
+    );
+    println!("   📂 Types: {}", out_path.join("types").display());
+    println!("   📂 WASM:  {}", out_path.join("wasm").display());
+    println!("   📄 Client: {}", out_path.join("index.ts").display());
     Ok(())
 }
 
-fn run_wasm_pack(client_path: &str, out_dir: &Path) -> Result<()> {
+fn run_wasm_pack(
+    client_path: &Path,
+    out_dir: &Path,
+    target: Target,
+    profile: Profile,
+) -> Result<()> {
     let out_abs = fs::canonicalize(out_dir).unwrap_or(out_dir.to_path_buf());
     let status = Command::new("wasm-pack")
-        .args(["build", "--target", "web", "--out-dir"])
+        .args([
+            "build",
+            profile.as_flag(),
+            "--target",
+            target.as_str(),
+            "--out-dir",
+        ])
         .arg(out_abs)
         .current_dir(client_path)
         .stdout(Stdio::inherit())
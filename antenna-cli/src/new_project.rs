@@ -0,0 +1,182 @@
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use dialoguer::{Input, Select};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Room logic a new project starts with.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Template {
+    /// Text chat relaying messages to everyone in the room
+    Chat,
+    /// Players moving on a canvas, simulated by the room at a fixed tick rate
+    Game,
+    /// Chat with presence and microphone audio relayed between peers
+    Voice,
+}
+
+impl Template {
+    const ALL: [Template; 3] = [Template::Chat, Template::Game, Template::Voice];
+
+    fn describe(self) -> &'static str {
+        match self {
+            Template::Chat => "chat  - text chat room",
+            Template::Game => "game  - room simulating players at a fixed tick rate",
+            Template::Voice => "voice - chat room with presence and voice calls",
+        }
+    }
+
+    /// Files differing between templates, by their path in the project.
+    fn files(self) -> [(&'static str, &'static str); 4] {
+        macro_rules! template {
+            ($dir:literal) => {
+                [
+                    (
+                        "shared/src/lib.rs",
+                        include_str!(concat!("../templates/new/", $dir, "/shared/src/lib.rs")),
+                    ),
+                    (
+                        "server/src/main.rs",
+                        include_str!(concat!("../templates/new/", $dir, "/server/src/main.rs")),
+                    ),
+                    (
+                        "wasm-lib/src/lib.rs",
+                        include_str!(concat!("../templates/new/", $dir, "/wasm-lib/src/lib.rs")),
+                    ),
+                    (
+                        "frontend/src/main.ts",
+                        include_str!(concat!("../templates/new/", $dir, "/frontend/src/main.ts")),
+                    ),
+                ]
+            };
+        }
+
+        match self {
+            Template::Chat => template!("chat"),
+            Template::Game => template!("game"),
+            Template::Voice => template!("voice"),
+        }
+    }
+}
+
+/// Files every template shares, by their path in the project.
+const COMMON_FILES: [(&str, &str); 10] = [
+    (
+        "Cargo.toml",
+        include_str!("../templates/new/Cargo.toml.tmpl"),
+    ),
+    (
+        "Antenna.toml",
+        include_str!("../templates/new/Antenna.toml"),
+    ),
+    (".gitignore", include_str!("../templates/new/gitignore")),
+    ("README.md", include_str!("../templates/new/README.md")),
+    (
+        "shared/Cargo.toml",
+        include_str!("../templates/new/shared/Cargo.toml.tmpl"),
+    ),
+    (
+        "server/Cargo.toml",
+        include_str!("../templates/new/server/Cargo.toml.tmpl"),
+    ),
+    (
+        "wasm-lib/Cargo.toml",
+        include_str!("../templates/new/wasm-lib/Cargo.toml.tmpl"),
+    ),
+    (
+        "frontend/package.json",
+        include_str!("../templates/new/frontend/package.json"),
+    ),
+    (
+        "frontend/tsconfig.json",
+        include_str!("../templates/new/frontend/tsconfig.json"),
+    ),
+    (
+        "frontend/index.html",
+        include_str!("../templates/new/frontend/index.html"),
+    ),
+];
+
+pub struct NewOptions {
+    pub name: String,
+    pub template: Option<Template>,
+    pub antenna_path: Option<PathBuf>,
+    /// Take defaults instead of prompting, for scripts
+    pub yes: bool,
+}
+
+/// Creates the `shared` / `server` / `wasm-lib` / `frontend` layout of
+/// `examples/simple-group-chat` in a new directory, depending on a local `antenna` checkout.
+pub fn run(options: NewOptions) -> Result<PathBuf> {
+    let root = PathBuf::from(&options.name);
+    if root.exists() && fs::read_dir(&root)?.next().is_some() {
+        anyhow::bail!("{} already exists and is not empty", root.display());
+    }
+    let name = root
+        .file_name()
+        .and_then(|name| name.to_str())
+        .context("The project name must end in a directory name")?
+        .to_string();
+
+    let template = match options.template {
+        Some(template) => template,
+        None if options.yes => Template::Chat,
+        None => {
+            let items: Vec<&str> = Template::ALL.iter().map(|t| t.describe()).collect();
+            let index = Select::new()
+                .with_prompt("Template")
+                .items(&items)
+                .default(0)
+                .interact()?;
+            Template::ALL[index]
+        }
+    };
+    let antenna_path = resolve_antenna_path(options.antenna_path, options.yes)?;
+
+    let placeholders = [
+        ("{{name}}", name.as_str()),
+        (
+            "{{antenna_path}}",
+            antenna_path.to_str().unwrap_or_default(),
+        ),
+    ];
+    for (path, content) in COMMON_FILES.iter().chain(template.files().iter()) {
+        let content = placeholders
+            .iter()
+            .fold(content.to_string(), |content, (key, value)| {
+                content.replace(key, value)
+            });
+        let target = root.join(path);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&target, content)
+            .with_context(|| format!("Failed to write {}", target.display()))?;
+    }
+    Ok(root)
+}
+
+/// The `antenna` crate directory, checked to contain its manifest. Defaults to the checkout this
+/// CLI was built from, so new projects build without fetching Antenna.
+fn resolve_antenna_path(path: Option<PathBuf>, yes: bool) -> Result<PathBuf> {
+    let bundled = Path::new(env!("CARGO_MANIFEST_DIR")).join("../antenna");
+    let path = match path {
+        Some(path) => path,
+        None if yes => bundled,
+        None => {
+            let mut input = Input::<String>::new().with_prompt("Path to the antenna crate");
+            if bundled.exists() {
+                input = input.default(bundled.display().to_string());
+            }
+            PathBuf::from(input.interact_text()?)
+        }
+    };
+
+    if !path.join("Cargo.toml").is_file() {
+        anyhow::bail!(
+            "{} does not contain the antenna crate, pass --antenna-path",
+            path.display()
+        );
+    }
+    Ok(fs::canonicalize(path)?)
+}
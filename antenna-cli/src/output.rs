@@ -0,0 +1,295 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::{env, process};
+use xxhash_rust::xxh3::Xxh3;
+
+/// Lists what the CLI wrote into the output directory; nothing else there is touched.
+const MANIFEST_FILE: &str = ".antenna-manifest.json";
+
+/// Directories never hashed as build inputs.
+const IGNORED_DIRS: [&str; 3] = ["target", "node_modules", "pkg"];
+
+#[derive(Default, Serialize, Deserialize)]
+struct Manifest {
+    /// Hash of the crates and options the files were generated from
+    inputs: String,
+    /// Hash of every generated file by its `/`-separated path in the output directory
+    files: BTreeMap<String, String>,
+}
+
+impl Manifest {
+    fn load(out: &Path) -> Result<Self> {
+        let path = out.join(MANIFEST_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let text = fs::read_to_string(&path)?;
+        let manifest: Self =
+            serde_json::from_str(&text).with_context(|| format!("Invalid {}", path.display()))?;
+        // Keys name files to overwrite and delete, so they must stay inside the output directory
+        if let Some(key) = manifest.files.keys().find(|key| !is_relative_file(key)) {
+            anyhow::bail!(
+                "{} lists {:?} outside the output directory",
+                path.display(),
+                key
+            );
+        }
+        Ok(manifest)
+    }
+}
+
+/// Scratch directory the build steps write into before the result is compared with the output.
+pub struct Staging {
+    dir: PathBuf,
+}
+
+impl Staging {
+    pub fn new() -> Result<Self> {
+        let dir = env::temp_dir().join(format!("antenna-build-{}", process::id()));
+        if dir.exists() {
+            fs::remove_dir_all(&dir)?;
+        }
+        fs::create_dir_all(dir.join("types"))?;
+        fs::create_dir_all(dir.join("wasm"))?;
+        Ok(Self { dir })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn types(&self) -> PathBuf {
+        self.dir.join("types")
+    }
+
+    pub fn wasm(&self) -> PathBuf {
+        self.dir.join("wasm")
+    }
+}
+
+impl Drop for Staging {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// What [`sync`] did to the output directory.
+#[derive(Default)]
+pub struct SyncReport {
+    pub written: usize,
+    pub unchanged: usize,
+    pub removed: usize,
+}
+
+/// Hash of every file in the crates outside [`IGNORED_DIRS`] and hidden directories, plus
+/// `options`. Path dependencies outside the crates are not covered, `--force` rebuilds anyway.
+pub fn hash_inputs(crates: &[&Path], options: &str) -> Result<String> {
+    let mut hasher = Xxh3::new();
+    hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+    hasher.update(options.as_bytes());
+    for dir in crates {
+        let mut files = Vec::new();
+        collect_files(dir, &mut files, true)?;
+        for file in files {
+            hasher.update(relative(dir, &file).as_bytes());
+            hasher.update(&fs::read(&file)?);
+        }
+    }
+    Ok(format!("{:016x}", hasher.digest()))
+}
+
+/// Whether `out` still holds every file generated from `inputs`, unmodified.
+pub fn up_to_date(out: &Path, inputs: &str) -> Result<bool> {
+    let manifest = Manifest::load(out)?;
+    if manifest.inputs != inputs || manifest.files.is_empty() {
+        return Ok(false);
+    }
+    Ok(manifest
+        .files
+        .iter()
+        .all(|(path, hash)| hash_file(&out.join(path)).ok().as_ref() == Some(hash)))
+}
+
+/// Copies the staged files whose content differs into `out` and deletes files the previous
+/// build generated but this one did not. Files missing from the manifest are left alone.
+pub fn sync(staging: &Path, out: &Path, inputs: &str) -> Result<SyncReport> {
+    let previous = Manifest::load(out)?;
+    let mut manifest = Manifest {
+        inputs: inputs.to_string(),
+        files: BTreeMap::new(),
+    };
+    let mut report = SyncReport::default();
+
+    for (path, hash) in staged_files(staging)? {
+        let target = out.join(&path);
+        if hash_file(&target).ok().as_ref() == Some(&hash) {
+            report.unchanged += 1;
+        } else {
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(staging.join(&path), &target)
+                .with_context(|| format!("Failed to write {}", target.display()))?;
+            report.written += 1;
+        }
+        manifest.files.insert(path, hash);
+    }
+
+    for path in previous.files.keys() {
+        let stale = out.join(path);
+        if !manifest.files.contains_key(path) && stale.exists() {
+            fs::remove_file(&stale)?;
+            report.removed += 1;
+        }
+    }
+
+    fs::write(
+        out.join(MANIFEST_FILE),
+        serde_json::to_string_pretty(&manifest)?,
+    )?;
+    Ok(report)
+}
+
+/// Output files that differ from the staged build, are missing, or are stale leftovers.
+pub fn check(staging: &Path, out: &Path) -> Result<Vec<String>> {
+    let previous = Manifest::load(out)?;
+    let staged = staged_files(staging)?;
+    let mut outdated: Vec<String> = staged
+        .iter()
+        .filter(|(path, hash)| hash_file(&out.join(path)).ok().as_ref() != Some(hash))
+        .map(|(path, _)| path.clone())
+        .collect();
+    outdated.extend(
+        previous
+            .files
+            .keys()
+            .filter(|path| !staged.contains_key(*path) && out.join(path).exists())
+            .cloned(),
+    );
+    Ok(outdated)
+}
+
+fn staged_files(staging: &Path) -> Result<BTreeMap<String, String>> {
+    let mut files = Vec::new();
+    collect_files(staging, &mut files, false)?;
+    files
+        .into_iter()
+        .map(|file| Ok((relative(staging, &file), hash_file(&file)?)))
+        .collect()
+}
+
+fn hash_file(path: &Path) -> Result<String> {
+    let bytes = fs::read(path)?;
+    Ok(format!("{:016x}", xxhash_rust::xxh3::xxh3_64(&bytes)))
+}
+
+/// Files under `dir` in a stable order, skipping build and hidden directories when `skip_ignored`.
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>, skip_ignored: bool) -> Result<()> {
+    let mut entries = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read {}", dir.display()))?
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if skip_ignored && (name.starts_with('.') || IGNORED_DIRS.contains(&name.as_ref())) {
+                continue;
+            }
+            collect_files(&path, files, skip_ignored)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn is_relative_file(path: &str) -> bool {
+    let mut components = Path::new(path).components().peekable();
+    components.peek().is_some()
+        && components.all(|component| matches!(component, Component::Normal(_)))
+}
+
+fn relative(base: &Path, file: &Path) -> String {
+    file.strip_prefix(base)
+        .unwrap_or(file)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(dir: &Path, path: &str, content: &str) {
+        let file = dir.join(path);
+        fs::create_dir_all(file.parent().unwrap()).unwrap();
+        fs::write(file, content).unwrap();
+    }
+
+    #[test]
+    fn test_sync_writes_only_changed_files() {
+        let (staging, out) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        write(staging.path(), "client.ts", "client");
+        write(staging.path(), "types/Message.ts", "message");
+        let report = sync(staging.path(), out.path(), "a").unwrap();
+        assert_eq!((report.written, report.unchanged), (2, 0));
+
+        write(staging.path(), "types/Message.ts", "edited");
+        let report = sync(staging.path(), out.path(), "b").unwrap();
+        assert_eq!((report.written, report.unchanged), (1, 1));
+        let message = fs::read_to_string(out.path().join("types/Message.ts")).unwrap();
+        assert_eq!(message, "edited");
+        assert!(up_to_date(out.path(), "b").unwrap());
+        assert!(!up_to_date(out.path(), "a").unwrap());
+    }
+
+    #[test]
+    fn test_sync_removes_stale_files_only() {
+        let (staging, out) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        write(staging.path(), "client.ts", "client");
+        write(staging.path(), "types/Old.ts", "old");
+        sync(staging.path(), out.path(), "a").unwrap();
+        write(out.path(), "main.ts", "user code");
+
+        fs::remove_file(staging.path().join("types/Old.ts")).unwrap();
+        let report = sync(staging.path(), out.path(), "b").unwrap();
+        assert_eq!(report.removed, 1);
+        assert!(!out.path().join("types/Old.ts").exists());
+        assert!(out.path().join("client.ts").exists());
+        assert_eq!(
+            fs::read_to_string(out.path().join("main.ts")).unwrap(),
+            "user code"
+        );
+        assert!(check(staging.path(), out.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_manifest_rejects_paths_outside_output() {
+        let (staging, parent) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        let out = parent.path().join("out");
+        write(parent.path(), "secret.txt", "keep");
+        for key in ["../secret.txt", "/etc/passwd", "types/../../secret.txt", ""] {
+            let manifest = Manifest {
+                inputs: "a".to_string(),
+                files: BTreeMap::from([(key.to_string(), "0".to_string())]),
+            };
+            write(
+                &out,
+                MANIFEST_FILE,
+                &serde_json::to_string(&manifest).unwrap(),
+            );
+
+            assert!(sync(staging.path(), &out, "b").is_err(), "{:?}", key);
+            assert!(check(staging.path(), &out).is_err(), "{:?}", key);
+            assert!(parent.path().join("secret.txt").exists());
+        }
+    }
+}
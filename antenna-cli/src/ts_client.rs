@@ -0,0 +1,371 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::Target;
+
+const ANTENNA_TEMPLATE: &str = include_str!("../templates/ts/antenna.ts");
+const CLIENT_TEMPLATE: &str = include_str!("../templates/ts/client.ts");
+
+/// Marker `#[antenna_client]` leaves in the wasm-bindgen typings.
+const MANIFEST_PREFIX: &str = "// antenna-client: ";
+
+/// Types a wrapper struct was declared with in `#[antenna_client(...)]`.
+struct ClientSpec {
+    wrapper: String,
+    client_msg: String,
+    server_msg: String,
+    state: Option<String>,
+}
+
+impl ClientSpec {
+    fn parse(line: &str) -> Option<Self> {
+        let mut names = line.strip_prefix(MANIFEST_PREFIX)?.split_whitespace();
+        Some(Self {
+            wrapper: names.next()?.to_string(),
+            client_msg: names.next()?.to_string(),
+            server_msg: names.next()?.to_string(),
+            state: names.next().map(str::to_string),
+        })
+    }
+
+    /// `ChatWrapper` becomes `ChatClient`.
+    fn class_name(&self) -> String {
+        let base = self
+            .wrapper
+            .strip_suffix("Wrapper")
+            .or_else(|| self.wrapper.strip_suffix("Client"))
+            .filter(|base| !base.is_empty())
+            .unwrap_or(&self.wrapper);
+        format!("{}Client", base)
+    }
+
+    fn render(&self) -> String {
+        let name = self.class_name();
+        let (state_import, state_event, state_listener, state_method) = match &self.state {
+            Some(state) => (
+                format!("import type {{ {0} }} from './types/{0}';\n", state),
+                format!(
+                    "    /** The shared state after every snapshot and patch. */\n    state: [state: {}, room: string, version: number];\n",
+                    state
+                ),
+                "        client.on_state_patch((state, room, version) => this.emit('state', state, room, version));\n"
+                    .to_string(),
+                format!(
+                    "\n    /** Shared state of the constructor's room, `undefined` before its first snapshot. */\n    state(): {} | undefined {{\n        return this.connected().state_snapshot();\n    }}\n",
+                    state
+                ),
+            ),
+            None => Default::default(),
+        };
+
+        CLIENT_TEMPLATE
+            .replace("{{state_import}}", &state_import)
+            .replace("{{state_event}}", &state_event)
+            .replace("{{state_listener}}", &state_listener)
+            .replace("{{state_method}}", &state_method)
+            .replace("{{name}}", &name)
+            .replace("{{wrapper}}", &self.wrapper)
+            .replace("{{client_msg}}", &self.client_msg)
+            .replace("{{server_msg}}", &self.server_msg)
+    }
+}
+
+/// Writes a typed client class for every `#[antenna_client]` wrapper found in the wasm-pack
+/// output, the shared `antenna.ts` runtime and an `index.ts` re-exporting them with the types.
+pub fn generate(
+    out_dir: &Path,
+    wasm_dir: &Path,
+    types_dir: &Path,
+    target: Target,
+) -> Result<Vec<PathBuf>> {
+    let specs = find_clients(wasm_dir)?;
+    if specs.is_empty() {
+        anyhow::bail!(
+            "No #[antenna_client] wrapper found in {}",
+            wasm_dir.display()
+        );
+    }
+
+    let mut written = Vec::new();
+    let mut index = String::from("// Generated by `cargo antenna build`, do not edit.\n");
+    index.push_str("export * from './antenna';\n");
+
+    let runtime = out_dir.join("antenna.ts");
+    fs::write(&runtime, render_runtime(target))?;
+    written.push(runtime);
+
+    for spec in &specs {
+        let name = spec.class_name();
+        let path = out_dir.join(format!("{}.ts", name));
+        fs::write(&path, spec.render())?;
+        written.push(path);
+        index.push_str(&format!(
+            "export {{ {0}, type {0}Events }} from './{0}';\n",
+            name
+        ));
+    }
+
+    for ty in type_names(types_dir)? {
+        index.push_str(&format!("export type {{ {0} }} from './types/{0}';\n", ty));
+    }
+
+    let index_path = out_dir.join("index.ts");
+    fs::write(&index_path, index)?;
+    written.push(index_path);
+    Ok(written)
+}
+
+/// Only `--target web` exports an `init` to call, bundlers and Node.js load the module on import.
+fn render_runtime(target: Target) -> String {
+    let (import, init) = match target {
+        Target::Web => ("import init from './wasm';\n", "init()"),
+        Target::Bundler | Target::Nodejs => ("", "Promise.resolve()"),
+    };
+    ANTENNA_TEMPLATE
+        .replace("{{wasm_import}}", import)
+        .replace("{{wasm_init}}", init)
+}
+
+fn find_clients(wasm_dir: &Path) -> Result<Vec<ClientSpec>> {
+    let mut specs = Vec::new();
+    for entry in
+        fs::read_dir(wasm_dir).with_context(|| format!("Failed to read {}", wasm_dir.display()))?
+    {
+        let path = entry?.path();
+        let file_name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+        // `<crate>_bg.wasm.d.ts` only declares the raw exports
+        if !file_name.ends_with(".d.ts") || file_name.ends_with(".wasm.d.ts") {
+            continue;
+        }
+        let typings = fs::read_to_string(&path)?;
+        specs.extend(typings.lines().filter_map(ClientSpec::parse));
+    }
+    Ok(specs)
+}
+
+/// Types exported from the shared crate, sorted so the barrel is stable.
+fn type_names(types_dir: &Path) -> Result<Vec<String>> {
+    let mut names = Vec::new();
+    if !types_dir.exists() {
+        return Ok(names);
+    }
+    for entry in fs::read_dir(types_dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "ts") {
+            if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                names.push(stem.to_string());
+            }
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    const CHAT: &str = "// antenna-client: ChatWrapper ChatClientMsg ChatServerMsg";
+    const GAME: &str = "// antenna-client: GameClient GameInput GameUpdate GameState";
+
+    /// Typings wasm-bindgen generates for the `#[antenna_client]` wrappers above.
+    const WASM_TYPINGS: &str = r#"
+import type { ChatServerMsg } from '../types/ChatServerMsg';
+import type { GameUpdate } from '../types/GameUpdate';
+import type { GameState } from '../types/GameState';
+export default function init(): Promise<unknown>;
+type ErrorCallback = (error: { kind: string; description: string; fatal: boolean; room?: string; [field: string]: unknown }) => void;
+declare class Wrapper<E> {
+    free(): void;
+    on_event(cb: (event: E, room: string) => void): void;
+    send_client_message(msg: any): void;
+    on_connect(cb: (room: string) => void): void;
+    on_presence(cb: (event: { kind: "joined" | "left"; peer: string }, room: string) => void): void;
+    on_track(cb: Function): void;
+    on_error(cb: ErrorCallback): void;
+    on_match(cb: (room: string, queue: string) => void): void;
+    matchmake(queue: string, attributes: any): void;
+    cancel_matchmake(queue?: string | null): void;
+    leave(): void;
+    join_room(room: string): void;
+    join_room_with_metadata(room: string, metadata: any): void;
+    wait_position(room: string): number | undefined;
+    leave_room(room: string): void;
+    rooms(): string[];
+    add_track(track: MediaStreamTrack, stream: MediaStream): void;
+}
+export class ChatWrapper extends Wrapper<ChatServerMsg> {
+    constructor(url: string, room: string);
+}
+export class GameClient extends Wrapper<GameUpdate> {
+    constructor(url: string, room: string);
+    state_snapshot(): any;
+    on_state_patch(cb: (state: GameState, room: string, version: number) => void): void;
+}
+"#;
+
+    /// A wasm-pack output and exported types for [`CHAT`] and [`GAME`].
+    fn project() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        let (wasm, types) = (dir.path().join("wasm"), dir.path().join("types"));
+        fs::create_dir_all(&wasm).unwrap();
+        fs::create_dir_all(&types).unwrap();
+        fs::write(
+            wasm.join("index.d.ts"),
+            format!("{}\n{}\n{}", CHAT, GAME, WASM_TYPINGS),
+        )
+        .unwrap();
+        // Declares the raw exports only, markers in it are not wrappers
+        fs::write(wasm.join("index_bg.wasm.d.ts"), CHAT).unwrap();
+        for (name, ty) in [
+            ("ChatClientMsg", "{ text: string }"),
+            ("ChatServerMsg", "{ text: string; author: string }"),
+            ("GameInput", "\"Jump\" | { Move: [number, number] }"),
+            ("GameUpdate", "{ tick: number }"),
+            ("GameState", "{ players: Map<string, number> }"),
+        ] {
+            fs::write(
+                types.join(format!("{}.ts", name)),
+                format!("export type {} = {};\n", name, ty),
+            )
+            .unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_parse_client_spec() {
+        let chat = ClientSpec::parse(CHAT).unwrap();
+        assert_eq!(
+            (
+                chat.wrapper.as_str(),
+                chat.client_msg.as_str(),
+                chat.server_msg.as_str()
+            ),
+            ("ChatWrapper", "ChatClientMsg", "ChatServerMsg")
+        );
+        assert_eq!(chat.state, None);
+        assert_eq!(
+            ClientSpec::parse(GAME).unwrap().state.as_deref(),
+            Some("GameState")
+        );
+
+        assert!(ClientSpec::parse("// antenna-client: ChatWrapper ChatClientMsg").is_none());
+        assert!(ClientSpec::parse("export class ChatWrapper {").is_none());
+    }
+
+    #[test]
+    fn test_class_name() {
+        let name = |wrapper: &str| {
+            ClientSpec::parse(&format!("{}{} A B", MANIFEST_PREFIX, wrapper))
+                .unwrap()
+                .class_name()
+        };
+        assert_eq!(name("ChatWrapper"), "ChatClient");
+        assert_eq!(name("GameClient"), "GameClient");
+        assert_eq!(name("Lobby"), "LobbyClient");
+        assert_eq!(name("Wrapper"), "WrapperClient");
+    }
+
+    #[test]
+    fn test_render_fills_every_placeholder() {
+        let chat = ClientSpec::parse(CHAT).unwrap().render();
+        assert!(!chat.contains("{{"));
+        assert!(chat.contains("export class ChatClient extends Emitter<ChatClientEvents>"));
+        assert!(chat.contains("import { ChatWrapper as RawClient } from './wasm';"));
+        assert!(chat.contains("send(msg: ChatClientMsg): void"));
+        assert!(!chat.contains("state"));
+
+        let game = ClientSpec::parse(GAME).unwrap().render();
+        assert!(!game.contains("{{"));
+        assert!(game.contains("import type { GameState } from './types/GameState';"));
+        assert!(game.contains("state: [state: GameState, room: string, version: number];"));
+        assert!(game.contains("state(): GameState | undefined"));
+
+        for target in [Target::Web, Target::Bundler, Target::Nodejs] {
+            assert!(!render_runtime(target).contains("{{"));
+        }
+        assert!(render_runtime(Target::Web).contains("import init from './wasm';"));
+        assert!(!render_runtime(Target::Bundler).contains("import init"));
+    }
+
+    #[test]
+    fn test_generate_writes_clients_and_index() {
+        let dir = project();
+        let written = generate(
+            dir.path(),
+            &dir.path().join("wasm"),
+            &dir.path().join("types"),
+            Target::Web,
+        )
+        .unwrap();
+
+        let names: Vec<_> = written
+            .iter()
+            .map(|path| path.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            ["antenna.ts", "ChatClient.ts", "GameClient.ts", "index.ts"]
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("index.ts")).unwrap(),
+            "// Generated by `cargo antenna build`, do not edit.\n\
+             export * from './antenna';\n\
+             export { ChatClient, type ChatClientEvents } from './ChatClient';\n\
+             export { GameClient, type GameClientEvents } from './GameClient';\n\
+             export type { ChatClientMsg } from './types/ChatClientMsg';\n\
+             export type { ChatServerMsg } from './types/ChatServerMsg';\n\
+             export type { GameInput } from './types/GameInput';\n\
+             export type { GameState } from './types/GameState';\n\
+             export type { GameUpdate } from './types/GameUpdate';\n"
+        );
+    }
+
+    #[test]
+    fn test_generate_requires_a_wrapper() {
+        let dir = project();
+        fs::remove_file(dir.path().join("wasm/index.d.ts")).unwrap();
+        let error = generate(
+            dir.path(),
+            &dir.path().join("wasm"),
+            &dir.path().join("types"),
+            Target::Web,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("No #[antenna_client] wrapper"));
+    }
+
+    #[test]
+    #[ignore = "needs `tsc` on PATH"]
+    fn test_generated_client_type_checks() {
+        let dir = project();
+        let written = generate(
+            dir.path(),
+            &dir.path().join("wasm"),
+            &dir.path().join("types"),
+            Target::Web,
+        )
+        .unwrap();
+
+        let output = Command::new("tsc")
+            .args([
+                "--noEmit", "--strict", "--target", "es2022", "--module", "esnext",
+            ])
+            .args(["--moduleResolution", "bundler", "--lib", "es2022,dom"])
+            .args(&written)
+            .output()
+            .expect("Failed to run tsc");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stdout)
+        );
+    }
+}
@@ -0,0 +1,110 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Helper crate generated inside the shared crate's target directory.
+const HELPER_DIR: &str = "antenna-types";
+
+/// Writes the TypeScript declarations of the types the shared crate registers with
+/// `export_types!` into `out_dir`.
+///
+/// Builds and runs a small binary depending on the shared crate that calls the registration's
+/// `__antenna_types()`, so only the shared crate and its dependencies compile, not its tests.
+pub fn run(shared_path: &Path, out_dir: &Path) -> Result<()> {
+    let shared = fs::canonicalize(shared_path)
+        .with_context(|| format!("{} does not exist", shared_path.display()))?;
+    let (package, lib) = crate_names(&shared)?;
+
+    let helper = target_directory(&shared)?.join(HELPER_DIR);
+    fs::create_dir_all(helper.join("src"))?;
+    fs::write(
+        helper.join("Cargo.toml"),
+        format!(
+            "# Generated by `cargo antenna build`, do not edit.\n\
+             [package]\nname = \"antenna-types\"\nversion = \"0.0.0\"\nedition = \"2021\"\npublish = false\n\n\
+             [dependencies]\n{} = {{ path = {} }}\n\n\
+             # Not part of the project's workspace\n[workspace]\n",
+            package,
+            toml::Value::String(shared.display().to_string())
+        ),
+    )?;
+    fs::write(
+        helper.join("src/main.rs"),
+        include_str!("../templates/types/main.rs").replace("{{lib}}", &lib),
+    )?;
+    // Resolve the same dependency versions as the project
+    if let Some(lock) = find_lockfile(&shared) {
+        fs::copy(lock, helper.join("Cargo.lock"))?;
+    }
+
+    let out_abs = fs::canonicalize(out_dir).unwrap_or(out_dir.to_path_buf());
+    let status = Command::new("cargo")
+        .args(["run", "--quiet", "--"])
+        .arg(out_abs)
+        .current_dir(&helper)
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .status()
+        .context("Failed to run cargo for type generation")?;
+
+    if !status.success() {
+        anyhow::bail!(
+            "Type generation failed, does {} call `antenna::utils::export_types!`?",
+            shared_path.display()
+        );
+    }
+    Ok(())
+}
+
+/// The package name and the name its library is imported by.
+fn crate_names(shared: &Path) -> Result<(String, String)> {
+    let manifest = shared.join("Cargo.toml");
+    let text = fs::read_to_string(&manifest)
+        .with_context(|| format!("Failed to read {}", manifest.display()))?;
+    let table: toml::Table =
+        toml::from_str(&text).with_context(|| format!("Invalid {}", manifest.display()))?;
+
+    let package = table
+        .get("package")
+        .and_then(|package| package.get("name"))
+        .and_then(|name| name.as_str())
+        .with_context(|| format!("{} has no package name", manifest.display()))?
+        .to_string();
+    let lib = table
+        .get("lib")
+        .and_then(|lib| lib.get("name"))
+        .and_then(|name| name.as_str())
+        .unwrap_or(&package)
+        .replace('-', "_");
+    Ok((package, lib))
+}
+
+/// The target directory cargo builds the shared crate into, honoring its workspace,
+/// `CARGO_TARGET_DIR` and `build.target-dir`.
+fn target_directory(shared: &Path) -> Result<PathBuf> {
+    let output = Command::new("cargo")
+        .args(["metadata", "--format-version", "1", "--no-deps"])
+        .current_dir(shared)
+        .stderr(Stdio::inherit())
+        .output()
+        .context("Failed to run cargo metadata")?;
+    if !output.status.success() {
+        anyhow::bail!("cargo metadata failed for {}", shared.display());
+    }
+
+    let metadata: serde_json::Value =
+        serde_json::from_slice(&output.stdout).context("Invalid cargo metadata output")?;
+    metadata
+        .get("target_directory")
+        .and_then(|dir| dir.as_str())
+        .map(PathBuf::from)
+        .context("cargo metadata has no target directory")
+}
+
+fn find_lockfile(shared: &Path) -> Option<PathBuf> {
+    shared
+        .ancestors()
+        .map(|dir| dir.join("Cargo.lock"))
+        .find(|lock| lock.is_file())
+}
@@ -0,0 +1,33 @@
+import { ChatClient } from './antenna';
+
+const protocol = window.location.protocol === 'https:' ? 'wss:' : 'ws:';
+const serverUrl = import.meta.env.VITE_SERVER_URL ?? `${protocol}//${window.location.hostname}:3000/ws`;
+const room = new URLSearchParams(window.location.search).get('room') ?? 'lobby';
+
+const app = document.querySelector<HTMLDivElement>('#app')!;
+app.innerHTML = `
+  <h1>Room ${room}</h1>
+  <ul id="messages"></ul>
+  <form id="compose"><input id="text" autocomplete="off" /><button>Send</button></form>
+`;
+const messages = app.querySelector<HTMLUListElement>('#messages')!;
+const compose = app.querySelector<HTMLFormElement>('#compose')!;
+const input = app.querySelector<HTMLInputElement>('#text')!;
+
+const client = new ChatClient(`${serverUrl}/${crypto.randomUUID()}`, room);
+client.on('event', (msg) => {
+    const item = document.createElement('li');
+    item.textContent = `${new Date(msg.timestamp).toLocaleTimeString()} ${msg.author_id}: ${msg.text}`;
+    messages.append(item);
+});
+client.on('error', (error) => console.error(error.description));
+
+compose.addEventListener('submit', (event) => {
+    event.preventDefault();
+    if (input.value.trim()) {
+        client.send({ text: input.value });
+        input.value = '';
+    }
+});
+
+client.connect().catch((error) => console.error('Could not connect:', error));
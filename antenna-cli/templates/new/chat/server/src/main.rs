@@ -0,0 +1,73 @@
+use std::env;
+use std::net::SocketAddr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use antenna::server::{
+    AntennaServer, RoomBehavior, RoomContext, antenna_logic, antenna_room, signaling::ws_axum_handler,
+};
+use antenna::utils::PeerId;
+use axum::{Router, routing::get};
+use shared::{ChatClientMsg, ChatServerMsg};
+use tower_http::cors::CorsLayer;
+use tracing::{Level, info, warn};
+
+#[antenna_room(client = ChatClientMsg, server = ChatServerMsg)]
+#[derive(Default)]
+struct ChatRoom;
+
+#[antenna_logic]
+impl ChatRoom {
+    async fn on_join(&self, _ctx: &RoomContext, peer_id: PeerId) {
+        info!("{} joined", peer_id);
+    }
+
+    #[msg(ChatClientMsg)]
+    async fn handle_chat(&self, ctx: &RoomContext, peer_id: PeerId, msg: ChatClientMsg) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_millis() as u64);
+        let reply = ChatServerMsg {
+            author_id: peer_id.to_string(),
+            text: msg.text,
+            timestamp,
+        };
+        if let Err(e) = Self::broadcast_server_message(ctx, reply).await {
+            warn!("Failed to relay a message of {}: {}", peer_id, e);
+        }
+    }
+
+    async fn on_leave(&self, _ctx: &RoomContext, peer_id: PeerId) {
+        info!("{} left", peer_id);
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt().with_max_level(Level::INFO).init();
+
+    let mut server = AntennaServer::new();
+    if let Ok(turn_url) = env::var("TURN_URL") {
+        server = server.with_ice_server(
+            turn_url,
+            env::var("TURN_USERNAME").ok(),
+            env::var("TURN_CREDENTIAL").ok(),
+        );
+    }
+    let state = server.build::<ChatRoom>();
+
+    let app = Router::new()
+        .route("/ws/{user_id}", get(ws_axum_handler))
+        .layer(CorsLayer::permissive())
+        .with_state(state.clone());
+
+    let addr = SocketAddr::from(([0, 0, 0, 0], 3000));
+    info!("Listening on http://{}", addr);
+    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move {
+            let _ = tokio::signal::ctrl_c().await;
+            state.shutdown().await;
+        })
+        .await
+        .unwrap();
+}
@@ -0,0 +1,25 @@
+use antenna::client::{AntennaEngine, EngineConfig, antenna_client};
+use shared::{ChatClientMsg, ChatServerMsg};
+use wasm_bindgen::prelude::*;
+use web_sys::js_sys;
+
+#[antenna_client(ChatClientMsg, ChatServerMsg)]
+#[wasm_bindgen]
+pub struct ChatWrapper {
+    engine: AntennaEngine<ChatClientMsg, ChatServerMsg>,
+}
+
+#[wasm_bindgen]
+impl ChatWrapper {
+    #[wasm_bindgen(constructor)]
+    pub fn new(url: String, room_id: String) -> Result<ChatWrapper, JsValue> {
+        let engine = AntennaEngine::new(EngineConfig {
+            url,
+            room_id,
+            ice_servers: None,
+            transport: Default::default(),
+            send_buffer: Default::default(),
+        })?;
+        Ok(ChatWrapper { engine })
+    }
+}
@@ -0,0 +1,50 @@
+import { GameClient } from './antenna';
+
+const WORLD_SIZE = 600;
+
+const protocol = window.location.protocol === 'https:' ? 'wss:' : 'ws:';
+const serverUrl = import.meta.env.VITE_SERVER_URL ?? `${protocol}//${window.location.hostname}:3000/ws`;
+const room = new URLSearchParams(window.location.search).get('room') ?? 'arena';
+const peerId = crypto.randomUUID();
+
+const canvas = document.createElement('canvas');
+canvas.width = WORLD_SIZE;
+canvas.height = WORLD_SIZE;
+document.querySelector('#app')!.append(canvas);
+const draw = canvas.getContext('2d')!;
+
+const client = new GameClient(`${serverUrl}/${peerId}`, room);
+client.on('event', (update) => {
+    draw.clearRect(0, 0, WORLD_SIZE, WORLD_SIZE);
+    for (const player of update.players) {
+        draw.fillStyle = player.id === peerId ? 'tomato' : 'steelblue';
+        draw.fillRect(player.x - 8, player.y - 8, 16, 16);
+    }
+});
+client.on('error', (error) => console.error(error.description));
+
+// Arrow keys held down, sent as one direction whenever it changes
+const pressed = new Set<string>();
+const direction = () => ({
+    dx: (pressed.has('ArrowRight') ? 1 : 0) - (pressed.has('ArrowLeft') ? 1 : 0),
+    dy: (pressed.has('ArrowDown') ? 1 : 0) - (pressed.has('ArrowUp') ? 1 : 0),
+});
+const onKey = (event: KeyboardEvent) => {
+    if (!event.key.startsWith('Arrow')) {
+        return;
+    }
+    const before = direction();
+    if (event.type === 'keydown') {
+        pressed.add(event.key);
+    } else {
+        pressed.delete(event.key);
+    }
+    const after = direction();
+    if (after.dx !== before.dx || after.dy !== before.dy) {
+        client.send({ Move: after });
+    }
+};
+window.addEventListener('keydown', onKey);
+window.addEventListener('keyup', onKey);
+
+client.connect().catch((error) => console.error('Could not connect:', error));
@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+use std::env;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use antenna::server::{
+    AntennaServer, RoomBehavior, RoomContext, antenna_logic, antenna_room, signaling::ws_axum_handler,
+};
+use antenna::utils::PeerId;
+use axum::{Router, routing::get};
+use shared::{GameInput, GameUpdate, PlayerState};
+use tower_http::cors::CorsLayer;
+use tracing::{Level, info, warn};
+
+/// Same as `tick_rate` of `#[antenna_room]`
+const TICK_RATE: u32 = 20;
+/// Distance a player covers per second
+const SPEED: f32 = 200.0;
+const WORLD_SIZE: f32 = 600.0;
+
+#[derive(Default)]
+struct Player {
+    x: f32,
+    y: f32,
+    dx: f32,
+    dy: f32,
+}
+
+#[antenna_room(tick_rate = 20, client = GameInput, server = GameUpdate)]
+#[derive(Default)]
+struct GameRoom {
+    players: Mutex<HashMap<PeerId, Player>>,
+    tick: AtomicU64,
+}
+
+#[antenna_logic]
+impl GameRoom {
+    async fn on_join(&self, _ctx: &RoomContext, peer_id: PeerId) {
+        info!("{} joined", peer_id);
+        let player = Player {
+            x: WORLD_SIZE / 2.0,
+            y: WORLD_SIZE / 2.0,
+            ..Default::default()
+        };
+        self.players.lock().unwrap().insert(peer_id, player);
+    }
+
+    #[msg(GameInput)]
+    async fn handle_input(&self, _ctx: &RoomContext, peer_id: PeerId, input: GameInput) {
+        let GameInput::Move { dx, dy } = input;
+        if let Some(player) = self.players.lock().unwrap().get_mut(&peer_id) {
+            player.dx = dx.clamp(-1.0, 1.0);
+            player.dy = dy.clamp(-1.0, 1.0);
+        }
+    }
+
+    async fn on_tick(&self, ctx: &RoomContext) {
+        let step = SPEED / TICK_RATE as f32;
+        let players = {
+            let mut players = self.players.lock().unwrap();
+            players
+                .iter_mut()
+                .map(|(peer_id, player)| {
+                    player.x = (player.x + player.dx * step).clamp(0.0, WORLD_SIZE);
+                    player.y = (player.y + player.dy * step).clamp(0.0, WORLD_SIZE);
+                    PlayerState {
+                        id: peer_id.to_string(),
+                        x: player.x,
+                        y: player.y,
+                    }
+                })
+                .collect()
+        };
+        let update = GameUpdate {
+            tick: self.tick.fetch_add(1, Ordering::Relaxed),
+            players,
+        };
+        if let Err(e) = Self::broadcast_server_message(ctx, update).await {
+            warn!("Failed to broadcast tick: {}", e);
+        }
+    }
+
+    async fn on_leave(&self, _ctx: &RoomContext, peer_id: PeerId) {
+        info!("{} left", peer_id);
+        self.players.lock().unwrap().remove(&peer_id);
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt().with_max_level(Level::INFO).init();
+
+    let mut server = AntennaServer::new();
+    if let Ok(turn_url) = env::var("TURN_URL") {
+        server = server.with_ice_server(
+            turn_url,
+            env::var("TURN_USERNAME").ok(),
+            env::var("TURN_CREDENTIAL").ok(),
+        );
+    }
+    let state = server.build::<GameRoom>();
+
+    let app = Router::new()
+        .route("/ws/{user_id}", get(ws_axum_handler))
+        .layer(CorsLayer::permissive())
+        .with_state(state.clone());
+
+    let addr = SocketAddr::from(([0, 0, 0, 0], 3000));
+    info!("Listening on http://{}", addr);
+    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move {
+            let _ = tokio::signal::ctrl_c().await;
+            state.shutdown().await;
+        })
+        .await
+        .unwrap();
+}
@@ -0,0 +1,38 @@
+use antenna::utils::{Channel, Message, export_types};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum GameInput {
+    /// Direction the player walks in until the next input, each axis in `-1.0..=1.0`
+    Move { dx: f32, dy: f32 },
+}
+
+impl Message for GameInput {
+    fn channel(&self) -> Channel {
+        Channel::Reliable
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PlayerState {
+    pub id: String,
+    pub x: f32,
+    pub y: f32,
+}
+
+/// World state broadcast on every tick.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GameUpdate {
+    pub tick: u64,
+    pub players: Vec<PlayerState>,
+}
+
+impl Message for GameUpdate {
+    // A lost update is replaced by the next tick
+    fn channel(&self) -> Channel {
+        Channel::Unreliable
+    }
+}
+
+// Exported to TypeScript by `cargo antenna build`, with the types they contain
+export_types!(GameInput, GameUpdate);
@@ -0,0 +1,25 @@
+use antenna::client::{AntennaEngine, EngineConfig, antenna_client};
+use shared::{GameInput, GameUpdate};
+use wasm_bindgen::prelude::*;
+use web_sys::js_sys;
+
+#[antenna_client(GameInput, GameUpdate)]
+#[wasm_bindgen]
+pub struct GameWrapper {
+    engine: AntennaEngine<GameInput, GameUpdate>,
+}
+
+#[wasm_bindgen]
+impl GameWrapper {
+    #[wasm_bindgen(constructor)]
+    pub fn new(url: String, room_id: String) -> Result<GameWrapper, JsValue> {
+        let engine = AntennaEngine::new(EngineConfig {
+            url,
+            room_id,
+            ice_servers: None,
+            transport: Default::default(),
+            send_buffer: Default::default(),
+        })?;
+        Ok(GameWrapper { engine })
+    }
+}
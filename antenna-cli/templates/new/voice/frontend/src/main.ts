@@ -0,0 +1,53 @@
+import { VoiceClient } from './antenna';
+
+const protocol = window.location.protocol === 'https:' ? 'wss:' : 'ws:';
+const serverUrl = import.meta.env.VITE_SERVER_URL ?? `${protocol}//${window.location.hostname}:3000/ws`;
+const room = new URLSearchParams(window.location.search).get('room') ?? 'lounge';
+
+const app = document.querySelector<HTMLDivElement>('#app')!;
+app.innerHTML = `
+  <h1>Room ${room}</h1>
+  <button id="voice">Join voice</button>
+  <ul id="log"></ul>
+  <form id="compose"><input id="text" autocomplete="off" /><button>Send</button></form>
+`;
+const log = app.querySelector<HTMLUListElement>('#log')!;
+const voice = app.querySelector<HTMLButtonElement>('#voice')!;
+const compose = app.querySelector<HTMLFormElement>('#compose')!;
+const input = app.querySelector<HTMLInputElement>('#text')!;
+
+const append = (text: string) => {
+    const item = document.createElement('li');
+    item.textContent = text;
+    log.append(item);
+};
+
+const client = new VoiceClient(`${serverUrl}/${crypto.randomUUID()}`, room);
+client.on('event', (msg) => append(`${msg.author_id}: ${msg.text}`));
+client.on('presence', (event) => append(`${event.peer} ${event.kind}`));
+client.on('track', (event) => {
+    const audio = new Audio();
+    audio.srcObject = event.streams[0] ?? new MediaStream([event.track]);
+    audio.autoplay = true;
+    event.track.addEventListener('ended', () => audio.remove());
+});
+client.on('error', (error) => console.error(error.description));
+
+voice.addEventListener('click', async () => {
+    const stream = await navigator.mediaDevices.getUserMedia({ audio: true, video: false });
+    for (const track of stream.getAudioTracks()) {
+        client.addTrack(track, stream);
+    }
+    voice.disabled = true;
+    voice.textContent = 'In voice call';
+});
+
+compose.addEventListener('submit', (event) => {
+    event.preventDefault();
+    if (input.value.trim()) {
+        client.send({ text: input.value });
+        input.value = '';
+    }
+});
+
+client.connect().catch((error) => console.error('Could not connect:', error));
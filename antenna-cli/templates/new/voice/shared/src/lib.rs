@@ -0,0 +1,30 @@
+use antenna::utils::{Channel, Message, export_types};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VoiceClientMsg {
+    pub text: String,
+}
+
+impl Message for VoiceClientMsg {
+    fn channel(&self) -> Channel {
+        Channel::Reliable
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VoiceServerMsg {
+    pub author_id: String,
+    pub text: String,
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+}
+
+impl Message for VoiceServerMsg {
+    fn channel(&self) -> Channel {
+        Channel::Reliable
+    }
+}
+
+// Exported to TypeScript by `cargo antenna build`, with the types they contain
+export_types!(VoiceClientMsg, VoiceServerMsg);
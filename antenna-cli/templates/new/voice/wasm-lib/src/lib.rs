@@ -0,0 +1,25 @@
+use antenna::client::{AntennaEngine, EngineConfig, antenna_client};
+use shared::{VoiceClientMsg, VoiceServerMsg};
+use wasm_bindgen::prelude::*;
+use web_sys::js_sys;
+
+#[antenna_client(VoiceClientMsg, VoiceServerMsg)]
+#[wasm_bindgen]
+pub struct VoiceWrapper {
+    engine: AntennaEngine<VoiceClientMsg, VoiceServerMsg>,
+}
+
+#[wasm_bindgen]
+impl VoiceWrapper {
+    #[wasm_bindgen(constructor)]
+    pub fn new(url: String, room_id: String) -> Result<VoiceWrapper, JsValue> {
+        let engine = AntennaEngine::new(EngineConfig {
+            url,
+            room_id,
+            ice_servers: None,
+            transport: Default::default(),
+            send_buffer: Default::default(),
+        })?;
+        Ok(VoiceWrapper { engine })
+    }
+}
@@ -0,0 +1,56 @@
+// Generated by `cargo antenna build`, do not edit.
+{{wasm_import}}
+/** Error reported by the engine, see `AntennaError`. A fatal error closes its room, or the whole session when `room` is missing. */
+export interface AntennaErrorEvent {
+    kind: string;
+    description: string;
+    fatal: boolean;
+    room?: string;
+    [field: string]: unknown;
+}
+
+/** Another peer joined or left a room that announces presence (`#[antenna_room(presence = true)]`). */
+export interface PresenceEvent {
+    kind: 'joined' | 'left';
+    peer: string;
+}
+
+let wasmReady: Promise<unknown> | null = null;
+
+/** Loads the wasm module; every client shares the first load. */
+export function loadWasm(): Promise<unknown> {
+    wasmReady ??= {{wasm_init}};
+    return wasmReady;
+}
+
+type Listener<A extends unknown[]> = (...args: A) => void;
+
+/** Typed event emitter the generated clients extend. */
+export class Emitter<Events extends { [K in keyof Events]: unknown[] }> {
+    private readonly listeners: { [K in keyof Events]?: Set<Listener<Events[K]>> } = {};
+
+    /** Registers `listener` and returns a function removing it again. */
+    on<K extends keyof Events>(event: K, listener: Listener<Events[K]>): () => void {
+        (this.listeners[event] ??= new Set()).add(listener);
+        return () => this.off(event, listener);
+    }
+
+    /** Like `on`, but the listener is removed after its first call. */
+    once<K extends keyof Events>(event: K, listener: Listener<Events[K]>): () => void {
+        const off = this.on(event, (...args) => {
+            off();
+            listener(...args);
+        });
+        return off;
+    }
+
+    off<K extends keyof Events>(event: K, listener: Listener<Events[K]>): void {
+        this.listeners[event]?.delete(listener);
+    }
+
+    protected emit<K extends keyof Events>(event: K, ...args: Events[K]): void {
+        for (const listener of [...(this.listeners[event] ?? [])]) {
+            listener(...args);
+        }
+    }
+}
@@ -0,0 +1,125 @@
+// Generated by `cargo antenna build` from `#[antenna_client]` on `{{wrapper}}`, do not edit.
+import { {{wrapper}} as RawClient } from './wasm';
+import { Emitter, loadWasm, type AntennaErrorEvent, type PresenceEvent } from './antenna';
+import type { {{client_msg}} } from './types/{{client_msg}}';
+import type { {{server_msg}} } from './types/{{server_msg}}';
+{{state_import}}
+export type {{name}}Events = {
+    /** A server message and the room it came from. */
+    event: [event: {{server_msg}}, room: string];
+    /** A room finished connecting, over WebRTC or the WebSocket fallback. */
+    connect: [room: string];
+    presence: [event: PresenceEvent, room: string];
+{{state_event}}    track: [event: RTCTrackEvent];
+    error: [error: AntennaErrorEvent];
+    /** The matchmaker put this client into `room`; join it with `joinRoom`. */
+    match: [room: string, queue: string];
+};
+
+/** Typed client around the `{{wrapper}}` wasm module. */
+export class {{name}} extends Emitter<{{name}}Events> {
+    private readonly args: ConstructorParameters<typeof RawClient>;
+    private client: RawClient | null = null;
+    private connecting: Promise<void> | null = null;
+
+    /** Takes the arguments of the `{{wrapper}}` constructor. Nothing connects before `connect()`. */
+    constructor(...args: ConstructorParameters<typeof RawClient>) {
+        super();
+        this.args = args;
+    }
+
+    /** Loads the wasm module and resolves once the first room is connected, or rejects with the fatal error that closed it. Concurrent calls share one attempt. */
+    connect(): Promise<void> {
+        this.connecting ??= this.open();
+        return this.connecting;
+    }
+
+    private async open(): Promise<void> {
+        await loadWasm();
+        const client = new RawClient(...this.args);
+        this.client = client;
+
+        client.on_event((event, room) => this.emit('event', event, room));
+        client.on_connect((room) => this.emit('connect', room));
+        client.on_presence((event, room) => this.emit('presence', event, room));
+{{state_listener}}        client.on_track((event: RTCTrackEvent) => this.emit('track', event));
+        client.on_error((error) => this.emit('error', error));
+        client.on_match((room, queue) => this.emit('match', room, queue));
+
+        try {
+            await new Promise<void>((resolve, reject) => {
+                const offConnect = this.once('connect', () => {
+                    offError();
+                    resolve();
+                });
+                const offError = this.on('error', (error) => {
+                    if (error.fatal) {
+                        offConnect();
+                        offError();
+                        reject(error);
+                    }
+                });
+            });
+        } catch (error) {
+            this.close();
+            throw error;
+        }
+    }
+
+    /** Sends a message to the room given to the constructor. */
+    send(msg: {{client_msg}}): void {
+        this.connected().send_client_message(msg);
+    }
+
+    /** Joins another room; `metadata` reaches the room's `on_join_request` hook. */
+    joinRoom(room: string, metadata: Record<string, string> = {}): void {
+        this.connected().join_room_with_metadata(room, metadata);
+    }
+
+    leaveRoom(room: string): void {
+        this.connected().leave_room(room);
+    }
+
+    rooms(): string[] {
+        return this.connected().rooms();
+    }
+
+    /** Place on the wait-list of a full room, `undefined` once admitted. */
+    waitPosition(room: string): number | undefined {
+        return this.connected().wait_position(room);
+    }
+
+    /** Queues for a match; `attributes` are compared by the queue's rules, e.g. `skill`. */
+    matchmake(queue: string, attributes: Record<string, number> = {}): void {
+        this.connected().matchmake(queue, attributes);
+    }
+
+    /** Leaves `queue`, or every queue when omitted. */
+    cancelMatchmake(queue?: string): void {
+        this.connected().cancel_matchmake(queue);
+    }
+
+    addTrack(track: MediaStreamTrack, stream: MediaStream): void {
+        this.connected().add_track(track, stream);
+    }
+{{state_method}}
+    /** Leaves every room and releases the wasm client. */
+    close(): void {
+        this.client?.leave();
+        this.client?.free();
+        this.client = null;
+        this.connecting = null;
+    }
+
+    /** The wasm-bindgen client, for methods declared on `{{wrapper}}` itself. */
+    get raw(): RawClient {
+        return this.connected();
+    }
+
+    private connected(): RawClient {
+        if (!this.client) {
+            throw new Error('{{name}} is not connected, call connect() first');
+        }
+        return this.client;
+    }
+}
@@ -0,0 +1,16 @@
+// Generated by `cargo antenna build`, do not edit.
+use std::path::Path;
+
+fn main() {
+    let dir = std::env::args_os()
+        .nth(1)
+        .expect("the output directory is passed by cargo antenna");
+    let exporter = {{lib}}::__antenna_types();
+    for warning in exporter.warnings() {
+        eprintln!("warning: {}", warning);
+    }
+    if let Err(e) = exporter.write_to(Path::new(&dir)) {
+        eprintln!("error: failed to write the TypeScript types: {}", e);
+        std::process::exit(1);
+    }
+}
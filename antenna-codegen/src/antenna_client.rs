@@ -4,8 +4,9 @@ use syn::parse::{Parse, ParseStream};
 use syn::{Ident, Token, parse2};
 
 struct AntennaClientArgs {
-    _client_msg: Ident,
+    client_msg: Ident,
     server_msg: Ident,
+    state: Option<Ident>,
 }
 
 impl Parse for AntennaClientArgs {
@@ -13,17 +14,24 @@ impl Parse for AntennaClientArgs {
         let client_msg: Ident = input.parse()?;
         input.parse::<Token![,]>()?;
         let server_msg: Ident = input.parse()?;
+        let state = if input.parse::<Option<Token![,]>>()?.is_some() {
+            Some(input.parse()?)
+        } else {
+            None
+        };
         Ok(AntennaClientArgs {
-            _client_msg: client_msg,
+            client_msg,
             server_msg,
+            state,
         })
     }
 }
 
 pub fn antenna_client_impl(args: TokenStream, input: TokenStream) -> TokenStream {
     let AntennaClientArgs {
-        _client_msg,
+        client_msg,
         server_msg,
+        state,
     } = match parse2::<AntennaClientArgs>(args) {
         Ok(a) => a,
         Err(e) => return e.to_compile_error(),
@@ -37,28 +45,123 @@ pub fn antenna_client_impl(args: TokenStream, input: TokenStream) -> TokenStream
     let struct_name = &item_struct.ident;
 
     let server_msg_str = server_msg.to_string();
-    let callback_type_str = format!("(event: {}) => void", server_msg_str);
+    let callback_type_str = format!("(event: {}, room: string) => void", server_msg_str);
     let import_str = format!(
         "import {{ {} }} from './types/{}';",
         server_msg_str, server_msg_str
     );
 
     let callback_ident = Ident::new(&format!("{}Callback", struct_name), struct_name.span());
+    let error_callback_ident =
+        Ident::new(&format!("{}ErrorCallback", struct_name), struct_name.span());
+    let error_callback_type_str = "(error: { kind: string; description: string; fatal: boolean; room?: string; [field: string]: unknown }) => void";
+    let ack_callback_ident = Ident::new(&format!("{}AckCallback", struct_name), struct_name.span());
+    let ack_callback_type_str =
+        "(header: { seq: number; ack?: number; ack_bits: number }, room: string) => void";
+    let match_callback_ident =
+        Ident::new(&format!("{}MatchCallback", struct_name), struct_name.span());
+    let match_callback_type_str = "(room: string, queue: string) => void";
+    let presence_callback_ident = Ident::new(
+        &format!("{}PresenceCallback", struct_name),
+        struct_name.span(),
+    );
+    let presence_callback_type_str =
+        "(event: { kind: \"joined\" | \"left\"; peer: string }, room: string) => void";
+    let connect_callback_ident = Ident::new(
+        &format!("{}ConnectCallback", struct_name),
+        struct_name.span(),
+    );
+    let connect_callback_type_str = "(room: string) => void";
     let import_const_ident = Ident::new(
         &format!("_TS_IMPORT_{}", struct_name).to_uppercase(),
         struct_name.span(),
     );
 
+    // Read by `cargo antenna build` to generate the typed client class around the wrapper
+    let manifest_str = format!(
+        "// antenna-client: {} {} {}{}",
+        struct_name,
+        client_msg,
+        server_msg,
+        state
+            .as_ref()
+            .map(|state| format!(" {}", state))
+            .unwrap_or_default()
+    );
+    let manifest_const_ident = Ident::new(
+        &format!("_TS_ANTENNA_CLIENT_{}", struct_name).to_uppercase(),
+        struct_name.span(),
+    );
+
+    let state_methods = state.map(|state| {
+        let state_str = state.to_string();
+        let state_callback_type_str = format!(
+            "(state: {}, room: string, version: number) => void",
+            state_str
+        );
+        let state_import_str = format!("import {{ {} }} from './types/{}';", state_str, state_str);
+        let state_callback_ident =
+            Ident::new(&format!("{}StateCallback", struct_name), struct_name.span());
+        let state_import_const_ident = Ident::new(
+            &format!("_TS_IMPORT_STATE_{}", struct_name).to_uppercase(),
+            struct_name.span(),
+        );
+
+        quote! {
+            #[wasm_bindgen(typescript_custom_section)]
+            const #state_import_const_ident: &'static str = #state_import_str;
+
+            #[wasm_bindgen]
+            extern "C" {
+                #[wasm_bindgen(typescript_type = #state_callback_type_str)]
+                pub type #state_callback_ident;
+            }
+
+            #[wasm_bindgen]
+            impl #struct_name {
+                pub fn state_snapshot(&self) -> wasm_bindgen::JsValue {
+                    self.engine.state_snapshot_js::<#state>()
+                }
+
+                pub fn on_state_patch(&self, cb: #state_callback_ident) {
+                    use wasm_bindgen::JsCast;
+                    let func: js_sys::Function = cb.unchecked_into();
+                    self.engine.set_state_patch_handler::<#state>(func);
+                }
+            }
+        }
+    });
+
     quote! {
         #item_struct
 
+        #state_methods
+
         #[wasm_bindgen(typescript_custom_section)]
         const #import_const_ident: &'static str = #import_str;
 
+        #[wasm_bindgen(typescript_custom_section)]
+        const #manifest_const_ident: &'static str = #manifest_str;
+
         #[wasm_bindgen]
         extern "C" {
             #[wasm_bindgen(typescript_type = #callback_type_str)]
             pub type #callback_ident;
+
+            #[wasm_bindgen(typescript_type = #error_callback_type_str)]
+            pub type #error_callback_ident;
+
+            #[wasm_bindgen(typescript_type = #ack_callback_type_str)]
+            pub type #ack_callback_ident;
+
+            #[wasm_bindgen(typescript_type = #match_callback_type_str)]
+            pub type #match_callback_ident;
+
+            #[wasm_bindgen(typescript_type = #presence_callback_type_str)]
+            pub type #presence_callback_ident;
+
+            #[wasm_bindgen(typescript_type = #connect_callback_type_str)]
+            pub type #connect_callback_ident;
         }
 
         #[wasm_bindgen]
@@ -69,10 +172,84 @@ pub fn antenna_client_impl(args: TokenStream, input: TokenStream) -> TokenStream
                 self.engine.set_event_handler(func);
             }
 
+            /// Sends a client message, given as the object `serde_wasm_bindgen` makes of it. Named
+            /// apart from `send`, which wrappers commonly declare themselves.
+            pub fn send_client_message(&self, msg: wasm_bindgen::JsValue) -> Result<(), wasm_bindgen::JsValue> {
+                self.engine.send_js(msg).map_err(Into::into)
+            }
+
+            pub fn on_connect(&self, cb: #connect_callback_ident) {
+                use wasm_bindgen::JsCast;
+                let func: js_sys::Function = cb.unchecked_into();
+                self.engine.set_connect_handler(func);
+            }
+
+            pub fn on_presence(&self, cb: #presence_callback_ident) {
+                use wasm_bindgen::JsCast;
+                let func: js_sys::Function = cb.unchecked_into();
+                self.engine.set_presence_handler(func);
+            }
+
             pub fn on_track(&self, cb: js_sys::Function) {
                 self.engine.set_track_handler(cb);
             }
 
+            pub fn on_stream(&self, cb: js_sys::Function) {
+                self.engine.set_stream_handler(cb);
+            }
+
+            pub fn on_error(&self, cb: #error_callback_ident) {
+                use wasm_bindgen::JsCast;
+                let func: js_sys::Function = cb.unchecked_into();
+                self.engine.set_error_handler(func);
+            }
+
+            pub fn on_ack(&self, cb: #ack_callback_ident) {
+                use wasm_bindgen::JsCast;
+                let func: js_sys::Function = cb.unchecked_into();
+                self.engine.set_ack_handler(func);
+            }
+
+            pub fn on_match(&self, cb: #match_callback_ident) {
+                use wasm_bindgen::JsCast;
+                let func: js_sys::Function = cb.unchecked_into();
+                self.engine.set_match_handler(func);
+            }
+
+            pub fn matchmake(&self, queue: String, attributes: wasm_bindgen::JsValue) -> Result<(), wasm_bindgen::JsValue> {
+                self.engine.matchmake_js(&queue, attributes).map_err(Into::into)
+            }
+
+            pub fn cancel_matchmake(&self, queue: Option<String>) -> Result<(), wasm_bindgen::JsValue> {
+                self.engine.cancel_matchmake(queue.as_deref()).map_err(Into::into)
+            }
+
+            pub fn leave(&self) {
+                self.engine.leave();
+            }
+
+            pub fn join_room(&self, room: String) -> Result<(), wasm_bindgen::JsValue> {
+                self.engine.join_room(&room).map_err(Into::into)
+            }
+
+            /// Joins `room` sending `metadata`, an object of string values, to the room's
+            /// `on_join_request` hook.
+            pub fn join_room_with_metadata(&self, room: String, metadata: wasm_bindgen::JsValue) -> Result<(), wasm_bindgen::JsValue> {
+                self.engine.join_room_with_metadata_js(&room, metadata).map_err(Into::into)
+            }
+
+            pub fn wait_position(&self, room: String) -> Option<usize> {
+                self.engine.wait_position(&room)
+            }
+
+            pub fn leave_room(&self, room: String) {
+                self.engine.leave_room(&room);
+            }
+
+            pub fn rooms(&self) -> Vec<String> {
+                self.engine.rooms()
+            }
+
             pub fn add_track(&self, track: web_sys::MediaStreamTrack, stream: web_sys::MediaStream) -> Result<(), wasm_bindgen::JsValue> {
                 self.engine.add_track(track, stream)
             }
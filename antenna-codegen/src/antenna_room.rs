@@ -1,108 +1,563 @@
+use crate::handler_signature::{Hooks, check_handler};
 use proc_macro2::TokenStream;
-use quote::quote;
-use syn::{ImplItem, ItemImpl, Type, parse2};
+use quote::{ToTokens, quote, quote_spanned};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{
+    Attribute, Ident, ImplItem, ItemImpl, ItemStruct, LitBool, LitInt, MetaNameValue, Path, Token,
+    Type, parse2,
+};
 
-pub fn antenna_room_impl(_args: TokenStream, input: TokenStream) -> TokenStream {
-    input
+/// Arguments of `#[antenna_room(...)]`.
+#[derive(Default)]
+struct RoomArgs {
+    max_peers: Option<LitInt>,
+    wait_list: Option<LitInt>,
+    tick_rate: Option<LitInt>,
+    presence: Option<LitBool>,
+    client: Option<Type>,
+    server: Option<Type>,
+    factory: Option<Path>,
 }
 
-pub fn antenna_logic_impl(_args: TokenStream, input: TokenStream) -> TokenStream {
+impl Parse for RoomArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = RoomArgs::default();
+        for pair in Punctuated::<MetaNameValue, Token![,]>::parse_terminated(input)? {
+            let key = pair
+                .path
+                .get_ident()
+                .map(ToString::to_string)
+                .unwrap_or_default();
+            let value = &pair.value;
+            match key.as_str() {
+                "max_peers" => args.max_peers = Some(parse_value(value, "a number of peers")?),
+                "wait_list" => args.wait_list = Some(parse_value(value, "a number of peers")?),
+                "tick_rate" => {
+                    let tick_rate: LitInt = parse_value(value, "ticks per second")?;
+                    if tick_rate.base10_parse::<u32>()? == 0 {
+                        return Err(syn::Error::new_spanned(
+                            value,
+                            "tick_rate must be at least 1, leave it out to not tick",
+                        ));
+                    }
+                    args.tick_rate = Some(tick_rate);
+                }
+                "presence" => args.presence = Some(parse_value(value, "`true` or `false`")?),
+                "client" => args.client = Some(parse_value(value, "the client message type")?),
+                "server" => args.server = Some(parse_value(value, "the server message type")?),
+                "factory" => {
+                    args.factory = Some(parse_value(value, "a function returning the room")?)
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        &pair.path,
+                        "unknown argument, expected one of `max_peers`, `wait_list`, `tick_rate`, `presence`, `client`, `server`, `factory`",
+                    ));
+                }
+            }
+        }
+        Ok(args)
+    }
+}
+
+fn parse_value<T: Parse>(value: &syn::Expr, expected: &str) -> syn::Result<T> {
+    parse2(value.to_token_stream())
+        .map_err(|_| syn::Error::new_spanned(value, format!("expected {}", expected)))
+}
+
+pub fn antenna_room_impl(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args = match parse2::<RoomArgs>(args) {
+        Ok(a) => a,
+        Err(e) => return e.to_compile_error(),
+    };
+    let item_struct = match parse2::<ItemStruct>(input) {
+        Ok(s) => s,
+        Err(e) => return e.to_compile_error(),
+    };
+
+    let name = &item_struct.ident;
+    let (impl_generics, ty_generics, where_clause) = item_struct.generics.split_for_impl();
+
+    // Rooms are created through their own `Default` unless they bring a factory
+    let create = match &args.factory {
+        Some(factory) => quote! { #factory() },
+        None => quote_spanned! { name.span()=> ::core::default::Default::default() },
+    };
+
+    let mut config = quote! { ::antenna::server::RoomConfig::new() };
+    if let Some(max_peers) = &args.max_peers {
+        config.extend(quote! { .with_max_peers(#max_peers) });
+    }
+    if let Some(wait_list) = &args.wait_list {
+        config.extend(quote! { .with_wait_list(#wait_list) });
+    }
+    if let Some(tick_rate) = &args.tick_rate {
+        config.extend(quote! { .with_tick_rate(#tick_rate) });
+    }
+    if args.presence.as_ref().is_some_and(LitBool::value) {
+        config.extend(quote! { .with_presence() });
+    }
+    if let (Some(client), Some(server)) = (&args.client, &args.server) {
+        config.extend(quote! { .with_schema::<#client, #server>() });
+    }
+
+    let send_helpers = args.server.as_ref().map(|server| {
+        quote! {
+            impl #impl_generics #name #ty_generics #where_clause {
+                /// Sends a server message to one peer, see `RoomContext::send_message`.
+                pub async fn send_server_message(
+                    ctx: &::antenna::server::RoomContext,
+                    peer_id: &::antenna::utils::PeerId,
+                    msg: #server,
+                ) -> ::core::result::Result<(), ::antenna::server::SendError> {
+                    ctx.send_message(peer_id, &msg).await
+                }
+
+                /// Sends a server message to every peer, see `RoomContext::broadcast_message`.
+                pub async fn broadcast_server_message(
+                    ctx: &::antenna::server::RoomContext,
+                    msg: #server,
+                ) -> ::core::result::Result<(), ::antenna::server::SendError> {
+                    ctx.broadcast_message(&msg).await
+                }
+            }
+        }
+    });
+
+    quote! {
+        #item_struct
+
+        impl #impl_generics ::antenna::server::RoomDefinition for #name #ty_generics #where_clause {
+            fn create() -> Self {
+                #create
+            }
+
+            fn config() -> ::antenna::server::RoomConfig {
+                #config
+            }
+        }
+
+        #send_helpers
+    }
+}
+
+pub fn antenna_logic_impl(args: TokenStream, input: TokenStream) -> TokenStream {
     let mut item_impl = match parse2::<ItemImpl>(input) {
         Ok(i) => i,
         Err(e) => return e.to_compile_error(),
     };
 
+    let mut errors = Errors::default();
+    if !args.is_empty() {
+        errors.push(syn::Error::new_spanned(
+            &args,
+            "#[antenna_logic] takes no arguments",
+        ));
+    }
+
     let struct_name = &item_impl.self_ty;
 
-    let mut handlers = Vec::new();
-    let mut other_items = Vec::new();
-
-    for item in item_impl.items.drain(..) {
-        if let ImplItem::Fn(mut method) = item {
-            let mut msg_type = None;
-            let mut is_system_msg = false;
-            let mut new_attrs = Vec::new();
-
-            for attr in method.attrs.drain(..) {
-                if attr.path().is_ident("msg") {
-                    msg_type = Some(
-                        attr.parse_args::<Type>()
-                            .expect("Expected type in #[msg(...)]"),
-                    );
-                } else if attr.path().is_ident("handle_user_message") {
-                    msg_type = Some(
-                        attr.parse_args::<Type>()
-                            .expect("Expected type in #[handle_user_message(...)]"),
-                    );
-                } else if attr.path().is_ident("handle_system_message") {
-                    is_system_msg = true;
-                } else {
-                    new_attrs.push(attr);
-                }
-            }
-            method.attrs = new_attrs;
+    let mut dispatch = Dispatch::default();
+    let mut hooks = Hooks::default();
 
-            if let Some(msg_ty) = msg_type {
-                let method_name = &method.sig.ident;
-                handlers.push(quote! {
-                    if let Ok(Packet::User(msg)) = postcard::from_bytes::<Packet<#msg_ty>>(&data) {
-                        self.#method_name(ctx, peer_id, msg).await;
-                        return;
-                    }
-                });
-                other_items.push(ImplItem::Fn(method));
-            } else if is_system_msg {
-                let method_name = &method.sig.ident;
-                handlers.push(quote! {
-                    if let Ok(Packet::System(msg)) = postcard::from_bytes::<Packet<()>>(&data) {
-                        self.#method_name(ctx, peer_id, msg).await;
-                        return;
-                    }
-                });
-                other_items.push(ImplItem::Fn(method));
-            } else {
-                other_items.push(ImplItem::Fn(method));
+    for item in &mut item_impl.items {
+        let ImplItem::Fn(method) = item else {
+            continue;
+        };
+
+        let mut roles = Vec::new();
+        let mut new_attrs = Vec::new();
+        for attr in method.attrs.drain(..) {
+            match HandlerRole::parse(&attr) {
+                Ok(Some(role)) => roles.push((role, attr)),
+                Ok(None) => new_attrs.push(attr),
+                Err(e) => errors.push(e),
             }
-        } else {
-            other_items.push(item);
+        }
+        method.attrs = new_attrs;
+
+        if roles.is_empty() {
+            errors.extend(hooks.add(&method.sig));
+            continue;
+        }
+        for (role, attr) in roles {
+            errors.extend(check_handler(&method.sig, &role, &attr).err());
+            errors.extend(dispatch.add(role, &attr, method.sig.ident.clone()).err());
         }
     }
 
-    item_impl.items = other_items;
+    if let Some(errors) = errors.into_error() {
+        // Keep the trait implemented, so the only errors reported are the ones above
+        let errors = errors.to_compile_error();
+        return quote! {
+            #item_impl
+
+            #[async_trait::async_trait]
+            impl RoomBehavior for #struct_name {
+                async fn on_join(&self, _ctx: &RoomContext, _peer_id: PeerId) {}
+                async fn on_message(&self, _ctx: &RoomContext, _peer_id: PeerId, _data: bytes::Bytes) {}
+                async fn on_leave(&self, _ctx: &RoomContext, _peer_id: PeerId) {}
+            }
 
-    let mut on_join_call = quote! {};
-    let mut on_leave_call = quote! {};
+            #errors
+        };
+    }
 
-    for item in &item_impl.items {
-        if let ImplItem::Fn(method) = item {
-            if method.sig.ident == "on_join" {
-                on_join_call = quote! { self.on_join(ctx, peer_id).await; };
+    let Hooks {
+        on_join,
+        on_leave,
+        snapshot,
+        restore,
+        on_tick,
+        on_close,
+        on_join_request,
+        on_stream_open,
+    } = hooks;
+    let on_join_call = on_join.then(|| quote! { self.on_join(ctx, peer_id).await; });
+    let on_leave_call = on_leave.then(|| quote! { self.on_leave(ctx, peer_id).await; });
+    let snapshot = snapshot.then(|| {
+        quote! {
+            async fn snapshot(&self) -> Option<Vec<u8>> {
+                self.snapshot().await
             }
-            if method.sig.ident == "on_leave" {
-                on_leave_call = quote! { self.on_leave(ctx, peer_id).await; };
+        }
+    });
+    let on_join_request = on_join_request.then(|| {
+        quote! {
+            async fn on_join_request(
+                &self,
+                ctx: &RoomContext,
+                peer_id: &PeerId,
+                metadata: &::std::collections::HashMap<String, String>,
+            ) -> ::antenna::server::Admission {
+                self.on_join_request(ctx, peer_id, metadata).await
             }
         }
-    }
+    });
+    let on_tick = on_tick.then(|| {
+        quote! {
+            async fn on_tick(&self, ctx: &RoomContext) {
+                self.on_tick(ctx).await
+            }
+        }
+    });
+    let on_close = on_close.then(|| {
+        quote! {
+            async fn on_close(&self, ctx: &RoomContext) {
+                self.on_close(ctx).await
+            }
+        }
+    });
+    let on_stream_open = on_stream_open.then(|| {
+        quote! {
+            async fn on_stream_open(
+                &self,
+                ctx: &RoomContext,
+                peer_id: &PeerId,
+                to: Option<&PeerId>,
+                name: &str,
+                size: u64,
+            ) -> bool {
+                self.on_stream_open(ctx, peer_id, to, name, size).await
+            }
+        }
+    });
+    let restore = restore.then(|| {
+        quote! {
+            async fn restore(&self, snapshot: &[u8]) -> ::antenna::server::anyhow::Result<()> {
+                self.restore(snapshot).await
+            }
+        }
+    });
+
+    let on_message = dispatch.on_message();
 
     quote! {
         #item_impl
 
         #[async_trait::async_trait]
         impl RoomBehavior for #struct_name {
+            #on_join_request
+
             async fn on_join(&self, ctx: &RoomContext, peer_id: PeerId) {
                 #on_join_call
             }
 
             async fn on_message(&self, ctx: &RoomContext, peer_id: PeerId, data: bytes::Bytes) {
-                use postcard::from_bytes;
-                use antenna::utils::Packet;
-
-                #(#handlers)*
+                #on_message
             }
 
             async fn on_leave(&self, ctx: &RoomContext, peer_id: PeerId) {
                 #on_leave_call
             }
+
+            #on_stream_open
+            #on_tick
+            #on_close
+            #snapshot
+            #restore
+        }
+    }
+}
+
+/// Collects every diagnostic of a macro invocation, so they are reported together.
+#[derive(Default)]
+struct Errors(Option<syn::Error>);
+
+impl Errors {
+    fn push(&mut self, error: syn::Error) {
+        match &mut self.0 {
+            Some(errors) => errors.combine(error),
+            None => self.0 = Some(error),
+        }
+    }
+
+    fn into_error(self) -> Option<syn::Error> {
+        self.0
+    }
+}
+
+impl Extend<syn::Error> for Errors {
+    fn extend<I: IntoIterator<Item = syn::Error>>(&mut self, iter: I) {
+        for error in iter {
+            self.push(error);
+        }
+    }
+}
+
+/// What a method of an `#[antenna_logic]` impl handles.
+pub(crate) enum HandlerRole {
+    /// `#[handle_user_message(T)]` or `#[msg(T)]`: every message of type `T`
+    Message(Type),
+    /// `#[handle_variant(T::Variant)]`: a single variant of the message enum `T`
+    Variant(Path),
+    /// `#[handle_system_message]`
+    System,
+    /// `#[fallback]`: input that does not decode or has no handler
+    Fallback,
+}
+
+impl HandlerRole {
+    /// Reads a handler attribute, `None` for attributes that belong to the method itself.
+    fn parse(attr: &Attribute) -> syn::Result<Option<Self>> {
+        let path = attr.path();
+        let role = if path.is_ident("msg") || path.is_ident("handle_user_message") {
+            HandlerRole::Message(attr.parse_args::<Type>().map_err(|e| {
+                syn::Error::new(
+                    e.span(),
+                    "expected the message type, e.g. `#[handle_user_message(ChatMsg)]`",
+                )
+            })?)
+        } else if path.is_ident("handle_variant") {
+            let variant = attr.parse_args::<Path>().map_err(|e| {
+                syn::Error::new(
+                    e.span(),
+                    "expected an enum variant, e.g. `#[handle_variant(ChatMsg::Text)]`",
+                )
+            })?;
+            if variant.segments.len() < 2 {
+                return Err(syn::Error::new_spanned(
+                    &variant,
+                    "expected `Enum::Variant` in #[handle_variant(...)]",
+                ));
+            }
+            HandlerRole::Variant(variant)
+        } else if path.is_ident("handle_system_message") || path.is_ident("fallback") {
+            if !matches!(attr.meta, syn::Meta::Path(_)) {
+                return Err(syn::Error::new_spanned(
+                    &attr.meta,
+                    format!("#[{}] takes no arguments", path.to_token_stream()),
+                ));
+            }
+            if path.is_ident("fallback") {
+                HandlerRole::Fallback
+            } else {
+                HandlerRole::System
+            }
+        } else {
+            return Ok(None);
+        };
+        Ok(Some(role))
+    }
+
+    /// Type of the last handler argument.
+    pub(crate) fn argument(&self) -> Type {
+        match self {
+            HandlerRole::Message(ty) => ty.clone(),
+            HandlerRole::Variant(path) => Type::Path(syn::TypePath {
+                qself: None,
+                path: enum_of(path),
+            }),
+            HandlerRole::System => syn::parse_quote!(SystemMessage),
+            HandlerRole::Fallback => syn::parse_quote!(Bytes),
+        }
+    }
+}
+
+/// `Enum` of `Enum::Variant`.
+fn enum_of(variant: &Path) -> Path {
+    let mut path = variant.clone();
+    path.segments.pop();
+    path.segments.pop_punct();
+    path
+}
+
+/// Handlers of an `#[antenna_logic]` impl. Postcard is not self-describing, so every message is
+/// decoded once as `Packet<T>` of the single message type `T` and dispatched by its variant.
+#[derive(Default)]
+struct Dispatch {
+    /// Message type and the attribute that declared it first
+    message_type: Option<(Type, String)>,
+    message: Option<Ident>,
+    variants: Vec<(Path, Ident)>,
+    system: Option<Ident>,
+    fallback: Option<Ident>,
+}
+
+impl Dispatch {
+    fn add(&mut self, role: HandlerRole, attr: &Attribute, method: Ident) -> syn::Result<()> {
+        match role {
+            HandlerRole::Message(ty) => {
+                self.set_message_type(ty, attr)?;
+                Self::set_once(&mut self.message, method, attr, "message handler")
+            }
+            HandlerRole::Variant(path) => {
+                self.set_message_type(
+                    Type::Path(syn::TypePath {
+                        qself: None,
+                        path: enum_of(&path),
+                    }),
+                    attr,
+                )?;
+
+                let name = Self::type_name(&path);
+                if self
+                    .variants
+                    .iter()
+                    .any(|(other, _)| Self::type_name(other) == name)
+                {
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        format!("variant `{}` already has a handler", name),
+                    ));
+                }
+                self.variants.push((path, method));
+                Ok(())
+            }
+            HandlerRole::System => {
+                Self::set_once(&mut self.system, method, attr, "system message handler")
+            }
+            HandlerRole::Fallback => {
+                Self::set_once(&mut self.fallback, method, attr, "fallback handler")
+            }
+        }
+    }
+
+    fn set_once(
+        slot: &mut Option<Ident>,
+        method: Ident,
+        attr: &Attribute,
+        what: &str,
+    ) -> syn::Result<()> {
+        if let Some(existing) = slot {
+            return Err(syn::Error::new_spanned(
+                attr,
+                format!(
+                    "only one {} is allowed, `{}` is one already",
+                    what, existing
+                ),
+            ));
+        }
+        *slot = Some(method);
+        Ok(())
+    }
+
+    fn set_message_type(&mut self, ty: Type, attr: &Attribute) -> syn::Result<()> {
+        let name = Self::type_name(&ty);
+        match &self.message_type {
+            Some((_, existing)) if *existing != name => Err(syn::Error::new_spanned(
+                attr,
+                format!(
+                    "handlers expect both `{}` and `{}`, but a message cannot be told apart by \
+                     type; put them into one enum and use #[handle_variant(...)]",
+                    existing, name
+                ),
+            )),
+            Some(_) => Ok(()),
+            None => {
+                self.message_type = Some((ty, name));
+                Ok(())
+            }
+        }
+    }
+
+    fn type_name(tokens: &impl quote::ToTokens) -> String {
+        quote!(#tokens).to_string().replace(' ', "")
+    }
+
+    fn on_message(&self) -> TokenStream {
+        let fallback = match &self.fallback {
+            Some(method) => quote! { self.#method(ctx, peer_id, data).await },
+            None => quote! { ::antenna::server::log_unhandled_message(&peer_id, &data) },
+        };
+
+        let message_type = match &self.message_type {
+            Some((ty, _)) => quote! { #ty },
+            None => quote! { () },
+        };
+
+        let unmatched = match &self.message {
+            Some(method) => quote! { self.#method(ctx, peer_id, msg).await },
+            None => fallback.clone(),
+        };
+        let user = if self.variants.is_empty() {
+            quote! { #unmatched }
+        } else {
+            let arms = self.variants.iter().map(|(path, method)| {
+                quote! { msg @ #path { .. } => self.#method(ctx, peer_id, msg).await, }
+            });
+            quote! {
+                match msg {
+                    #(#arms)*
+                    #[allow(unused_variables)]
+                    msg => #unmatched,
+                }
+            }
+        };
+        let user = if self.message_type.is_some() {
+            quote! {
+                Packet::User(msg) | Packet::Sequenced { payload: msg, .. } => #user,
+            }
+        } else {
+            quote! {}
+        };
+
+        let system = match &self.system {
+            Some(method) => {
+                quote! { Packet::System(msg) => self.#method(ctx, peer_id, msg).await, }
+            }
+            None => quote! { Packet::System(_) => {} },
+        };
+
+        quote! {
+            use ::antenna::utils::Packet;
+
+            // Trailing bytes mean the message was encoded as another type
+            let packet = match postcard::take_from_bytes::<Packet<#message_type>>(&data) {
+                Ok((packet, rest)) if rest.is_empty() => Some(packet),
+                _ => None,
+            };
+            let Some(packet) = packet else {
+                return #fallback;
+            };
+
+            match packet {
+                #user
+                #system
+                #[allow(unreachable_patterns)]
+                _ => #fallback,
+            }
         }
     }
 }
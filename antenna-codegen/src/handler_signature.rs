@@ -0,0 +1,301 @@
+use crate::antenna_room::HandlerRole;
+use quote::ToTokens;
+use syn::{Attribute, FnArg, PathArguments, ReturnType, Signature, Type};
+
+/// Lifecycle methods of an `#[antenna_logic]` impl forwarded to `RoomBehavior`.
+#[derive(Default)]
+pub struct Hooks {
+    pub on_join: bool,
+    pub on_leave: bool,
+    pub snapshot: bool,
+    pub restore: bool,
+    pub on_tick: bool,
+    pub on_close: bool,
+    pub on_join_request: bool,
+    pub on_stream_open: bool,
+}
+
+impl Hooks {
+    /// Registers a method without handler attributes if it is a lifecycle hook, checking its
+    /// signature. Methods shaped like `on_join` / `on_leave` whose name is one or two typos away
+    /// are rejected, since they would silently never be called; other helpers are left alone.
+    pub fn add(&mut self, sig: &Signature) -> Vec<syn::Error> {
+        let name = sig.ident.to_string();
+        let peer_hook = "(&self, ctx: &RoomContext, peer_id: PeerId)";
+        let (slot, arguments, expected, output) = match name.as_str() {
+            "on_join" => (&mut self.on_join, 2, peer_hook, None),
+            "on_leave" => (&mut self.on_leave, 2, peer_hook, None),
+            "snapshot" => (&mut self.snapshot, 0, "(&self)", Some("Option<Vec<u8>>")),
+            "restore" => (
+                &mut self.restore,
+                1,
+                "(&self, snapshot: &[u8])",
+                Some("anyhow::Result<()>"),
+            ),
+            "on_tick" => (&mut self.on_tick, 1, "(&self, ctx: &RoomContext)", None),
+            "on_close" => (&mut self.on_close, 1, "(&self, ctx: &RoomContext)", None),
+            "on_join_request" => (
+                &mut self.on_join_request,
+                3,
+                "(&self, ctx: &RoomContext, peer_id: &PeerId, metadata: &HashMap<String, String>)",
+                Some("Admission"),
+            ),
+            "on_stream_open" => (
+                &mut self.on_stream_open,
+                5,
+                "(&self, ctx: &RoomContext, peer_id: &PeerId, to: Option<&PeerId>, name: &str, size: u64)",
+                Some("bool"),
+            ),
+            _ if !is_peer_hook_shaped(sig) => return Vec::new(),
+            _ => {
+                return ["on_join", "on_leave"]
+                    .into_iter()
+                    .find(|hook| edit_distance(&name, hook) <= 2)
+                    .map(|hook| {
+                        syn::Error::new_spanned(
+                            &sig.ident,
+                            format!(
+                                "`{}` is not a room hook and would never be called; did you mean `{}`?",
+                                name, hook
+                            ),
+                        )
+                    })
+                    .into_iter()
+                    .collect();
+            }
+        };
+        *slot = true;
+
+        let mut errors = Vec::new();
+        errors.extend(check_async_method(sig).err());
+        let inputs: Vec<&FnArg> = sig.inputs.iter().collect();
+        match (name.as_str(), inputs.len()) {
+            ("on_join" | "on_leave", 3) => {
+                errors.extend(check_context(inputs[1]).err());
+                errors.extend(check_named(inputs[2], "PeerId").err());
+            }
+            ("on_tick" | "on_close", 2) => {
+                errors.extend(check_context(inputs[1]).err());
+            }
+            ("on_join_request", 4) => {
+                errors.extend(check_context(inputs[1]).err());
+                errors.extend(check_reference(inputs[2], "PeerId").err());
+                errors.extend(check_reference(inputs[3], "HashMap<String, String>").err());
+            }
+            ("on_stream_open", 6) => {
+                errors.extend(check_context(inputs[1]).err());
+                errors.extend(check_reference(inputs[2], "PeerId").err());
+                errors.extend(check_named(inputs[3], "Option<&PeerId>").err());
+                errors.extend(check_reference(inputs[4], "str").err());
+                errors.extend(check_named(inputs[5], "u64").err());
+            }
+            (_, len) if len != arguments + 1 => {
+                let output = output.map(|ty| format!(" -> {}", ty)).unwrap_or_default();
+                errors.push(syn::Error::new_spanned(
+                    &sig.inputs,
+                    format!(
+                        "`{}` takes {} argument(s) besides `&self`: `{}{}{}`",
+                        name, arguments, name, expected, output
+                    ),
+                ));
+            }
+            _ => {}
+        }
+        errors.extend(check_output(sig, output).err());
+        errors
+    }
+}
+
+/// Checks that a handler is `async fn name(&self, ctx: &RoomContext, peer_id: PeerId, msg: T)`
+/// with `T` matching its attribute.
+pub fn check_handler(sig: &Signature, role: &HandlerRole, attr: &Attribute) -> syn::Result<()> {
+    check_async_method(sig)?;
+
+    let argument = role.argument();
+    let inputs: Vec<&FnArg> = sig.inputs.iter().collect();
+    if inputs.len() != 4 {
+        return Err(syn::Error::new_spanned(
+            &sig.inputs,
+            format!(
+                "handlers take `(&self, ctx: &RoomContext, peer_id: PeerId, {}: {})`, found {} argument(s) besides `&self`",
+                if matches!(role, HandlerRole::Fallback) {
+                    "data"
+                } else {
+                    "msg"
+                },
+                argument.to_token_stream(),
+                inputs.len().saturating_sub(1)
+            ),
+        ));
+    }
+
+    check_context(inputs[1])?;
+    check_named(inputs[2], "PeerId")?;
+
+    let FnArg::Typed(last) = inputs[3] else {
+        unreachable!("only the first argument can be a receiver");
+    };
+    if last_segment(&last.ty) != last_segment(&argument) {
+        return Err(syn::Error::new_spanned(
+            &last.ty,
+            format!(
+                "expected `{}`, the type named in `{}`",
+                argument.to_token_stream(),
+                attr.to_token_stream()
+            ),
+        ));
+    }
+
+    if let ReturnType::Type(_, ty) = &sig.output {
+        return Err(syn::Error::new_spanned(
+            ty,
+            "handlers must not return a value",
+        ));
+    }
+    Ok(())
+}
+
+/// Checks that a hook returns `expected`, or nothing when `None`, so a mismatch is reported on
+/// the hook rather than inside the generated `RoomBehavior` impl.
+fn check_output(sig: &Signature, expected: Option<&str>) -> syn::Result<()> {
+    let returned = match &sig.output {
+        ReturnType::Type(_, ty) if !matches!(&**ty, Type::Tuple(tuple) if tuple.elems.is_empty()) => {
+            Some(ty)
+        }
+        _ => None,
+    };
+    match (returned, expected) {
+        (None, None) => Ok(()),
+        (Some(ty), None) => Err(syn::Error::new_spanned(
+            ty,
+            format!("`{}` must not return a value", sig.ident),
+        )),
+        (Some(ty), Some(expected))
+            if last_segment(ty) == last_segment(&syn::parse_str(expected)?) =>
+        {
+            Ok(())
+        }
+        (Some(ty), Some(expected)) => Err(syn::Error::new_spanned(
+            ty,
+            format!("`{}` must return `{}`", sig.ident, expected),
+        )),
+        (None, Some(expected)) => Err(syn::Error::new(
+            sig.paren_token.span.close(),
+            format!("`{}` must return `{}`", sig.ident, expected),
+        )),
+    }
+}
+
+fn check_async_method(sig: &Signature) -> syn::Result<()> {
+    if sig.asyncness.is_none() {
+        return Err(syn::Error::new_spanned(
+            sig.fn_token,
+            format!("`{}` must be an `async fn`", sig.ident),
+        ));
+    }
+
+    match sig.inputs.first() {
+        Some(FnArg::Receiver(receiver))
+            if receiver.reference.is_some() && receiver.mutability.is_none() =>
+        {
+            Ok(())
+        }
+        Some(FnArg::Receiver(receiver)) => Err(syn::Error::new_spanned(
+            receiver,
+            "rooms are shared between tasks, take `&self` and use interior mutability",
+        )),
+        _ => Err(syn::Error::new_spanned(
+            &sig.ident,
+            format!("`{}` must take `&self`", sig.ident),
+        )),
+    }
+}
+
+fn check_context(arg: &FnArg) -> syn::Result<()> {
+    let FnArg::Typed(arg) = arg else {
+        unreachable!("only the first argument can be a receiver");
+    };
+    match &*arg.ty {
+        Type::Reference(reference)
+            if reference.mutability.is_none()
+                && last_segment(&reference.elem).as_deref() == Some("RoomContext") =>
+        {
+            Ok(())
+        }
+        ty => Err(syn::Error::new_spanned(ty, "expected `&RoomContext`")),
+    }
+}
+
+fn check_named(arg: &FnArg, expected: &str) -> syn::Result<()> {
+    let FnArg::Typed(arg) = arg else {
+        unreachable!("only the first argument can be a receiver");
+    };
+    if last_segment(&arg.ty).as_deref() == Some(expected) {
+        return Ok(());
+    }
+    Err(syn::Error::new_spanned(
+        &arg.ty,
+        format!("expected `{}`", expected),
+    ))
+}
+
+/// `async fn name(&self, ctx: &RoomContext, peer_id: PeerId)`, the shape of `on_join` and
+/// `on_leave`.
+fn is_peer_hook_shaped(sig: &Signature) -> bool {
+    let inputs: Vec<&FnArg> = sig.inputs.iter().collect();
+    sig.asyncness.is_some()
+        && inputs.len() == 3
+        && matches!(inputs[0], FnArg::Receiver(_))
+        && check_context(inputs[1]).is_ok()
+        && check_named(inputs[2], "PeerId").is_ok()
+}
+
+fn check_reference(arg: &FnArg, expected: &str) -> syn::Result<()> {
+    let FnArg::Typed(arg) = arg else {
+        unreachable!("only the first argument can be a receiver");
+    };
+    match &*arg.ty {
+        Type::Reference(reference)
+            if reference.mutability.is_none()
+                && last_segment(&reference.elem) == Some(expected.replace(' ', "")) =>
+        {
+            Ok(())
+        }
+        ty => Err(syn::Error::new_spanned(
+            ty,
+            format!("expected `&{}`", expected),
+        )),
+    }
+}
+
+/// Last path segment of a type with its generic arguments, e.g. `Vec<u8>` for `std::vec::Vec<u8>`.
+fn last_segment(ty: &Type) -> Option<String> {
+    let Type::Path(path) = ty else {
+        return Some(ty.to_token_stream().to_string());
+    };
+    let segment = path.path.segments.last()?;
+    let arguments = match &segment.arguments {
+        PathArguments::None => String::new(),
+        arguments => arguments.to_token_stream().to_string(),
+    };
+    Some(format!("{}{}", segment.ident, arguments).replace(' ', ""))
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                previous
+            } else {
+                1 + previous.min(row[j]).min(current)
+            };
+            previous = current;
+        }
+    }
+    row[b.len()]
+}
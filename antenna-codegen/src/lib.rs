@@ -2,6 +2,7 @@ use proc_macro::TokenStream;
 
 mod antenna_client;
 mod antenna_room;
+mod handler_signature;
 
 #[proc_macro_attribute]
 pub fn antenna_client(args: TokenStream, input: TokenStream) -> TokenStream {
@@ -0,0 +1,19 @@
+use antenna::server::{RoomBehavior, RoomContext, antenna_logic};
+use antenna::utils::PeerId;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+enum ChatMsg {
+    Text(String),
+    Typing,
+}
+
+struct ChatRoom;
+
+#[antenna_logic]
+impl ChatRoom {
+    #[handle_user_message(42)]
+    async fn message(&self, _ctx: &RoomContext, _peer_id: PeerId, _msg: ChatMsg) {}
+}
+
+fn main() {}
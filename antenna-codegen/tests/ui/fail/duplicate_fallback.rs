@@ -0,0 +1,23 @@
+use antenna::server::{RoomBehavior, RoomContext, antenna_logic};
+use antenna::utils::PeerId;
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+enum ChatMsg {
+    Text(String),
+    Typing,
+}
+
+struct ChatRoom;
+
+#[antenna_logic]
+impl ChatRoom {
+    #[fallback]
+    async fn fallback(&self, _ctx: &RoomContext, _peer_id: PeerId, _data: Bytes) {}
+
+    #[fallback]
+    async fn fallback_again(&self, _ctx: &RoomContext, _peer_id: PeerId, _data: Bytes) {}
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+use antenna::server::{RoomBehavior, RoomContext, antenna_logic};
+use antenna::utils::PeerId;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+enum ChatMsg {
+    Text(String),
+    Typing,
+}
+
+struct ChatRoom;
+
+#[antenna_logic]
+impl ChatRoom {
+    async fn on_leave(&self, _peer_id: PeerId) {}
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+use antenna::server::{Admission, RoomBehavior, RoomContext, antenna_logic};
+use antenna::utils::PeerId;
+use std::collections::HashMap;
+
+struct Lobby;
+
+#[antenna_logic]
+impl Lobby {
+    async fn on_join_request(
+        &self,
+        _ctx: &RoomContext,
+        _peer_id: PeerId,
+        _metadata: &HashMap<String, u32>,
+    ) -> Admission {
+        Admission::Accept
+    }
+}
+
+fn main() {}
@@ -0,0 +1,22 @@
+use antenna::server::{RoomContext, antenna_logic};
+use antenna::utils::PeerId;
+
+struct ChatRoom;
+
+#[antenna_logic]
+impl ChatRoom {
+    async fn on_close(&self, _ctx: &RoomContext, _peer_id: PeerId) {}
+
+    async fn on_stream_open(
+        &self,
+        _ctx: &RoomContext,
+        _peer_id: PeerId,
+        _to: Option<&PeerId>,
+        _name: &str,
+        _size: u64,
+    ) -> bool {
+        true
+    }
+}
+
+fn main() {}
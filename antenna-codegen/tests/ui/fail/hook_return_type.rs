@@ -0,0 +1,27 @@
+use antenna::server::{RoomContext, antenna_logic};
+use antenna::utils::PeerId;
+
+struct ChatRoom;
+
+#[antenna_logic]
+impl ChatRoom {
+    async fn on_join(&self, _ctx: &RoomContext, _peer_id: PeerId) -> bool {
+        true
+    }
+
+    async fn snapshot(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    async fn on_stream_open(
+        &self,
+        _ctx: &RoomContext,
+        _peer_id: &PeerId,
+        _to: Option<&PeerId>,
+        _name: &str,
+        _size: u64,
+    ) {
+    }
+}
+
+fn main() {}
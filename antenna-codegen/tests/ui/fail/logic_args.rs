@@ -0,0 +1,18 @@
+use antenna::server::{RoomBehavior, RoomContext, antenna_logic};
+use antenna::utils::PeerId;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+enum ChatMsg {
+    Text(String),
+    Typing,
+}
+
+struct ChatRoom;
+
+#[antenna_logic(max_peers = 4)]
+impl ChatRoom {
+    async fn on_join(&self, _ctx: &RoomContext, _peer_id: PeerId) {}
+}
+
+fn main() {}
@@ -0,0 +1,22 @@
+use antenna::server::{RoomBehavior, RoomContext, antenna_logic};
+use antenna::utils::PeerId;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+enum ChatMsg {
+    Text(String),
+    Typing,
+}
+
+struct ChatRoom;
+
+#[antenna_logic]
+impl ChatRoom {
+    #[handle_user_message(ChatMsg)]
+    async fn message(&self, _ctx: &RoomContext, _peer_id: PeerId, _msg: ChatMsg) {}
+
+    #[handle_user_message(String)]
+    async fn text(&self, _ctx: &RoomContext, _peer_id: PeerId, _msg: String) {}
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use antenna::server::antenna_room;
+
+#[antenna_room(max_peers = "eight")]
+struct GameRoom;
+
+fn main() {}
@@ -0,0 +1,6 @@
+use antenna::server::antenna_room;
+
+#[antenna_room(max_peers = 8, tickrate = 30)]
+struct GameRoom;
+
+fn main() {}
@@ -0,0 +1,6 @@
+use antenna::server::antenna_room;
+
+#[antenna_room(tick_rate = 0)]
+struct GameRoom;
+
+fn main() {}
@@ -0,0 +1,19 @@
+use antenna::server::{RoomBehavior, RoomContext, antenna_logic};
+use antenna::utils::{PeerId, SystemMessage};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+enum ChatMsg {
+    Text(String),
+    Typing,
+}
+
+struct ChatRoom;
+
+#[antenna_logic]
+impl ChatRoom {
+    #[handle_system_message(ChatMsg)]
+    async fn system(&self, _ctx: &RoomContext, _peer_id: PeerId, _msg: SystemMessage) {}
+}
+
+fn main() {}
@@ -0,0 +1,66 @@
+use antenna::server::{RoomBehavior, RoomContext, antenna_logic};
+use antenna::utils::{PeerId, SystemMessage};
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+enum ChatMsg {
+    Text(String),
+    Typing,
+}
+
+#[derive(Default)]
+struct ChatRoom;
+
+#[antenna_logic]
+impl ChatRoom {
+    async fn on_join(&self, _ctx: &RoomContext, _peer_id: PeerId) {}
+
+    async fn on_leave(&self, _ctx: &RoomContext, _peer_id: PeerId) {}
+
+    #[handle_variant(ChatMsg::Typing)]
+    async fn typing(&self, _ctx: &RoomContext, _peer_id: PeerId, _msg: ChatMsg) {}
+
+    #[handle_user_message(ChatMsg)]
+    async fn message(&self, _ctx: &RoomContext, _peer_id: PeerId, _msg: ChatMsg) {}
+
+    #[handle_system_message]
+    async fn system(&self, _ctx: &RoomContext, _peer_id: PeerId, _msg: SystemMessage) {}
+
+    #[fallback]
+    async fn fallback(&self, _ctx: &RoomContext, _peer_id: PeerId, _data: Bytes) {}
+
+    async fn on_stream_open(
+        &self,
+        _ctx: &RoomContext,
+        _peer_id: &PeerId,
+        to: Option<&PeerId>,
+        _name: &str,
+        size: u64,
+    ) -> bool {
+        to.is_some() && size < 1024
+    }
+
+    async fn on_tick(&self, _ctx: &RoomContext) {}
+
+    async fn on_close(&self, _ctx: &RoomContext) {}
+
+    async fn snapshot(&self) -> Option<Vec<u8>> {
+        None
+    }
+
+    async fn restore(&self, _snapshot: &[u8]) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn helper(&self) {}
+
+    // Not shaped like a hook, so not mistaken for a misspelled `on_join`
+    fn on_joined(&self, _peer_id: &PeerId) -> bool {
+        false
+    }
+}
+
+fn main() {
+    let _: Box<dyn RoomBehavior> = Box::new(ChatRoom);
+}
@@ -0,0 +1,142 @@
+use antenna::server::{
+    Admission, RoomBehavior, RoomConfig, RoomContext, RoomDefinition, antenna_logic, antenna_room,
+};
+use antenna::utils::{Channel, Message, PeerId};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[derive(Serialize, Deserialize)]
+struct ClientMsg(String);
+
+#[derive(Serialize, Deserialize)]
+struct ServerMsg(u64);
+
+impl Message for ClientMsg {
+    fn channel(&self) -> Channel {
+        Channel::Reliable
+    }
+}
+
+impl Message for ServerMsg {
+    fn channel(&self) -> Channel {
+        Channel::Unreliable
+    }
+}
+
+#[antenna_room(max_peers = 8, tick_rate = 30, client = ClientMsg, server = ServerMsg)]
+#[derive(Default)]
+struct GameRoom {
+    ticks: AtomicU64,
+}
+
+#[antenna_logic]
+impl GameRoom {
+    #[handle_user_message(ClientMsg)]
+    async fn message(&self, ctx: &RoomContext, peer_id: PeerId, _msg: ClientMsg) {
+        let ticks = self.ticks.load(Ordering::Relaxed);
+        let _ = Self::send_server_message(ctx, &peer_id, ServerMsg(ticks)).await;
+    }
+
+    async fn on_tick(&self, ctx: &RoomContext) {
+        let ticks = self.ticks.fetch_add(1, Ordering::Relaxed);
+        let _ = Self::broadcast_server_message(ctx, ServerMsg(ticks)).await;
+    }
+}
+
+#[antenna_room(max_peers = 2, wait_list = 4, presence = true, factory = Lobby::open)]
+struct Lobby {
+    name: String,
+}
+
+impl Lobby {
+    fn open() -> Self {
+        Self {
+            name: "lobby".to_string(),
+        }
+    }
+}
+
+#[antenna_logic]
+impl Lobby {
+    async fn on_join_request(
+        &self,
+        _ctx: &RoomContext,
+        _peer_id: &PeerId,
+        metadata: &HashMap<String, String>,
+    ) -> Admission {
+        if metadata.contains_key("ticket") {
+            Admission::Accept
+        } else {
+            Admission::deny(format!("{} needs a ticket", self.name))
+        }
+    }
+}
+
+#[antenna_room]
+#[derive(Default)]
+struct Marker;
+
+#[antenna_logic]
+impl Marker {}
+
+/// Rooms may implement `Default` by hand, the macro does not generate one.
+#[antenna_room(max_peers = 4)]
+struct Arena {
+    capacity: usize,
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        Self { capacity: 4 }
+    }
+}
+
+#[antenna_logic]
+impl Arena {}
+
+/// Generated code names the crate by absolute paths, so a local `antenna` does not shadow it.
+mod shadowed {
+    use ::antenna::server::{Admission, RoomBehavior, RoomContext, antenna_logic, antenna_room};
+    use ::antenna::utils::PeerId;
+    use std::collections::HashMap;
+
+    #[allow(dead_code)]
+    mod antenna {}
+
+    #[antenna_room(max_peers = 2, server = super::ServerMsg)]
+    #[derive(Default)]
+    pub struct Shadowed;
+
+    #[antenna_logic]
+    impl Shadowed {
+        async fn on_join_request(
+            &self,
+            ctx: &RoomContext,
+            peer_id: &PeerId,
+            _metadata: &HashMap<String, String>,
+        ) -> Admission {
+            let _ = Self::send_server_message(ctx, peer_id, super::ServerMsg(0)).await;
+            Admission::Accept
+        }
+
+        async fn restore(&self, _snapshot: &[u8]) -> ::antenna::server::anyhow::Result<()> {
+            Ok(())
+        }
+    }
+}
+
+fn main() {
+    let config: RoomConfig = GameRoom::config();
+    assert_eq!(config.max_peers, Some(8));
+    assert_eq!(config.tick_rate, Some(30));
+    assert!(config.schema.is_some());
+
+    assert_eq!(Lobby::create().name, "lobby");
+    assert!(!config.presence);
+    assert_eq!(Lobby::config().schema, None);
+    assert!(Lobby::config().presence);
+    let _: Box<dyn RoomBehavior> = Box::new(Marker::create());
+    assert_eq!(Arena::create().capacity, 4);
+    assert_eq!(shadowed::Shadowed::config().max_peers, Some(2));
+}
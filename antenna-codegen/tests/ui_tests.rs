@@ -0,0 +1,6 @@
+#[test]
+fn test_antenna_logic_ui() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/pass/*.rs");
+    t.compile_fail("tests/ui/fail/*.rs");
+}
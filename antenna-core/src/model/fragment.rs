@@ -0,0 +1,245 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+
+/// Largest frame put on a data channel. Browsers reliably deliver SCTP messages up to 16 KiB.
+pub const DEFAULT_FRAGMENT_SIZE: usize = 16 * 1024;
+
+/// Default upper bound of a reassembled message.
+pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
+
+/// Default number of partially received messages kept per link; the oldest one is discarded
+/// beyond that.
+pub const DEFAULT_MAX_PARTIAL_MESSAGES: usize = 8;
+
+const KIND_WHOLE: u8 = 0;
+const KIND_FRAGMENT: u8 = 1;
+
+/// Kind byte, message id, fragment index and fragment count.
+pub const FRAGMENT_HEADER_SIZE: usize = 1 + 4 + 4 + 4;
+
+/// Splits `data` into data channel frames no larger than `fragment_size`.
+///
+/// Small messages become a single frame prefixed with one kind byte. Larger ones are cut into
+/// fragments carrying `message_id`, their index and the total count, so a [`Reassembler`] can
+/// restore them even when fragments of several messages interleave.
+pub fn split_frames(message_id: u32, data: &[u8], fragment_size: usize) -> Vec<Vec<u8>> {
+    if data.len() < fragment_size {
+        let mut frame = Vec::with_capacity(data.len() + 1);
+        frame.push(KIND_WHOLE);
+        frame.extend_from_slice(data);
+        return vec![frame];
+    }
+
+    let chunk_size = fragment_size.saturating_sub(FRAGMENT_HEADER_SIZE).max(1);
+    let count = data.len().div_ceil(chunk_size) as u32;
+
+    data.chunks(chunk_size)
+        .enumerate()
+        .map(|(index, chunk)| {
+            let mut frame = Vec::with_capacity(FRAGMENT_HEADER_SIZE + chunk.len());
+            frame.push(KIND_FRAGMENT);
+            frame.extend_from_slice(&message_id.to_be_bytes());
+            frame.extend_from_slice(&(index as u32).to_be_bytes());
+            frame.extend_from_slice(&count.to_be_bytes());
+            frame.extend_from_slice(chunk);
+            frame
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FragmentError {
+    /// The frame header is truncated or inconsistent.
+    Malformed,
+    /// The message grew beyond the configured maximum and was discarded.
+    TooLarge { size: usize, max: usize },
+}
+
+impl fmt::Display for FragmentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FragmentError::Malformed => write!(f, "malformed frame"),
+            FragmentError::TooLarge { size, max } => {
+                write!(
+                    f,
+                    "message of {} bytes exceeds the maximum of {}",
+                    size, max
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for FragmentError {}
+
+struct PartialMessage {
+    count: usize,
+    /// Payload length of every fragment but the last, they are cut to the same size
+    chunk_size: Option<usize>,
+    fragments: BTreeMap<usize, Vec<u8>>,
+    /// Payload bytes received so far
+    size: usize,
+    /// Bytes charged against the buffer limit: the received frames, headers included
+    buffered: usize,
+}
+
+/// Restores messages split by [`split_frames`]. One instance per link.
+///
+/// Buffered frames of all partial messages together, headers included, stay within twice the
+/// maximum message size, so a link never holds more than that regardless of how many messages
+/// interleave or how small their fragments are.
+pub struct Reassembler {
+    max_message_size: usize,
+    max_partial_messages: usize,
+    buffered: usize,
+    partial: HashMap<u32, PartialMessage>,
+    order: VecDeque<u32>,
+}
+
+impl Reassembler {
+    pub fn new(max_message_size: usize) -> Self {
+        Self {
+            max_message_size,
+            max_partial_messages: DEFAULT_MAX_PARTIAL_MESSAGES,
+            buffered: 0,
+            partial: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Number of partially received messages kept before the oldest one is discarded.
+    pub fn with_max_partial_messages(mut self, max_partial_messages: usize) -> Self {
+        self.max_partial_messages = max_partial_messages.max(1);
+        self
+    }
+
+    fn max_buffered(&self) -> usize {
+        self.max_message_size.saturating_mul(2)
+    }
+
+    /// Consumes a frame and returns the message once all of its fragments arrived.
+    pub fn push(&mut self, frame: &[u8]) -> Result<Option<Vec<u8>>, FragmentError> {
+        let (&kind, rest) = frame.split_first().ok_or(FragmentError::Malformed)?;
+
+        match kind {
+            KIND_WHOLE => {
+                if rest.len() > self.max_message_size {
+                    return Err(FragmentError::TooLarge {
+                        size: rest.len(),
+                        max: self.max_message_size,
+                    });
+                }
+                Ok(Some(rest.to_vec()))
+            }
+            KIND_FRAGMENT => self.push_fragment(rest),
+            _ => Err(FragmentError::Malformed),
+        }
+    }
+
+    fn push_fragment(&mut self, rest: &[u8]) -> Result<Option<Vec<u8>>, FragmentError> {
+        if rest.len() < FRAGMENT_HEADER_SIZE - 1 {
+            return Err(FragmentError::Malformed);
+        }
+        let message_id = u32::from_be_bytes(rest[0..4].try_into().unwrap());
+        let index = u32::from_be_bytes(rest[4..8].try_into().unwrap()) as usize;
+        let count = u32::from_be_bytes(rest[8..12].try_into().unwrap()) as usize;
+        let chunk = &rest[12..];
+
+        if index >= count {
+            return Err(FragmentError::Malformed);
+        }
+        // Every fragment but the last carries at least one byte
+        if count - 1 > self.max_message_size {
+            return Err(FragmentError::TooLarge {
+                size: count - 1,
+                max: self.max_message_size,
+            });
+        }
+
+        if !self.partial.contains_key(&message_id) {
+            if self.order.len() >= self.max_partial_messages
+                && let Some(&oldest) = self.order.front()
+            {
+                self.discard(oldest);
+            }
+            self.order.push_back(message_id);
+            self.partial.insert(
+                message_id,
+                PartialMessage {
+                    count,
+                    chunk_size: None,
+                    fragments: BTreeMap::new(),
+                    size: 0,
+                    buffered: 0,
+                },
+            );
+        }
+
+        let message = self.partial.get_mut(&message_id).unwrap();
+        let inconsistent = index + 1 < count
+            && (chunk.is_empty() || message.chunk_size.is_some_and(|size| size != chunk.len()));
+        if message.count != count || inconsistent {
+            self.discard(message_id);
+            return Err(FragmentError::Malformed);
+        }
+
+        if index + 1 < count {
+            message.chunk_size = Some(chunk.len());
+        }
+        if !message.fragments.contains_key(&index) {
+            message.size += chunk.len();
+            message.buffered += FRAGMENT_HEADER_SIZE + chunk.len();
+            message.fragments.insert(index, chunk.to_vec());
+            self.buffered += FRAGMENT_HEADER_SIZE + chunk.len();
+        }
+
+        // The announced count tells the full size once the fragment size is known
+        let announced = message
+            .chunk_size
+            .map_or(0, |size| size.saturating_mul(count - 1));
+        if message.size > self.max_message_size || announced > self.max_message_size {
+            let size = message.size.max(announced);
+            self.discard(message_id);
+            return Err(FragmentError::TooLarge {
+                size,
+                max: self.max_message_size,
+            });
+        }
+
+        // Older partial messages make room for the one that is currently arriving
+        while self.buffered > self.max_buffered()
+            && let Some(&oldest) = self.order.iter().find(|id| **id != message_id)
+        {
+            self.discard(oldest);
+        }
+
+        let message = &self.partial[&message_id];
+
+        if message.fragments.len() < count {
+            return Ok(None);
+        }
+
+        let message = self.partial.remove(&message_id).unwrap();
+        self.order.retain(|id| *id != message_id);
+        self.buffered -= message.buffered;
+
+        let mut data = Vec::with_capacity(message.size);
+        for fragment in message.fragments.into_values() {
+            data.extend_from_slice(&fragment);
+        }
+        Ok(Some(data))
+    }
+
+    fn discard(&mut self, message_id: u32) {
+        if let Some(message) = self.partial.remove(&message_id) {
+            self.buffered -= message.buffered;
+        }
+        self.order.retain(|id| *id != message_id);
+    }
+}
+
+impl Default for Reassembler {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_MESSAGE_SIZE)
+    }
+}
@@ -1,12 +1,34 @@
 //! Shared models of antenna SDK
 
 mod channel;
+mod fragment;
 mod packet;
 mod peer;
+mod protocol;
 mod request;
+mod schema;
+mod sequence;
 mod signaling;
+mod state;
+mod trace;
+mod typescript;
+mod ws_frame;
 
 pub use channel::Channel;
-pub use packet::{Packet, SystemMessage};
+pub use fragment::{
+    DEFAULT_FRAGMENT_SIZE, DEFAULT_MAX_MESSAGE_SIZE, DEFAULT_MAX_PARTIAL_MESSAGES,
+    FRAGMENT_HEADER_SIZE, FragmentError, Reassembler, split_frames,
+};
+pub use packet::{Packet, STREAM_CHUNK_SIZE, SystemMessage};
 pub use peer::PeerId;
-pub use signaling::{IceServerConfig, SignalMessage};
+pub use protocol::{
+    FEATURE_FRAGMENTATION, FEATURE_SEQUENCING, FEATURE_SHARED_STATE, FEATURE_STREAMS,
+    FEATURE_WEBSOCKET_TRANSPORT, JoinRejection, PROTOCOL_VERSION, RejectReason, SUPPORTED_FEATURES,
+    negotiate,
+};
+pub use schema::{protocol_schema, schema_hash};
+pub use sequence::{AckTracker, SequenceHeader};
+pub use signaling::{ErrorCode, IceServerConfig, SignalMessage};
+pub use state::{StateDelta, StateError};
+pub use typescript::TypeExporter;
+pub use ws_frame::{MAX_WS_FRAME_ROOM, decode_ws_frame, encode_ws_frame};
@@ -1,4 +1,6 @@
 use crate::model::peer::PeerId;
+use crate::model::sequence::SequenceHeader;
+use crate::model::state::StateDelta;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -12,12 +14,58 @@ pub enum Packet<T> {
         payload: Vec<u8>,
         is_error: bool,
     },
+    /// User message with a sequence number and acks, for client-side prediction: clients send
+    /// their inputs this way and the room answers with the last input it processed.
+    /// Receivers drop sequenced packets that are not newer than the newest one they got.
+    Sequenced {
+        header: SequenceHeader,
+        payload: T,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub enum SystemMessage {
-    Ping { timestamp: u64 },
-    Pong { timestamp: u64 },
+    Ping {
+        timestamp: u64,
+    },
+    Pong {
+        timestamp: u64,
+    },
     PeerLeft(PeerId),
     PeerJoined(PeerId),
+    /// Starts a transfer relayed by the room to `to`, or to every other peer when `None`.
+    /// `from` is filled in by the server.
+    StreamOpen {
+        stream_id: Uuid,
+        from: Option<PeerId>,
+        to: Option<PeerId>,
+        name: String,
+        size: u64,
+    },
+    StreamChunk {
+        stream_id: Uuid,
+        #[serde(with = "serde_bytes")]
+        data: Vec<u8>,
+    },
+    StreamClose {
+        stream_id: Uuid,
+    },
+    /// Sent instead of `StreamClose` when the transfer was rejected or a side went away.
+    StreamAbort {
+        stream_id: Uuid,
+    },
+    /// Full postcard encoding of the room's shared state, sent to every joining peer.
+    StateSnapshot {
+        version: u64,
+        #[serde(with = "serde_bytes")]
+        data: Vec<u8>,
+    },
+    /// Change of the shared state from `version - 1` to `version`.
+    StatePatch {
+        version: u64,
+        delta: StateDelta,
+    },
 }
+
+/// Payload size of a single `SystemMessage::StreamChunk`.
+pub const STREAM_CHUNK_SIZE: usize = 64 * 1024;
@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Version of the signaling and packet protocol. Bumped on every incompatible wire change.
+pub const PROTOCOL_VERSION: u32 = 3;
+
+/// Binary data frames over the signaling WebSocket (`SignalMessage::UseWebSocketTransport`).
+pub const FEATURE_WEBSOCKET_TRANSPORT: &str = "websocket-transport";
+/// Framing header and fragmentation of data channel messages.
+pub const FEATURE_FRAGMENTATION: &str = "fragmentation";
+/// Relayed `SystemMessage::Stream*` transfers.
+pub const FEATURE_STREAMS: &str = "streams";
+/// `SystemMessage::StateSnapshot` and `SystemMessage::StatePatch` of a room's shared state.
+pub const FEATURE_SHARED_STATE: &str = "shared-state";
+/// `Packet::Sequenced` inputs acknowledged by the room.
+pub const FEATURE_SEQUENCING: &str = "sequencing";
+
+/// Optional capabilities of this build, announced in `Join` and confirmed in `Welcome`.
+pub const SUPPORTED_FEATURES: &[&str] = &[
+    FEATURE_WEBSOCKET_TRANSPORT,
+    FEATURE_FRAGMENTATION,
+    FEATURE_STREAMS,
+    FEATURE_SHARED_STATE,
+    FEATURE_SEQUENCING,
+];
+
+/// Why the server refused a `Join`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum RejectReason {
+    /// Client and server speak different protocol versions.
+    VersionMismatch { server: u32, client: u32 },
+    /// Client and server were built with different message types.
+    SchemaMismatch { server: String, client: String },
+}
+
+impl fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RejectReason::VersionMismatch { server, client } => write!(
+                f,
+                "protocol version mismatch: server speaks v{}, client v{}",
+                server, client
+            ),
+            RejectReason::SchemaMismatch { server, client } => write!(
+                f,
+                "message schema mismatch: server {}, client {}",
+                server, client
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RejectReason {}
+
+/// Why a room refused a peer, carried by `SignalMessage::JoinRejected`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum JoinRejection {
+    /// The room holds `max_peers` peers and its wait-list, if any, is full as well.
+    RoomFull { max_peers: usize },
+    /// The room's `on_join_request` hook refused the peer.
+    Denied { reason: String },
+}
+
+impl fmt::Display for JoinRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JoinRejection::RoomFull { max_peers } => {
+                write!(f, "room is full ({} peers)", max_peers)
+            }
+            JoinRejection::Denied { reason } => write!(f, "join denied: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for JoinRejection {}
+
+/// Server side of the handshake: checks what the client announced in `Join` and returns the
+/// features both sides support. A schema is only compared when both sides provide one.
+pub fn negotiate(
+    version: u32,
+    features: &[String],
+    schema: Option<&str>,
+    server_schema: Option<&str>,
+) -> Result<Vec<String>, RejectReason> {
+    if version != PROTOCOL_VERSION {
+        return Err(RejectReason::VersionMismatch {
+            server: PROTOCOL_VERSION,
+            client: version,
+        });
+    }
+
+    if let (Some(client), Some(server)) = (schema, server_schema)
+        && client != server
+    {
+        return Err(RejectReason::SchemaMismatch {
+            server: server.to_owned(),
+            client: client.to_owned(),
+        });
+    }
+
+    Ok(features
+        .iter()
+        .filter(|feature| SUPPORTED_FEATURES.contains(&feature.as_str()))
+        .cloned()
+        .collect())
+}
@@ -0,0 +1,114 @@
+use crate::model::trace::{self, Content, Declaration, Primitive, Shape, TraceError};
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+/// Structural fingerprint of a message type: field names and types, enum variant names and
+/// order, container names. The hash changes whenever the shape or any of these names change;
+/// containers are named without their module path.
+///
+/// Types that cannot be traced without a self-describing format (`#[serde(flatten)]`,
+/// `#[serde(untagged)]`) fall back to a hash of the type name.
+pub fn schema_hash<T: DeserializeOwned>() -> u64 {
+    match describe::<T>() {
+        Ok(description) => fnv1a(description.as_bytes()),
+        Err(_) => fnv1a(std::any::type_name::<T>().as_bytes()),
+    }
+}
+
+/// Combines the schema hashes of the client and server message types into the hex string
+/// exchanged during the signaling handshake.
+pub fn protocol_schema(client: u64, server: u64) -> String {
+    let mut bytes = client.to_be_bytes().to_vec();
+    bytes.extend_from_slice(&server.to_be_bytes());
+    format!("{:016x}", fnv1a(&bytes))
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Postcard encodes a non-human-readable format, so e.g. `Uuid` is traced as bytes.
+fn describe<T: DeserializeOwned>() -> Result<String, TraceError> {
+    let trace = trace::trace::<T>(false)?;
+
+    // Enums list their variants and describe each one as `Enum::Variant`
+    let mut containers = BTreeMap::new();
+    for (name, declaration) in &trace.declarations {
+        let body = match declaration {
+            Declaration::UnitStruct => "unit".to_owned(),
+            Declaration::Newtype(inner) => shape(inner),
+            Declaration::TupleStruct(shapes) => elements(shapes),
+            Declaration::Struct(fields) => named(fields),
+            Declaration::Enum(variants) => {
+                for (variant, content) in variants {
+                    let content = match content {
+                        Some(Content::Unit) | None => "unit".to_owned(),
+                        Some(Content::Newtype(inner)) => shape(inner),
+                        Some(Content::Tuple(shapes)) => elements(shapes),
+                        Some(Content::Struct(fields)) => named(fields),
+                    };
+                    containers.insert(format!("{}::{}", name, variant), content);
+                }
+                let names: Vec<&str> = variants.iter().map(|(variant, _)| *variant).collect();
+                names.join("|")
+            }
+        };
+        containers.insert((*name).to_owned(), body);
+    }
+
+    let mut description = shape(&trace.root);
+    for (name, body) in &containers {
+        let _ = write!(description, ";{}={}", name, body);
+    }
+    Ok(description)
+}
+
+fn shape(traced: &Shape) -> String {
+    match traced {
+        Shape::Primitive(primitive) => primitive_name(*primitive).to_owned(),
+        Shape::Option(inner) => format!("option<{}>", shape(inner)),
+        Shape::Seq(inner) => format!("seq({})", shape(inner)),
+        Shape::Tuple(shapes) => format!("tuple{}", elements(shapes)),
+        Shape::Map(key, value) => format!("map<{},{}>", shape(key), shape(value)),
+        Shape::Named(name) => (*name).to_owned(),
+    }
+}
+
+fn elements(shapes: &[Shape]) -> String {
+    let shapes: Vec<String> = shapes.iter().map(shape).collect();
+    format!("({})", shapes.join(","))
+}
+
+fn named(fields: &[(&str, Shape)]) -> String {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|(name, field)| format!("{}:{}", name, shape(field)))
+        .collect();
+    format!("({})", fields.join(","))
+}
+
+fn primitive_name(primitive: Primitive) -> &'static str {
+    match primitive {
+        Primitive::Bool => "bool",
+        Primitive::I8 => "i8",
+        Primitive::I16 => "i16",
+        Primitive::I32 => "i32",
+        Primitive::I64 => "i64",
+        Primitive::I128 => "i128",
+        Primitive::U8 => "u8",
+        Primitive::U16 => "u16",
+        Primitive::U32 => "u32",
+        Primitive::U64 => "u64",
+        Primitive::U128 => "u128",
+        Primitive::F32 => "f32",
+        Primitive::F64 => "f64",
+        Primitive::Char => "char",
+        Primitive::Str => "str",
+        Primitive::Bytes => "bytes",
+        Primitive::Unit => "unit",
+        Primitive::Identifier => "id",
+    }
+}
@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+
+/// Sequencing data of a `Packet::Sequenced`: the sender's sequence number plus acknowledgements
+/// of the sequenced packets it received from the other side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SequenceHeader {
+    pub seq: u32,
+    /// Newest sequence number received (on the server: processed) from the other side
+    pub ack: Option<u32>,
+    /// Bit `i` set means `ack - 1 - i` was received as well
+    pub ack_bits: u32,
+}
+
+impl SequenceHeader {
+    /// Every sequence number acknowledged by this header, newest first.
+    pub fn acked(&self) -> impl Iterator<Item = u32> + '_ {
+        self.ack.into_iter().flat_map(move |ack| {
+            std::iter::once(ack).chain(
+                (0..32)
+                    .filter(move |i| self.ack_bits & (1 << i) != 0)
+                    .map(move |i| ack.wrapping_sub(1 + i)),
+            )
+        })
+    }
+}
+
+/// Receiving side of a sequenced stream: remembers the newest sequence number and which of the
+/// 32 before it arrived, to fill `SequenceHeader::ack` and `ack_bits`. Sequence numbers wrap.
+#[derive(Debug, Clone, Default)]
+pub struct AckTracker {
+    latest: Option<u32>,
+    bits: u32,
+}
+
+impl AckTracker {
+    /// Records `seq`, returning `true` when it is newer than everything received so far.
+    /// Late packets still show up in the ack bits, duplicates are ignored.
+    pub fn record(&mut self, seq: u32) -> bool {
+        let Some(latest) = self.latest else {
+            self.latest = Some(seq);
+            return true;
+        };
+
+        let distance = seq.wrapping_sub(latest) as i32;
+        if distance > 0 {
+            let shift = distance as u32;
+            self.bits = if shift > 32 {
+                0
+            } else {
+                // The previous latest becomes bit `shift - 1`
+                ((self.bits as u64) << shift | 1u64 << (shift - 1)) as u32
+            };
+            self.latest = Some(seq);
+            true
+        } else {
+            let behind = distance.unsigned_abs();
+            if (1..=32).contains(&behind) {
+                self.bits |= 1 << (behind - 1);
+            }
+            false
+        }
+    }
+
+    /// Newest sequence number received.
+    pub fn latest(&self) -> Option<u32> {
+        self.latest
+    }
+
+    /// Header of the next outgoing packet `seq`, acknowledging what was received.
+    pub fn header(&self, seq: u32) -> SequenceHeader {
+        SequenceHeader {
+            seq,
+            ack: self.latest,
+            ack_bits: self.bits,
+        }
+    }
+}
@@ -1,5 +1,8 @@
 use crate::model::peer::PeerId;
+use crate::model::protocol::{JoinRejection, RejectReason};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IceServerConfig {
@@ -8,20 +11,149 @@ pub struct IceServerConfig {
     pub credential: Option<String>,
 }
 
+/// What went wrong on the server side, carried by `SignalMessage::Error`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ErrorCode {
+    /// The signaling message could not be parsed.
+    InvalidMessage,
+    /// `Offer`, `IceCandidate`, `UseWebSocketTransport` or `Matchmake` arrived before `Join`.
+    NotJoined,
+    /// The SDP offer was rejected or no answer could be created for it.
+    SdpFailed,
+    /// The server failed to create a transport for the peer.
+    TransportFailed,
+    /// The room directory could not be reached to find the node running the room.
+    RoomUnavailable,
+    /// `Matchmake` named a queue the server does not run.
+    UnknownQueue,
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            ErrorCode::InvalidMessage => "invalid message",
+            ErrorCode::NotJoined => "not joined",
+            ErrorCode::SdpFailed => "SDP negotiation failed",
+            ErrorCode::TransportFailed => "transport creation failed",
+            ErrorCode::RoomUnavailable => "room unavailable",
+            ErrorCode::UnknownQueue => "unknown matchmaking queue",
+        };
+        f.write_str(text)
+    }
+}
+
 /// Set of possible types of signaling messages. Defines the signaling protocol of antenna SDK.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "op", content = "d")]
 pub enum SignalMessage {
     /// Contains STUN/TURN server urls and creds. Sent by the server to the client immediately after connection.
     IceConfig { ice_servers: Vec<IceServerConfig> },
-    /// Client-joining-room message. contains room id what client wants to join.
-    Join { room: String },
-    /// Sent when client initiates peer connection, contains its own SDP string.
-    Offer { sdp: String },
+    /// Client-joining-room message. contains room id what client wants to join, the protocol
+    /// version and optional features the client speaks, the schema hash of its message types and
+    /// application metadata handed to the room's `on_join_request` hook (e.g. a ticket or a name).
+    Join {
+        room: String,
+        #[serde(default)]
+        version: u32,
+        #[serde(default)]
+        features: Vec<String>,
+        #[serde(default)]
+        schema: Option<String>,
+        #[serde(default)]
+        metadata: HashMap<String, String>,
+    },
+    /// Sent when client initiates peer connection, contains its own SDP string. `room` selects the peer
+    /// connection when the client joined several rooms; `None` means the room joined last.
+    Offer {
+        sdp: String,
+        #[serde(default)]
+        room: Option<String>,
+    },
     /// Sent in response of clients offer, contains server SDP string.
-    Answer { sdp: String },
+    Answer {
+        sdp: String,
+        #[serde(default)]
+        room: Option<String>,
+    },
     /// sent by both sides to discover new network paths.
-    IceCandidate { candidate: String },
-    /// Sent by the server to confirm the client has successfully joined the room. Contains new session id (peer_id)
-    Welcome { peer_id: PeerId },
+    IceCandidate {
+        candidate: String,
+        #[serde(default)]
+        room: Option<String>,
+    },
+    /// Sent by the server to confirm the client has successfully joined the room. Contains new session id (peer_id),
+    /// the joined room, the protocol version of the server and the features enabled for this session.
+    Welcome {
+        peer_id: PeerId,
+        #[serde(default)]
+        room: Option<String>,
+        #[serde(default)]
+        version: u32,
+        #[serde(default)]
+        features: Vec<String>,
+    },
+    /// Sent by the server instead of `Welcome` when it cannot serve the client. The connection is closed afterwards.
+    Reject { reason: RejectReason },
+    /// Sent by the server instead of `Answer` when the room refused the peer. The client is expected to leave `room`.
+    JoinRejected {
+        reason: JoinRejection,
+        #[serde(default)]
+        room: Option<String>,
+    },
+    /// Sent by the server instead of `Answer` while the room is full and the peer waits for a free place.
+    /// `position` starts at 1 and is sent again whenever it changes; `Answer` or `Admitted` follows once the peer is
+    /// admitted.
+    WaitListed {
+        position: usize,
+        #[serde(default)]
+        room: Option<String>,
+    },
+    /// Sent by the server instead of `Answer` when a peer on the WebSocket transport (`UseWebSocketTransport`) takes its
+    /// place in `room`, right away or after waiting on the wait-list. Data the room receives before is dropped.
+    Admitted {
+        #[serde(default)]
+        room: Option<String>,
+    },
+    /// Sent by the server instead of `Welcome` when `room` runs on another node of the cluster, and to the peers of a
+    /// room another node took over. The client is expected to join it again through `url`.
+    Redirect {
+        url: String,
+        #[serde(default)]
+        room: Option<String>,
+    },
+    /// Sent by the server when it could not handle a client message. `fatal` errors leave the peer without a
+    /// usable connection to `room` (or to the server when `None`), the client is expected to give up or rejoin.
+    Error {
+        code: ErrorCode,
+        message: String,
+        fatal: bool,
+        #[serde(default)]
+        room: Option<String>,
+    },
+    /// Sent by the client to leave `room`, or every room when `None`, so the room drops it right away instead of
+    /// waiting for a transport timeout. Sent by the server with `None` when it shuts down.
+    Leave {
+        #[serde(default)]
+        room: Option<String>,
+    },
+    /// Sent by the client to be grouped with other players waiting in `queue`. `attributes` are matched by the
+    /// queue's rules, e.g. `skill`. Sending it again replaces the ticket.
+    Matchmake {
+        queue: String,
+        #[serde(default)]
+        attributes: HashMap<String, f64>,
+    },
+    /// Sent by the client to leave the matchmaking `queue`, or every queue when `None`.
+    CancelMatchmake {
+        #[serde(default)]
+        queue: Option<String>,
+    },
+    /// Sent by the server when a match from `queue` was formed. The client is expected to `Join` `room`.
+    MatchFound { queue: String, room: String },
+    /// Sent by the client to carry data packets of `room` as binary frames over this WebSocket instead of WebRTC.
+    /// Every frame names its room (`encode_ws_frame`), so several rooms of a connection can use it.
+    UseWebSocketTransport {
+        #[serde(default)]
+        room: Option<String>,
+    },
 }
@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Change between two postcard encodings of a shared state: `delete` bytes at `offset` are
+/// replaced by `insert`. Produced by [`StateDelta::diff`] from the common prefix and suffix, so
+/// a single changed field costs a few bytes regardless of the state size.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateDelta {
+    pub offset: u32,
+    pub delete: u32,
+    #[serde(with = "serde_bytes")]
+    pub insert: Vec<u8>,
+}
+
+impl StateDelta {
+    pub fn diff(old: &[u8], new: &[u8]) -> Self {
+        let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+        let max_suffix = old.len().min(new.len()) - prefix;
+        let suffix = old
+            .iter()
+            .rev()
+            .zip(new.iter().rev())
+            .take(max_suffix)
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        Self {
+            offset: prefix as u32,
+            delete: (old.len() - prefix - suffix) as u32,
+            insert: new[prefix..new.len() - suffix].to_vec(),
+        }
+    }
+
+    /// `true` when both encodings were equal.
+    pub fn is_empty(&self) -> bool {
+        self.delete == 0 && self.insert.is_empty()
+    }
+
+    /// Turns the old encoding in `base` into the new one.
+    pub fn apply(&self, base: &mut Vec<u8>) -> Result<(), StateError> {
+        let start = self.offset as usize;
+        let end = start + self.delete as usize;
+        if end > base.len() {
+            return Err(StateError::OutOfBounds {
+                end,
+                len: base.len(),
+            });
+        }
+
+        base.splice(start..end, self.insert.iter().copied());
+        Ok(())
+    }
+}
+
+/// A `SystemMessage::StatePatch` that does not fit the local copy of the state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateError {
+    /// The patch does not follow the local version, a patch was missed.
+    VersionGap { expected: u64, got: u64 },
+    /// The patch reaches past the end of the local encoding.
+    OutOfBounds { end: usize, len: usize },
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StateError::VersionGap { expected, got } => {
+                write!(f, "expected state version {}, got {}", expected, got)
+            }
+            StateError::OutOfBounds { end, len } => {
+                write!(f, "patch ends at byte {} of a {} byte state", end, len)
+            }
+        }
+    }
+}
+
+impl std::error::Error for StateError {}
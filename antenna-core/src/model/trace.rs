@@ -0,0 +1,513 @@
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// Nesting depth at which tracing gives up, e.g. on recursive types.
+const MAX_DEPTH: usize = 32;
+
+/// Upper bound of tracing passes needed to visit every enum variant.
+const MAX_PASSES: usize = 256;
+
+/// Value handed to string visitors. Types parsed from strings (`Uuid` and with it `PeerId`)
+/// reject an empty one, the nil UUID satisfies them and plain strings alike.
+const PLACEHOLDER_STR: &str = "00000000-0000-0000-0000-000000000000";
+
+/// What a type asked the deserializer for.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Primitive {
+    Bool,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    F32,
+    F64,
+    Char,
+    Str,
+    Bytes,
+    Unit,
+    /// `deserialize_identifier`, answered with index 0
+    Identifier,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) enum Shape {
+    Primitive(Primitive),
+    Option(Box<Shape>),
+    Seq(Box<Shape>),
+    Tuple(Vec<Shape>),
+    Map(Box<Shape>, Box<Shape>),
+    /// A struct or enum of [`Trace::declarations`]
+    Named(&'static str),
+}
+
+pub(crate) type Fields = Vec<(&'static str, Shape)>;
+
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) enum Declaration {
+    UnitStruct,
+    Newtype(Shape),
+    TupleStruct(Vec<Shape>),
+    Struct(Fields),
+    /// Variants in declaration order; each is filled in by the pass that visits it
+    Enum(Vec<(&'static str, Option<Content>)>),
+}
+
+/// Content of an enum variant.
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) enum Content {
+    Unit,
+    Newtype(Shape),
+    Tuple(Vec<Shape>),
+    Struct(Fields),
+}
+
+/// Shape of a type and of every struct and enum reachable from it, by name.
+pub(crate) struct Trace {
+    pub root: Shape,
+    pub declarations: BTreeMap<&'static str, Declaration>,
+}
+
+/// Walks the `Deserialize` implementation of `T` with placeholder values, once per variant of
+/// its largest enum. `human_readable` is what `T` sees from `is_human_readable`, selecting e.g.
+/// the string or the byte form of `Uuid`.
+pub(crate) fn trace<T: DeserializeOwned>(human_readable: bool) -> Result<Trace, TraceError> {
+    let mut tracer = Tracer {
+        human_readable,
+        declarations: BTreeMap::new(),
+        enum_visits: HashMap::new(),
+        depth: 0,
+    };
+    for _ in 0..MAX_PASSES {
+        let mut root = Shape::Primitive(Primitive::Unit);
+        T::deserialize(TraceDeserializer {
+            tracer: &mut tracer,
+            out: &mut root,
+        })?;
+
+        let complete = tracer
+            .declarations
+            .values()
+            .all(|declaration| match declaration {
+                Declaration::Enum(variants) => {
+                    variants.iter().all(|(_, variant)| variant.is_some())
+                }
+                _ => true,
+            });
+        if complete {
+            return Ok(Trace {
+                root,
+                declarations: tracer.declarations,
+            });
+        }
+    }
+    Err(TraceError("enum variants are not reachable".to_owned()))
+}
+
+struct Tracer {
+    human_readable: bool,
+    declarations: BTreeMap<&'static str, Declaration>,
+    /// Passes through each enum so far, selecting the variant of the next one
+    enum_visits: HashMap<&'static str, usize>,
+    depth: usize,
+}
+
+#[derive(Debug)]
+pub(crate) struct TraceError(String);
+
+impl fmt::Display for TraceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for TraceError {}
+
+impl de::Error for TraceError {
+    fn custom<M: fmt::Display>(msg: M) -> Self {
+        TraceError(msg.to_string())
+    }
+}
+
+/// Deserializer producing placeholder values while recording the shape of what is requested.
+struct TraceDeserializer<'a> {
+    tracer: &'a mut Tracer,
+    out: &'a mut Shape,
+}
+
+impl<'a> TraceDeserializer<'a> {
+    fn enter(&mut self) -> Result<(), TraceError> {
+        self.tracer.depth += 1;
+        if self.tracer.depth > MAX_DEPTH {
+            return Err(TraceError("type nesting is too deep".to_owned()));
+        }
+        Ok(())
+    }
+
+    /// Traces `len` elements into `shapes`.
+    fn visit_elements<'de, V: Visitor<'de>>(
+        mut self,
+        len: usize,
+        shapes: &mut Vec<Shape>,
+        visitor: V,
+    ) -> Result<V::Value, TraceError> {
+        self.enter()?;
+        let value = visitor.visit_seq(TraceSeq {
+            tracer: &mut *self.tracer,
+            shapes,
+            len,
+        })?;
+        self.tracer.depth -= 1;
+        Ok(value)
+    }
+
+    /// Refers to the container by name and records its declaration.
+    fn visit_container<F, R>(self, name: &'static str, body: F) -> Result<R, TraceError>
+    where
+        F: FnOnce(&mut Tracer) -> Result<(R, Declaration), TraceError>,
+    {
+        *self.out = Shape::Named(name);
+        let (value, declaration) = body(&mut *self.tracer)?;
+        self.tracer.declarations.insert(name, declaration);
+        Ok(value)
+    }
+}
+
+/// Traces `len` elements with a fresh deserializer, for containers that are not a shape themselves.
+fn trace_elements<'de, V: Visitor<'de>>(
+    tracer: &mut Tracer,
+    len: usize,
+    visitor: V,
+) -> Result<(V::Value, Vec<Shape>), TraceError> {
+    let mut shapes = Vec::new();
+    let mut unused = Shape::Primitive(Primitive::Unit);
+    let value = TraceDeserializer {
+        tracer,
+        out: &mut unused,
+    }
+    .visit_elements(len, &mut shapes, visitor)?;
+    Ok((value, shapes))
+}
+
+fn named(fields: &'static [&'static str], shapes: Vec<Shape>) -> Fields {
+    fields.iter().copied().zip(shapes).collect()
+}
+
+macro_rules! trace_primitive {
+    ($method:ident, $visit:ident, $primitive:ident, $value:expr) => {
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, TraceError> {
+            *self.out = Shape::Primitive(Primitive::$primitive);
+            visitor.$visit($value)
+        }
+    };
+}
+
+impl<'de, 'a> de::Deserializer<'de> for TraceDeserializer<'a> {
+    type Error = TraceError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, TraceError> {
+        Err(TraceError(
+            "self-describing formats are not supported".to_owned(),
+        ))
+    }
+
+    trace_primitive!(deserialize_bool, visit_bool, Bool, false);
+    trace_primitive!(deserialize_i8, visit_i8, I8, 0);
+    trace_primitive!(deserialize_i16, visit_i16, I16, 0);
+    trace_primitive!(deserialize_i32, visit_i32, I32, 0);
+    trace_primitive!(deserialize_i64, visit_i64, I64, 0);
+    trace_primitive!(deserialize_i128, visit_i128, I128, 0);
+    trace_primitive!(deserialize_u8, visit_u8, U8, 0);
+    trace_primitive!(deserialize_u16, visit_u16, U16, 0);
+    trace_primitive!(deserialize_u32, visit_u32, U32, 0);
+    trace_primitive!(deserialize_u64, visit_u64, U64, 0);
+    trace_primitive!(deserialize_u128, visit_u128, U128, 0);
+    trace_primitive!(deserialize_f32, visit_f32, F32, 0.0);
+    trace_primitive!(deserialize_f64, visit_f64, F64, 0.0);
+    trace_primitive!(deserialize_char, visit_char, Char, '\0');
+    trace_primitive!(deserialize_str, visit_str, Str, PLACEHOLDER_STR);
+    trace_primitive!(deserialize_string, visit_str, Str, PLACEHOLDER_STR);
+    // Fixed-size byte types (e.g. `Uuid`) validate the length, 16 zero bytes satisfy the common ones
+    trace_primitive!(deserialize_bytes, visit_bytes, Bytes, &[0; 16]);
+    trace_primitive!(deserialize_byte_buf, visit_bytes, Bytes, &[0; 16]);
+    // Field and variant visitors accept an index, 0 names the first one
+    trace_primitive!(deserialize_identifier, visit_u32, Identifier, 0);
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, TraceError> {
+        *self.out = Shape::Primitive(Primitive::Unit);
+        visitor.visit_unit()
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, TraceError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(mut self, visitor: V) -> Result<V::Value, TraceError> {
+        self.enter()?;
+        let mut inner = Shape::Primitive(Primitive::Unit);
+        let value = visitor.visit_some(TraceDeserializer {
+            tracer: &mut *self.tracer,
+            out: &mut inner,
+        })?;
+        *self.out = Shape::Option(Box::new(inner));
+        self.tracer.depth -= 1;
+        Ok(value)
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, TraceError> {
+        self.visit_container(name, |_| {
+            Ok((visitor.visit_unit()?, Declaration::UnitStruct))
+        })
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, TraceError> {
+        self.visit_container(name, |tracer| {
+            let mut inner = Shape::Primitive(Primitive::Unit);
+            let value = visitor.visit_newtype_struct(TraceDeserializer {
+                tracer,
+                out: &mut inner,
+            })?;
+            Ok((value, Declaration::Newtype(inner)))
+        })
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, TraceError> {
+        let (value, mut shapes) = trace_elements(self.tracer, 1, visitor)?;
+        let element = shapes.pop().unwrap_or(Shape::Primitive(Primitive::Unit));
+        *self.out = Shape::Seq(Box::new(element));
+        Ok(value)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, TraceError> {
+        let (value, shapes) = trace_elements(self.tracer, len, visitor)?;
+        *self.out = Shape::Tuple(shapes);
+        Ok(value)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, TraceError> {
+        self.visit_container(name, |tracer| {
+            let (value, shapes) = trace_elements(tracer, len, visitor)?;
+            Ok((value, Declaration::TupleStruct(shapes)))
+        })
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(mut self, visitor: V) -> Result<V::Value, TraceError> {
+        self.enter()?;
+        let (mut key, mut entry) = (
+            Shape::Primitive(Primitive::Unit),
+            Shape::Primitive(Primitive::Unit),
+        );
+        let value = visitor.visit_map(TraceMap {
+            tracer: &mut *self.tracer,
+            key: &mut key,
+            value: &mut entry,
+            done: false,
+        })?;
+        *self.out = Shape::Map(Box::new(key), Box::new(entry));
+        self.tracer.depth -= 1;
+        Ok(value)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, TraceError> {
+        self.visit_container(name, |tracer| {
+            let (value, shapes) = trace_elements(tracer, fields.len(), visitor)?;
+            Ok((value, Declaration::Struct(named(fields, shapes))))
+        })
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        mut self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, TraceError> {
+        if variants.is_empty() {
+            return Err(TraceError(format!("enum {} has no variants", name)));
+        }
+        self.enter()?;
+
+        let visits = self.tracer.enum_visits.entry(name).or_default();
+        let index = *visits % variants.len();
+        *visits += 1;
+
+        *self.out = Shape::Named(name);
+        let mut content = None;
+        let value = visitor.visit_enum(TraceEnum {
+            tracer: &mut *self.tracer,
+            out: &mut content,
+            index: index as u32,
+        })?;
+
+        let declaration =
+            self.tracer.declarations.entry(name).or_insert_with(|| {
+                Declaration::Enum(variants.iter().map(|v| (*v, None)).collect())
+            });
+        if let Declaration::Enum(slots) = declaration {
+            slots[index].1 = content;
+        }
+        self.tracer.depth -= 1;
+        Ok(value)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.tracer.human_readable
+    }
+}
+
+struct TraceSeq<'a> {
+    tracer: &'a mut Tracer,
+    shapes: &'a mut Vec<Shape>,
+    len: usize,
+}
+
+impl<'de, 'a> SeqAccess<'de> for TraceSeq<'a> {
+    type Error = TraceError;
+
+    fn next_element_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<Option<S::Value>, TraceError> {
+        if self.shapes.len() >= self.len {
+            return Ok(None);
+        }
+        let mut shape = Shape::Primitive(Primitive::Unit);
+        let value = seed.deserialize(TraceDeserializer {
+            tracer: &mut *self.tracer,
+            out: &mut shape,
+        })?;
+        self.shapes.push(shape);
+        Ok(Some(value))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len - self.shapes.len())
+    }
+}
+
+struct TraceMap<'a> {
+    tracer: &'a mut Tracer,
+    key: &'a mut Shape,
+    value: &'a mut Shape,
+    done: bool,
+}
+
+impl<'de, 'a> MapAccess<'de> for TraceMap<'a> {
+    type Error = TraceError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, TraceError> {
+        if self.done {
+            return Ok(None);
+        }
+        self.done = true;
+        seed.deserialize(TraceDeserializer {
+            tracer: &mut *self.tracer,
+            out: &mut *self.key,
+        })
+        .map(Some)
+    }
+
+    fn next_value_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<S::Value, TraceError> {
+        seed.deserialize(TraceDeserializer {
+            tracer: &mut *self.tracer,
+            out: &mut *self.value,
+        })
+    }
+}
+
+struct TraceEnum<'a> {
+    tracer: &'a mut Tracer,
+    out: &'a mut Option<Content>,
+    index: u32,
+}
+
+impl<'de, 'a> EnumAccess<'de> for TraceEnum<'a> {
+    type Error = TraceError;
+    type Variant = Self;
+
+    fn variant_seed<S: DeserializeSeed<'de>>(
+        self,
+        seed: S,
+    ) -> Result<(S::Value, Self), TraceError> {
+        let variant = seed.deserialize(self.index.into_deserializer())?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de, 'a> VariantAccess<'de> for TraceEnum<'a> {
+    type Error = TraceError;
+
+    fn unit_variant(self) -> Result<(), TraceError> {
+        *self.out = Some(Content::Unit);
+        Ok(())
+    }
+
+    fn newtype_variant_seed<S: DeserializeSeed<'de>>(
+        self,
+        seed: S,
+    ) -> Result<S::Value, TraceError> {
+        let mut shape = Shape::Primitive(Primitive::Unit);
+        let value = seed.deserialize(TraceDeserializer {
+            tracer: self.tracer,
+            out: &mut shape,
+        })?;
+        *self.out = Some(Content::Newtype(shape));
+        Ok(value)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, TraceError> {
+        let (value, shapes) = trace_elements(self.tracer, len, visitor)?;
+        *self.out = Some(Content::Tuple(shapes));
+        Ok(value)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, TraceError> {
+        let (value, shapes) = trace_elements(self.tracer, fields.len(), visitor)?;
+        *self.out = Some(Content::Struct(named(fields, shapes)));
+        Ok(value)
+    }
+}
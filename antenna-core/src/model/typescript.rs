@@ -0,0 +1,288 @@
+use crate::model::trace::{self, Content, Declaration, Primitive, Shape};
+use serde::de::DeserializeOwned;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Writes the registered types of a shared crate as TypeScript declarations.
+///
+/// ```rust,ignore
+/// antenna::utils::export_types!(ChatClientMsg, ChatServerMsg);
+/// ```
+///
+/// The macro defines the `__antenna_types` function `cargo antenna build` calls to collect the
+/// types; structs and enums they contain are exported as well.
+#[macro_export]
+macro_rules! export_types {
+    ($($ty:ty),+ $(,)?) => {
+        #[doc(hidden)]
+        pub fn __antenna_types() -> $crate::TypeExporter {
+            let mut exporter = $crate::TypeExporter::new();
+            $(exporter.register::<$ty>();)+
+            exporter
+        }
+    };
+}
+
+/// TypeScript declarations of message types in the shape `serde_wasm_bindgen` gives their values
+/// in JS, which is what the engine hands to and takes from the browser.
+///
+/// Types are traced through their `Deserialize` implementation, like [`schema_hash`](crate::schema_hash)
+/// does, so field and variant names follow the serde attributes. Types that need a self-describing
+/// format (`#[serde(flatten)]`, `#[serde(tag)]`, `#[serde(untagged)]`) and recursive types cannot be
+/// traced; they are declared as `unknown` and reported by [`TypeExporter::warnings`].
+///
+/// Only the deserializing side is seen: attributes that differ by direction, such as
+/// `rename(serialize = ...)`, `skip_serializing_if`, `skip_deserializing` or `serialize_with`, make
+/// the declarations wrong for values coming from Rust. Keep message types symmetric.
+#[derive(Default)]
+pub struct TypeExporter {
+    /// `None` for types that could not be traced
+    declarations: BTreeMap<String, Option<Declaration>>,
+    warnings: Vec<String>,
+}
+
+impl TypeExporter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declares `T` and every struct and enum reachable from it.
+    pub fn register<T: DeserializeOwned>(&mut self) -> &mut Self {
+        // `serde_wasm_bindgen` is human-readable, so e.g. `Uuid` is traced as a string
+        match trace::trace::<T>(true) {
+            Ok(trace) => {
+                for (name, declaration) in trace.declarations {
+                    let name = name.to_owned();
+                    let declaration = Some(declaration);
+                    match self.declarations.get(&name) {
+                        Some(existing) if *existing != declaration => self.warnings.push(format!(
+                            "{} is declared differently by two types, e.g. generic \
+                             instances; keeping the first",
+                            name
+                        )),
+                        Some(_) => {}
+                        None => {
+                            self.declarations.insert(name, declaration);
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                let name = type_name::<T>();
+                self.warnings.push(format!(
+                    "{} cannot be traced ({}), it is declared as unknown",
+                    name, e
+                ));
+                self.declarations.entry(name).or_insert(None);
+            }
+        }
+        self
+    }
+
+    /// Types that could not be exported as declared in Rust.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// `<Name>.ts` and its content for every declared type.
+    pub fn files(&self) -> Vec<(String, String)> {
+        self.declarations
+            .iter()
+            .map(|(name, declaration)| {
+                let content = render_file(name, declaration.as_ref());
+                (format!("{}.ts", name), content)
+            })
+            .collect()
+    }
+
+    /// Writes [`files`](Self::files) into `dir`, returning their paths.
+    pub fn write_to(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+        fs::create_dir_all(dir)?;
+        self.files()
+            .into_iter()
+            .map(|(file, content)| {
+                let path = dir.join(file);
+                fs::write(&path, content)?;
+                Ok(path)
+            })
+            .collect()
+    }
+}
+
+/// `shared::chat::ChatMsg<u8>` becomes `ChatMsg`.
+fn type_name<T>() -> String {
+    let full = std::any::type_name::<T>();
+    let base = full.split('<').next().unwrap_or(full);
+    base.rsplit("::").next().unwrap_or(base).to_owned()
+}
+
+/// Declares `name` as the JS value `serde_wasm_bindgen::to_value` produces for it, `unknown`
+/// when it could not be traced.
+fn render_file(name: &str, declaration: Option<&Declaration>) -> String {
+    let mut imports = BTreeSet::new();
+    if let Some(declaration) = declaration {
+        collect_imports(declaration, &mut imports);
+    }
+    imports.remove(name);
+
+    let mut file = String::from("// Generated by `cargo antenna build`, do not edit.\n");
+    for import in &imports {
+        let _ = writeln!(file, "import type {{ {0} }} from './{0}';", import);
+    }
+    if !imports.is_empty() {
+        file.push('\n');
+    }
+    // Enums start with a line break before their first variant
+    let body = declaration.map_or_else(|| "unknown".to_owned(), |d| render_declaration(d, 0));
+    let separator = if body.starts_with('\n') { "" } else { " " };
+    let _ = writeln!(file, "export type {} ={}{};", name, separator, body);
+    file
+}
+
+fn collect_imports(declaration: &Declaration, imports: &mut BTreeSet<&'static str>) {
+    fn visit(shape: &Shape, imports: &mut BTreeSet<&'static str>) {
+        match shape {
+            Shape::Named(name) => {
+                imports.insert(name);
+            }
+            Shape::Option(inner) | Shape::Seq(inner) => visit(inner, imports),
+            Shape::Tuple(shapes) => shapes.iter().for_each(|s| visit(s, imports)),
+            Shape::Map(key, value) => {
+                visit(key, imports);
+                visit(value, imports);
+            }
+            Shape::Primitive(_) => {}
+        }
+    }
+
+    match declaration {
+        Declaration::UnitStruct => {}
+        Declaration::Newtype(s) => visit(s, imports),
+        Declaration::TupleStruct(shapes) => shapes.iter().for_each(|s| visit(s, imports)),
+        Declaration::Struct(fields) => fields.iter().for_each(|(_, s)| visit(s, imports)),
+        Declaration::Enum(variants) => {
+            for (_, content) in variants {
+                match content {
+                    Some(Content::Newtype(s)) => visit(s, imports),
+                    Some(Content::Tuple(shapes)) => shapes.iter().for_each(|s| visit(s, imports)),
+                    Some(Content::Struct(fields)) => {
+                        fields.iter().for_each(|(_, s)| visit(s, imports))
+                    }
+                    Some(Content::Unit) | None => {}
+                }
+            }
+        }
+    }
+}
+
+fn render_declaration(declaration: &Declaration, indent: usize) -> String {
+    match declaration {
+        // Unit structs are serialized as `()`
+        Declaration::UnitStruct => "undefined".to_owned(),
+        Declaration::Newtype(shape) => render(shape),
+        Declaration::TupleStruct(shapes) => render_tuple(shapes),
+        Declaration::Struct(fields) => render_object(fields, indent),
+        // Unit variants become their name, others `{ Variant: content }`
+        Declaration::Enum(variants) => {
+            let pad = "    ".repeat(indent + 1);
+            variants
+                .iter()
+                .map(|(variant, content)| {
+                    let content = match content {
+                        Some(Content::Unit) | None => {
+                            return format!("\n{}| {}", pad, quote(variant));
+                        }
+                        Some(Content::Newtype(shape)) => render(shape),
+                        Some(Content::Tuple(shapes)) => render_tuple(shapes),
+                        Some(Content::Struct(fields)) => render_object(fields, indent + 1),
+                    };
+                    format!("\n{}| {{ {}: {} }}", pad, property(variant), content)
+                })
+                .collect()
+        }
+    }
+}
+
+fn render_object(fields: &[(&str, Shape)], indent: usize) -> String {
+    if fields.is_empty() {
+        return "Record<string, never>".to_owned();
+    }
+    let pad = "    ".repeat(indent + 1);
+    let mut body = String::from("{\n");
+    for (field, shape) in fields {
+        let key = property(field);
+        let _ = match shape {
+            Shape::Option(inner) => writeln!(body, "{}{}?: {};", pad, key, render(inner)),
+            shape => writeln!(body, "{}{}: {};", pad, key, render(shape)),
+        };
+    }
+    body.push_str(&"    ".repeat(indent));
+    body.push('}');
+    body
+}
+
+fn render(shape: &Shape) -> String {
+    match shape {
+        Shape::Primitive(primitive) => render_primitive(*primitive).to_owned(),
+        // `None` becomes `undefined`
+        Shape::Option(inner) => format!("{} | undefined", render(inner)),
+        Shape::Seq(inner) => match **inner {
+            Shape::Option(_) => format!("Array<{}>", render(inner)),
+            _ => format!("{}[]", render(inner)),
+        },
+        Shape::Tuple(shapes) => render_tuple(shapes),
+        // Maps become ES `Map`s, not plain objects
+        Shape::Map(key, value) => format!("Map<{}, {}>", render(key), render(value)),
+        Shape::Named(name) => (*name).to_owned(),
+    }
+}
+
+fn render_tuple(shapes: &[Shape]) -> String {
+    let shapes: Vec<String> = shapes.iter().map(render).collect();
+    format!("[{}]", shapes.join(", "))
+}
+
+fn render_primitive(primitive: Primitive) -> &'static str {
+    match primitive {
+        Primitive::Bool => "boolean",
+        // 64-bit values must stay within the safe integer range
+        Primitive::I8
+        | Primitive::I16
+        | Primitive::I32
+        | Primitive::I64
+        | Primitive::U8
+        | Primitive::U16
+        | Primitive::U32
+        | Primitive::U64
+        | Primitive::F32
+        | Primitive::F64 => "number",
+        Primitive::I128 | Primitive::U128 => "bigint",
+        Primitive::Char | Primitive::Str | Primitive::Identifier => "string",
+        // `serde_bytes` and other types deserialized as bytes
+        Primitive::Bytes => "Uint8Array",
+        Primitive::Unit => "undefined",
+    }
+}
+
+/// Object key, quoted unless it is a plain identifier.
+fn property(name: &str) -> String {
+    let identifier = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if identifier {
+        name.to_owned()
+    } else {
+        quote(name)
+    }
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
@@ -0,0 +1,28 @@
+/// Longest room name a WebSocket transport frame can address.
+pub const MAX_WS_FRAME_ROOM: usize = u16::MAX as usize;
+
+/// Prefixes `data` with the room it belongs to, so one signaling WebSocket can carry the data
+/// packets of several rooms (`SignalMessage::UseWebSocketTransport`).
+///
+/// The frame starts with the length of the room name (big-endian `u16`) followed by the name
+/// and the packet. Returns `None` when the name is longer than [`MAX_WS_FRAME_ROOM`].
+pub fn encode_ws_frame(room: &str, data: &[u8]) -> Option<Vec<u8>> {
+    let len = u16::try_from(room.len()).ok()?;
+    let mut frame = Vec::with_capacity(2 + room.len() + data.len());
+    frame.extend_from_slice(&len.to_be_bytes());
+    frame.extend_from_slice(room.as_bytes());
+    frame.extend_from_slice(data);
+    Some(frame)
+}
+
+/// Splits a frame of [`encode_ws_frame`] into its room and packet. `None` if it is truncated or
+/// the room name is not UTF-8.
+pub fn decode_ws_frame(frame: &[u8]) -> Option<(&str, &[u8])> {
+    let (len, rest) = frame.split_first_chunk::<2>()?;
+    let len = u16::from_be_bytes(*len) as usize;
+    if rest.len() < len {
+        return None;
+    }
+    let (room, data) = rest.split_at(len);
+    Some((std::str::from_utf8(room).ok()?, data))
+}
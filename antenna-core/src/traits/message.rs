@@ -1,4 +1,4 @@
-use crate::model::Channel;
+use crate::model::{Channel, schema_hash};
 use serde::{Serialize, de::DeserializeOwned};
 
 /// Base web message trait of the object that is sendable between client and server.
@@ -8,4 +8,13 @@ pub trait Message: Serialize + DeserializeOwned + Send + Sync + 'static {
     fn is_rpc(&self) -> bool {
         false
     }
+
+    /// Fingerprint of the wire format, compared during the signaling handshake. Override for
+    /// types whose layout cannot be traced by [`schema_hash`].
+    fn schema_hash() -> u64
+    where
+        Self: Sized,
+    {
+        schema_hash::<Self>()
+    }
 }
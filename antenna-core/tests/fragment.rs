@@ -0,0 +1,123 @@
+use antenna_core::{FRAGMENT_HEADER_SIZE, FragmentError, Reassembler, split_frames};
+
+/// Four payload bytes per fragment; messages of 17 bytes and more are fragmented.
+const FRAGMENT_SIZE: usize = FRAGMENT_HEADER_SIZE + 4;
+
+fn message(tag: u8) -> Vec<u8> {
+    vec![tag; 20]
+}
+
+fn push_all(reassembler: &mut Reassembler, frames: &[Vec<u8>]) -> Option<Vec<u8>> {
+    let mut result = None;
+    for frame in frames {
+        result = reassembler.push(frame).expect("Valid frame");
+    }
+    result
+}
+
+#[test]
+fn test_interleaved_messages() {
+    let mut reassembler = Reassembler::new(1024);
+    let first = split_frames(1, &message(1), FRAGMENT_SIZE);
+    let second = split_frames(2, &message(2), FRAGMENT_SIZE);
+    assert_eq!(first.len(), 5);
+
+    for (a, b) in first[..4].iter().zip(&second[..4]) {
+        assert_eq!(reassembler.push(a), Ok(None));
+        assert_eq!(reassembler.push(b), Ok(None));
+    }
+    assert_eq!(reassembler.push(&second[4]), Ok(Some(message(2))));
+    assert_eq!(reassembler.push(&first[4]), Ok(Some(message(1))));
+}
+
+#[test]
+fn test_oversized_message() {
+    let mut reassembler = Reassembler::new(16);
+    let frames = split_frames(1, &message(1), FRAGMENT_SIZE);
+
+    assert_eq!(push_all(&mut reassembler, &frames[..4]), None);
+    assert_eq!(
+        reassembler.push(&frames[4]),
+        Err(FragmentError::TooLarge { size: 20, max: 16 })
+    );
+}
+
+#[test]
+fn test_partial_message_limit() {
+    let mut reassembler = Reassembler::new(1024).with_max_partial_messages(2);
+    let messages: Vec<_> = (0..3)
+        .map(|id| split_frames(id, &message(id as u8), FRAGMENT_SIZE))
+        .collect();
+
+    for frames in &messages {
+        assert_eq!(reassembler.push(&frames[0]), Ok(None));
+    }
+
+    // The oldest message was discarded, so its first fragment is missing
+    assert_eq!(push_all(&mut reassembler, &messages[0][1..]), None);
+    assert_eq!(
+        push_all(&mut reassembler, &messages[2][1..]),
+        Some(message(2))
+    );
+}
+
+#[test]
+fn test_buffered_bytes_limit() {
+    // Five frames of 17 bytes per message, two partial messages fit below 200 bytes
+    let mut reassembler = Reassembler::new(100);
+    let stale = split_frames(1, &message(1), FRAGMENT_SIZE);
+    let older = split_frames(2, &message(2), FRAGMENT_SIZE);
+    let fresh = split_frames(3, &message(3), FRAGMENT_SIZE);
+
+    assert_eq!(push_all(&mut reassembler, &stale[..4]), None);
+    assert_eq!(push_all(&mut reassembler, &older[..4]), None);
+    // Going past twice the maximum message size evicts the oldest partial message
+    assert_eq!(push_all(&mut reassembler, &fresh), Some(message(3)));
+    assert_eq!(reassembler.push(&older[4]), Ok(Some(message(2))));
+    assert_eq!(reassembler.push(&stale[4]), Ok(None));
+}
+
+fn fragment(message_id: u32, index: u32, count: u32, chunk: &[u8]) -> Vec<u8> {
+    let mut frame = vec![1];
+    frame.extend_from_slice(&message_id.to_be_bytes());
+    frame.extend_from_slice(&index.to_be_bytes());
+    frame.extend_from_slice(&count.to_be_bytes());
+    frame.extend_from_slice(chunk);
+    frame
+}
+
+#[test]
+fn test_announced_fragment_count() {
+    let mut reassembler = Reassembler::new(16);
+
+    // More fragments than the maximum message size has bytes
+    assert_eq!(
+        reassembler.push(&fragment(1, 0, u32::MAX, &[])),
+        Err(FragmentError::TooLarge {
+            size: u32::MAX as usize - 1,
+            max: 16
+        })
+    );
+    // Empty or uneven fragments before the last one
+    assert_eq!(
+        reassembler.push(&fragment(2, 0, 4, &[])),
+        Err(FragmentError::Malformed)
+    );
+    assert_eq!(reassembler.push(&fragment(3, 0, 4, &[1, 2])), Ok(None));
+    assert_eq!(
+        reassembler.push(&fragment(3, 1, 4, &[1])),
+        Err(FragmentError::Malformed)
+    );
+    // Four-byte fragments make a message of at least 28 bytes
+    assert_eq!(
+        reassembler.push(&fragment(4, 0, 8, &[1, 2, 3, 4])),
+        Err(FragmentError::TooLarge { size: 28, max: 16 })
+    );
+}
+
+#[test]
+fn test_malformed_frame() {
+    let mut reassembler = Reassembler::default();
+    assert_eq!(reassembler.push(&[]), Err(FragmentError::Malformed));
+    assert_eq!(reassembler.push(&[7, 1, 2]), Err(FragmentError::Malformed));
+}
@@ -0,0 +1,161 @@
+#![allow(dead_code)]
+
+use antenna_core::{protocol_schema, schema_hash};
+use serde::Deserialize;
+
+mod base {
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    pub struct Player {
+        pub name: String,
+        pub score: u32,
+    }
+
+    #[derive(Deserialize)]
+    pub enum Action {
+        Move { x: f32, y: f32 },
+        Chat(String),
+        Leave,
+    }
+
+    #[derive(Deserialize)]
+    pub struct Lobby {
+        pub players: Vec<Player>,
+        pub last: Option<Action>,
+    }
+}
+
+mod renamed_field {
+    #[derive(serde::Deserialize)]
+    pub struct Player {
+        pub nick: String,
+        pub score: u32,
+    }
+}
+
+mod reordered_fields {
+    #[derive(serde::Deserialize)]
+    pub struct Player {
+        pub score: u32,
+        pub name: String,
+    }
+}
+
+mod retyped_field {
+    #[derive(serde::Deserialize)]
+    pub struct Player {
+        pub name: String,
+        pub score: u64,
+    }
+}
+
+mod optional_field {
+    #[derive(serde::Deserialize)]
+    pub struct Player {
+        pub name: Option<String>,
+        pub score: u32,
+    }
+}
+
+mod renamed_variant {
+    #[derive(serde::Deserialize)]
+    pub enum Action {
+        Walk { x: f32, y: f32 },
+        Chat(String),
+        Leave,
+    }
+}
+
+mod reordered_variants {
+    #[derive(serde::Deserialize)]
+    pub enum Action {
+        Chat(String),
+        Move { x: f32, y: f32 },
+        Leave,
+    }
+}
+
+mod changed_variant {
+    #[derive(serde::Deserialize)]
+    pub enum Action {
+        Move { x: f32, y: f32, z: f32 },
+        Chat(String),
+        Leave,
+    }
+}
+
+mod nested_change {
+    #[derive(serde::Deserialize)]
+    pub struct Player {
+        pub name: String,
+        pub score: i32,
+    }
+
+    #[derive(serde::Deserialize)]
+    pub struct Lobby {
+        pub players: Vec<Player>,
+        pub last: Option<super::base::Action>,
+    }
+}
+
+mod same_shape {
+    #[derive(serde::Deserialize)]
+    pub struct Player {
+        pub name: String,
+        pub score: u32,
+    }
+}
+
+#[test]
+fn test_struct_fields() {
+    let base = schema_hash::<base::Player>();
+    assert_eq!(base, schema_hash::<base::Player>());
+    assert_ne!(base, schema_hash::<renamed_field::Player>());
+    assert_ne!(base, schema_hash::<reordered_fields::Player>());
+    assert_ne!(base, schema_hash::<retyped_field::Player>());
+    assert_ne!(base, schema_hash::<optional_field::Player>());
+}
+
+#[test]
+fn test_enum_variants() {
+    let base = schema_hash::<base::Action>();
+    assert_ne!(base, schema_hash::<renamed_variant::Action>());
+    assert_ne!(base, schema_hash::<reordered_variants::Action>());
+    assert_ne!(base, schema_hash::<changed_variant::Action>());
+}
+
+#[test]
+fn test_nested_change() {
+    assert_ne!(
+        schema_hash::<base::Lobby>(),
+        schema_hash::<nested_change::Lobby>()
+    );
+}
+
+#[test]
+fn test_container_name_without_module() {
+    #[derive(Deserialize)]
+    struct Gamer {
+        name: String,
+        score: u32,
+    }
+
+    assert_eq!(
+        schema_hash::<base::Player>(),
+        schema_hash::<same_shape::Player>()
+    );
+    assert_ne!(schema_hash::<base::Player>(), schema_hash::<Gamer>());
+}
+
+#[test]
+fn test_protocol_schema() {
+    let client = schema_hash::<base::Action>();
+    let server = schema_hash::<base::Lobby>();
+    let schema = protocol_schema(client, server);
+
+    assert_eq!(schema.len(), 16);
+    assert!(schema.chars().all(|c| c.is_ascii_hexdigit()));
+    assert_eq!(schema, protocol_schema(client, server));
+    assert_ne!(schema, protocol_schema(server, client));
+}
@@ -0,0 +1,59 @@
+use antenna_core::{StateDelta, StateError};
+
+fn roundtrip(old: &[u8], new: &[u8]) -> StateDelta {
+    let delta = StateDelta::diff(old, new);
+    let mut base = old.to_vec();
+    delta.apply(&mut base).expect("Delta fits its base");
+    assert_eq!(base, new);
+    delta
+}
+
+#[test]
+fn test_equal_encodings() {
+    let delta = roundtrip(b"state", b"state");
+    assert!(delta.is_empty());
+}
+
+#[test]
+fn test_changed_middle() {
+    let delta = roundtrip(b"score:10;name:ann", b"score:42;name:ann");
+    assert_eq!(
+        delta,
+        StateDelta {
+            offset: 6,
+            delete: 2,
+            insert: b"42".to_vec(),
+        }
+    );
+}
+
+#[test]
+fn test_grow_and_shrink() {
+    let delta = roundtrip(b"abc", b"abcdef");
+    assert_eq!((delta.offset, delta.delete), (3, 0));
+    assert_eq!(delta.insert, b"def");
+
+    let delta = roundtrip(b"abcdef", b"af");
+    assert_eq!((delta.offset, delta.delete), (1, 4));
+    assert!(delta.insert.is_empty());
+}
+
+#[test]
+fn test_repeated_bytes() {
+    // Prefix and suffix must not overlap when the shorter encoding is all repeats
+    roundtrip(b"aaaa", b"aa");
+    roundtrip(b"aa", b"aaaa");
+    roundtrip(b"", b"new");
+    roundtrip(b"old", b"");
+}
+
+#[test]
+fn test_out_of_bounds() {
+    let delta = StateDelta::diff(b"a longer state", b"a longer stat!");
+    let mut base = b"short".to_vec();
+    assert_eq!(
+        delta.apply(&mut base),
+        Err(StateError::OutOfBounds { end: 14, len: 5 })
+    );
+    assert_eq!(base, b"short");
+}
@@ -0,0 +1,279 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use antenna_core::{PeerId, TypeExporter};
+use serde::Deserializer;
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+struct Primitives {
+    flag: bool,
+    small: i8,
+    count: u32,
+    big: u64,
+    huge: i128,
+    ratio: f32,
+    letter: char,
+    text: String,
+    #[serde(with = "serde_bytes")]
+    data: Vec<u8>,
+    nothing: (),
+}
+
+#[derive(Serialize, Deserialize)]
+struct Collections {
+    list: Vec<u16>,
+    maybe: Option<String>,
+    gaps: Vec<Option<u8>>,
+    pair: (u8, String),
+    scores: HashMap<String, f64>,
+    ordered: BTreeMap<u32, Vec<Point>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Point {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Meters(f64);
+
+#[derive(Serialize, Deserialize)]
+struct Pair(u8, u8);
+
+#[derive(Serialize, Deserialize)]
+struct Marker;
+
+#[derive(Serialize, Deserialize)]
+struct Empty {}
+
+#[derive(Serialize, Deserialize)]
+enum Event {
+    Ping,
+    Moved(Point),
+    Swapped(u8, u8),
+    Said { from: PeerId, text: String },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Renamed {
+    user_name: String,
+    #[serde(rename = "data-id")]
+    data_id: u8,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type")]
+enum Tagged {
+    A,
+}
+
+/// Only reached through the second variant of [`Outer`], i.e. on a later pass.
+#[derive(Serialize, Deserialize)]
+enum Inner {
+    Low,
+    High { level: u8 },
+}
+
+#[derive(Serialize, Deserialize)]
+enum Outer {
+    Idle,
+    Nested(Inner),
+}
+
+/// Deserialized through `deserialize_identifier`, which the tracer answers with index 0.
+struct Key;
+
+impl<'de> Deserialize<'de> for Key {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct KeyVisitor;
+
+        impl Visitor<'_> for KeyVisitor {
+            type Value = Key;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a key")
+            }
+
+            fn visit_u64<E: de::Error>(self, _: u64) -> Result<Key, E> {
+                Ok(Key)
+            }
+
+            fn visit_str<E: de::Error>(self, _: &str) -> Result<Key, E> {
+                Ok(Key)
+            }
+        }
+
+        deserializer.deserialize_identifier(KeyVisitor)
+    }
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct Keyed {
+    key: Key,
+}
+
+fn export<F: FnOnce(&mut TypeExporter)>(register: F) -> TypeExporter {
+    let mut exporter = TypeExporter::new();
+    register(&mut exporter);
+    exporter
+}
+
+fn file(exporter: &TypeExporter, name: &str) -> String {
+    exporter
+        .files()
+        .into_iter()
+        .find(|(file, _)| file == &format!("{}.ts", name))
+        .map(|(_, content)| content)
+        .unwrap_or_else(|| panic!("{} was not exported", name))
+}
+
+const HEADER: &str = "// Generated by `cargo antenna build`, do not edit.\n";
+
+#[test]
+fn exports_primitives() {
+    let exporter = export(|e| {
+        e.register::<Primitives>();
+    });
+
+    assert_eq!(
+        file(&exporter, "Primitives"),
+        format!(
+            "{}export type Primitives = {{\n    flag: boolean;\n    small: number;\n    count: number;\n    big: number;\n    huge: bigint;\n    ratio: number;\n    letter: string;\n    text: string;\n    data: Uint8Array;\n    nothing: undefined;\n}};\n",
+            HEADER
+        )
+    );
+}
+
+#[test]
+fn exports_collections() {
+    let exporter = export(|e| {
+        e.register::<Collections>();
+    });
+
+    assert_eq!(
+        file(&exporter, "Collections"),
+        format!(
+            "{}import type {{ Point }} from './Point';\n\nexport type Collections = {{\n    list: number[];\n    maybe?: string;\n    gaps: Array<number | undefined>;\n    pair: [number, string];\n    scores: Map<string, number>;\n    ordered: Map<number, Point[]>;\n}};\n",
+            HEADER
+        )
+    );
+    assert_eq!(
+        file(&exporter, "Point"),
+        format!(
+            "{}export type Point = {{\n    x: number;\n    y: number;\n}};\n",
+            HEADER
+        )
+    );
+}
+
+#[test]
+fn exports_structs() {
+    let exporter = export(|e| {
+        e.register::<Meters>()
+            .register::<Pair>()
+            .register::<Marker>()
+            .register::<Empty>()
+            .register::<Renamed>();
+    });
+
+    assert_eq!(
+        file(&exporter, "Meters"),
+        format!("{}export type Meters = number;\n", HEADER)
+    );
+    assert_eq!(
+        file(&exporter, "Pair"),
+        format!("{}export type Pair = [number, number];\n", HEADER)
+    );
+    assert_eq!(
+        file(&exporter, "Marker"),
+        format!("{}export type Marker = undefined;\n", HEADER)
+    );
+    assert_eq!(
+        file(&exporter, "Empty"),
+        format!("{}export type Empty = Record<string, never>;\n", HEADER)
+    );
+    assert_eq!(
+        file(&exporter, "Renamed"),
+        format!(
+            "{}export type Renamed = {{\n    userName: string;\n    \"data-id\": number;\n}};\n",
+            HEADER
+        )
+    );
+}
+
+#[test]
+fn exports_enums() {
+    let exporter = export(|e| {
+        e.register::<Event>();
+    });
+
+    assert_eq!(
+        file(&exporter, "Event"),
+        format!(
+            "{}import type {{ PeerId }} from './PeerId';\nimport type {{ Point }} from './Point';\n\nexport type Event =\n    | \"Ping\"\n    | {{ Moved: Point }}\n    | {{ Swapped: [number, number] }}\n    | {{ Said: {{\n        from: PeerId;\n        text: string;\n    }} }};\n",
+            HEADER
+        )
+    );
+    assert_eq!(
+        file(&exporter, "PeerId"),
+        format!("{}export type PeerId = string;\n", HEADER)
+    );
+}
+
+#[test]
+fn exports_enums_reached_on_later_passes() {
+    let exporter = export(|e| {
+        e.register::<Outer>();
+    });
+
+    assert!(exporter.warnings().is_empty());
+    assert_eq!(
+        file(&exporter, "Outer"),
+        format!(
+            "{}import type {{ Inner }} from './Inner';\n\nexport type Outer =\n    | \"Idle\"\n    | {{ Nested: Inner }};\n",
+            HEADER
+        )
+    );
+    assert_eq!(
+        file(&exporter, "Inner"),
+        format!(
+            "{}export type Inner =\n    | \"Low\"\n    | {{ High: {{\n        level: number;\n    }} }};\n",
+            HEADER
+        )
+    );
+}
+
+#[test]
+fn exports_identifiers_as_strings() {
+    let exporter = export(|e| {
+        e.register::<Keyed>();
+    });
+
+    assert_eq!(
+        file(&exporter, "Keyed"),
+        format!("{}export type Keyed = {{\n    key: string;\n}};\n", HEADER)
+    );
+}
+
+#[test]
+fn declares_untraceable_types_unknown() {
+    let exporter = export(|e| {
+        e.register::<Tagged>();
+    });
+
+    assert_eq!(
+        file(&exporter, "Tagged"),
+        format!("{}export type Tagged = unknown;\n", HEADER)
+    );
+    assert_eq!(
+        exporter.warnings(),
+        [
+            "Tagged cannot be traced (self-describing formats are not supported), it is declared as unknown"
+        ]
+    );
+}
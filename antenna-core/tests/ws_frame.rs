@@ -0,0 +1,25 @@
+use antenna_core::{MAX_WS_FRAME_ROOM, decode_ws_frame, encode_ws_frame};
+
+#[test]
+fn test_round_trip() {
+    let frame = encode_ws_frame("lobby", b"hello").expect("Room name fits");
+    assert_eq!(decode_ws_frame(&frame), Some(("lobby", &b"hello"[..])));
+
+    let frame = encode_ws_frame("", b"").expect("Room name fits");
+    assert_eq!(decode_ws_frame(&frame), Some(("", &b""[..])));
+}
+
+#[test]
+fn test_invalid_frames() {
+    assert_eq!(decode_ws_frame(&[]), None);
+    assert_eq!(decode_ws_frame(&[0]), None);
+    // Announces a longer room name than the frame holds
+    assert_eq!(decode_ws_frame(&[0, 5, b'l', b'o']), None);
+    assert_eq!(decode_ws_frame(&[0, 1, 0xff]), None);
+}
+
+#[test]
+fn test_room_name_limit() {
+    assert!(encode_ws_frame(&"a".repeat(MAX_WS_FRAME_ROOM), b"").is_some());
+    assert!(encode_ws_frame(&"a".repeat(MAX_WS_FRAME_ROOM + 1), b"").is_none());
+}
@@ -0,0 +1,77 @@
+use crate::cluster::room_directory::{NodeInfo, RoomDirectory};
+use anyhow::Result;
+use async_trait::async_trait;
+use dashmap::DashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+
+struct Ownership {
+    owner: NodeInfo,
+    /// When another node may take the room over, never without a lease
+    expires: Option<Instant>,
+}
+
+/// `RoomDirectory` kept in memory. Clones share the same map, so nodes running in one process
+/// (e.g. in tests) can use it directly; `DirectoryServer` exposes it to other processes.
+#[derive(Clone, Default)]
+pub struct MemoryRoomDirectory {
+    owners: Arc<DashMap<String, Ownership>>,
+    lease: Option<Duration>,
+}
+
+impl MemoryRoomDirectory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lets ownership expire `lease` after the owner last claimed the room, so the rooms of a
+    /// crashed node can be claimed by others. Nodes renew their claims every
+    /// `RoomManager::with_lease_renewal`, which has to be well below `lease`.
+    pub fn with_lease(mut self, lease: Duration) -> Self {
+        self.lease = Some(lease);
+        self
+    }
+}
+
+impl Ownership {
+    fn expired(&self, now: Instant) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+}
+
+#[async_trait]
+impl RoomDirectory for MemoryRoomDirectory {
+    async fn claim(&self, room_id: &str, node: &NodeInfo) -> Result<NodeInfo> {
+        let now = Instant::now();
+        let mut ownership = self
+            .owners
+            .entry(room_id.to_string())
+            .or_insert_with(|| Ownership {
+                owner: node.clone(),
+                expires: None,
+            });
+        if ownership.owner.id != node.id && ownership.expired(now) {
+            ownership.owner = node.clone();
+        }
+        if ownership.owner.id == node.id {
+            ownership.expires = self.lease.map(|lease| now + lease);
+        }
+        Ok(ownership.owner.clone())
+    }
+
+    async fn release(&self, room_id: &str, node: &NodeInfo) -> Result<()> {
+        self.owners
+            .remove_if(room_id, |_, ownership| ownership.owner.id == node.id);
+        Ok(())
+    }
+
+    async fn lookup(&self, room_id: &str) -> Result<Option<NodeInfo>> {
+        let now = Instant::now();
+        Ok(self
+            .owners
+            .get(room_id)
+            .filter(|ownership| !ownership.expired(now))
+            .map(|ownership| ownership.owner.clone()))
+    }
+}
@@ -0,0 +1,7 @@
+mod memory_directory;
+mod room_directory;
+mod tcp_directory;
+
+pub use memory_directory::*;
+pub use room_directory::*;
+pub use tcp_directory::*;
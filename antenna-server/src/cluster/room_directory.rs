@@ -0,0 +1,54 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Lease of room ownership in a `DirectoryServer` unless configured otherwise.
+pub const DEFAULT_DIRECTORY_LEASE: Duration = Duration::from_secs(30);
+
+/// How often a node claims its rooms again to keep their leases, see
+/// `RoomManager::with_lease_renewal`.
+pub const DEFAULT_LEASE_RENEWAL: Duration = Duration::from_secs(10);
+
+/// A server process taking part in a cluster.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeInfo {
+    /// Unique name of the node, e.g. its hostname.
+    pub id: String,
+    /// Public WebSocket endpoint without the peer id, e.g. `ws://10.0.0.2:3000/ws`. Clients
+    /// redirected to this node connect to `{url}/{peer_id}`.
+    pub url: String,
+    /// Public WebTransport endpoint, sent to QUIC clients instead of `url`.
+    #[serde(default)]
+    pub quic_url: Option<String>,
+}
+
+impl NodeInfo {
+    pub fn new(id: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            url: url.into(),
+            quic_url: None,
+        }
+    }
+
+    pub fn with_quic_url(mut self, quic_url: impl Into<String>) -> Self {
+        self.quic_url = Some(quic_url.into());
+        self
+    }
+}
+
+/// Maps room ids to the node running the room, so all peers of a room end up in the same
+/// process. Shared by every node of the cluster, see `RoomManager::with_directory`.
+#[async_trait]
+pub trait RoomDirectory: Send + Sync + 'static {
+    /// Returns the owner of `room_id`, making `node` the owner when the room has none yet or its
+    /// lease expired. Renews the lease when `node` already owns the room.
+    async fn claim(&self, room_id: &str, node: &NodeInfo) -> Result<NodeInfo>;
+
+    /// Gives up the ownership of `room_id` if `node` holds it.
+    async fn release(&self, room_id: &str, node: &NodeInfo) -> Result<()>;
+
+    /// Returns the owner of `room_id` without claiming it.
+    async fn lookup(&self, room_id: &str) -> Result<Option<NodeInfo>>;
+}
@@ -0,0 +1,261 @@
+use crate::cluster::memory_directory::MemoryRoomDirectory;
+use crate::cluster::room_directory::{DEFAULT_DIRECTORY_LEASE, NodeInfo, RoomDirectory};
+use anyhow::{Context, Result, anyhow, bail};
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+use tokio_util::codec::{FramedRead, LinesCodec};
+use tracing::{info, warn};
+
+/// How long `TcpRoomDirectory` waits for an answer unless configured otherwise.
+pub const DEFAULT_DIRECTORY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Longest request or response line; a room id and two `NodeInfo`s fit many times over.
+const MAX_LINE_LENGTH: usize = 64 * 1024;
+
+/// Idle connections `TcpRoomDirectory` keeps for later requests.
+const MAX_IDLE_CONNECTIONS: usize = 8;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op")]
+enum DirectoryRequest {
+    Claim { room: String, node: NodeInfo },
+    Release { room: String, node: NodeInfo },
+    Lookup { room: String },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op")]
+enum DirectoryResponse {
+    Owner { node: Option<NodeInfo> },
+    Error { message: String },
+}
+
+/// Reference directory service: a `MemoryRoomDirectory` served over TCP as newline-delimited
+/// JSON. Run it once per cluster and point every node at it with `TcpRoomDirectory`.
+///
+/// Ownership is leased for [`DEFAULT_DIRECTORY_LEASE`], see `MemoryRoomDirectory::with_lease`.
+pub struct DirectoryServer {
+    listener: TcpListener,
+    directory: MemoryRoomDirectory,
+}
+
+impl DirectoryServer {
+    pub async fn bind(addr: SocketAddr) -> Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr).await?,
+            directory: MemoryRoomDirectory::new().with_lease(DEFAULT_DIRECTORY_LEASE),
+        })
+    }
+
+    /// How long ownership lasts without being claimed again by its node.
+    pub fn with_lease(mut self, lease: Duration) -> Self {
+        self.directory = self.directory.with_lease(lease);
+        self
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    pub async fn run(self) {
+        info!(
+            "Room directory listening on {:?}",
+            self.listener.local_addr()
+        );
+
+        loop {
+            let (stream, addr) = match self.listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    warn!("Failed to accept directory connection: {:?}", e);
+                    continue;
+                }
+            };
+            let directory = self.directory.clone();
+
+            tokio::spawn(async move {
+                if let Err(e) = serve_connection(stream, directory).await {
+                    warn!("Directory connection {} failed: {:?}", addr, e);
+                }
+            });
+        }
+    }
+}
+
+async fn serve_connection(stream: TcpStream, directory: MemoryRoomDirectory) -> Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = FramedRead::new(reader, LinesCodec::new_with_max_length(MAX_LINE_LENGTH));
+
+    while let Some(line) = lines.next().await.transpose()? {
+        let response = match serde_json::from_str::<DirectoryRequest>(&line) {
+            Ok(request) => handle_request(&directory, request).await,
+            Err(e) => DirectoryResponse::Error {
+                message: e.to_string(),
+            },
+        };
+
+        let mut json = serde_json::to_vec(&response)?;
+        json.push(b'\n');
+        writer.write_all(&json).await?;
+    }
+    Ok(())
+}
+
+async fn handle_request(
+    directory: &MemoryRoomDirectory,
+    request: DirectoryRequest,
+) -> DirectoryResponse {
+    let result = match request {
+        DirectoryRequest::Claim { room, node } => directory.claim(&room, &node).await.map(Some),
+        DirectoryRequest::Release { room, node } => {
+            directory.release(&room, &node).await.map(|_| None)
+        }
+        DirectoryRequest::Lookup { room } => directory.lookup(&room).await,
+    };
+
+    match result {
+        Ok(node) => DirectoryResponse::Owner { node },
+        Err(e) => DirectoryResponse::Error {
+            message: e.to_string(),
+        },
+    }
+}
+
+/// `RoomDirectory` client of a `DirectoryServer`. Every request in flight uses a connection of
+/// its own; up to `MAX_IDLE_CONNECTIONS` of them are kept for later requests, and a failed one is
+/// dropped.
+#[derive(Debug, Clone)]
+pub struct TcpRoomDirectory {
+    addr: SocketAddr,
+    timeout: Duration,
+    idle: Arc<Mutex<Vec<Connection>>>,
+}
+
+#[derive(Debug)]
+struct Connection {
+    lines: FramedRead<OwnedReadHalf, LinesCodec>,
+    writer: OwnedWriteHalf,
+}
+
+impl TcpRoomDirectory {
+    pub fn new(addr: SocketAddr) -> Self {
+        Self {
+            addr,
+            timeout: DEFAULT_DIRECTORY_TIMEOUT,
+            idle: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Upper bound for connecting and answering one request, [`DEFAULT_DIRECTORY_TIMEOUT`] by
+    /// default.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    async fn request(&self, request: &DirectoryRequest) -> Result<Option<NodeInfo>> {
+        let response = loop {
+            let idle = self.idle.lock().await.pop();
+            let reused = idle.is_some();
+            let mut connection = idle;
+            let result =
+                tokio::time::timeout(self.timeout, self.exchange(&mut connection, request)).await;
+            // Whatever failed, the connection may hold half a request or a late answer, so it
+            // is dropped instead of going back to the pool
+            match result {
+                Ok(Ok(response)) => {
+                    let mut idle = self.idle.lock().await;
+                    if let Some(connection) = connection
+                        && idle.len() < MAX_IDLE_CONNECTIONS
+                    {
+                        idle.push(connection);
+                    }
+                    break response;
+                }
+                // The server may have dropped an idle connection, retry once on a new one
+                Ok(Err(_)) if reused => {}
+                Ok(Err(e)) => return Err(e),
+                Err(_) => bail!(
+                    "Room directory {} did not answer within {:?}",
+                    self.addr,
+                    self.timeout
+                ),
+            }
+        };
+
+        match response {
+            DirectoryResponse::Owner { node } => Ok(node),
+            DirectoryResponse::Error { message } => bail!("Room directory error: {}", message),
+        }
+    }
+
+    async fn exchange(
+        &self,
+        connection: &mut Option<Connection>,
+        request: &DirectoryRequest,
+    ) -> Result<DirectoryResponse> {
+        let connection = match connection {
+            Some(connection) => connection,
+            None => {
+                let stream = TcpStream::connect(self.addr).await.with_context(|| {
+                    format!("Failed to connect to room directory {}", self.addr)
+                })?;
+                let (reader, writer) = stream.into_split();
+                connection.insert(Connection {
+                    lines: FramedRead::new(
+                        reader,
+                        LinesCodec::new_with_max_length(MAX_LINE_LENGTH),
+                    ),
+                    writer,
+                })
+            }
+        };
+
+        let mut json = serde_json::to_vec(request)?;
+        json.push(b'\n');
+        connection.writer.write_all(&json).await?;
+
+        let line = connection
+            .lines
+            .next()
+            .await
+            .context("Room directory closed the connection")??;
+        Ok(serde_json::from_str(&line)?)
+    }
+}
+
+#[async_trait]
+impl RoomDirectory for TcpRoomDirectory {
+    async fn claim(&self, room_id: &str, node: &NodeInfo) -> Result<NodeInfo> {
+        let request = DirectoryRequest::Claim {
+            room: room_id.to_string(),
+            node: node.clone(),
+        };
+        self.request(&request)
+            .await?
+            .ok_or_else(|| anyhow!("Room directory returned no owner for '{}'", room_id))
+    }
+
+    async fn release(&self, room_id: &str, node: &NodeInfo) -> Result<()> {
+        let request = DirectoryRequest::Release {
+            room: room_id.to_string(),
+            node: node.clone(),
+        };
+        self.request(&request).await.map(|_| ())
+    }
+
+    async fn lookup(&self, room_id: &str) -> Result<Option<NodeInfo>> {
+        let request = DirectoryRequest::Lookup {
+            room: room_id.to_string(),
+        };
+        self.request(&request).await
+    }
+}
@@ -1,8 +1,12 @@
+mod cluster;
+mod matchmaking;
 mod room;
 mod server;
 mod signaling;
 mod transport;
 
+pub use cluster::*;
+pub use matchmaking::*;
 pub use room::*;
 pub use server::*;
 pub use signaling::*;
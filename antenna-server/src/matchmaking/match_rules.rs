@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+/// How the tickets of a matchmaking queue are grouped into rooms.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchRules {
+    /// Players per room
+    pub size: usize,
+    /// Attribute compared between players; without it players are grouped first come first served
+    pub skill: Option<SkillRange>,
+}
+
+/// Accepted difference of a numeric attribute, widening while a ticket waits.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkillRange {
+    /// Name of the attribute in `SignalMessage::Matchmake`, missing values count as `0.0`
+    pub attribute: String,
+    /// Accepted difference right after the ticket was queued
+    pub initial: f64,
+    /// Growth of the accepted difference per second of waiting
+    pub widening_per_second: f64,
+    /// Upper bound of the accepted difference, unbounded when `None`
+    pub max: Option<f64>,
+}
+
+impl MatchRules {
+    /// Groups `size` players in the order they queued. Panics if `size` is zero.
+    pub fn new(size: usize) -> Self {
+        assert!(size > 0, "match size must not be zero");
+        Self { size, skill: None }
+    }
+
+    /// Only groups players whose `attribute` differs by at most `initial`, plus
+    /// `widening_per_second` for every second the oldest of them waits.
+    pub fn with_skill_range(
+        mut self,
+        attribute: impl Into<String>,
+        initial: f64,
+        widening_per_second: f64,
+    ) -> Self {
+        self.skill = Some(SkillRange {
+            attribute: attribute.into(),
+            initial,
+            widening_per_second,
+            max: None,
+        });
+        self
+    }
+
+    /// Caps the widened skill range. Has no effect without `with_skill_range`.
+    pub fn with_max_skill_range(mut self, max: f64) -> Self {
+        if let Some(skill) = &mut self.skill {
+            skill.max = Some(max);
+        }
+        self
+    }
+}
+
+impl SkillRange {
+    /// Accepted difference for a ticket that waited `waited`.
+    pub fn range_after(&self, waited: Duration) -> f64 {
+        let range = self.initial + self.widening_per_second * waited.as_secs_f64();
+        match self.max {
+            Some(max) => range.min(max),
+            None => range,
+        }
+    }
+}
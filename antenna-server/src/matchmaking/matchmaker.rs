@@ -0,0 +1,346 @@
+use crate::matchmaking::match_rules::MatchRules;
+use crate::{RoomManager, RoomRoute, SignalingService};
+use antenna_core::{ErrorCode, PeerId, SignalMessage};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Once, Weak};
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::{error, info};
+use uuid::Uuid;
+
+/// How often queues are checked again, so that widening skill ranges form new matches.
+pub const DEFAULT_MATCHMAKING_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long the room of a match waits for its first player before it is closed.
+pub const DEFAULT_MATCH_JOIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Upper bound for creating or claiming the room of a match.
+const ROUTE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, thiserror::Error)]
+pub enum MatchmakeError {
+    #[error("unknown matchmaking queue '{0}'")]
+    UnknownQueue(String),
+}
+
+/// A player waiting in a queue.
+#[derive(Clone)]
+struct Ticket {
+    /// Unique per `enqueue`, so a match formed from an outdated ticket can be detected
+    id: u64,
+    peer_id: PeerId,
+    attributes: HashMap<String, f64>,
+    queued_at: Instant,
+}
+
+#[derive(Clone)]
+struct MatchQueue {
+    rules: MatchRules,
+    /// Oldest first
+    tickets: Vec<Ticket>,
+}
+
+/// Groups players sending `SignalMessage::Matchmake` into rooms. Every formed match gets a new
+/// room through the `RoomManager`, announced to its players with `SignalMessage::MatchFound`.
+///
+/// Rooms nobody joined within the join timeout are closed again. The periodic re-matching stops
+/// once every clone of the matchmaker is dropped.
+#[derive(Clone)]
+pub struct Matchmaker {
+    inner: Arc<Inner>,
+    interval: Duration,
+    join_timeout: Duration,
+}
+
+struct Inner {
+    queues: Mutex<HashMap<String, MatchQueue>>,
+    room_manager: RoomManager,
+    signaling_service: SignalingService,
+    next_ticket: AtomicU64,
+    ticker: Once,
+}
+
+impl Matchmaker {
+    pub fn new(room_manager: RoomManager, signaling_service: SignalingService) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                queues: Mutex::new(HashMap::new()),
+                room_manager,
+                signaling_service,
+                next_ticket: AtomicU64::new(0),
+                ticker: Once::new(),
+            }),
+            interval: DEFAULT_MATCHMAKING_INTERVAL,
+            join_timeout: DEFAULT_MATCH_JOIN_TIMEOUT,
+        }
+    }
+
+    pub fn with_queue(self, name: impl Into<String>, rules: MatchRules) -> Self {
+        self.inner.queues.lock().unwrap().insert(
+            name.into(),
+            MatchQueue {
+                rules,
+                tickets: Vec::new(),
+            },
+        );
+        self
+    }
+
+    /// How often waiting tickets are matched again, `DEFAULT_MATCHMAKING_INTERVAL` by default.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// How long a match room may stay empty, `DEFAULT_MATCH_JOIN_TIMEOUT` by default.
+    pub fn with_join_timeout(mut self, join_timeout: Duration) -> Self {
+        self.join_timeout = join_timeout;
+        self
+    }
+
+    /// Puts `peer_id` into `queue`, replacing its previous ticket there, and tries to match it
+    /// right away.
+    pub async fn enqueue(
+        &self,
+        peer_id: PeerId,
+        queue: &str,
+        attributes: HashMap<String, f64>,
+    ) -> Result<(), MatchmakeError> {
+        {
+            let mut queues = self.inner.queues.lock().unwrap();
+            let match_queue = queues
+                .get_mut(queue)
+                .ok_or_else(|| MatchmakeError::UnknownQueue(queue.to_string()))?;
+            match_queue
+                .tickets
+                .retain(|ticket| ticket.peer_id != peer_id);
+            match_queue.tickets.push(Ticket {
+                id: self.inner.next_ticket.fetch_add(1, Ordering::Relaxed),
+                peer_id,
+                attributes,
+                queued_at: Instant::now(),
+            });
+        }
+
+        // The first ticket starts the periodic re-matching
+        self.inner.ticker.call_once(|| {
+            tokio::spawn(Self::run(
+                Arc::downgrade(&self.inner),
+                self.interval,
+                self.join_timeout,
+            ));
+        });
+        self.process().await;
+        Ok(())
+    }
+
+    /// Removes the tickets of `peer_id` from `queue`, or from every queue when `None`.
+    pub fn cancel(&self, peer_id: &PeerId, queue: Option<&str>) {
+        let mut queues = self.inner.queues.lock().unwrap();
+        for (name, match_queue) in queues.iter_mut() {
+            if queue.is_none_or(|queue| queue == name) {
+                match_queue
+                    .tickets
+                    .retain(|ticket| ticket.peer_id != *peer_id);
+            }
+        }
+    }
+
+    /// Players waiting in `queue`.
+    pub fn waiting(&self, queue: &str) -> usize {
+        self.inner
+            .queues
+            .lock()
+            .unwrap()
+            .get(queue)
+            .map_or(0, |match_queue| match_queue.tickets.len())
+    }
+
+    async fn run(inner: Weak<Inner>, interval: Duration, join_timeout: Duration) {
+        let mut timer = tokio::time::interval_at(Instant::now() + interval, interval);
+        loop {
+            timer.tick().await;
+            let Some(inner) = inner.upgrade() else {
+                break;
+            };
+            let matchmaker = Self {
+                inner,
+                interval,
+                join_timeout,
+            };
+            matchmaker.process().await;
+        }
+    }
+
+    /// Forms every match the queues allow and starts their rooms.
+    async fn process(&self) {
+        // Matching is quadratic in the waiting tickets, so it runs on a copy outside the lock
+        let snapshot: Vec<(String, MatchQueue)> = {
+            let queues = self.inner.queues.lock().unwrap();
+            queues
+                .iter()
+                .map(|(name, match_queue)| (name.clone(), match_queue.clone()))
+                .collect()
+        };
+        let now = Instant::now();
+        let mut proposed = Vec::new();
+        for (name, mut match_queue) in snapshot {
+            for tickets in match_queue.take_matches(now) {
+                proposed.push((name.clone(), tickets));
+            }
+        }
+
+        let matches: Vec<(String, Vec<PeerId>)> = {
+            let mut queues = self.inner.queues.lock().unwrap();
+            proposed
+                .into_iter()
+                .filter_map(|(name, tickets)| {
+                    // Tickets cancelled, replaced or matched elsewhere meanwhile void the match
+                    let current = &queues.get(&name)?.tickets;
+                    if !tickets
+                        .iter()
+                        .all(|ticket| current.iter().any(|other| other.id == ticket.id))
+                    {
+                        return None;
+                    }
+                    let players: Vec<PeerId> =
+                        tickets.into_iter().map(|ticket| ticket.peer_id).collect();
+                    // A matched player leaves the other queues too
+                    for match_queue in queues.values_mut() {
+                        match_queue
+                            .tickets
+                            .retain(|ticket| !players.contains(&ticket.peer_id));
+                    }
+                    Some((name, players))
+                })
+                .collect()
+        };
+
+        for (queue, players) in matches {
+            self.start_match(queue, players).await;
+        }
+    }
+
+    async fn start_match(&self, queue: String, players: Vec<PeerId>) {
+        let room = format!("{}-{}", queue, Uuid::new_v4());
+        info!(
+            "Matched {} players of queue '{}' into room {}",
+            players.len(),
+            queue,
+            room
+        );
+
+        // Creates the room, or assigns it to a node of the cluster
+        let route = tokio::time::timeout(ROUTE_TIMEOUT, self.inner.room_manager.route(&room))
+            .await
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("timed out creating the room")));
+        let route = match route {
+            Ok(route) => route,
+            Err(e) => {
+                error!("Failed to create room {} for a match: {:?}", room, e);
+                for peer_id in players {
+                    self.inner.signaling_service.send_error(
+                        peer_id,
+                        Some(room.clone()),
+                        ErrorCode::RoomUnavailable,
+                        e.to_string(),
+                        false,
+                    );
+                }
+                return;
+            }
+        };
+
+        for peer_id in players {
+            self.inner.signaling_service.send_signal(
+                peer_id,
+                SignalMessage::MatchFound {
+                    queue: queue.clone(),
+                    room: room.clone(),
+                },
+            );
+        }
+
+        // Rooms on other nodes are cleaned up by their owner
+        if let RoomRoute::Local(_) = route {
+            let room_manager = self.inner.room_manager.clone();
+            let join_timeout = self.join_timeout;
+            tokio::spawn(async move {
+                tokio::time::sleep(join_timeout).await;
+                if room_manager.close_if_empty(&room).await {
+                    info!("Closed room {} of a match nobody joined", room);
+                }
+            });
+        }
+    }
+}
+
+impl MatchQueue {
+    /// Removes and returns the groups of tickets that can be matched at `now`. Starting with the
+    /// oldest ticket, each ticket is grouped with the closest players within its skill range.
+    fn take_matches(&mut self, now: Instant) -> Vec<Vec<Ticket>> {
+        let mut matches = Vec::new();
+        if self.rules.size == 0 {
+            return matches;
+        }
+
+        let mut anchor = 0;
+        while anchor < self.tickets.len() {
+            let Some(mut picked) = self.pick(anchor, now) else {
+                anchor += 1;
+                continue;
+            };
+            // Tickets before the anchor may have been picked too
+            anchor -= picked.iter().filter(|index| **index < anchor).count();
+            // Back to front, so the remaining indices stay valid
+            picked.sort_unstable_by(|a, b| b.cmp(a));
+            let mut players: Vec<Ticket> = picked
+                .into_iter()
+                .map(|index| self.tickets.remove(index))
+                .collect();
+            players.reverse();
+            matches.push(players);
+        }
+        matches
+    }
+
+    /// Indices of `anchor` and the players closest to it, if there are enough.
+    fn pick(&self, anchor: usize, now: Instant) -> Option<Vec<usize>> {
+        let needed = self.rules.size - 1;
+        let Some(skill) = &self.rules.skill else {
+            let others = (0..self.tickets.len()).filter(|index| *index != anchor);
+            let picked: Vec<usize> = std::iter::once(anchor).chain(others.take(needed)).collect();
+            return (picked.len() == self.rules.size).then_some(picked);
+        };
+
+        let value = |ticket: &Ticket| {
+            ticket
+                .attributes
+                .get(&skill.attribute)
+                .copied()
+                .unwrap_or(0.0)
+        };
+        let center = value(&self.tickets[anchor]);
+        let range = skill.range_after(now - self.tickets[anchor].queued_at);
+
+        let mut candidates: Vec<(f64, usize)> = self
+            .tickets
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| *index != anchor)
+            .map(|(index, ticket)| ((value(ticket) - center).abs(), index))
+            .filter(|(distance, _)| *distance <= range)
+            .collect();
+        if candidates.len() < needed {
+            return None;
+        }
+        candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        Some(
+            std::iter::once(anchor)
+                .chain(candidates.into_iter().take(needed).map(|(_, index)| index))
+                .collect(),
+        )
+    }
+}
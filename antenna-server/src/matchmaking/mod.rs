@@ -0,0 +1,5 @@
+mod match_rules;
+mod matchmaker;
+
+pub use match_rules::*;
+pub use matchmaker::*;
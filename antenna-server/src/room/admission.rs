@@ -0,0 +1,147 @@
+use crate::transport::PeerSender;
+use antenna_core::PeerId;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+/// Total bytes of keys and values a `Join` may carry in its metadata.
+pub const MAX_JOIN_METADATA: usize = 4096;
+
+/// ICE candidates kept per waiting peer until its transport exists.
+const MAX_PENDING_CANDIDATES: usize = 64;
+
+/// Answer of `RoomBehavior::on_join_request`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Admission {
+    /// Let the peer in, or onto the wait-list when the room is full.
+    Accept,
+    /// Refuse the peer; the reason reaches the client in `JoinRejection::Denied`.
+    Deny(String),
+}
+
+impl Admission {
+    pub fn deny(reason: impl Into<String>) -> Self {
+        Admission::Deny(reason.into())
+    }
+}
+
+/// How a joining peer connects: an SDP offer for a room-owned transport or a transport managed
+/// outside the room.
+pub(crate) enum JoinKind {
+    Offer(String),
+    Attach(Arc<dyn PeerSender>),
+}
+
+/// A `JoinRequest` or `Attach` waiting for admission.
+pub(crate) struct PendingJoin {
+    pub peer_id: PeerId,
+    pub metadata: HashMap<String, String>,
+    /// Protocol features negotiated in the peer's handshake
+    pub features: Vec<String>,
+    pub kind: JoinKind,
+    /// Candidates trickled while waiting, added once the transport is created
+    pub candidates: Vec<String>,
+}
+
+impl PendingJoin {
+    pub fn new(
+        peer_id: PeerId,
+        metadata: HashMap<String, String>,
+        features: Vec<String>,
+        kind: JoinKind,
+    ) -> Self {
+        Self {
+            peer_id,
+            metadata,
+            features,
+            kind,
+            candidates: Vec::new(),
+        }
+    }
+
+    pub fn metadata_size(&self) -> usize {
+        self.metadata
+            .iter()
+            .map(|(key, value)| key.len() + value.len())
+            .sum()
+    }
+
+    /// Buffers a candidate, dropping it once [`MAX_PENDING_CANDIDATES`] are held.
+    /// Returns whether it was kept.
+    pub fn add_candidate(&mut self, candidate: String) -> bool {
+        if self.candidates.len() >= MAX_PENDING_CANDIDATES {
+            return false;
+        }
+        self.candidates.push(candidate);
+        true
+    }
+}
+
+/// Accepted peers waiting for a place in a full room, first come first served.
+#[derive(Default)]
+pub(crate) struct WaitList {
+    joins: VecDeque<PendingJoin>,
+}
+
+impl WaitList {
+    pub fn len(&self) -> usize {
+        self.joins.len()
+    }
+
+    /// Queues `join` and returns its 1-based position. A peer already waiting keeps its place
+    /// and only has its request replaced, e.g. after switching to the WebSocket transport.
+    pub fn push(&mut self, join: PendingJoin) -> usize {
+        if let Some(index) = self.position(&join.peer_id) {
+            self.joins[index] = join;
+            return index + 1;
+        }
+        self.joins.push_back(join);
+        self.joins.len()
+    }
+
+    pub fn contains(&self, peer_id: &PeerId) -> bool {
+        self.position(peer_id).is_some()
+    }
+
+    pub fn get(&self, peer_id: &PeerId) -> Option<&PendingJoin> {
+        let index = self.position(peer_id)?;
+        self.joins.get(index)
+    }
+
+    pub fn get_mut(&mut self, peer_id: &PeerId) -> Option<&mut PendingJoin> {
+        let index = self.position(peer_id)?;
+        self.joins.get_mut(index)
+    }
+
+    pub fn remove(&mut self, peer_id: &PeerId) -> Option<PendingJoin> {
+        let index = self.position(peer_id)?;
+        self.joins.remove(index)
+    }
+
+    pub fn pop_front(&mut self) -> Option<PendingJoin> {
+        self.joins.pop_front()
+    }
+
+    pub fn drain(&mut self) -> impl Iterator<Item = PendingJoin> + '_ {
+        self.joins.drain(..)
+    }
+
+    /// Waiting peers with their 1-based positions.
+    pub fn positions(&self) -> impl Iterator<Item = (&PendingJoin, usize)> {
+        self.joins
+            .iter()
+            .enumerate()
+            .map(|(index, join)| (join, index + 1))
+    }
+
+    /// Senders of the waiting peers that attached over their own connection.
+    pub fn attached(&self) -> impl Iterator<Item = (PeerId, Arc<dyn PeerSender>)> + '_ {
+        self.joins.iter().filter_map(|join| match &join.kind {
+            JoinKind::Attach(sender) => Some((join.peer_id.clone(), sender.clone())),
+            JoinKind::Offer(_) => None,
+        })
+    }
+
+    fn position(&self, peer_id: &PeerId) -> Option<usize> {
+        self.joins.iter().position(|join| join.peer_id == *peer_id)
+    }
+}
@@ -0,0 +1,156 @@
+use antenna_core::PeerId;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+/// Grid cell edge used by rooms unless configured otherwise, in world units.
+pub const DEFAULT_INTEREST_CELL_SIZE: f32 = 64.0;
+
+/// Point of a peer in the room's 2D world, see `RoomContext::set_position`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Position {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Position {
+    pub fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    fn distance_squared(&self, other: &Position) -> f32 {
+        (self.x - other.x).powi(2) + (self.y - other.y).powi(2)
+    }
+}
+
+impl From<(f32, f32)> for Position {
+    fn from((x, y): (f32, f32)) -> Self {
+        Self::new(x, y)
+    }
+}
+
+type Cell = (i32, i32);
+
+/// Groups and positions of the peers of a room. Positions are bucketed into a uniform grid,
+/// so a radius query only visits the cells it overlaps.
+pub(crate) struct InterestMap {
+    inner: Mutex<InterestInner>,
+}
+
+struct InterestInner {
+    cell_size: f32,
+    groups: HashMap<PeerId, HashSet<String>>,
+    positions: HashMap<PeerId, Position>,
+    cells: HashMap<Cell, HashSet<PeerId>>,
+}
+
+impl InterestMap {
+    pub(crate) fn new(cell_size: f32) -> Self {
+        Self {
+            inner: Mutex::new(InterestInner {
+                cell_size,
+                groups: HashMap::new(),
+                positions: HashMap::new(),
+                cells: HashMap::new(),
+            }),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, InterestInner> {
+        // The map stays consistent even if a holder panicked, every update is a single step
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub(crate) fn set_groups(&self, peer_id: &PeerId, groups: HashSet<String>) {
+        let mut inner = self.lock();
+        if groups.is_empty() {
+            inner.groups.remove(peer_id);
+        } else {
+            inner.groups.insert(peer_id.clone(), groups);
+        }
+    }
+
+    pub(crate) fn groups(&self, peer_id: &PeerId) -> HashSet<String> {
+        self.lock().groups.get(peer_id).cloned().unwrap_or_default()
+    }
+
+    pub(crate) fn group_members(&self, group: &str) -> Vec<PeerId> {
+        self.lock()
+            .groups
+            .iter()
+            .filter(|(_, groups)| groups.contains(group))
+            .map(|(peer_id, _)| peer_id.clone())
+            .collect()
+    }
+
+    pub(crate) fn set_position(&self, peer_id: &PeerId, position: Position) {
+        let mut inner = self.lock();
+        let cell = inner.cell(&position);
+        if let Some(previous) = inner.positions.insert(peer_id.clone(), position) {
+            let previous = inner.cell(&previous);
+            if previous == cell {
+                return;
+            }
+            inner.leave_cell(previous, peer_id);
+        }
+        inner.cells.entry(cell).or_default().insert(peer_id.clone());
+    }
+
+    pub(crate) fn position(&self, peer_id: &PeerId) -> Option<Position> {
+        self.lock().positions.get(peer_id).copied()
+    }
+
+    pub(crate) fn peers_near(&self, center: Position, radius: f32) -> Vec<PeerId> {
+        let inner = self.lock();
+        let (min_x, min_y) = inner.cell(&Position::new(center.x - radius, center.y - radius));
+        let (max_x, max_y) = inner.cell(&Position::new(center.x + radius, center.y + radius));
+        let radius_squared = radius * radius;
+        let in_range = |peer_id: &&PeerId| {
+            inner.positions[*peer_id].distance_squared(&center) <= radius_squared
+        };
+
+        let covered = (max_x as i64 - min_x as i64 + 1) * (max_y as i64 - min_y as i64 + 1);
+        if covered > inner.cells.len() as i64 {
+            // A radius spanning more cells than are occupied: scanning the occupied ones is cheaper
+            return inner
+                .cells
+                .iter()
+                .filter(|((x, y), _)| (min_x..=max_x).contains(x) && (min_y..=max_y).contains(y))
+                .flat_map(|(_, peers)| peers.iter().filter(in_range).cloned())
+                .collect();
+        }
+
+        (min_x..=max_x)
+            .flat_map(|x| (min_y..=max_y).map(move |y| (x, y)))
+            .filter_map(|cell| inner.cells.get(&cell))
+            .flat_map(|peers| peers.iter().filter(in_range).cloned())
+            .collect()
+    }
+
+    /// Forgets everything about a peer that left.
+    pub(crate) fn remove_peer(&self, peer_id: &PeerId) {
+        let mut inner = self.lock();
+        inner.groups.remove(peer_id);
+        if let Some(position) = inner.positions.remove(peer_id) {
+            let cell = inner.cell(&position);
+            inner.leave_cell(cell, peer_id);
+        }
+    }
+}
+
+impl InterestInner {
+    fn cell(&self, position: &Position) -> Cell {
+        (
+            (position.x / self.cell_size).floor() as i32,
+            (position.y / self.cell_size).floor() as i32,
+        )
+    }
+
+    fn leave_cell(&mut self, cell: Cell, peer_id: &PeerId) {
+        if let Some(peers) = self.cells.get_mut(&cell) {
+            peers.remove(peer_id);
+            if peers.is_empty() {
+                self.cells.remove(&cell);
+            }
+        }
+    }
+}
@@ -1,3 +1,11 @@
+mod interest;
+mod peer_sequence;
 mod room_context;
+mod send_queue;
 
+pub(crate) use interest::InterestMap;
+pub use interest::{DEFAULT_INTEREST_CELL_SIZE, Position};
+pub(crate) use peer_sequence::PeerSequence;
 pub use room_context::RoomContext;
+pub(crate) use send_queue::PeerQueue;
+pub use send_queue::{OverflowPolicy, SendError, SendQueueConfig};
@@ -0,0 +1,27 @@
+use antenna_core::{AckTracker, SequenceHeader};
+
+/// Sequencing state between the room and one peer, see `Packet::Sequenced`.
+#[derive(Debug, Default)]
+pub(crate) struct PeerSequence {
+    /// Inputs of the peer processed by the behavior
+    inputs: AckTracker,
+    /// Sequence number of the next packet sent to the peer
+    next_seq: u32,
+}
+
+impl PeerSequence {
+    /// Whether input `seq` is newer than every input processed so far. Marks it as received.
+    pub(crate) fn accept(&mut self, seq: u32) -> bool {
+        self.inputs.record(seq)
+    }
+
+    pub(crate) fn last_input(&self) -> Option<u32> {
+        self.inputs.latest()
+    }
+
+    pub(crate) fn next_header(&mut self) -> SequenceHeader {
+        let header = self.inputs.header(self.next_seq);
+        self.next_seq = self.next_seq.wrapping_add(1);
+        header
+    }
+}
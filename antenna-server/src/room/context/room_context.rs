@@ -1,15 +1,29 @@
-use antenna_core::PeerId;
+use crate::room::context::interest::{InterestMap, Position};
+use crate::room::context::peer_sequence::PeerSequence;
+use crate::room::context::send_queue::{PeerQueue, SendError};
+use crate::room::shared_state::{SharedState, StateSync};
+use antenna_core::{Channel, Message, Packet, PeerId, SequenceHeader};
 use bytes::Bytes;
 use dashmap::DashMap;
+use futures::future::join_all;
+use std::collections::HashSet;
 use std::fmt;
 use std::sync::Arc;
 use tracing::error;
-use webrtc::data_channel::RTCDataChannel;
 
 #[derive(Clone)]
 pub struct RoomContext {
-    /// Map of available peers data channels in room
-    peers: Arc<DashMap<PeerId, Arc<RTCDataChannel>>>,
+    /// Map of available peers send queues in room
+    peers: Arc<DashMap<PeerId, PeerQueue>>,
+
+    /// Replicated state of the room, if it has one
+    shared_state: Option<Arc<dyn StateSync>>,
+
+    /// Input and output sequence numbers of peers using `Packet::Sequenced`
+    sequences: Arc<DashMap<PeerId, PeerSequence>>,
+
+    /// Groups and positions deciding who receives targeted broadcasts
+    interest: Arc<InterestMap>,
 }
 
 impl fmt::Debug for RoomContext {
@@ -22,39 +36,239 @@ impl fmt::Debug for RoomContext {
 }
 
 impl RoomContext {
-    pub(crate) fn new(peers: Arc<DashMap<PeerId, Arc<RTCDataChannel>>>) -> Self {
-        Self { peers }
+    pub(crate) fn new(
+        peers: Arc<DashMap<PeerId, PeerQueue>>,
+        sequences: Arc<DashMap<PeerId, PeerSequence>>,
+        interest: Arc<InterestMap>,
+    ) -> Self {
+        Self {
+            peers,
+            shared_state: None,
+            sequences,
+            interest,
+        }
     }
 
-    pub async fn send(&self, peer_id: &PeerId, data: Bytes) {
-        if let Some(peer) = self.peers.get(peer_id) {
-            println!("sending data {:?}", data);
+    pub(crate) fn with_shared_state(mut self, shared_state: Option<Arc<dyn StateSync>>) -> Self {
+        self.shared_state = shared_state;
+        self
+    }
 
-            if let Err(e) = peer.send(&data).await {
-                error!("Failed to send message to user {:?}: {}", peer_id, e);
-            }
-        } else {
-            error!(
-                "Attempted to send message to disconnected user {:?}",
-                peer_id
-            );
-        }
+    /// The shared state of the room, `None` if the room has none or it holds another type.
+    pub fn state<S: 'static>(&self) -> Option<SharedState<S>> {
+        self.shared_state
+            .as_ref()?
+            .as_any()
+            .downcast_ref::<SharedState<S>>()
+            .cloned()
+    }
+
+    /// Sequence number of the newest `Packet::Sequenced` input of the peer handed to the
+    /// behavior, `None` before the first one.
+    pub fn last_input(&self, peer_id: &PeerId) -> Option<u32> {
+        self.sequences.get(peer_id)?.last_input()
+    }
+
+    /// Header for the next `Packet::Sequenced` sent to the peer. It acknowledges the inputs
+    /// processed so far, which lets the client drop confirmed predictions and replay the rest.
+    pub fn next_header(&self, peer_id: &PeerId) -> SequenceHeader {
+        self.sequences
+            .entry(peer_id.clone())
+            .or_default()
+            .next_header()
+    }
+
+    /// Queues a reliable message for the peer. Resolves once the peer's send queue accepted it,
+    /// which may wait for the queue to drain when the peer is slow, at most
+    /// `SendQueueConfig::send_timeout`.
+    pub async fn send(&self, peer_id: &PeerId, data: Bytes) -> Result<(), SendError> {
+        self.send_on_channel(peer_id, Channel::Reliable, data).await
+    }
+
+    /// Sends `msg` as a `Packet::User` on the channel it picks with `Message::channel`.
+    pub async fn send_message<T: Message>(
+        &self,
+        peer_id: &PeerId,
+        msg: &T,
+    ) -> Result<(), SendError> {
+        let data = Self::encode_message(msg)?;
+        self.send_on_channel(peer_id, msg.channel(), data).await
+    }
+
+    /// Broadcasts `msg` as a `Packet::User` on the channel it picks with `Message::channel`.
+    pub async fn broadcast_message<T: Message>(&self, msg: &T) -> Result<(), SendError> {
+        let data = Self::encode_message(msg)?;
+        self.broadcast_on_channel(msg.channel(), data).await;
+        Ok(())
     }
 
+    fn encode_message<T: Message>(msg: &T) -> Result<Bytes, SendError> {
+        postcard::to_allocvec(&Packet::User(msg))
+            .map(Bytes::from)
+            .map_err(|e| SendError::Encode(e.to_string()))
+    }
+
+    /// Queues a message for every peer in the room and waits until all queues accepted it. Peers
+    /// whose queue stays full for `SendQueueConfig::broadcast_timeout` miss the message.
     pub async fn broadcast(&self, data: Bytes) {
-        let mut channels = Vec::new();
-        for entry in self.peers.iter() {
-            channels.push(entry.value().clone());
-        }
+        self.broadcast_on_channel(Channel::Reliable, data).await
+    }
+
+    /// Sends with the delivery guarantees and overflow policy of `channel`. Transports with
+    /// several data paths (e.g. QUIC streams vs datagrams) also pick the path by it.
+    pub async fn send_on_channel(
+        &self,
+        peer_id: &PeerId,
+        channel: Channel,
+        data: Bytes,
+    ) -> Result<(), SendError> {
+        let Some(queue) = self.peers.get(peer_id).map(|p| p.value().clone()) else {
+            return Err(SendError::PeerNotFound(peer_id.clone()));
+        };
+
+        queue.enqueue_within_send_timeout(channel, data).await
+    }
+
+    pub async fn broadcast_on_channel(&self, channel: Channel, data: Bytes) {
+        let queues = self
+            .peers
+            .iter()
+            .map(|e| (e.key().clone(), e.value().clone()))
+            .collect();
+        Self::enqueue_all(queues, channel, data).await;
+    }
+
+    /// Queues a message for the listed peers that are still in the room.
+    pub async fn broadcast_to_on_channel(
+        &self,
+        peer_ids: &[PeerId],
+        channel: Channel,
+        data: Bytes,
+    ) {
+        self.try_broadcast_to(peer_ids, channel, data).await;
+    }
 
-        for channel in channels {
-            let data_clone = data.clone();
-            tokio::spawn(async move {
-                if let Err(e) = channel.send(&data_clone).await {
-                    error!("Broadcast error: {}", e);
-                }
-            });
+    /// Like [`RoomContext::broadcast_to_on_channel`], but returns the peers that missed the
+    /// message, so callers can repair what they lost.
+    pub(crate) async fn try_broadcast_to(
+        &self,
+        peer_ids: &[PeerId],
+        channel: Channel,
+        data: Bytes,
+    ) -> Vec<PeerId> {
+        let queues = peer_ids
+            .iter()
+            .filter_map(|peer_id| {
+                self.peers
+                    .get(peer_id)
+                    .map(|p| (peer_id.clone(), p.value().clone()))
+            })
+            .collect();
+        Self::enqueue_all(queues, channel, data).await
+    }
+
+    /// Sends to every peer except `peer_id`, typically the author of the message.
+    pub async fn broadcast_except(&self, peer_id: &PeerId, data: Bytes) {
+        let queues = self
+            .peers
+            .iter()
+            .filter(|e| e.key() != peer_id)
+            .map(|e| (e.key().clone(), e.value().clone()))
+            .collect();
+        Self::enqueue_all(queues, Channel::Reliable, data).await;
+    }
+
+    /// Sends to the members of `group`, see [`RoomContext::set_groups`].
+    pub async fn broadcast_to_group(&self, group: &str, data: Bytes) {
+        let members = self.interest.group_members(group);
+        self.broadcast_to_on_channel(&members, Channel::Reliable, data)
+            .await
+    }
+
+    /// Sends to the peers whose position is within `radius` of `center`. Peers without a
+    /// position receive nothing.
+    pub async fn broadcast_near(&self, center: impl Into<Position>, radius: f32, data: Bytes) {
+        self.broadcast_near_on_channel(center, radius, Channel::Reliable, data)
+            .await
+    }
+
+    pub async fn broadcast_near_on_channel(
+        &self,
+        center: impl Into<Position>,
+        radius: f32,
+        channel: Channel,
+        data: Bytes,
+    ) {
+        let peers = self.peers_near(center, radius);
+        self.broadcast_to_on_channel(&peers, channel, data).await
+    }
+
+    /// Replaces the groups of the peer. Groups live as long as the peer stays in the room.
+    pub fn set_groups<G: Into<String>>(
+        &self,
+        peer_id: &PeerId,
+        groups: impl IntoIterator<Item = G>,
+    ) {
+        let groups = groups.into_iter().map(Into::into).collect();
+        self.interest.set_groups(peer_id, groups);
+    }
+
+    pub fn groups(&self, peer_id: &PeerId) -> HashSet<String> {
+        self.interest.groups(peer_id)
+    }
+
+    pub fn group_members(&self, group: &str) -> Vec<PeerId> {
+        self.interest.group_members(group)
+    }
+
+    /// Moves the peer in the room's interest grid, see [`RoomContext::broadcast_near`].
+    pub fn set_position(&self, peer_id: &PeerId, position: impl Into<Position>) {
+        self.interest.set_position(peer_id, position.into());
+    }
+
+    pub fn position(&self, peer_id: &PeerId) -> Option<Position> {
+        self.interest.position(peer_id)
+    }
+
+    /// Peers within `radius` of `center`, in no particular order.
+    pub fn peers_near(&self, center: impl Into<Position>, radius: f32) -> Vec<PeerId> {
+        self.interest.peers_near(center.into(), radius)
+    }
+
+    /// Queues `data` for every peer at once and returns the peers whose queue did not accept it.
+    async fn enqueue_all(
+        queues: Vec<(PeerId, PeerQueue)>,
+        channel: Channel,
+        data: Bytes,
+    ) -> Vec<PeerId> {
+        let results = join_all(
+            queues
+                .iter()
+                .map(|(_, queue)| queue.enqueue_within_timeout(channel, data.clone())),
+        )
+        .await;
+
+        let mut failed = Vec::new();
+        for ((peer_id, _), result) in queues.into_iter().zip(results) {
+            if let Err(err) = result {
+                error!("Broadcast error: {}", err);
+                failed.push(peer_id);
+            }
         }
+        failed
+    }
+
+    /// Resolves once the peer's send queue is empty, or right away if the peer left.
+    pub(crate) async fn flushed(&self, peer_id: &PeerId) {
+        let Some(queue) = self.peers.get(peer_id).map(|p| p.value().clone()) else {
+            return;
+        };
+        queue.flushed().await
+    }
+
+    /// Bytes waiting in the peer's send queue, `None` if the peer is not connected.
+    pub fn queued_bytes(&self, peer_id: &PeerId) -> Option<usize> {
+        self.peers.get(peer_id).map(|queue| queue.queued_bytes())
     }
 
     pub fn list_users(&self) -> Vec<PeerId> {
@@ -0,0 +1,327 @@
+use crate::transport::PeerSender;
+use antenna_core::{Channel, DEFAULT_MAX_MESSAGE_SIZE, PeerId, split_frames};
+use bytes::Bytes;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::Notify;
+use tracing::error;
+
+/// How often the writer re-checks a transport whose own buffer is above the high watermark.
+const BUFFER_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// What happens to a message that does not fit into a full send queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Evict the oldest queued messages of the same channel to make room.
+    DropOldest,
+    /// Reject the message with `SendError::QueueFull`.
+    Fail,
+    /// Wait until the queue drains below the low watermark.
+    Backpressure,
+}
+
+/// Bounds of the per-peer send queue, in bytes.
+#[derive(Debug, Clone)]
+pub struct SendQueueConfig {
+    /// Queue size at which the overflow policy kicks in. Also the transport buffer size above
+    /// which the writer pauses.
+    pub high_watermark: usize,
+    /// Queue size at which backpressured senders and a paused writer resume.
+    pub low_watermark: usize,
+    /// Policy for `Channel::Reliable` and `Channel::ReliableUnordered`.
+    pub reliable: OverflowPolicy,
+    /// Policy for `Channel::Unreliable`.
+    pub unreliable: OverflowPolicy,
+    /// Largest single message, in both directions. Bigger sends fail with
+    /// `SendError::MessageTooLarge`, bigger incoming messages are dropped during reassembly.
+    pub max_message_size: usize,
+    /// How long a broadcast waits for a backpressured peer before skipping it, so one slow peer
+    /// does not stall the room for everyone else.
+    pub broadcast_timeout: Duration,
+    /// How long a send to a single peer waits for its backpressured queue before failing with
+    /// `SendError::Timeout`. The room handles nothing else meanwhile.
+    pub send_timeout: Duration,
+}
+
+impl SendQueueConfig {
+    pub fn policy(&self, channel: Channel) -> OverflowPolicy {
+        match channel {
+            Channel::Unreliable => self.unreliable,
+            Channel::Reliable | Channel::ReliableUnordered => self.reliable,
+        }
+    }
+}
+
+impl Default for SendQueueConfig {
+    fn default() -> Self {
+        Self {
+            high_watermark: 1024 * 1024,
+            low_watermark: 256 * 1024,
+            reliable: OverflowPolicy::Backpressure,
+            unreliable: OverflowPolicy::DropOldest,
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+            broadcast_timeout: Duration::from_millis(250),
+            send_timeout: Duration::from_secs(1),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SendError {
+    #[error("peer {0} is not connected")]
+    PeerNotFound(PeerId),
+    #[error("send queue of peer {0} is full")]
+    QueueFull(PeerId),
+    #[error("peer {0} disconnected before the message was accepted")]
+    Closed(PeerId),
+    #[error("send queue of peer {0} stayed full until the send timed out")]
+    Timeout(PeerId),
+    #[error("message of {size} bytes exceeds the maximum message size of {max}")]
+    MessageTooLarge { size: usize, max: usize },
+    #[error("failed to encode message: {0}")]
+    Encode(String),
+}
+
+#[derive(Default)]
+struct QueueState {
+    messages: VecDeque<(Channel, Bytes)>,
+    queued_bytes: usize,
+    closed: bool,
+}
+
+struct QueueInner {
+    peer_id: PeerId,
+    config: SendQueueConfig,
+    state: Mutex<QueueState>,
+    writer_wake: Notify,
+    space_available: Notify,
+    drained: Notify,
+}
+
+/// Bounded outgoing queue of a single peer, drained into its transport by a dedicated writer task.
+#[derive(Clone)]
+pub(crate) struct PeerQueue {
+    inner: Arc<QueueInner>,
+}
+
+impl PeerQueue {
+    pub(crate) fn spawn(
+        peer_id: PeerId,
+        sender: Arc<dyn PeerSender>,
+        config: SendQueueConfig,
+    ) -> Self {
+        let inner = Arc::new(QueueInner {
+            peer_id,
+            config,
+            state: Mutex::new(QueueState::default()),
+            writer_wake: Notify::new(),
+            space_available: Notify::new(),
+            drained: Notify::new(),
+        });
+
+        tokio::spawn(run_writer(inner.clone(), sender));
+        Self { inner }
+    }
+
+    /// Resolves once the message is accepted into the queue, according to the channel policy.
+    pub(crate) async fn enqueue(&self, channel: Channel, data: Bytes) -> Result<(), SendError> {
+        let inner = &self.inner;
+        if data.len() > inner.config.max_message_size {
+            return Err(SendError::MessageTooLarge {
+                size: data.len(),
+                max: inner.config.max_message_size,
+            });
+        }
+
+        loop {
+            let space_available = inner.space_available.notified();
+            tokio::pin!(space_available);
+            space_available.as_mut().enable();
+
+            {
+                let mut state = inner.state.lock().unwrap();
+                if state.closed {
+                    return Err(SendError::Closed(inner.peer_id.clone()));
+                }
+
+                if state.queued_bytes + data.len() <= inner.config.high_watermark
+                    || state.messages.is_empty()
+                {
+                    state.queued_bytes += data.len();
+                    state.messages.push_back((channel, data));
+                    inner.writer_wake.notify_one();
+                    return Ok(());
+                }
+
+                match inner.config.policy(channel) {
+                    OverflowPolicy::Fail => {
+                        return Err(SendError::QueueFull(inner.peer_id.clone()));
+                    }
+                    OverflowPolicy::DropOldest => {
+                        drop_oldest(&mut state, channel, data, inner.config.high_watermark);
+                        inner.writer_wake.notify_one();
+                        return Ok(());
+                    }
+                    OverflowPolicy::Backpressure => {}
+                }
+            }
+
+            space_available.await;
+        }
+    }
+
+    /// Like [`PeerQueue::enqueue`], but gives up after the configured broadcast timeout.
+    pub(crate) async fn enqueue_within_timeout(
+        &self,
+        channel: Channel,
+        data: Bytes,
+    ) -> Result<(), SendError> {
+        self.enqueue_within(self.inner.config.broadcast_timeout, channel, data)
+            .await
+    }
+
+    /// Like [`PeerQueue::enqueue`], but gives up after the configured send timeout.
+    pub(crate) async fn enqueue_within_send_timeout(
+        &self,
+        channel: Channel,
+        data: Bytes,
+    ) -> Result<(), SendError> {
+        self.enqueue_within(self.inner.config.send_timeout, channel, data)
+            .await
+    }
+
+    async fn enqueue_within(
+        &self,
+        timeout: Duration,
+        channel: Channel,
+        data: Bytes,
+    ) -> Result<(), SendError> {
+        tokio::time::timeout(timeout, self.enqueue(channel, data))
+            .await
+            .unwrap_or_else(|_| Err(SendError::Timeout(self.inner.peer_id.clone())))
+    }
+
+    /// Stops the writer and fails every pending backpressured send.
+    pub(crate) fn close(&self) {
+        let mut state = self.inner.state.lock().unwrap();
+        state.closed = true;
+        state.messages.clear();
+        state.queued_bytes = 0;
+        drop(state);
+
+        self.inner.writer_wake.notify_one();
+        self.inner.space_available.notify_waiters();
+        self.inner.drained.notify_waiters();
+    }
+
+    /// Resolves once every queued message was handed to the transport or the queue is closed.
+    pub(crate) async fn flushed(&self) {
+        loop {
+            let drained = self.inner.drained.notified();
+            tokio::pin!(drained);
+            drained.as_mut().enable();
+
+            {
+                let state = self.inner.state.lock().unwrap();
+                if state.closed || state.queued_bytes == 0 {
+                    return;
+                }
+            }
+
+            drained.await;
+        }
+    }
+
+    pub(crate) fn queued_bytes(&self) -> usize {
+        self.inner.state.lock().unwrap().queued_bytes
+    }
+}
+
+/// Evicts the oldest queued messages of `channel` until `data` fits. `data` itself is dropped when
+/// other channels' messages still fill the queue.
+fn drop_oldest(state: &mut QueueState, channel: Channel, data: Bytes, high_watermark: usize) {
+    while state.queued_bytes + data.len() > high_watermark {
+        let Some(index) = state.messages.iter().position(|(c, _)| *c == channel) else {
+            break;
+        };
+        if let Some((_, evicted)) = state.messages.remove(index) {
+            state.queued_bytes -= evicted.len();
+        }
+    }
+
+    if state.queued_bytes + data.len() <= high_watermark || state.messages.is_empty() {
+        state.queued_bytes += data.len();
+        state.messages.push_back((channel, data));
+    }
+}
+
+async fn run_writer(inner: Arc<QueueInner>, sender: Arc<dyn PeerSender>) {
+    let fragment_size = sender.fragment_size();
+    let mut next_message_id: u32 = 0;
+
+    loop {
+        // The message in flight stays counted in `queued_bytes` until the transport accepted it
+        let next = {
+            let mut state = inner.state.lock().unwrap();
+            if state.closed {
+                break;
+            }
+            state.messages.pop_front()
+        };
+
+        let Some((channel, data)) = next else {
+            inner.writer_wake.notified().await;
+            continue;
+        };
+
+        let result = match fragment_size {
+            Some(fragment_size) => {
+                let message_id = next_message_id;
+                next_message_id = next_message_id.wrapping_add(1);
+                send_frames(sender.as_ref(), channel, message_id, &data, fragment_size).await
+            }
+            None => sender.send_on(channel, &data).await,
+        };
+        if let Err(e) = result {
+            error!("Failed to send message to user {:?}: {}", inner.peer_id, e);
+        }
+
+        {
+            let mut state = inner.state.lock().unwrap();
+            if state.closed {
+                break;
+            }
+            state.queued_bytes -= data.len();
+            if state.queued_bytes <= inner.config.low_watermark {
+                inner.space_available.notify_waiters();
+            }
+            if state.queued_bytes == 0 {
+                inner.drained.notify_waiters();
+            }
+        }
+
+        if sender.buffered_amount().await > inner.config.high_watermark {
+            while sender.buffered_amount().await > inner.config.low_watermark {
+                if inner.state.lock().unwrap().closed {
+                    return;
+                }
+                tokio::time::sleep(BUFFER_POLL_INTERVAL).await;
+            }
+        }
+    }
+}
+
+async fn send_frames(
+    sender: &dyn PeerSender,
+    channel: Channel,
+    message_id: u32,
+    data: &[u8],
+    fragment_size: usize,
+) -> anyhow::Result<()> {
+    for frame in split_frames(message_id, data, fragment_size) {
+        sender.send_on(channel, &Bytes::from(frame)).await?;
+    }
+    Ok(())
+}
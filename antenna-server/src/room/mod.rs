@@ -1,11 +1,20 @@
+mod admission;
 mod context;
 mod room_agent;
 mod room_behavior;
 mod room_command;
+mod room_definition;
 mod room_manager;
+mod shared_state;
+mod store;
+mod stream_relay;
 
+pub use admission::{Admission, MAX_JOIN_METADATA};
 pub use context::*;
 pub use room_agent::*;
 pub use room_behavior::*;
 pub use room_command::*;
+pub use room_definition::*;
 pub use room_manager::*;
+pub use shared_state::SharedState;
+pub use store::*;
@@ -1,20 +1,44 @@
 use crate::SignalingService;
-use crate::room::context::RoomContext;
+use crate::cluster::NodeInfo;
+use crate::room::admission::{Admission, JoinKind, MAX_JOIN_METADATA, PendingJoin, WaitList};
+use crate::room::context::{
+    DEFAULT_INTEREST_CELL_SIZE, InterestMap, PeerQueue, PeerSequence, RoomContext, SendQueueConfig,
+};
 use crate::room::room_behavior::RoomBehavior;
 use crate::room::room_command::RoomCommand;
-use crate::transport::{ConnectionWrapper, TransportConfig, TransportEvent};
-use antenna_core::{PeerId, SignalMessage};
+use crate::room::room_definition::RoomConfig;
+use crate::room::shared_state::{SharedState, StateSync};
+use crate::room::store::RoomStore;
+use crate::room::stream_relay::StreamRelay;
+use crate::transport::{
+    PeerSender, Transport, TransportConfig, TransportEvent, TransportFactory,
+    WebRtcTransportFactory,
+};
+use antenna_core::Packet as AntennaPacket;
+use antenna_core::{
+    ErrorCode, FEATURE_SEQUENCING, FEATURE_STREAMS, JoinRejection, PeerId, Reassembler,
+    SignalMessage, SystemMessage,
+};
+use bytes::Bytes;
 use dashmap::DashMap;
-use std::collections::HashMap;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::{broadcast, mpsc};
-use tracing::{error, info, warn};
-use webrtc::data_channel::RTCDataChannel;
+use tokio::time::{Instant, MissedTickBehavior};
+use tracing::{debug, error, info, warn};
 use webrtc::rtp::packet::Packet;
 use webrtc::rtp_transceiver::rtp_codec::RTCRtpCodecCapability;
 use webrtc::track::track_local::TrackLocalWriter;
 use webrtc::track::track_local::track_local_static_rtp::TrackLocalStaticRTP;
 
+/// How long a shutting down room waits for queued messages to reach its peers.
+const SHUTDOWN_DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often a room with a `RoomStore` saves its snapshot unless configured otherwise.
+pub const DEFAULT_SNAPSHOT_INTERVAL: Duration = Duration::from_secs(60);
+
 pub type BehaviorFactory = Arc<dyn Fn() -> Box<dyn RoomBehavior> + Send + Sync>;
 
 /// Track handling sender to provide SFU
@@ -29,11 +53,53 @@ pub struct Room {
     /// User-implemented room logic
     behavior: Box<dyn RoomBehavior>,
 
+    /// Capacity and tick rate declared by the room type
+    config: RoomConfig,
+
+    /// Accepted peers waiting for a place while the room holds `RoomConfig::max_peers`
+    wait_list: WaitList,
+
     /// Map of active room data channels, passed to room context in room loop
-    peers_data: Arc<DashMap<PeerId, Arc<RTCDataChannel>>>,
+    peers_data: Arc<DashMap<PeerId, PeerQueue>>,
+
+    /// Sequencing state of peers sending `Packet::Sequenced` inputs
+    sequences: Arc<DashMap<PeerId, PeerSequence>>,
+
+    /// Protocol features each admitted peer negotiated in its handshake
+    features: HashMap<PeerId, Vec<String>>,
+
+    /// Peer groups and positions of area-of-interest broadcasts
+    interest: Arc<InterestMap>,
+
+    /// Bounds and overflow policies of every peer's send queue
+    send_queue_config: SendQueueConfig,
+
+    /// Map of active connections of room participants
+    transports: HashMap<PeerId, Box<dyn Transport>>,
+
+    /// Creates a transport for every joining peer, WebRTC by default
+    transport_factory: Arc<dyn TransportFactory>,
+
+    /// Peers connected through a transport managed outside the room (WebSocket fallback, QUIC)
+    attached_peers: HashMap<PeerId, Arc<dyn PeerSender>>,
+
+    /// Restores fragmented messages arriving on data channels, per peer
+    reassemblers: HashMap<PeerId, Reassembler>,
+
+    /// Routes of file streams passing through the room
+    stream_relay: StreamRelay,
+
+    /// Id under which the room is registered, attached to the signals it sends
+    room_id: Option<String>,
+
+    /// Persists behavior snapshots under `room_id`
+    store: Option<Arc<dyn RoomStore>>,
 
-    /// Map of active webrtc connections of room participants
-    transports: HashMap<PeerId, ConnectionWrapper>,
+    /// Period of snapshots while the room runs
+    snapshot_interval: Duration,
+
+    /// State replicated to every peer, exposed to the behavior through `RoomContext::state`
+    shared_state: Option<Arc<dyn StateSync>>,
 
     /// Room control signaling command receiver: or can be said, room central input
     command_rx: mpsc::Receiver<RoomCommand>,
@@ -64,8 +130,22 @@ impl Room {
 
         Self {
             behavior,
+            config: RoomConfig::default(),
+            wait_list: WaitList::default(),
             peers_data: Arc::new(DashMap::new()),
+            sequences: Arc::new(DashMap::new()),
+            features: HashMap::new(),
+            interest: Arc::new(InterestMap::new(DEFAULT_INTEREST_CELL_SIZE)),
+            send_queue_config: SendQueueConfig::default(),
             transports: HashMap::new(),
+            transport_factory: Arc::new(WebRtcTransportFactory),
+            attached_peers: HashMap::new(),
+            reassemblers: HashMap::new(),
+            stream_relay: StreamRelay::default(),
+            room_id: None,
+            store: None,
+            snapshot_interval: DEFAULT_SNAPSHOT_INTERVAL,
+            shared_state: None,
             command_rx,
             transport_rx,
             transport_tx,
@@ -75,15 +155,119 @@ impl Room {
         }
     }
 
+    /// Applies the settings of the room type, see `RoomDefinition`.
+    pub fn with_config(mut self, config: RoomConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Replaces the transport used for joining peers, e.g. with a `MemoryTransportFactory` in tests.
+    pub fn with_transport_factory(mut self, factory: Arc<dyn TransportFactory>) -> Self {
+        self.transport_factory = factory;
+        self
+    }
+
+    pub fn with_send_queue_config(mut self, config: SendQueueConfig) -> Self {
+        self.send_queue_config = config;
+        self
+    }
+
+    /// Edge of the grid cells of `RoomContext::broadcast_near`. Pick about the typical
+    /// interest radius: smaller cells mean more cells per query, larger ones more peers per cell.
+    /// Panics unless `cell_size` is positive and finite.
+    pub fn with_interest_cell_size(mut self, cell_size: f32) -> Self {
+        assert!(
+            cell_size.is_finite() && cell_size > 0.0,
+            "interest cell size must be positive and finite"
+        );
+        self.interest = Arc::new(InterestMap::new(cell_size));
+        self
+    }
+
+    /// Tags `Answer`, `IceCandidate` and `Error` signals with `room_id`, so clients in several
+    /// rooms route them to the right peer connection.
+    pub fn with_room_id(mut self, room_id: impl Into<String>) -> Self {
+        self.room_id = Some(room_id.into());
+        self
+    }
+
+    /// Restores the behavior from `store` on start and saves its snapshot every
+    /// `snapshot_interval` and on shutdown. Requires `with_room_id`.
+    pub fn with_store(mut self, store: Arc<dyn RoomStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Period of snapshots while the room runs. Panics if `interval` is zero.
+    pub fn with_snapshot_interval(mut self, interval: Duration) -> Self {
+        assert!(!interval.is_zero(), "snapshot interval must not be zero");
+        self.snapshot_interval = interval;
+        self
+    }
+
+    /// Replicates `state` to the peers, see `SharedState`.
+    pub fn with_shared_state<S>(mut self, state: SharedState<S>) -> Self
+    where
+        S: Serialize + Send + Sync + 'static,
+    {
+        self.shared_state = Some(Arc::new(state));
+        self
+    }
+
+    pub(crate) fn with_state_sync(mut self, state: Arc<dyn StateSync>) -> Self {
+        self.shared_state = Some(state);
+        self
+    }
+
+    fn context(&self) -> RoomContext {
+        RoomContext::new(
+            self.peers_data.clone(),
+            self.sequences.clone(),
+            self.interest.clone(),
+        )
+        .with_shared_state(self.shared_state.clone())
+    }
+
     pub async fn run(mut self) {
         info!("Room event loop started");
 
+        self.restore().await;
+        let mut snapshot_timer = tokio::time::interval_at(
+            Instant::now() + self.snapshot_interval,
+            self.snapshot_interval,
+        );
+        let mut tick_timer = self.config.tick_rate.filter(|rate| *rate > 0).map(|rate| {
+            let period = Duration::from_secs(1) / rate;
+            let mut timer = tokio::time::interval_at(Instant::now() + period, period);
+            timer.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            timer
+        });
+
         loop {
-            let ctx = RoomContext::new(self.peers_data.clone());
+            let ctx = self.context();
 
             tokio::select! {
             cmd = self.command_rx.recv() => {
                     match cmd {
+                        Some(RoomCommand::Shutdown { done }) => {
+                            self.shutdown().await;
+                            let _ = done.send(());
+                            break;
+                        }
+                        Some(RoomCommand::Redirect { owner, done }) => {
+                            self.redirect(&owner).await;
+                            let _ = done.send(());
+                            break;
+                        }
+                        Some(RoomCommand::ShutdownIfEmpty { done }) => {
+                            if !self.is_empty() {
+                                let _ = done.send(false);
+                                continue;
+                            }
+                            self.shutdown().await;
+                            let _ = done.send(true);
+                            break;
+                        }
                         Some(c) => self.handle_command(c).await,
                         None => {
                             info!("Command channel closed. Shutting down room.");
@@ -101,6 +285,12 @@ impl Room {
                         }
                     }
                 }
+
+                _ = snapshot_timer.tick(), if self.store.is_some() => self.save_snapshot().await,
+
+                _ = async { tick_timer.as_mut().unwrap().tick().await }, if tick_timer.is_some() => {
+                    self.behavior.on_tick(&ctx).await
+                }
             }
         }
 
@@ -109,61 +299,29 @@ impl Room {
 
     async fn handle_command(&mut self, cmd: RoomCommand) {
         match cmd {
-            RoomCommand::JoinRequest { peer_id, offer } => {
-                info!("Processing JoinRequest for user {:?}", peer_id);
-
-                if self.transports.contains_key(&peer_id) {
-                    self.remove_peer(&peer_id).await;
-                }
-
-                let transport_res = ConnectionWrapper::new(
-                    peer_id.clone(),
-                    self.transport_config.clone(),
-                    self.transport_tx.clone(),
-                )
+            RoomCommand::JoinRequest {
+                peer_id,
+                offer,
+                metadata,
+                features,
+            } => {
+                self.request_join(PendingJoin::new(
+                    peer_id,
+                    metadata,
+                    features,
+                    JoinKind::Offer(offer),
+                ))
                 .await;
-
-                match transport_res {
-                    Ok(transport) => {
-                        if let Err(e) = transport.set_remote_description(offer).await {
-                            error!("SDP error for {:?}: {:?}", peer_id, e);
-                            return;
-                        }
-
-                        for (track_id, sfu_sender) in &self.track_senders {
-                            let local_track = Arc::new(TrackLocalStaticRTP::new(
-                                sfu_sender.codec.clone(),
-                                track_id.clone(),
-                                sfu_sender.stream_id.clone(),
-                            ));
-
-                            if transport.add_track(local_track.clone()).await.is_ok() {
-                                let mut rx = sfu_sender.tx.subscribe();
-                                tokio::spawn(async move {
-                                    while let Ok(packet) = rx.recv().await {
-                                        let _ = local_track.write_rtp(&packet).await;
-                                    }
-                                });
-                            }
-                        }
-
-                        match transport.create_answer().await {
-                            Ok(answer_sdp) => {
-                                self.transports.insert(peer_id.clone(), transport);
-                                self.signaling_service.send_signal(
-                                    peer_id,
-                                    SignalMessage::Answer { sdp: answer_sdp },
-                                );
-                            }
-                            Err(e) => error!("Failed to create answer for {:?}: {:?}", peer_id, e),
-                        }
-                    }
-                    Err(e) => error!("Failed to create transport for {:?}: {:?}", peer_id, e),
-                }
             }
 
             RoomCommand::IceCandidate { peer_id, candidate } => {
                 let Some(transport) = self.transports.get(&peer_id) else {
+                    // Trickled before the peer got a place, added once it is admitted
+                    if let Some(join) = self.wait_list.get_mut(&peer_id)
+                        && !join.add_candidate(candidate)
+                    {
+                        warn!("Dropping ICE candidate of waiting peer {:?}", peer_id);
+                    }
                     return;
                 };
                 let Err(e) = transport.add_ice_candidate(candidate).await else {
@@ -172,26 +330,374 @@ impl Room {
                 warn!("Failed to add ICE candidate for {:?}: {:?}", peer_id, e);
             }
 
+            RoomCommand::Attach {
+                peer_id,
+                sender,
+                metadata,
+                features,
+            } => {
+                self.request_join(PendingJoin::new(
+                    peer_id,
+                    metadata,
+                    features,
+                    JoinKind::Attach(sender),
+                ))
+                .await;
+            }
+
+            RoomCommand::Data { peer_id, data } => {
+                if !self.attached_peers.contains_key(&peer_id)
+                    || !self.peers_data.contains_key(&peer_id)
+                {
+                    warn!("Dropping data from unattached peer {:?}", peer_id);
+                    return;
+                }
+                let ctx = self.context();
+                self.deliver(&ctx, peer_id, data).await;
+            }
+
             RoomCommand::Disconnect { peer_id } => {
-                self.remove_peer_with_notify(&peer_id, &RoomContext::new(self.peers_data.clone()))
+                if self.leave_wait_list(&peer_id).await {
+                    return;
+                }
+                self.remove_peer_with_notify(&peer_id, &self.context())
                     .await;
             }
+
+            RoomCommand::Leave { peer_id } => {
+                info!("User {:?} left the room", peer_id);
+                if self.leave_wait_list(&peer_id).await {
+                    return;
+                }
+                self.remove_peer_with_notify(&peer_id, &self.context())
+                    .await;
+            }
+
+            // Handled by `run`, which stops the room afterwards
+            RoomCommand::Shutdown { .. }
+            | RoomCommand::Redirect { .. }
+            | RoomCommand::ShutdownIfEmpty { .. } => {}
         }
     }
 
+    /// Runs the admission of a joining peer: `on_join_request`, then the `max_peers` check,
+    /// which puts accepted peers on the wait-list while there is room on it. Peers already in
+    /// the room (renegotiating or switching transports) keep their place.
+    async fn request_join(&mut self, join: PendingJoin) {
+        let peer_id = join.peer_id.clone();
+        let present = self.transports.contains_key(&peer_id)
+            || self.attached_peers.contains_key(&peer_id)
+            || self.peers_data.contains_key(&peer_id);
+        if present {
+            self.join(join).await;
+            return;
+        }
+        if join.metadata_size() > MAX_JOIN_METADATA {
+            info!("Join metadata of {:?} is too large", peer_id);
+            let reason = format!("join metadata exceeds {} bytes", MAX_JOIN_METADATA);
+            self.reject_join(join, JoinRejection::Denied { reason })
+                .await;
+            return;
+        }
+        if self.wait_list.contains(&peer_id) {
+            let position = self.wait_list.push(join);
+            self.send_wait_position(&peer_id, position).await;
+            return;
+        }
+
+        let admission = self
+            .behavior
+            .on_join_request(&self.context(), &peer_id, &join.metadata)
+            .await;
+        if let Admission::Deny(reason) = admission {
+            info!("Join of {:?} denied: {}", peer_id, reason);
+            self.reject_join(join, JoinRejection::Denied { reason })
+                .await;
+            return;
+        }
+
+        match self.config.max_peers {
+            Some(max_peers) if self.peer_count() >= max_peers => {
+                if self.wait_list.len() < self.config.wait_list {
+                    let position = self.wait_list.push(join);
+                    info!("Room full, {:?} waits at position {}", peer_id, position);
+                    self.send_wait_position(&peer_id, position).await;
+                } else {
+                    info!("Room full, rejecting {:?}", peer_id);
+                    self.reject_join(join, JoinRejection::RoomFull { max_peers })
+                        .await;
+                }
+            }
+            _ => self.join(join).await,
+        }
+    }
+
+    async fn join(&mut self, join: PendingJoin) {
+        let peer_id = join.peer_id.clone();
+        match join.kind {
+            JoinKind::Offer(offer) => self.connect(join.peer_id, offer, join.candidates).await,
+            JoinKind::Attach(sender) => self.attach(join.peer_id, sender).await,
+        }
+
+        if join
+            .features
+            .iter()
+            .any(|feature| feature == FEATURE_STREAMS)
+        {
+            self.stream_relay.add_peer(peer_id.clone());
+        }
+        self.features.insert(peer_id, join.features);
+    }
+
+    fn supports(&self, peer_id: &PeerId, feature: &str) -> bool {
+        self.features
+            .get(peer_id)
+            .is_some_and(|features| features.iter().any(|f| f == feature))
+    }
+
+    /// Creates a transport for `peer_id`, answers its offer and adds the ICE `candidates` it
+    /// trickled while waiting.
+    async fn connect(&mut self, peer_id: PeerId, offer: String, candidates: Vec<String>) {
+        info!("Processing JoinRequest for user {:?}", peer_id);
+
+        if self.transports.contains_key(&peer_id) || self.attached_peers.contains_key(&peer_id) {
+            self.remove_peer(&peer_id).await;
+        }
+
+        let transport_res = self
+            .transport_factory
+            .create(
+                peer_id.clone(),
+                self.transport_config.clone(),
+                self.transport_tx.clone(),
+            )
+            .await;
+
+        match transport_res {
+            Ok(transport) => {
+                if let Err(e) = transport.set_remote_description(offer).await {
+                    error!("SDP error for {:?}: {:?}", peer_id, e);
+                    self.signaling_service.send_error(
+                        peer_id,
+                        self.room_id.clone(),
+                        ErrorCode::SdpFailed,
+                        e.to_string(),
+                        true,
+                    );
+                    let _ = transport.close().await;
+                    return;
+                }
+                for candidate in candidates {
+                    if let Err(e) = transport.add_ice_candidate(candidate).await {
+                        warn!("Failed to add ICE candidate for {:?}: {:?}", peer_id, e);
+                    }
+                }
+
+                for (track_id, sfu_sender) in &self.track_senders {
+                    let local_track = Arc::new(TrackLocalStaticRTP::new(
+                        sfu_sender.codec.clone(),
+                        track_id.clone(),
+                        sfu_sender.stream_id.clone(),
+                    ));
+
+                    if transport.add_track(local_track.clone()).await.is_ok() {
+                        let mut rx = sfu_sender.tx.subscribe();
+                        tokio::spawn(async move {
+                            while let Ok(packet) = rx.recv().await {
+                                let _ = local_track.write_rtp(&packet).await;
+                            }
+                        });
+                    }
+                }
+
+                match transport.create_answer().await {
+                    Ok(answer_sdp) => {
+                        self.transports.insert(peer_id.clone(), transport);
+                        self.signaling_service.send_signal(
+                            peer_id,
+                            SignalMessage::Answer {
+                                sdp: answer_sdp,
+                                room: self.room_id.clone(),
+                            },
+                        );
+                    }
+                    Err(e) => {
+                        error!("Failed to create answer for {:?}: {:?}", peer_id, e);
+                        self.signaling_service.send_error(
+                            peer_id,
+                            self.room_id.clone(),
+                            ErrorCode::SdpFailed,
+                            e.to_string(),
+                            true,
+                        );
+                        let _ = transport.close().await;
+                    }
+                }
+            }
+            Err(e) => {
+                error!("Failed to create transport for {:?}: {:?}", peer_id, e);
+                self.signaling_service.send_error(
+                    peer_id,
+                    self.room_id.clone(),
+                    ErrorCode::TransportFailed,
+                    e.to_string(),
+                    true,
+                );
+            }
+        }
+    }
+
+    /// Adds a peer whose transport is managed outside the room.
+    async fn attach(&mut self, peer_id: PeerId, sender: Arc<dyn PeerSender>) {
+        info!("User {:?} attached through an external transport", peer_id);
+
+        let was_active = self.peers_data.contains_key(&peer_id);
+        if let Some(transport) = self.transports.remove(&peer_id) {
+            let _ = transport.close().await;
+        }
+        self.attached_peers.insert(peer_id.clone(), sender.clone());
+
+        // Sent before any data, so the peer knows everything it sends from now on arrives
+        let admitted = SignalMessage::Admitted {
+            room: self.room_id.clone(),
+        };
+        self.send_admission(&peer_id, Some(&sender), admitted).await;
+
+        if was_active {
+            self.insert_peer(peer_id, sender);
+            return;
+        }
+
+        let event = TransportEvent::DataChannelReady(peer_id, sender);
+        if let Err(e) = self.transport_tx.send(event).await {
+            error!("Failed to register attached transport: {}", e);
+        }
+    }
+
+    async fn reject_join(&self, join: PendingJoin, reason: JoinRejection) {
+        let rejected = SignalMessage::JoinRejected {
+            reason,
+            room: self.room_id.clone(),
+        };
+        let sender = match &join.kind {
+            JoinKind::Attach(sender) => Some(sender),
+            JoinKind::Offer(_) => None,
+        };
+        // A transport that delivered the rejection closes the connection once it got through
+        if !self.send_admission(&join.peer_id, sender, rejected).await
+            && let Some(sender) = sender
+        {
+            sender.close().await;
+        }
+    }
+
+    async fn send_wait_position(&self, peer_id: &PeerId, position: usize) {
+        let sender = match self.wait_list.get(peer_id).map(|join| &join.kind) {
+            Some(JoinKind::Attach(sender)) => Some(sender),
+            _ => None,
+        };
+        let waiting = SignalMessage::WaitListed {
+            position,
+            room: self.room_id.clone(),
+        };
+        self.send_admission(peer_id, sender, waiting).await;
+    }
+
+    /// Sends an admission signal through the peer's own connection when it attached over one
+    /// that carries signals, over signaling otherwise. Returns whether the connection took it.
+    async fn send_admission(
+        &self,
+        peer_id: &PeerId,
+        sender: Option<&Arc<dyn PeerSender>>,
+        msg: SignalMessage,
+    ) -> bool {
+        if let Some(sender) = sender
+            && sender.signal(&msg).await
+        {
+            return true;
+        }
+        self.signaling_service.send_signal(peer_id.clone(), msg);
+        false
+    }
+
+    /// Drops a waiting peer that gave up. Returns whether it was waiting.
+    async fn leave_wait_list(&mut self, peer_id: &PeerId) -> bool {
+        let Some(join) = self.wait_list.remove(peer_id) else {
+            return false;
+        };
+        info!("User {:?} left the wait-list", peer_id);
+        if let JoinKind::Attach(sender) = join.kind {
+            sender.close().await;
+        }
+        self.send_wait_positions().await;
+        true
+    }
+
+    /// Lets waiting peers take the places freed by leaving ones.
+    async fn admit_waiting(&mut self) {
+        let mut admitted = false;
+        while self
+            .config
+            .max_peers
+            .is_none_or(|max_peers| self.peer_count() < max_peers)
+        {
+            let Some(join) = self.wait_list.pop_front() else {
+                break;
+            };
+            info!("Admitting {:?} from the wait-list", join.peer_id);
+            self.join(join).await;
+            admitted = true;
+        }
+        if admitted {
+            self.send_wait_positions().await;
+        }
+    }
+
+    async fn send_wait_positions(&self) {
+        for (join, position) in self.wait_list.positions() {
+            self.send_wait_position(&join.peer_id, position).await;
+        }
+    }
+
+    /// Peers holding a place: connecting, connected or attached.
+    fn peer_count(&self) -> usize {
+        self.transports.len() + self.attached_peers.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.peer_count() == 0 && self.peers_data.is_empty() && self.wait_list.len() == 0
+    }
+
     async fn handle_transport_event(&mut self, event: TransportEvent, ctx: &RoomContext) {
         match event {
             TransportEvent::DataChannelReady(peer_id, channel) => {
                 info!("User {:?} fully joined (DataChannel ready).", peer_id);
-                self.peers_data.insert(peer_id.clone(), channel);
+                self.insert_peer(peer_id.clone(), channel);
+                self.send_state_snapshot(ctx, &peer_id).await;
+                self.announce(ctx, &peer_id, SystemMessage::PeerJoined(peer_id.clone()))
+                    .await;
                 self.behavior.on_join(ctx, peer_id).await;
             }
 
-            TransportEvent::Message(peer_id, data) => {
-                self.behavior.on_message(ctx, peer_id, data).await;
+            TransportEvent::Message(peer_id, frame) => {
+                let max_message_size = self.send_queue_config.max_message_size;
+                let reassembler = self
+                    .reassemblers
+                    .entry(peer_id.clone())
+                    .or_insert_with(|| Reassembler::new(max_message_size));
+
+                match reassembler.push(&frame) {
+                    Ok(Some(data)) => self.deliver(ctx, peer_id, Bytes::from(data)).await,
+                    Ok(None) => {}
+                    Err(e) => warn!("Dropping message from {:?}: {}", peer_id, e),
+                }
             }
 
             TransportEvent::Disconnected(peer_id) => {
+                if self.attached_peers.contains_key(&peer_id) {
+                    // Stale event of a WebRTC connection the peer has been moved away from
+                    return;
+                }
                 info!("Transport disconnected for {:?}", peer_id);
                 self.remove_peer_with_notify(&peer_id, ctx).await;
             }
@@ -201,6 +707,7 @@ impl Room {
                     peer_id,
                     SignalMessage::IceCandidate {
                         candidate: candidate_json,
+                        room: self.room_id.clone(),
                     },
                 );
             }
@@ -247,18 +754,217 @@ impl Room {
         }
     }
 
+    /// Relays stream packets and hands everything else to the behavior.
+    async fn deliver(&mut self, ctx: &RoomContext, peer_id: PeerId, data: Bytes) {
+        let relayed = self
+            .stream_relay
+            .handle(ctx, self.behavior.as_ref(), &peer_id, data)
+            .await;
+
+        let Some(data) = relayed else {
+            return;
+        };
+
+        if let Ok(AntennaPacket::Sequenced { header, .. }) =
+            postcard::from_bytes::<AntennaPacket<()>>(&data)
+        {
+            if !self.supports(&peer_id, FEATURE_SEQUENCING) {
+                warn!(
+                    "Dropping sequenced input of {:?}: sequencing was not negotiated",
+                    peer_id
+                );
+                return;
+            }
+            let accepted = self
+                .sequences
+                .entry(peer_id.clone())
+                .or_default()
+                .accept(header.seq);
+            if !accepted {
+                debug!("Dropping stale input {} of {:?}", header.seq, peer_id);
+                return;
+            }
+        }
+
+        self.behavior.on_message(ctx, peer_id, data).await;
+    }
+
+    /// Lets the behavior say goodbye, flushes the send queues and disconnects every peer.
+    async fn shutdown(&mut self) {
+        info!("Shutting down room");
+
+        let ctx = self.context();
+        self.behavior.on_close(&ctx).await;
+        self.save_snapshot().await;
+
+        self.drain_queues().await;
+        self.disconnect_all().await;
+    }
+
+    /// Closes a room `owner` took over and points every peer, the wait-listed ones included, at
+    /// it. Neither `on_close` nor the store run: the room's state belongs to the new owner now.
+    async fn redirect(&mut self, owner: &NodeInfo) {
+        info!(
+            "Room taken over by node {}, redirecting its peers",
+            owner.id
+        );
+
+        let mut peers: HashSet<PeerId> = self
+            .peers_data
+            .iter()
+            .map(|e| e.key().clone())
+            .chain(self.transports.keys().cloned())
+            .chain(
+                self.wait_list
+                    .positions()
+                    .map(|(join, _)| join.peer_id.clone()),
+            )
+            .collect();
+        self.drain_queues().await;
+
+        // Peers attached over their own connection (QUIC) are redirected through it
+        let attached: Vec<(PeerId, Arc<dyn PeerSender>)> = self
+            .attached_peers
+            .iter()
+            .map(|(peer_id, sender)| (peer_id.clone(), sender.clone()))
+            .chain(self.wait_list.attached())
+            .collect();
+        for (peer_id, sender) in attached {
+            if sender.redirect(owner).await {
+                peers.remove(&peer_id);
+            } else {
+                peers.insert(peer_id);
+            }
+        }
+        self.disconnect_all().await;
+
+        let url = owner.url.trim_end_matches('/');
+        for peer_id in peers {
+            let url = format!("{}/{}", url, peer_id);
+            self.signaling_service.send_signal(
+                peer_id,
+                SignalMessage::Redirect {
+                    url,
+                    room: self.room_id.clone(),
+                },
+            );
+        }
+    }
+
+    /// Waits for the send queues to drain, up to `SHUTDOWN_DRAIN_TIMEOUT`.
+    async fn drain_queues(&self) {
+        let queues: Vec<PeerQueue> = self.peers_data.iter().map(|e| e.value().clone()).collect();
+        let flushed = futures::future::join_all(queues.iter().map(|queue| queue.flushed()));
+        if tokio::time::timeout(SHUTDOWN_DRAIN_TIMEOUT, flushed)
+            .await
+            .is_err()
+        {
+            warn!("Send queues not drained in time, dropping pending messages");
+        }
+    }
+
+    /// Disconnects every peer, the wait-listed ones included.
+    async fn disconnect_all(&mut self) {
+        let peers: HashSet<PeerId> = self
+            .peers_data
+            .iter()
+            .map(|e| e.key().clone())
+            .chain(self.transports.keys().cloned())
+            .chain(self.attached_peers.keys().cloned())
+            .collect();
+        for peer_id in peers {
+            self.remove_peer(&peer_id).await;
+        }
+        for join in self.wait_list.drain() {
+            if let JoinKind::Attach(sender) = join.kind {
+                sender.close().await;
+            }
+        }
+    }
+
+    async fn send_state_snapshot(&self, ctx: &RoomContext, peer_id: &PeerId) {
+        let Some(state) = &self.shared_state else {
+            return;
+        };
+
+        if let Err(e) = state.send_snapshot(ctx, peer_id).await {
+            error!("Failed to send state snapshot to {:?}: {:?}", peer_id, e);
+        }
+    }
+
+    async fn restore(&self) {
+        let (Some(store), Some(room_id)) = (&self.store, &self.room_id) else {
+            return;
+        };
+
+        match store.load(room_id).await {
+            Ok(Some(snapshot)) => match self.behavior.restore(&snapshot).await {
+                Ok(()) => info!("Room {} restored from {} bytes", room_id, snapshot.len()),
+                Err(e) => error!("Failed to restore room {}: {:?}", room_id, e),
+            },
+            Ok(None) => {}
+            Err(e) => error!("Failed to load snapshot of room {}: {:?}", room_id, e),
+        }
+    }
+
+    async fn save_snapshot(&self) {
+        let (Some(store), Some(room_id)) = (&self.store, &self.room_id) else {
+            return;
+        };
+        let Some(snapshot) = self.behavior.snapshot().await else {
+            return;
+        };
+
+        if let Err(e) = store.save(room_id, &snapshot).await {
+            error!("Failed to save snapshot of room {}: {:?}", room_id, e);
+        }
+    }
+
     async fn remove_peer_with_notify(&mut self, peer_id: &PeerId, ctx: &RoomContext) {
         let was_active = self.peers_data.contains_key(peer_id);
 
         self.remove_peer(peer_id).await;
 
         if was_active {
+            self.announce(ctx, peer_id, SystemMessage::PeerLeft(peer_id.clone()))
+                .await;
             self.behavior.on_leave(ctx, peer_id.clone()).await;
         }
+        self.admit_waiting().await;
+    }
+
+    /// Sends a presence change to every peer but its subject when `RoomConfig::presence` is set.
+    async fn announce(&self, ctx: &RoomContext, peer_id: &PeerId, msg: SystemMessage) {
+        if !self.config.presence {
+            return;
+        }
+        match postcard::to_allocvec(&AntennaPacket::<()>::System(msg)) {
+            Ok(bytes) => ctx.broadcast_except(peer_id, Bytes::from(bytes)).await,
+            Err(e) => error!("Failed to encode presence message: {:?}", e),
+        }
+    }
+
+    fn insert_peer(&self, peer_id: PeerId, sender: Arc<dyn PeerSender>) {
+        let queue = PeerQueue::spawn(peer_id.clone(), sender, self.send_queue_config.clone());
+        if let Some(previous) = self.peers_data.insert(peer_id, queue) {
+            previous.close();
+        }
     }
 
     async fn remove_peer(&mut self, peer_id: &PeerId) {
-        self.peers_data.remove(peer_id);
+        if let Some((_, queue)) = self.peers_data.remove(peer_id) {
+            queue.close();
+        }
+        if let Some(sender) = self.attached_peers.remove(peer_id) {
+            sender.close().await;
+        }
+        self.reassemblers.remove(peer_id);
+        self.sequences.remove(peer_id);
+        self.features.remove(peer_id);
+        self.interest.remove_peer(peer_id);
+
+        let ctx = self.context();
+        self.stream_relay.remove_peer(&ctx, peer_id).await;
 
         let Some(transport) = self.transports.remove(peer_id) else {
             return;
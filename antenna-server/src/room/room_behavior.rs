@@ -1,13 +1,73 @@
+use crate::room::admission::Admission;
 use crate::room::context::RoomContext;
 use antenna_core::PeerId;
+use anyhow::Result;
 use async_trait::async_trait;
 use bytes::Bytes;
+use std::collections::HashMap;
+use tracing::warn;
 
 #[async_trait]
 pub trait RoomBehavior: Send + Sync + 'static {
+    /// Decides whether `peer_id` may join, before any transport is created for it. `metadata`
+    /// is what the client sent in `SignalMessage::Join`. Peers are accepted by default; an
+    /// accepted peer still waits (or is rejected) while the room holds `RoomConfig::max_peers`.
+    async fn on_join_request(
+        &self,
+        _ctx: &RoomContext,
+        _peer_id: &PeerId,
+        _metadata: &HashMap<String, String>,
+    ) -> Admission {
+        Admission::Accept
+    }
+
     async fn on_join(&self, ctx: &RoomContext, peer_id: PeerId);
 
     async fn on_message(&self, ctx: &RoomContext, peer_id: PeerId, data: Bytes);
 
     async fn on_leave(&self, ctx: &RoomContext, peer_id: PeerId);
+
+    /// Decides whether a stream opened by `peer_id` is relayed. `to` is `None` for streams sent
+    /// to every other peer. Streams are accepted by default.
+    async fn on_stream_open(
+        &self,
+        _ctx: &RoomContext,
+        _peer_id: &PeerId,
+        _to: Option<&PeerId>,
+        _name: &str,
+        _size: u64,
+    ) -> bool {
+        true
+    }
+
+    /// Called `RoomConfig::tick_rate` times per second while the room runs, for fixed-rate
+    /// simulation steps. Slow ticks delay the next ones instead of piling up.
+    async fn on_tick(&self, _ctx: &RoomContext) {}
+
+    /// Called once when the room shuts down, while every peer is still connected. Messages sent
+    /// here are flushed before the peers are disconnected; `on_leave` is not called for them.
+    async fn on_close(&self, _ctx: &RoomContext) {}
+
+    /// Serializes the room state so it survives restarts, see `RoomStore`. Taken periodically
+    /// and on shutdown; `None` (the default) means there is nothing to persist.
+    async fn snapshot(&self) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Loads a state produced by `snapshot` when the room is created again, before any peer
+    /// joins. A failed restore is logged and the room starts empty.
+    async fn restore(&self, _snapshot: &[u8]) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Reports a message the `on_message` generated by `#[antenna_logic]` has no handler for, used
+/// when the behavior declares no `#[fallback]`.
+#[doc(hidden)]
+pub fn log_unhandled_message(peer_id: &PeerId, data: &[u8]) {
+    warn!(
+        "Dropping message of {} bytes from {:?}: it does not decode as a handled message",
+        data.len(),
+        peer_id
+    );
 }
@@ -1,14 +1,57 @@
+use crate::cluster::NodeInfo;
+use crate::transport::PeerSender;
 use antenna_core::PeerId;
+use bytes::Bytes;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::oneshot;
 
 /// Команды, поступающие в комнату от сигнального сервера (WebSocket/HTTP).
 #[derive(Debug)]
 pub enum RoomCommand {
     /// Запрос на подключение: новый пользователь прислал SDP Offer.
-    JoinRequest { peer_id: PeerId, offer: String },
+    /// `metadata` приходит из `SignalMessage::Join` и передаётся в `RoomBehavior::on_join_request`,
+    /// `features` — возможности протокола, согласованные при рукопожатии (`antenna_core::negotiate`).
+    JoinRequest {
+        peer_id: PeerId,
+        offer: String,
+        metadata: HashMap<String, String>,
+        features: Vec<String>,
+    },
 
     /// ICE Candidate от клиента (для пробития NAT).
     IceCandidate { peer_id: PeerId, candidate: String },
 
+    /// Подключение пира через транспорт, управляемый вне комнаты (WebSocket fallback, QUIC).
+    Attach {
+        peer_id: PeerId,
+        sender: Arc<dyn PeerSender>,
+        metadata: HashMap<String, String>,
+        features: Vec<String>,
+    },
+
+    /// Пакет данных от пира, подключённого через `Attach`.
+    Data { peer_id: PeerId, data: Bytes },
+
     /// Сигнал о разрыве WebSocket соединения.
     Disconnect { peer_id: PeerId },
+
+    /// Пир явно покинул комнату (`SignalMessage::Leave`): `on_leave` вызывается сразу.
+    Leave { peer_id: PeerId },
+
+    /// Остановка комнаты: `on_close`, отправка оставшихся сообщений, отключение всех пиров.
+    /// `done` срабатывает, когда комната завершила работу.
+    Shutdown { done: oneshot::Sender<()> },
+
+    /// Остановка комнаты, которой теперь владеет узел `owner`: без `on_close` и сохранения
+    /// снимка, ведь комната уже принадлежит другому узлу. Все пиры, включая ожидающих,
+    /// перенаправляются на `owner` через свой транспорт или `SignalMessage::Redirect`.
+    Redirect {
+        owner: NodeInfo,
+        done: oneshot::Sender<()>,
+    },
+
+    /// Остановка комнаты, как `Shutdown`, но только если в ней и в очереди ожидания нет пиров.
+    /// `done` получает `true`, если комната остановлена.
+    ShutdownIfEmpty { done: oneshot::Sender<bool> },
 }
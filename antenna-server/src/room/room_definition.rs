@@ -0,0 +1,61 @@
+use crate::room::room_behavior::RoomBehavior;
+use antenna_core::{Message, protocol_schema};
+
+/// Settings a room type declares about itself, see `#[antenna_room]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RoomConfig {
+    /// Upper bound of peers in one room, unlimited when `None`. Further joins are rejected
+    /// with `JoinRejection::RoomFull` or wait, see `wait_list`
+    pub max_peers: Option<usize>,
+    /// How many accepted peers may wait for a place once `max_peers` is reached, none by default
+    pub wait_list: usize,
+    /// How often per second `RoomBehavior::on_tick` runs, never when `None`
+    pub tick_rate: Option<u32>,
+    /// Whether the other peers receive `SystemMessage::PeerJoined` / `PeerLeft`
+    pub presence: bool,
+    /// Schema of the client and server message types, see `AntennaServer::with_schema`
+    pub schema: Option<String>,
+}
+
+impl RoomConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_peers(mut self, max_peers: usize) -> Self {
+        self.max_peers = Some(max_peers);
+        self
+    }
+
+    pub fn with_wait_list(mut self, wait_list: usize) -> Self {
+        self.wait_list = wait_list;
+        self
+    }
+
+    pub fn with_tick_rate(mut self, tick_rate: u32) -> Self {
+        self.tick_rate = Some(tick_rate);
+        self
+    }
+
+    /// Announces joining and leaving peers to the rest of the room.
+    pub fn with_presence(mut self) -> Self {
+        self.presence = true;
+        self
+    }
+
+    /// `C` is the type clients send, `S` the type the room sends back.
+    pub fn with_schema<C: Message, S: Message>(mut self) -> Self {
+        self.schema = Some(protocol_schema(C::schema_hash(), S::schema_hash()));
+        self
+    }
+}
+
+/// A room type `AntennaServer::build` can serve: how to create an instance and its `RoomConfig`.
+/// Implemented by `#[antenna_room]`.
+pub trait RoomDefinition: RoomBehavior + Sized {
+    fn create() -> Self;
+
+    fn config() -> RoomConfig {
+        RoomConfig::default()
+    }
+}
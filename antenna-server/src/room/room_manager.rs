@@ -1,15 +1,49 @@
-use crate::room::{Room, RoomCommand};
+use crate::cluster::{DEFAULT_LEASE_RENEWAL, NodeInfo, RoomDirectory};
+use crate::room::shared_state::StateSync;
+use crate::room::{
+    DEFAULT_INTEREST_CELL_SIZE, DEFAULT_SNAPSHOT_INTERVAL, Room, RoomCommand, RoomConfig,
+    RoomStore, SendQueueConfig, SharedState,
+};
 use crate::{BehaviorFactory, SignalingService};
+use anyhow::Result;
 use dashmap::DashMap;
-use std::sync::Arc;
-use tokio::sync::mpsc;
-use tracing::info;
+use futures::StreamExt;
+use serde::Serialize;
+use std::sync::{Arc, Once, Weak};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+/// Most leases claimed at the same time by one renewal pass.
+const LEASE_RENEWAL_CONCURRENCY: usize = 32;
+
+/// Creates the initial shared state of every new room.
+type StateFactory = Arc<dyn Fn() -> Result<Arc<dyn StateSync>> + Send + Sync>;
+
+/// Where a room runs, see [`RoomManager::route`].
+#[derive(Debug, Clone)]
+pub enum RoomRoute {
+    /// The room runs in this process.
+    Local(mpsc::Sender<RoomCommand>),
+    /// The room is owned by another node; clients have to be redirected there.
+    Remote(NodeInfo),
+}
 
 #[derive(Clone)]
 pub struct RoomManager {
     rooms: Arc<DashMap<String, mpsc::Sender<RoomCommand>>>,
     behavior_factory: BehaviorFactory,
     signaling_sender: Arc<SignalingService>,
+    send_queue_config: SendQueueConfig,
+    cluster: Option<(Arc<dyn RoomDirectory>, NodeInfo)>,
+    store: Option<Arc<dyn RoomStore>>,
+    snapshot_interval: Duration,
+    shared_state: Option<StateFactory>,
+    interest_cell_size: f32,
+    room_config: RoomConfig,
+    lease_renewal: Duration,
+    renewing: Arc<Once>,
 }
 
 impl RoomManager {
@@ -18,9 +52,118 @@ impl RoomManager {
             rooms: Arc::new(DashMap::new()),
             behavior_factory,
             signaling_sender,
+            send_queue_config: SendQueueConfig::default(),
+            cluster: None,
+            store: None,
+            snapshot_interval: DEFAULT_SNAPSHOT_INTERVAL,
+            shared_state: None,
+            interest_cell_size: DEFAULT_INTEREST_CELL_SIZE,
+            room_config: RoomConfig::default(),
+            lease_renewal: DEFAULT_LEASE_RENEWAL,
+            renewing: Arc::new(Once::new()),
         }
     }
 
+    /// Gives every room created afterwards a `SharedState<S>` starting at `S::default()`.
+    pub fn with_shared_state<S>(mut self) -> Self
+    where
+        S: Default + Serialize + Send + Sync + 'static,
+    {
+        self.shared_state = Some(Arc::new(|| {
+            Ok(Arc::new(SharedState::new(S::default())?) as Arc<dyn StateSync>)
+        }));
+        self
+    }
+
+    /// Persists the state of every room in `store` and restores rooms from it when they are
+    /// created again, see `RoomBehavior::snapshot`.
+    pub fn with_room_store(mut self, store: Arc<dyn RoomStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// How often rooms save their snapshot while running, `DEFAULT_SNAPSHOT_INTERVAL` by default.
+    /// Panics if `interval` is zero.
+    pub fn with_snapshot_interval(mut self, interval: Duration) -> Self {
+        assert!(!interval.is_zero(), "snapshot interval must not be zero");
+        self.snapshot_interval = interval;
+        self
+    }
+
+    /// Runs this manager as `node` of a cluster: rooms are created only after `directory`
+    /// assigned them to this node, other rooms are reported as [`RoomRoute::Remote`].
+    pub fn with_directory(mut self, directory: Arc<dyn RoomDirectory>, node: NodeInfo) -> Self {
+        self.cluster = Some((directory, node));
+        self
+    }
+
+    /// How often the rooms of this node are claimed again, keeping their leases in a directory
+    /// with `MemoryRoomDirectory::with_lease`. `DEFAULT_LEASE_RENEWAL` by default.
+    pub fn with_lease_renewal(mut self, interval: Duration) -> Self {
+        self.lease_renewal = interval;
+        self
+    }
+
+    /// This node, when running in a cluster.
+    pub fn node(&self) -> Option<&NodeInfo> {
+        self.cluster.as_ref().map(|(_, node)| node)
+    }
+
+    /// Finds the node running `room_id`, claiming the room for this node if nobody owns it yet.
+    /// Without a directory every room is local.
+    pub async fn route(&self, room_id: &str) -> Result<RoomRoute> {
+        if let Some(sender) = self.rooms.get(room_id) {
+            return Ok(RoomRoute::Local(sender.clone()));
+        }
+
+        if let Some((directory, node)) = &self.cluster {
+            let owner = directory.claim(room_id, node).await?;
+            if owner.id != node.id {
+                info!("Room {} is owned by node {}", room_id, owner.id);
+                return Ok(RoomRoute::Remote(owner));
+            }
+            // The first owned room starts renewing the leases, until the manager is dropped
+            self.renewing.call_once(|| {
+                tokio::spawn(renew_leases(
+                    Arc::downgrade(&self.rooms),
+                    directory.clone(),
+                    node.clone(),
+                    self.lease_renewal,
+                ));
+            });
+        }
+
+        Ok(RoomRoute::Local(self.get_room_sender(room_id)))
+    }
+
+    /// Settings of the room type applied to every room created afterwards.
+    pub fn with_room_config(mut self, config: RoomConfig) -> Self {
+        self.room_config = config;
+        self
+    }
+
+    /// Schema clients must announce to join, see `RoomConfig::schema`.
+    pub fn schema(&self) -> Option<&str> {
+        self.room_config.schema.as_deref()
+    }
+
+    /// Grid cell edge of the area-of-interest broadcasts of every room created afterwards.
+    /// Panics unless `cell_size` is positive and finite.
+    pub fn with_interest_cell_size(mut self, cell_size: f32) -> Self {
+        assert!(
+            cell_size.is_finite() && cell_size > 0.0,
+            "interest cell size must be positive and finite"
+        );
+        self.interest_cell_size = cell_size;
+        self
+    }
+
+    /// Applies `config` to the send queues of every room created afterwards.
+    pub fn with_send_queue_config(mut self, config: SendQueueConfig) -> Self {
+        self.send_queue_config = config;
+        self
+    }
+
     pub fn get_room_sender(&self, room_id: &str) -> mpsc::Sender<RoomCommand> {
         if let Some(sender) = self.rooms.get(room_id) {
             return sender.clone();
@@ -30,10 +173,134 @@ impl RoomManager {
         let (tx, rx) = mpsc::channel(256);
         let behavior = (self.behavior_factory)();
 
-        let room = Room::new(behavior, rx, self.signaling_sender.clone());
+        let mut room = Room::new(behavior, rx, self.signaling_sender.clone())
+            .with_send_queue_config(self.send_queue_config.clone())
+            .with_room_id(room_id)
+            .with_snapshot_interval(self.snapshot_interval)
+            .with_interest_cell_size(self.interest_cell_size)
+            .with_config(self.room_config.clone());
+        // The room restores its snapshot before handling the first command
+        if let Some(store) = &self.store {
+            room = room.with_store(store.clone());
+        }
+        match self.shared_state.as_ref().map(|factory| factory()) {
+            Some(Ok(state)) => room = room.with_state_sync(state),
+            Some(Err(e)) => warn!("Failed to create shared state of room {}: {:?}", room_id, e),
+            None => {}
+        }
         tokio::spawn(room.run());
 
         self.rooms.insert(room_id.to_string(), tx.clone());
         tx
     }
+
+    /// Whether `room_id` runs in this process.
+    pub fn has_room(&self, room_id: &str) -> bool {
+        self.rooms.contains_key(room_id)
+    }
+
+    /// Shuts `room_id` down unless a peer is in it or waiting for a place, then releases it in
+    /// the directory. Returns whether the room was closed.
+    pub async fn close_if_empty(&self, room_id: &str) -> bool {
+        let Some(tx) = self.rooms.get(room_id).map(|sender| sender.clone()) else {
+            return false;
+        };
+        let (done, closed) = oneshot::channel();
+        if tx
+            .send(RoomCommand::ShutdownIfEmpty { done })
+            .await
+            .is_err()
+        {
+            return false;
+        }
+        if !closed.await.unwrap_or(false) {
+            return false;
+        }
+        info!("Room {} closed", room_id);
+
+        self.rooms
+            .remove_if(room_id, |_, sender| sender.same_channel(&tx));
+        self.release(room_id).await;
+        true
+    }
+
+    /// Shuts every room down (see `RoomCommand::Shutdown`) and waits until all of them finished,
+    /// then releases them in the directory. Rooms requested afterwards are created anew.
+    pub async fn shutdown(&self) {
+        let rooms: Vec<(String, mpsc::Sender<RoomCommand>)> = self
+            .rooms
+            .iter()
+            .map(|e| (e.key().clone(), e.value().clone()))
+            .collect();
+        self.rooms.clear();
+
+        let closing = rooms.into_iter().map(|(room_id, tx)| async move {
+            let (done, closed) = oneshot::channel();
+            if tx.send(RoomCommand::Shutdown { done }).await.is_ok() {
+                let _ = closed.await;
+            }
+            info!("Room {} closed", room_id);
+            self.release(&room_id).await;
+        });
+        futures::future::join_all(closing).await;
+    }
+
+    async fn release(&self, room_id: &str) {
+        if let Some((directory, node)) = &self.cluster
+            && let Err(e) = directory.release(room_id, node).await
+        {
+            warn!("Failed to release room {}: {:?}", room_id, e);
+        }
+    }
+}
+
+/// Claims the rooms running on `node` again every `interval`. Rooms another node took over are
+/// shut down and their peers redirected there, so a room never keeps running on two nodes.
+async fn renew_leases(
+    rooms: Weak<DashMap<String, mpsc::Sender<RoomCommand>>>,
+    directory: Arc<dyn RoomDirectory>,
+    node: NodeInfo,
+    interval: Duration,
+) {
+    let mut timer = tokio::time::interval_at(Instant::now() + interval, interval);
+    loop {
+        timer.tick().await;
+        let Some(rooms) = rooms.upgrade() else {
+            break;
+        };
+        let claims: Vec<(String, mpsc::Sender<RoomCommand>)> = rooms
+            .iter()
+            .map(|e| (e.key().clone(), e.value().clone()))
+            .collect();
+
+        // Claimed concurrently, so a slow directory does not let the last leases expire
+        futures::stream::iter(claims)
+            .for_each_concurrent(LEASE_RENEWAL_CONCURRENCY, |(room_id, tx)| {
+                let (rooms, directory, node) = (&rooms, &directory, &node);
+                async move {
+                    match directory.claim(&room_id, node).await {
+                        Ok(owner) if owner.id != node.id => {
+                            warn!("Room {} was taken over by node {}", room_id, owner.id);
+                            rooms.remove_if(&room_id, |_, sender| sender.same_channel(&tx));
+                            hand_over(&tx, &owner).await;
+                        }
+                        Ok(_) => {}
+                        Err(e) => warn!("Failed to renew the lease of room {}: {:?}", room_id, e),
+                    }
+                }
+            })
+            .await;
+    }
+}
+
+/// Stops a room this node lost and sends its peers to `owner`.
+async fn hand_over(tx: &mpsc::Sender<RoomCommand>, owner: &NodeInfo) {
+    let (done, closed) = oneshot::channel();
+    let redirect = RoomCommand::Redirect {
+        owner: owner.clone(),
+        done,
+    };
+    if tx.send(redirect).await.is_ok() {
+        let _ = closed.await;
+    }
 }
@@ -0,0 +1,189 @@
+use crate::room::context::{RoomContext, SendError};
+use antenna_core::{Channel, Packet, PeerId, StateDelta, SystemMessage};
+use anyhow::Result;
+use async_trait::async_trait;
+use bytes::Bytes;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::any::Any;
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{debug, error};
+
+struct StateInner<S> {
+    value: S,
+    /// Postcard encoding of `value` as last sent to the peers
+    encoded: Vec<u8>,
+    version: u64,
+}
+
+/// State of a room replicated to its peers. The server is authoritative: behaviors change it
+/// with [`SharedState::update`], which broadcasts the change as a `SystemMessage::StatePatch`.
+/// Joining peers receive a `SystemMessage::StateSnapshot` before `on_join` runs, and so do
+/// peers that missed a patch because their send queue stayed full, once it drained.
+///
+/// Attach it with `Room::with_shared_state` (or `AntennaServer::with_shared_state`) and get it
+/// back in handlers with `RoomContext::state`. Clones refer to the same state.
+pub struct SharedState<S> {
+    inner: Arc<Mutex<StateInner<S>>>,
+    /// Held while a patch or snapshot is queued, so peers receive them in version order without
+    /// blocking readers of the state. Holds the peers that missed a patch and wait for a snapshot.
+    sending: Arc<Mutex<HashSet<PeerId>>>,
+}
+
+impl<S> Clone for SharedState<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            sending: self.sending.clone(),
+        }
+    }
+}
+
+impl<S> SharedState<S>
+where
+    S: Serialize + Send + Sync + 'static,
+{
+    pub fn new(value: S) -> Result<Self> {
+        let encoded = postcard::to_allocvec(&value)?;
+        Ok(Self {
+            inner: Arc::new(Mutex::new(StateInner {
+                value,
+                encoded,
+                version: 0,
+            })),
+            sending: Arc::new(Mutex::new(HashSet::new())),
+        })
+    }
+
+    pub async fn read<R>(&self, f: impl FnOnce(&S) -> R) -> R {
+        f(&self.inner.lock().await.value)
+    }
+
+    /// Number of changes since the state was created.
+    pub async fn version(&self) -> u64 {
+        self.inner.lock().await.version
+    }
+
+    /// Changes the state and sends the difference to every peer of `ctx`. Updates that leave
+    /// the encoding unchanged send nothing. A change that fails to encode is rolled back to the
+    /// last sent state.
+    pub async fn update<R>(&self, ctx: &RoomContext, f: impl FnOnce(&mut S) -> R) -> Result<R>
+    where
+        S: DeserializeOwned,
+    {
+        let mut lagging = self.sending.lock().await;
+        let (result, patch) = {
+            let mut inner = self.inner.lock().await;
+            let result = f(&mut inner.value);
+
+            let encoded = match postcard::to_allocvec(&inner.value) {
+                Ok(encoded) => encoded,
+                Err(e) => {
+                    inner.value = postcard::from_bytes(&inner.encoded)?;
+                    return Err(e.into());
+                }
+            };
+            let delta = StateDelta::diff(&inner.encoded, &encoded);
+            if delta.is_empty() {
+                return Ok(result);
+            }
+            inner.encoded = encoded;
+            inner.version += 1;
+
+            let patch = Packet::<()>::System(SystemMessage::StatePatch {
+                version: inner.version,
+                delta,
+            });
+            (result, postcard::to_allocvec(&patch)?)
+        };
+
+        // Lagging peers can not apply patches until their snapshot arrives
+        let peers: Vec<PeerId> = ctx
+            .list_users()
+            .into_iter()
+            .filter(|peer_id| !lagging.contains(peer_id))
+            .collect();
+        let missed = ctx
+            .try_broadcast_to(&peers, Channel::Reliable, Bytes::from(patch))
+            .await;
+        for peer_id in missed {
+            if lagging.insert(peer_id.clone()) {
+                tokio::spawn(self.clone().resync(ctx.clone(), peer_id));
+            }
+        }
+        Ok(result)
+    }
+
+    /// Sends a snapshot to a peer that missed a patch, once its send queue drained. The snapshot
+    /// is queued without holding `sending`, so patches to the other peers keep flowing; a patch
+    /// made meanwhile skipped the lagging peer, which then gets a newer snapshot.
+    async fn resync(self, ctx: RoomContext, peer_id: PeerId) {
+        loop {
+            ctx.flushed(&peer_id).await;
+
+            let (version, snapshot) = match self.snapshot().await {
+                Ok(snapshot) => snapshot,
+                Err(e) => {
+                    error!("Failed to encode state snapshot: {}", e);
+                    self.sending.lock().await.remove(&peer_id);
+                    return;
+                }
+            };
+            let sent = ctx
+                .send_on_channel(&peer_id, Channel::Reliable, snapshot)
+                .await;
+
+            let mut lagging = self.sending.lock().await;
+            match sent {
+                Err(SendError::Timeout(_) | SendError::QueueFull(_)) => continue,
+                Err(e) => debug!("Dropped state resync of {:?}: {}", peer_id, e),
+                Ok(()) if self.version().await != version => continue,
+                Ok(()) => {}
+            }
+            lagging.remove(&peer_id);
+            return;
+        }
+    }
+
+    /// Encoded `SystemMessage::StateSnapshot` of the current state and its version.
+    async fn snapshot(&self) -> Result<(u64, Bytes)> {
+        let inner = self.inner.lock().await;
+        let snapshot = Packet::<()>::System(SystemMessage::StateSnapshot {
+            version: inner.version,
+            data: inner.encoded.clone(),
+        });
+        Ok((
+            inner.version,
+            Bytes::from(postcard::to_allocvec(&snapshot)?),
+        ))
+    }
+}
+
+/// Type-erased `SharedState` held by the room.
+#[async_trait]
+pub(crate) trait StateSync: Send + Sync + 'static {
+    /// Queues a `SystemMessage::StateSnapshot` of the current state for the peer.
+    async fn send_snapshot(&self, ctx: &RoomContext, peer_id: &PeerId) -> Result<()>;
+
+    fn as_any(&self) -> &dyn Any;
+}
+
+#[async_trait]
+impl<S> StateSync for SharedState<S>
+where
+    S: Serialize + Send + Sync + 'static,
+{
+    async fn send_snapshot(&self, ctx: &RoomContext, peer_id: &PeerId) -> Result<()> {
+        let _sending = self.sending.lock().await;
+        let (_, snapshot) = self.snapshot().await?;
+        ctx.send_on_channel(peer_id, Channel::Reliable, snapshot)
+            .await?;
+        Ok(())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
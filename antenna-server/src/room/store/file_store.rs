@@ -0,0 +1,68 @@
+use crate::room::store::room_store::RoomStore;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::fmt::Write;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+/// `RoomStore` writing one `<room id>.snapshot` file per room into a directory. Files are
+/// replaced atomically, so a crash during `save` keeps the previous snapshot.
+#[derive(Debug, Clone)]
+pub struct FileRoomStore {
+    dir: PathBuf,
+}
+
+impl FileRoomStore {
+    /// Stores snapshots in `dir`, which is created on the first `save`.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Room ids are arbitrary strings, everything but `[A-Za-z0-9_-]` is percent-encoded.
+    fn path(&self, room_id: &str) -> PathBuf {
+        let mut name = String::with_capacity(room_id.len());
+        for byte in room_id.bytes() {
+            if byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'-' {
+                name.push(byte as char);
+            } else {
+                let _ = write!(name, "%{:02X}", byte);
+            }
+        }
+        self.dir.join(format!("{}.snapshot", name))
+    }
+}
+
+#[async_trait]
+impl RoomStore for FileRoomStore {
+    async fn save(&self, room_id: &str, snapshot: &[u8]) -> Result<()> {
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .with_context(|| format!("Failed to create {:?}", self.dir))?;
+
+        let path = self.path(room_id);
+        let tmp = path.with_extension("snapshot.tmp");
+        tokio::fs::write(&tmp, snapshot)
+            .await
+            .with_context(|| format!("Failed to write {:?}", tmp))?;
+        tokio::fs::rename(&tmp, &path)
+            .await
+            .with_context(|| format!("Failed to replace {:?}", path))?;
+        Ok(())
+    }
+
+    async fn load(&self, room_id: &str) -> Result<Option<Vec<u8>>> {
+        let path = self.path(room_id);
+        match tokio::fs::read(&path).await {
+            Ok(snapshot) => Ok(Some(snapshot)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {:?}", path)),
+        }
+    }
+
+    async fn delete(&self, room_id: &str) -> Result<()> {
+        match tokio::fs::remove_file(self.path(room_id)).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
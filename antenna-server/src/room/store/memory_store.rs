@@ -0,0 +1,36 @@
+use crate::room::store::room_store::RoomStore;
+use anyhow::Result;
+use async_trait::async_trait;
+use dashmap::DashMap;
+use std::sync::Arc;
+
+/// `RoomStore` kept in memory. Survives rooms being re-created, but not the process; clones
+/// share the same snapshots.
+#[derive(Clone, Default)]
+pub struct MemoryRoomStore {
+    snapshots: Arc<DashMap<String, Vec<u8>>>,
+}
+
+impl MemoryRoomStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl RoomStore for MemoryRoomStore {
+    async fn save(&self, room_id: &str, snapshot: &[u8]) -> Result<()> {
+        self.snapshots
+            .insert(room_id.to_string(), snapshot.to_vec());
+        Ok(())
+    }
+
+    async fn load(&self, room_id: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self.snapshots.get(room_id).map(|s| s.clone()))
+    }
+
+    async fn delete(&self, room_id: &str) -> Result<()> {
+        self.snapshots.remove(room_id);
+        Ok(())
+    }
+}
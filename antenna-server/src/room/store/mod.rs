@@ -0,0 +1,7 @@
+mod file_store;
+mod memory_store;
+mod room_store;
+
+pub use file_store::*;
+pub use memory_store::*;
+pub use room_store::*;
@@ -0,0 +1,15 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+/// Persists room snapshots taken with `RoomBehavior::snapshot`, keyed by room id.
+#[async_trait]
+pub trait RoomStore: Send + Sync + 'static {
+    /// Replaces the stored snapshot of `room_id`.
+    async fn save(&self, room_id: &str, snapshot: &[u8]) -> Result<()>;
+
+    /// Returns the last snapshot of `room_id`, `None` if there is none.
+    async fn load(&self, room_id: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Forgets the snapshot of `room_id`.
+    async fn delete(&self, room_id: &str) -> Result<()>;
+}
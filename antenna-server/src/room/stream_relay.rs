@@ -0,0 +1,208 @@
+use crate::room::context::RoomContext;
+use crate::room::room_behavior::RoomBehavior;
+use antenna_core::{Channel, Packet, PeerId, SystemMessage};
+use bytes::Bytes;
+use std::collections::{HashMap, HashSet};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+struct StreamRoute {
+    from: PeerId,
+    to: Option<PeerId>,
+}
+
+/// Forwards `SystemMessage::Stream*` packets between peers chunk by chunk, so transfers of any
+/// size pass through the room without being buffered as a whole.
+#[derive(Default)]
+pub(crate) struct StreamRelay {
+    routes: HashMap<Uuid, StreamRoute>,
+    /// Peers that negotiated `FEATURE_STREAMS`; the others neither open nor receive streams
+    peers: HashSet<PeerId>,
+}
+
+impl StreamRelay {
+    /// Lets `peer_id` take part in streams.
+    pub(crate) fn add_peer(&mut self, peer_id: PeerId) {
+        self.peers.insert(peer_id);
+    }
+
+    /// Relays `data` if it is a stream packet, otherwise hands it back.
+    pub(crate) async fn handle(
+        &mut self,
+        ctx: &RoomContext,
+        behavior: &dyn RoomBehavior,
+        peer_id: &PeerId,
+        data: Bytes,
+    ) -> Option<Bytes> {
+        let Ok(Packet::System(msg)) = postcard::from_bytes::<Packet<()>>(&data) else {
+            return Some(data);
+        };
+
+        let is_stream = matches!(
+            msg,
+            SystemMessage::StreamOpen { .. }
+                | SystemMessage::StreamChunk { .. }
+                | SystemMessage::StreamClose { .. }
+                | SystemMessage::StreamAbort { .. }
+        );
+        if is_stream && !self.peers.contains(peer_id) {
+            warn!(
+                "Dropping stream packet of {:?}: streams were not negotiated",
+                peer_id
+            );
+            return None;
+        }
+
+        match msg {
+            SystemMessage::StreamOpen {
+                stream_id,
+                to,
+                name,
+                size,
+                ..
+            } => {
+                let target_missing = to
+                    .as_ref()
+                    .is_some_and(|to| !ctx.contains_user(to) || !self.peers.contains(to));
+                if self.routes.contains_key(&stream_id)
+                    || target_missing
+                    || !behavior
+                        .on_stream_open(ctx, peer_id, to.as_ref(), &name, size)
+                        .await
+                {
+                    info!("Rejected stream {} of {:?}", stream_id, peer_id);
+                    send_packet(ctx, peer_id, SystemMessage::StreamAbort { stream_id }).await;
+                    return None;
+                }
+
+                let open = encode(SystemMessage::StreamOpen {
+                    stream_id,
+                    from: Some(peer_id.clone()),
+                    to: to.clone(),
+                    name,
+                    size,
+                });
+                let route = StreamRoute {
+                    from: peer_id.clone(),
+                    to,
+                };
+                let delivered = match open {
+                    Some(open) => forward(ctx, &route, &self.peers, open).await,
+                    None => true,
+                };
+                self.routes.insert(stream_id, route);
+                if !delivered {
+                    self.abort_stalled(ctx, stream_id).await;
+                }
+            }
+
+            SystemMessage::StreamChunk { stream_id, .. } => {
+                if let Some(route) = self.route_of(peer_id, &stream_id)
+                    && !forward(ctx, route, &self.peers, data).await
+                {
+                    self.abort_stalled(ctx, stream_id).await;
+                }
+            }
+
+            SystemMessage::StreamClose { stream_id } | SystemMessage::StreamAbort { stream_id } => {
+                if let Some(route) = self.route_of(peer_id, &stream_id) {
+                    forward(ctx, route, &self.peers, data).await;
+                    self.routes.remove(&stream_id);
+                }
+            }
+
+            _ => return Some(data),
+        }
+
+        None
+    }
+
+    /// Aborts every stream the leaving peer was sending or receiving.
+    pub(crate) async fn remove_peer(&mut self, ctx: &RoomContext, peer_id: &PeerId) {
+        self.peers.remove(peer_id);
+        let stream_ids: Vec<Uuid> = self
+            .routes
+            .iter()
+            .filter(|(_, route)| route.from == *peer_id || route.to.as_ref() == Some(peer_id))
+            .map(|(stream_id, _)| *stream_id)
+            .collect();
+
+        for stream_id in stream_ids {
+            let Some(route) = self.routes.remove(&stream_id) else {
+                continue;
+            };
+            let abort = SystemMessage::StreamAbort { stream_id };
+            if route.from == *peer_id {
+                if let Some(abort) = encode(abort) {
+                    forward(ctx, &route, &self.peers, abort).await;
+                }
+            } else {
+                send_packet(ctx, &route.from, abort).await;
+            }
+        }
+    }
+
+    /// Aborts a stream that a receiver did not accept within the broadcast timeout, so only
+    /// that transfer is lost instead of the whole room waiting for the slow receiver.
+    async fn abort_stalled(&mut self, ctx: &RoomContext, stream_id: Uuid) {
+        let Some(route) = self.routes.remove(&stream_id) else {
+            return;
+        };
+        warn!("Aborted stream {} stalled by a slow receiver", stream_id);
+
+        let Some(abort) = encode(SystemMessage::StreamAbort { stream_id }) else {
+            return;
+        };
+        ctx.try_broadcast_to(
+            std::slice::from_ref(&route.from),
+            Channel::Reliable,
+            abort.clone(),
+        )
+        .await;
+        forward(ctx, &route, &self.peers, abort).await;
+    }
+
+    fn route_of(&self, peer_id: &PeerId, stream_id: &Uuid) -> Option<&StreamRoute> {
+        let route = self.routes.get(stream_id)?;
+        if route.from != *peer_id {
+            warn!("Peer {:?} wrote to a foreign stream {}", peer_id, stream_id);
+            return None;
+        }
+        Some(route)
+    }
+}
+
+fn encode(msg: SystemMessage) -> Option<Bytes> {
+    postcard::to_allocvec(&Packet::<()>::System(msg))
+        .map(Bytes::from)
+        .ok()
+}
+
+/// Queues `data` for the receivers of the stream among `peers`. Returns `false` if any of them
+/// missed it.
+async fn forward(
+    ctx: &RoomContext,
+    route: &StreamRoute,
+    peers: &HashSet<PeerId>,
+    data: Bytes,
+) -> bool {
+    let targets = match &route.to {
+        Some(to) => vec![to.clone()],
+        None => ctx
+            .list_users()
+            .into_iter()
+            .filter(|peer_id| *peer_id != route.from && peers.contains(peer_id))
+            .collect(),
+    };
+
+    ctx.try_broadcast_to(&targets, Channel::Reliable, data)
+        .await
+        .is_empty()
+}
+
+async fn send_packet(ctx: &RoomContext, peer_id: &PeerId, msg: SystemMessage) {
+    if let Some(bytes) = encode(msg) {
+        ctx.try_broadcast_to(std::slice::from_ref(peer_id), Channel::Reliable, bytes)
+            .await;
+    }
+}
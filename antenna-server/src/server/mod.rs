@@ -1,15 +1,37 @@
-use crate::{AppState, BehaviorFactory, RoomBehavior, RoomManager, SignalingService};
-use antenna_core::IceServerConfig;
+use crate::{
+    AppState, BehaviorFactory, DEFAULT_INTEREST_CELL_SIZE, DEFAULT_SNAPSHOT_INTERVAL, MatchRules,
+    Matchmaker, NodeInfo, RoomBehavior, RoomDefinition, RoomDirectory, RoomManager, RoomStore,
+    SendQueueConfig, SignalingService,
+};
+use antenna_core::{IceServerConfig, Message, protocol_schema};
+use serde::Serialize;
 use std::sync::Arc;
+use std::time::Duration;
 
 pub struct AntennaServer {
     ice_servers: Vec<IceServerConfig>,
+    send_queue_config: SendQueueConfig,
+    schema: Option<String>,
+    cluster: Option<(Arc<dyn RoomDirectory>, NodeInfo)>,
+    store: Option<Arc<dyn RoomStore>>,
+    snapshot_interval: Duration,
+    shared_state: Option<fn(RoomManager) -> RoomManager>,
+    interest_cell_size: f32,
+    matchmaking: Vec<(String, MatchRules)>,
 }
 
 impl AntennaServer {
     pub fn new() -> Self {
         Self {
             ice_servers: Vec::new(),
+            send_queue_config: SendQueueConfig::default(),
+            schema: None,
+            cluster: None,
+            store: None,
+            snapshot_interval: DEFAULT_SNAPSHOT_INTERVAL,
+            shared_state: None,
+            interest_cell_size: DEFAULT_INTEREST_CELL_SIZE,
+            matchmaking: Vec::new(),
         }
     }
 
@@ -27,16 +49,107 @@ impl AntennaServer {
         self
     }
 
-    pub fn build<R: RoomBehavior + Default>(self) -> Arc<AppState> {
+    /// Bounds the outgoing buffer of every peer and picks what happens when it fills up.
+    pub fn with_send_queue_config(mut self, config: SendQueueConfig) -> Self {
+        self.send_queue_config = config;
+        self
+    }
+
+    /// Rejects clients built with different message types. `C` is the type clients send,
+    /// `S` the type the server sends back.
+    pub fn with_schema<C: Message, S: Message>(mut self) -> Self {
+        self.schema = Some(protocol_schema(C::schema_hash(), S::schema_hash()));
+        self
+    }
+
+    /// Runs the server as `node` of a cluster sharing `directory`. Clients joining a room owned
+    /// by another node are redirected there.
+    pub fn with_directory(mut self, directory: Arc<dyn RoomDirectory>, node: NodeInfo) -> Self {
+        self.cluster = Some((directory, node));
+        self
+    }
+
+    /// Keeps room state across restarts: rooms save `RoomBehavior::snapshot` into `store`
+    /// periodically and on shutdown, and restore it when they are created again.
+    pub fn with_room_store(mut self, store: Arc<dyn RoomStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// How often rooms save their snapshot while running. Panics if `interval` is zero.
+    pub fn with_snapshot_interval(mut self, interval: Duration) -> Self {
+        assert!(!interval.is_zero(), "snapshot interval must not be zero");
+        self.snapshot_interval = interval;
+        self
+    }
+
+    /// Gives every room a `SharedState<S>` starting at `S::default()`, replicated to its peers.
+    /// Behaviors reach it through `RoomContext::state::<S>()`.
+    pub fn with_shared_state<S>(mut self) -> Self
+    where
+        S: Default + Serialize + Send + Sync + 'static,
+    {
+        self.shared_state = Some(RoomManager::with_shared_state::<S>);
+        self
+    }
+
+    /// Grid cell edge of `RoomContext::broadcast_near`, about the typical interest radius.
+    /// Panics unless `cell_size` is positive and finite.
+    pub fn with_interest_cell_size(mut self, cell_size: f32) -> Self {
+        assert!(
+            cell_size.is_finite() && cell_size > 0.0,
+            "interest cell size must be positive and finite"
+        );
+        self.interest_cell_size = cell_size;
+        self
+    }
+
+    /// Runs the matchmaking queue `name`: clients sending `SignalMessage::Matchmake` for it are
+    /// grouped by `rules` into new rooms, see `Matchmaker`.
+    pub fn with_matchmaking_queue(mut self, name: impl Into<String>, rules: MatchRules) -> Self {
+        self.matchmaking.push((name.into(), rules));
+        self
+    }
+
+    /// Creates the server state serving rooms of type `R`. The schema declared by `R` (see
+    /// `#[antenna_room]`) applies unless `with_schema` set one.
+    pub fn build<R: RoomDefinition>(self) -> Arc<AppState> {
         let signaling_service = SignalingService::new(self.ice_servers);
         let signaling_arc = Arc::new(signaling_service.clone());
 
-        let factory: BehaviorFactory = Arc::new(|| Box::new(R::default()) as Box<dyn RoomBehavior>);
-        let room_manager = RoomManager::new(factory, signaling_arc);
+        let mut config = R::config();
+        if self.schema.is_some() {
+            config.schema = self.schema;
+        }
+        let schema = config.schema.clone();
+        let factory: BehaviorFactory = Arc::new(|| Box::new(R::create()) as Box<dyn RoomBehavior>);
+        let mut room_manager = RoomManager::new(factory, signaling_arc)
+            .with_room_config(config)
+            .with_send_queue_config(self.send_queue_config)
+            .with_snapshot_interval(self.snapshot_interval)
+            .with_interest_cell_size(self.interest_cell_size);
+        if let Some(store) = self.store {
+            room_manager = room_manager.with_room_store(store);
+        }
+        if let Some(with_shared_state) = self.shared_state {
+            room_manager = with_shared_state(room_manager);
+        }
+        if let Some((directory, node)) = self.cluster {
+            room_manager = room_manager.with_directory(directory, node);
+        }
+
+        let matchmaker = (!self.matchmaking.is_empty()).then(|| {
+            self.matchmaking.into_iter().fold(
+                Matchmaker::new(room_manager.clone(), signaling_service.clone()),
+                |matchmaker, (name, rules)| matchmaker.with_queue(name, rules),
+            )
+        });
 
         Arc::new(AppState {
             signaling_service,
             room_manager,
+            schema,
+            matchmaker,
         })
     }
 }
@@ -1,5 +1,7 @@
-use antenna_core::{IceServerConfig, PeerId, SignalMessage};
+use antenna_core::{ErrorCode, IceServerConfig, PeerId, SignalMessage};
+use anyhow::{Result, anyhow};
 use axum::extract::ws::Message;
+use bytes::Bytes;
 use dashmap::DashMap;
 use std::sync::Arc;
 use tokio::sync::mpsc;
@@ -54,4 +56,47 @@ impl SignalingService {
             );
         }
     }
+
+    /// Sends `Leave` to every connected peer and closes their WebSockets.
+    pub fn close_all(&self) {
+        let peers: Vec<PeerId> = self.inner.peers.iter().map(|e| e.key().clone()).collect();
+        for peer_id in peers {
+            self.send_signal(peer_id.clone(), SignalMessage::Leave { room: None });
+            if let Some((_, peer)) = self.inner.peers.remove(&peer_id) {
+                let _ = peer.send(Message::Close(None));
+            }
+        }
+    }
+
+    /// Reports a failed client request concerning `room`, see `SignalMessage::Error`.
+    pub fn send_error(
+        &self,
+        peer_id: PeerId,
+        room: Option<String>,
+        code: ErrorCode,
+        message: impl Into<String>,
+        fatal: bool,
+    ) {
+        self.send_signal(
+            peer_id,
+            SignalMessage::Error {
+                code,
+                message: message.into(),
+                fatal,
+                room,
+            },
+        );
+    }
+
+    /// Sends a binary data frame to the peer, used by the WebSocket fallback transport.
+    pub fn send_data(&self, peer_id: &PeerId, data: Bytes) -> Result<()> {
+        let peer = self
+            .inner
+            .peers
+            .get(peer_id)
+            .ok_or_else(|| anyhow!("Peer {} has no signaling connection", peer_id))?;
+
+        peer.send(Message::Binary(data))
+            .map_err(|e| anyhow!("Failed to send WS data to {}: {}", peer_id, e))
+    }
 }
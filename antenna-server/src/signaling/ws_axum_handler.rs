@@ -1,10 +1,15 @@
-use crate::{RoomCommand, RoomManager, SignalingService};
-use antenna_core::{PeerId, SignalMessage};
+use crate::{Matchmaker, RoomCommand, RoomManager, RoomRoute, SignalingService, WebSocketSender};
+use antenna_core::{
+    ErrorCode, FEATURE_WEBSOCKET_TRANSPORT, MAX_WS_FRAME_ROOM, PROTOCOL_VERSION, PeerId,
+    SignalMessage, decode_ws_frame, negotiate,
+};
 use axum::extract::ws::{Message, WebSocket};
 use axum::extract::{Path, State, WebSocketUpgrade};
 use axum::response::IntoResponse;
 use futures::{SinkExt, StreamExt};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc;
 use tracing::{error, info, warn};
 
@@ -12,6 +17,22 @@ use tracing::{error, info, warn};
 pub struct AppState {
     pub signaling_service: SignalingService,
     pub room_manager: RoomManager,
+    /// Schema hash of the message types the server was built with, see `antenna_core::protocol_schema`.
+    /// Clients announcing a different one are rejected.
+    pub schema: Option<String>,
+    /// Serves `SignalMessage::Matchmake`, `None` when the server runs no matchmaking queues.
+    pub matchmaker: Option<Matchmaker>,
+}
+
+impl AppState {
+    /// Gracefully stops the server: closes every room, waiting for `RoomBehavior::on_close` and
+    /// the queued messages, then sends `Leave` to all clients and closes their connections.
+    /// Stop accepting new connections first, e.g. with axum's `with_graceful_shutdown`.
+    pub async fn shutdown(&self) {
+        info!("Shutting down");
+        self.room_manager.shutdown().await;
+        self.signaling_service.close_all();
+    }
 }
 
 pub async fn ws_axum_handler(
@@ -52,58 +73,285 @@ async fn handle_socket(socket: WebSocket, peer_id: PeerId, state: Arc<AppState>)
         let state = state.clone();
 
         async move {
-            let mut current_room_tx: Option<mpsc::Sender<RoomCommand>> = None;
+            let mut rooms = JoinedRooms::default();
+            // Set by the first `Join` that passes the version and schema checks
+            let mut handshaken = false;
 
             while let Some(Ok(msg)) = receiver.next().await {
                 match msg {
                     Message::Text(text) => match serde_json::from_str::<SignalMessage>(&text) {
                         Ok(signal) => match signal {
-                            SignalMessage::Join { room, .. } => {
+                            SignalMessage::Join {
+                                room,
+                                version,
+                                features,
+                                schema,
+                                metadata,
+                            } => {
                                 info!("Peer {:?} wants to join room '{}'", peer_id, room);
-                                current_room_tx = Some(state.room_manager.get_room_sender(&room));
+
+                                let features = match negotiate(
+                                    version,
+                                    &features,
+                                    schema.as_deref(),
+                                    state.schema.as_deref(),
+                                ) {
+                                    Ok(features) => {
+                                        handshaken = true;
+                                        features
+                                    }
+                                    Err(reason) => {
+                                        warn!("Rejecting peer {:?}: {}", peer_id, reason);
+                                        signaling_service.send_signal(
+                                            peer_id.clone(),
+                                            SignalMessage::Reject { reason },
+                                        );
+                                        break;
+                                    }
+                                };
+
+                                let tx = match state.room_manager.route(&room).await {
+                                    Ok(RoomRoute::Local(tx)) => tx,
+                                    Ok(RoomRoute::Remote(node)) => {
+                                        info!(
+                                            "Redirecting peer {:?} to node {} for room '{}'",
+                                            peer_id, node.id, room
+                                        );
+                                        signaling_service.send_signal(
+                                            peer_id.clone(),
+                                            SignalMessage::Redirect {
+                                                url: format!(
+                                                    "{}/{}",
+                                                    node.url.trim_end_matches('/'),
+                                                    peer_id
+                                                ),
+                                                room: Some(room),
+                                            },
+                                        );
+                                        continue;
+                                    }
+                                    Err(e) => {
+                                        error!("Failed to route room '{}': {:?}", room, e);
+                                        signaling_service.send_error(
+                                            peer_id.clone(),
+                                            Some(room),
+                                            ErrorCode::RoomUnavailable,
+                                            e.to_string(),
+                                            true,
+                                        );
+                                        continue;
+                                    }
+                                };
+                                rooms.insert(room.clone(), tx, metadata, features.clone());
 
                                 signaling_service.send_signal(
                                     peer_id.clone(),
                                     SignalMessage::Welcome {
                                         peer_id: peer_id.clone(),
+                                        room: Some(room),
+                                        version: PROTOCOL_VERSION,
+                                        features,
                                     },
                                 )
                             }
-                            SignalMessage::Offer { sdp } => {
-                                if let Some(tx) = &current_room_tx {
+                            SignalMessage::Offer { sdp, room } => {
+                                if let Some((room, tx)) = rooms.get(room.as_deref()) {
                                     let cmd = RoomCommand::JoinRequest {
                                         peer_id: peer_id.clone(),
                                         offer: sdp,
+                                        metadata: rooms.metadata(&room),
+                                        features: rooms.features(&room),
                                     };
                                     info!("{:?}", cmd);
                                     if let Err(e) = tx.send(cmd).await {
-                                        error!("Room died: {}", e);
-                                        break;
+                                        error!("Room '{}' died: {}", room, e);
+                                        rooms.remove(&room);
                                     }
                                 } else {
                                     warn!("Peer {:?} sent Offer without joining a room", peer_id);
+                                    signaling_service.send_error(
+                                        peer_id.clone(),
+                                        room,
+                                        ErrorCode::NotJoined,
+                                        "Offer sent before Join",
+                                        false,
+                                    );
                                 }
                             }
-                            SignalMessage::IceCandidate { candidate, .. } => {
-                                if let Some(tx) = &current_room_tx {
+                            SignalMessage::IceCandidate { candidate, room } => {
+                                if let Some((_, tx)) = rooms.get(room.as_deref()) {
                                     let cmd = RoomCommand::IceCandidate {
                                         peer_id: peer_id.clone(),
                                         candidate,
                                     };
                                     info!("{:?}", cmd);
                                     let _ = tx.send(cmd).await;
+                                } else {
+                                    signaling_service.send_error(
+                                        peer_id.clone(),
+                                        room,
+                                        ErrorCode::NotJoined,
+                                        "IceCandidate sent before Join",
+                                        false,
+                                    );
+                                }
+                            }
+                            SignalMessage::Leave { room: Some(room) } => {
+                                if let Some(tx) = rooms.remove(&room) {
+                                    let _ = tx
+                                        .send(RoomCommand::Leave {
+                                            peer_id: peer_id.clone(),
+                                        })
+                                        .await;
+                                }
+                            }
+                            SignalMessage::Leave { room: None } => {
+                                for (_, tx) in rooms.drain() {
+                                    let _ = tx
+                                        .send(RoomCommand::Leave {
+                                            peer_id: peer_id.clone(),
+                                        })
+                                        .await;
+                                }
+                                break;
+                            }
+                            SignalMessage::Matchmake { queue, attributes } => {
+                                if !handshaken {
+                                    warn!("Peer {:?} sent Matchmake before Join", peer_id);
+                                    signaling_service.send_error(
+                                        peer_id.clone(),
+                                        None,
+                                        ErrorCode::NotJoined,
+                                        "Matchmake sent before Join",
+                                        false,
+                                    );
+                                    continue;
+                                }
+                                let Some(matchmaker) = &state.matchmaker else {
+                                    signaling_service.send_error(
+                                        peer_id.clone(),
+                                        None,
+                                        ErrorCode::UnknownQueue,
+                                        "matchmaking is not enabled",
+                                        false,
+                                    );
+                                    continue;
+                                };
+                                info!("Peer {:?} queues for '{}'", peer_id, queue);
+                                if let Err(e) = matchmaker
+                                    .enqueue(peer_id.clone(), &queue, attributes)
+                                    .await
+                                {
+                                    signaling_service.send_error(
+                                        peer_id.clone(),
+                                        None,
+                                        ErrorCode::UnknownQueue,
+                                        e.to_string(),
+                                        false,
+                                    );
+                                }
+                            }
+                            SignalMessage::CancelMatchmake { queue } => {
+                                if let Some(matchmaker) = &state.matchmaker {
+                                    matchmaker.cancel(&peer_id, queue.as_deref());
+                                }
+                            }
+                            SignalMessage::UseWebSocketTransport { room } => {
+                                let Some((room, tx)) = rooms.get(room.as_deref()) else {
+                                    warn!(
+                                        "Peer {:?} requested WebSocket transport without joining a room",
+                                        peer_id
+                                    );
+                                    signaling_service.send_error(
+                                        peer_id.clone(),
+                                        room,
+                                        ErrorCode::NotJoined,
+                                        "UseWebSocketTransport sent before Join",
+                                        false,
+                                    );
+                                    continue;
+                                };
+                                let features = rooms.features(&room);
+                                if !features.iter().any(|f| f == FEATURE_WEBSOCKET_TRANSPORT) {
+                                    signaling_service.send_error(
+                                        peer_id.clone(),
+                                        Some(room),
+                                        ErrorCode::TransportFailed,
+                                        "WebSocket transport was not negotiated in Join",
+                                        true,
+                                    );
+                                    continue;
+                                }
+                                if room.len() > MAX_WS_FRAME_ROOM {
+                                    signaling_service.send_error(
+                                        peer_id.clone(),
+                                        Some(room),
+                                        ErrorCode::TransportFailed,
+                                        "Room name is too long for the WebSocket transport",
+                                        true,
+                                    );
+                                    continue;
                                 }
+
+                                let metadata = rooms.metadata(&room);
+                                rooms.websocket_transport.insert(room.clone());
+                                let sender = WebSocketSender::new(
+                                    peer_id.clone(),
+                                    room,
+                                    signaling_service.clone(),
+                                );
+                                let cmd = RoomCommand::Attach {
+                                    peer_id: peer_id.clone(),
+                                    sender: Arc::new(sender),
+                                    metadata,
+                                    features,
+                                };
+                                info!("{:?}", cmd);
+                                let _ = tx.send(cmd).await;
                             }
                             _ => {}
                         },
-                        Err(e) => warn!("Invalid SignalMessage from {:?}: {:?}", peer_id, e),
+                        Err(e) => {
+                            warn!("Invalid SignalMessage from {:?}: {:?}", peer_id, e);
+                            signaling_service.send_error(
+                                peer_id.clone(),
+                                None,
+                                ErrorCode::InvalidMessage,
+                                e.to_string(),
+                                false,
+                            );
+                        }
                     },
+                    Message::Binary(frame) => {
+                        let Some((room, data)) = decode_ws_frame(&frame) else {
+                            warn!("Malformed WebSocket data frame from {:?}", peer_id);
+                            continue;
+                        };
+                        // Rooms still on WebRTC get their data over the data channel only
+                        if !rooms.websocket_transport.contains(room) {
+                            continue;
+                        }
+                        let Some((room, tx)) = rooms.get(Some(room)) else {
+                            continue;
+                        };
+                        let cmd = RoomCommand::Data {
+                            peer_id: peer_id.clone(),
+                            data: frame.slice_ref(data),
+                        };
+                        if let Err(e) = tx.send(cmd).await {
+                            error!("Room '{}' died: {}", room, e);
+                            rooms.remove(&room);
+                        }
+                    }
                     Message::Close(_) => break,
                     _ => {}
                 }
             }
 
-            if let Some(tx) = current_room_tx {
+            if let Some(matchmaker) = &state.matchmaker {
+                matchmaker.cancel(&peer_id, None);
+            }
+            for (_, tx) in rooms.drain() {
                 let _ = tx
                     .send(RoomCommand::Disconnect {
                         peer_id: peer_id.clone(),
@@ -115,9 +363,76 @@ async fn handle_socket(socket: WebSocket, peer_id: PeerId, state: Arc<AppState>)
 
     tokio::select! {
         _ = (&mut send_task) => recv_task.abort(),
-        _ = (&mut recv_task) => send_task.abort(),
+        _ = (&mut recv_task) => {
+            // Let already queued signals (e.g. `Reject`) reach the client before closing
+            signaling_service.remove_peer(&peer_id);
+            if tokio::time::timeout(Duration::from_secs(1), &mut send_task).await.is_err() {
+                send_task.abort();
+            }
+        }
     };
 
     signaling_service.remove_peer(&peer_id);
     info!("WebSocket disconnected: {:?}", peer_id);
 }
+
+/// Rooms a single WebSocket connection takes part in.
+#[derive(Default)]
+struct JoinedRooms {
+    rooms: HashMap<String, mpsc::Sender<RoomCommand>>,
+    /// `Join` metadata per room, handed to the room with the offer
+    metadata: HashMap<String, HashMap<String, String>>,
+    /// Features negotiated by the `Join` of each room
+    features: HashMap<String, Vec<String>>,
+    /// Join order; signals that name no room address the last room still joined
+    order: Vec<String>,
+    /// Rooms whose data travels as binary frames of this WebSocket
+    websocket_transport: HashSet<String>,
+}
+
+impl JoinedRooms {
+    /// Resolves `room`, falling back to the room joined last.
+    fn get(&self, room: Option<&str>) -> Option<(String, mpsc::Sender<RoomCommand>)> {
+        let room = room.or(self.order.last().map(String::as_str))?;
+        let tx = self.rooms.get(room)?.clone();
+        Some((room.to_string(), tx))
+    }
+
+    fn insert(
+        &mut self,
+        room: String,
+        tx: mpsc::Sender<RoomCommand>,
+        metadata: HashMap<String, String>,
+        features: Vec<String>,
+    ) {
+        self.rooms.insert(room.clone(), tx);
+        self.metadata.insert(room.clone(), metadata);
+        self.features.insert(room.clone(), features);
+        self.order.retain(|joined| *joined != room);
+        self.order.push(room);
+    }
+
+    fn remove(&mut self, room: &str) -> Option<mpsc::Sender<RoomCommand>> {
+        self.order.retain(|joined| joined != room);
+        self.websocket_transport.remove(room);
+        self.metadata.remove(room);
+        self.features.remove(room);
+        self.rooms.remove(room)
+    }
+
+    fn metadata(&self, room: &str) -> HashMap<String, String> {
+        self.metadata.get(room).cloned().unwrap_or_default()
+    }
+
+    fn features(&self, room: &str) -> Vec<String> {
+        self.features.get(room).cloned().unwrap_or_default()
+    }
+
+    fn drain(&mut self) -> Vec<(String, mpsc::Sender<RoomCommand>)> {
+        self.order.clear();
+        self.websocket_transport.clear();
+        self.metadata.clear();
+        self.features.clear();
+        self.rooms.drain().collect()
+    }
+}
@@ -1,7 +1,9 @@
+use crate::transport::peer_transport::{PeerSender, Transport, TransportFactory};
 use crate::transport::transport_config::TransportConfig;
 use crate::transport::transport_event::TransportEvent;
-use antenna_core::PeerId;
+use antenna_core::{DEFAULT_FRAGMENT_SIZE, PeerId};
 use anyhow::Result;
+use async_trait::async_trait;
 use bytes::Bytes;
 use std::default::Default;
 use std::sync::Arc;
@@ -160,8 +162,11 @@ impl ConnectionWrapper {
             peer_connection,
         })
     }
+}
 
-    pub async fn add_track(&self, track: Arc<TrackLocalStaticRTP>) -> Result<()> {
+#[async_trait]
+impl Transport for ConnectionWrapper {
+    async fn add_track(&self, track: Arc<TrackLocalStaticRTP>) -> Result<()> {
         self.peer_connection
             .add_track(
                 Arc::clone(&track) as Arc<dyn webrtc::track::track_local::TrackLocal + Send + Sync>
@@ -170,14 +175,14 @@ impl ConnectionWrapper {
         Ok(())
     }
 
-    pub async fn set_remote_description(&self, sdp: String) -> Result<()> {
+    async fn set_remote_description(&self, sdp: String) -> Result<()> {
         let desc =
             webrtc::peer_connection::sdp::session_description::RTCSessionDescription::offer(sdp)?;
         self.peer_connection.set_remote_description(desc).await?;
         Ok(())
     }
 
-    pub async fn create_answer(&self) -> Result<String> {
+    async fn create_answer(&self) -> Result<String> {
         let answer = self.peer_connection.create_answer(None).await?;
         self.peer_connection
             .set_local_description(answer.clone())
@@ -185,7 +190,7 @@ impl ConnectionWrapper {
         Ok(answer.sdp)
     }
 
-    pub async fn add_ice_candidate(&self, candidate_json: String) -> Result<()> {
+    async fn add_ice_candidate(&self, candidate_json: String) -> Result<()> {
         if candidate_json.is_empty() {
             return Ok(());
         }
@@ -209,8 +214,41 @@ impl ConnectionWrapper {
         Ok(())
     }
 
-    pub async fn close(&self) -> Result<()> {
+    async fn close(&self) -> Result<()> {
         self.peer_connection.close().await?;
         Ok(())
     }
 }
+
+#[async_trait]
+impl PeerSender for RTCDataChannel {
+    async fn send(&self, data: &Bytes) -> Result<()> {
+        RTCDataChannel::send(self, data).await?;
+        Ok(())
+    }
+
+    async fn buffered_amount(&self) -> usize {
+        RTCDataChannel::buffered_amount(self).await
+    }
+
+    fn fragment_size(&self) -> Option<usize> {
+        Some(DEFAULT_FRAGMENT_SIZE)
+    }
+}
+
+/// Default transport factory, establishing WebRTC peer connections.
+#[derive(Clone, Default)]
+pub struct WebRtcTransportFactory;
+
+#[async_trait]
+impl TransportFactory for WebRtcTransportFactory {
+    async fn create(
+        &self,
+        peer_id: PeerId,
+        config: TransportConfig,
+        event_tx: mpsc::Sender<TransportEvent>,
+    ) -> Result<Box<dyn Transport>> {
+        let transport = ConnectionWrapper::new(peer_id, config, event_tx).await?;
+        Ok(Box::new(transport))
+    }
+}
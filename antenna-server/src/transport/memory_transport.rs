@@ -0,0 +1,247 @@
+use crate::transport::peer_transport::{PeerSender, Transport, TransportFactory};
+use crate::transport::transport_config::TransportConfig;
+use crate::transport::transport_event::TransportEvent;
+use antenna_core::{DEFAULT_FRAGMENT_SIZE, PeerId, Reassembler, split_frames};
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use bytes::Bytes;
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+use tokio::sync::{Mutex, mpsc};
+use tokio::time::Instant;
+use tracing::warn;
+use webrtc::track::track_local::track_local_static_rtp::TrackLocalStaticRTP;
+
+type Relay<T> = mpsc::UnboundedSender<(Instant, T)>;
+
+/// Spawns a FIFO delivery task which hands every item to `deliver` after `latency` has passed.
+/// Timing is driven by the tokio clock, so paused-clock tests advance it instantly.
+fn spawn_relay<T, F, Fut>(latency: Duration, mut deliver: F) -> Relay<T>
+where
+    T: Send + 'static,
+    F: FnMut(T) -> Fut + Send + 'static,
+    Fut: Future<Output = bool> + Send,
+{
+    let (tx, mut rx) = mpsc::unbounded_channel::<(Instant, T)>();
+
+    tokio::spawn(async move {
+        while let Some((sent_at, item)) = rx.recv().await {
+            tokio::time::sleep_until(sent_at + latency).await;
+            if !deliver(item).await {
+                break;
+            }
+        }
+    });
+
+    tx
+}
+
+/// In-memory transport factory for testing room behaviors without WebRTC.
+///
+/// Every created transport completes negotiation immediately and hands the client side of the
+/// link to [`MemoryTransportFactory::accept`].
+#[derive(Clone)]
+pub struct MemoryTransportFactory {
+    latency: Duration,
+    fragment_size: usize,
+    clients_tx: mpsc::UnboundedSender<MemoryClient>,
+    clients_rx: Arc<Mutex<mpsc::UnboundedReceiver<MemoryClient>>>,
+}
+
+impl MemoryTransportFactory {
+    pub fn new() -> Self {
+        let (clients_tx, clients_rx) = mpsc::unbounded_channel();
+        Self {
+            latency: Duration::ZERO,
+            fragment_size: DEFAULT_FRAGMENT_SIZE,
+            clients_tx,
+            clients_rx: Arc::new(Mutex::new(clients_rx)),
+        }
+    }
+
+    /// Delays every delivery (in both directions) by `latency`.
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Frame size of the simulated data channel; larger messages are fragmented like on WebRTC.
+    pub fn with_fragment_size(mut self, fragment_size: usize) -> Self {
+        self.fragment_size = fragment_size;
+        self
+    }
+
+    /// Waits for the next peer whose join request was answered by the room.
+    pub async fn accept(&self) -> Option<MemoryClient> {
+        self.clients_rx.lock().await.recv().await
+    }
+}
+
+impl Default for MemoryTransportFactory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl TransportFactory for MemoryTransportFactory {
+    async fn create(
+        &self,
+        peer_id: PeerId,
+        _config: TransportConfig,
+        event_tx: mpsc::Sender<TransportEvent>,
+    ) -> Result<Box<dyn Transport>> {
+        Ok(Box::new(MemoryTransport {
+            peer_id,
+            latency: self.latency,
+            fragment_size: self.fragment_size,
+            event_tx,
+            clients_tx: self.clients_tx.clone(),
+        }))
+    }
+}
+
+struct MemoryTransport {
+    peer_id: PeerId,
+    latency: Duration,
+    fragment_size: usize,
+    event_tx: mpsc::Sender<TransportEvent>,
+    clients_tx: mpsc::UnboundedSender<MemoryClient>,
+}
+
+#[async_trait]
+impl Transport for MemoryTransport {
+    async fn set_remote_description(&self, _sdp: String) -> Result<()> {
+        Ok(())
+    }
+
+    async fn create_answer(&self) -> Result<String> {
+        let (inbound_tx, inbound) = mpsc::unbounded_channel();
+        let to_client = spawn_relay(self.latency, move |data: Bytes| {
+            let inbound_tx = inbound_tx.clone();
+            async move { inbound_tx.send(data).is_ok() }
+        });
+
+        let event_tx = self.event_tx.clone();
+        let to_room = spawn_relay(self.latency, move |event: TransportEvent| {
+            let event_tx = event_tx.clone();
+            async move { event_tx.send(event).await.is_ok() }
+        });
+
+        let sender = Arc::new(MemoryPeerSender {
+            relay: to_client,
+            fragment_size: self.fragment_size,
+        });
+        to_room
+            .send((
+                Instant::now(),
+                TransportEvent::DataChannelReady(self.peer_id.clone(), sender),
+            ))
+            .map_err(|_| anyhow!("Room transport channel closed"))?;
+
+        let client = MemoryClient {
+            peer_id: self.peer_id.clone(),
+            outbound: to_room,
+            inbound,
+            fragment_size: self.fragment_size,
+            next_message_id: AtomicU32::new(0),
+            reassembler: Reassembler::default(),
+        };
+        self.clients_tx
+            .send(client)
+            .map_err(|_| anyhow!("Memory transport factory dropped"))?;
+
+        Ok(format!("memory:{}", self.peer_id))
+    }
+
+    async fn add_ice_candidate(&self, _candidate: String) -> Result<()> {
+        Ok(())
+    }
+
+    async fn add_track(&self, _track: Arc<TrackLocalStaticRTP>) -> Result<()> {
+        Ok(())
+    }
+
+    async fn close(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+struct MemoryPeerSender {
+    relay: Relay<Bytes>,
+    fragment_size: usize,
+}
+
+#[async_trait]
+impl PeerSender for MemoryPeerSender {
+    async fn send(&self, data: &Bytes) -> Result<()> {
+        self.relay
+            .send((Instant::now(), data.clone()))
+            .map_err(|_| anyhow!("Memory client disconnected"))
+    }
+
+    fn fragment_size(&self) -> Option<usize> {
+        Some(self.fragment_size)
+    }
+}
+
+/// Client side of an in-memory link, playing the role of a browser peer in tests.
+pub struct MemoryClient {
+    pub peer_id: PeerId,
+    outbound: Relay<TransportEvent>,
+    inbound: mpsc::UnboundedReceiver<Bytes>,
+    fragment_size: usize,
+    next_message_id: AtomicU32,
+    reassembler: Reassembler,
+}
+
+impl MemoryClient {
+    /// Sends a message to the room, as if received on the peer's data channel.
+    pub fn send(&self, data: impl Into<Bytes>) {
+        let data = data.into();
+        let message_id = self.next_message_id.fetch_add(1, Ordering::Relaxed);
+
+        for frame in split_frames(message_id, &data, self.fragment_size) {
+            let event = TransportEvent::Message(self.peer_id.clone(), Bytes::from(frame));
+            let _ = self.outbound.send((Instant::now(), event));
+        }
+    }
+
+    /// Receives the next message sent by the room. Returns `None` once the room dropped the peer.
+    pub async fn recv(&mut self) -> Option<Bytes> {
+        loop {
+            let frame = self.inbound.recv().await?;
+            if let Some(message) = self.reassemble(&frame) {
+                return Some(message);
+            }
+        }
+    }
+
+    /// Returns an already delivered message without waiting.
+    pub fn try_recv(&mut self) -> Option<Bytes> {
+        loop {
+            let frame = self.inbound.try_recv().ok()?;
+            if let Some(message) = self.reassemble(&frame) {
+                return Some(message);
+            }
+        }
+    }
+
+    fn reassemble(&mut self, frame: &[u8]) -> Option<Bytes> {
+        match self.reassembler.push(frame) {
+            Ok(message) => message.map(Bytes::from),
+            Err(e) => {
+                warn!("Dropping message to {:?}: {}", self.peer_id, e);
+                None
+            }
+        }
+    }
+
+    /// Simulates the peer connection going away.
+    pub fn disconnect(self) {
+        let event = TransportEvent::Disconnected(self.peer_id.clone());
+        let _ = self.outbound.send((Instant::now(), event));
+    }
+}
@@ -1,7 +1,17 @@
 mod connection_wrapper;
+mod memory_transport;
+mod peer_transport;
+#[cfg(feature = "quic")]
+mod quic_transport;
 mod transport_config;
 mod transport_event;
+mod websocket_transport;
 
 pub use connection_wrapper::*;
+pub use memory_transport::*;
+pub use peer_transport::*;
+#[cfg(feature = "quic")]
+pub use quic_transport::*;
 pub use transport_config::*;
 pub use transport_event::*;
+pub use websocket_transport::*;
@@ -0,0 +1,84 @@
+use crate::cluster::NodeInfo;
+use crate::transport::transport_config::TransportConfig;
+use crate::transport::transport_event::TransportEvent;
+use antenna_core::{Channel, PeerId, SignalMessage};
+use anyhow::Result;
+use async_trait::async_trait;
+use bytes::Bytes;
+use std::fmt;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use webrtc::track::track_local::track_local_static_rtp::TrackLocalStaticRTP;
+
+/// Outgoing data path of a connected peer. Stored in `RoomContext` once the peer is ready.
+#[async_trait]
+pub trait PeerSender: Send + Sync + 'static {
+    async fn send(&self, data: &Bytes) -> Result<()>;
+
+    /// Sends with the delivery guarantees of `channel`. Transports with a single data path ignore it.
+    async fn send_on(&self, _channel: Channel, data: &Bytes) -> Result<()> {
+        self.send(data).await
+    }
+
+    /// Bytes accepted by the transport but not yet put on the wire.
+    async fn buffered_amount(&self) -> usize {
+        0
+    }
+
+    /// Largest frame the transport delivers intact. Bigger messages are split with
+    /// `antenna_core::split_frames` and every frame carries the framing header. `None` for
+    /// transports that frame messages on their own.
+    fn fragment_size(&self) -> Option<usize> {
+        None
+    }
+
+    /// Ends the connection when the room drops a peer it does not own the transport of
+    /// (`RoomCommand::Attach`). Does nothing by default.
+    async fn close(&self) {}
+
+    /// Delivers an admission signal (`WaitListed`, `JoinRejected`, `Admitted`) of a peer attached
+    /// with `RoomCommand::Attach` through its own connection; after `JoinRejected` the transport
+    /// ends the connection itself. Returns `false` when the transport has no way to, the room then
+    /// sends the signal over signaling. Returns `false` by default.
+    async fn signal(&self, _msg: &SignalMessage) -> bool {
+        false
+    }
+
+    /// Points the peer at `owner`, which took its room over, through the peer's own connection
+    /// and ends it. Returns `false` when the transport cannot carry the redirect, the room then
+    /// sends `SignalMessage::Redirect` over signaling. Returns `false` by default.
+    async fn redirect(&self, _owner: &NodeInfo) -> bool {
+        false
+    }
+}
+
+impl fmt::Debug for dyn PeerSender {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PeerSender")
+    }
+}
+
+/// Negotiated connection of a single peer, owned by the `Room`.
+#[async_trait]
+pub trait Transport: Send + Sync + 'static {
+    async fn set_remote_description(&self, sdp: String) -> Result<()>;
+
+    async fn create_answer(&self) -> Result<String>;
+
+    async fn add_ice_candidate(&self, candidate: String) -> Result<()>;
+
+    async fn add_track(&self, track: Arc<TrackLocalStaticRTP>) -> Result<()>;
+
+    async fn close(&self) -> Result<()>;
+}
+
+/// Creates transports for peers joining a room. Network events are reported through `event_tx`.
+#[async_trait]
+pub trait TransportFactory: Send + Sync + 'static {
+    async fn create(
+        &self,
+        peer_id: PeerId,
+        config: TransportConfig,
+        event_tx: mpsc::Sender<TransportEvent>,
+    ) -> Result<Box<dyn Transport>>;
+}
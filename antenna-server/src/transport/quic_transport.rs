@@ -0,0 +1,426 @@
+use crate::cluster::NodeInfo;
+use crate::room::{RoomCommand, RoomManager, RoomRoute};
+use crate::transport::peer_transport::PeerSender;
+use antenna_core::{
+    Channel, ErrorCode, FEATURE_WEBSOCKET_TRANSPORT, PROTOCOL_VERSION, PeerId, SignalMessage,
+    negotiate,
+};
+use anyhow::{Context, Result, anyhow, bail};
+use async_trait::async_trait;
+use bytes::Bytes;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, Semaphore, mpsc};
+use tracing::{info, warn};
+use wtransport::endpoint::IncomingSession;
+use wtransport::endpoint::endpoint_side::Server;
+use wtransport::error::StreamReadExactError;
+use wtransport::tls::Sha256Digest;
+use wtransport::{Connection, Endpoint, Identity, RecvStream, SendStream, ServerConfig};
+
+/// Upper bound of a single reliable frame or unordered stream payload.
+const MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
+
+/// Unidirectional streams of one peer read at the same time; further streams wait to be accepted.
+const MAX_CONCURRENT_STREAMS: usize = 16;
+
+/// How long a rejected peer's session stays open for the rejection to be acknowledged.
+const REJECT_LINGER: Duration = Duration::from_secs(5);
+
+/// Application error code of a session the server closed because another node took its room
+/// over. The close reason carries the URL to reconnect to (`NodeInfo::quic_url`).
+pub const QUIC_REDIRECT_CODE: u32 = 0x52;
+
+/// WebTransport (HTTP/3 over QUIC) endpoint, an alternative to WebRTC data channels for
+/// native and browser clients.
+///
+/// A client opens a bidirectional control stream and sends a length-prefixed JSON
+/// `SignalMessage::Join`; the server attaches the peer to the room and answers with
+/// `SignalMessage::Welcome` once the room admitted it (`WaitListed` and `JoinRejected` arrive
+/// the same way), or with `SignalMessage::Redirect` when the room runs on another node
+/// (`NodeInfo::quic_url`). Afterwards the control stream carries `Channel::Reliable` frames,
+/// unidirectional streams carry `Channel::ReliableUnordered` messages and datagrams carry
+/// `Channel::Unreliable` ones. When another node takes the room over, connected peers are closed
+/// with `QUIC_REDIRECT_CODE`. Clients announcing a message schema other than the one of
+/// `room_manager` (`RoomConfig::schema`) are rejected, like on the WebSocket signaling.
+pub struct QuicServer {
+    endpoint: Endpoint<Server>,
+    room_manager: RoomManager,
+    certificate_hashes: Vec<Sha256Digest>,
+    schema: Option<Arc<str>>,
+}
+
+impl QuicServer {
+    pub fn bind(addr: SocketAddr, identity: Identity, room_manager: RoomManager) -> Result<Self> {
+        let certificate_hashes = identity
+            .certificate_chain()
+            .as_slice()
+            .iter()
+            .map(|cert| cert.hash())
+            .collect();
+
+        let config = ServerConfig::builder()
+            .with_bind_address(addr)
+            .with_identity(identity)
+            .keep_alive_interval(Some(Duration::from_secs(3)))
+            .build();
+
+        Ok(Self {
+            endpoint: Endpoint::server(config)?,
+            schema: room_manager.schema().map(Arc::from),
+            room_manager,
+            certificate_hashes,
+        })
+    }
+
+    /// Binds with a freshly generated self-signed certificate, intended for local testing.
+    /// Browsers accept it through `serverCertificateHashes`, see [`QuicServer::certificate_hashes`].
+    pub fn bind_self_signed(addr: SocketAddr, room_manager: RoomManager) -> Result<Self> {
+        let identity = Identity::self_signed(["localhost", "127.0.0.1", "::1"])?;
+        Self::bind(addr, identity, room_manager)
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.endpoint.local_addr()?)
+    }
+
+    /// SHA-256 hashes of the served certificate chain.
+    pub fn certificate_hashes(&self) -> &[Sha256Digest] {
+        &self.certificate_hashes
+    }
+
+    pub async fn run(self) {
+        info!("QUIC server listening on {:?}", self.endpoint.local_addr());
+
+        loop {
+            let incoming = self.endpoint.accept().await;
+            let room_manager = self.room_manager.clone();
+            let schema = self.schema.clone();
+
+            tokio::spawn(async move {
+                if let Err(e) = handle_session(incoming, room_manager, schema).await {
+                    warn!("QUIC session finished with error: {:?}", e);
+                }
+            });
+        }
+    }
+}
+
+async fn handle_session(
+    incoming: IncomingSession,
+    room_manager: RoomManager,
+    server_schema: Option<Arc<str>>,
+) -> Result<()> {
+    let request = incoming.await?;
+    let connection = request.accept().await?;
+    let (mut control_send, mut control_recv) = connection.accept_bi().await?;
+
+    let join = read_frame(&mut control_recv)
+        .await?
+        .context("Control stream closed before Join")?;
+    let (room, mut features, metadata) = match serde_json::from_slice::<SignalMessage>(&join)? {
+        SignalMessage::Join {
+            room,
+            version,
+            features,
+            schema,
+            metadata,
+        } => match negotiate(
+            version,
+            &features,
+            schema.as_deref(),
+            server_schema.as_deref(),
+        ) {
+            Ok(features) => (room, features, metadata),
+            Err(reason) => {
+                let reject = SignalMessage::Reject {
+                    reason: reason.clone(),
+                };
+                write_frame(&mut control_send, &serde_json::to_vec(&reject)?).await?;
+                control_send.finish().await?;
+                bail!("Rejected QUIC peer: {}", reason);
+            }
+        },
+        other => bail!("Expected Join on control stream, got {:?}", other),
+    };
+
+    let room_tx = match room_manager.route(&room).await {
+        Ok(RoomRoute::Local(tx)) => tx,
+        Ok(RoomRoute::Remote(node)) => {
+            info!(
+                "Redirecting QUIC peer to node {} for room '{}'",
+                node.id, room
+            );
+            let redirect = SignalMessage::Redirect {
+                url: node.quic_url.unwrap_or(node.url),
+                room: Some(room),
+            };
+            write_frame(&mut control_send, &serde_json::to_vec(&redirect)?).await?;
+            control_send.finish().await?;
+            return Ok(());
+        }
+        Err(e) => {
+            let error = SignalMessage::Error {
+                code: ErrorCode::RoomUnavailable,
+                message: e.to_string(),
+                fatal: true,
+                room: Some(room),
+            };
+            write_frame(&mut control_send, &serde_json::to_vec(&error)?).await?;
+            control_send.finish().await?;
+            return Err(e);
+        }
+    };
+
+    let peer_id = PeerId::new();
+    info!("QUIC peer {:?} joins room '{}'", peer_id, room);
+
+    // There is no signaling WebSocket to fall back to
+    features.retain(|feature| feature != FEATURE_WEBSOCKET_TRANSPORT);
+    // Sent by the room once it admits the peer, after any `WaitListed`
+    let welcome = SignalMessage::Welcome {
+        peer_id: peer_id.clone(),
+        room: Some(room.clone()),
+        version: PROTOCOL_VERSION,
+        features: features.clone(),
+    };
+
+    let sender = QuicSender {
+        connection: connection.clone(),
+        control: Arc::new(Mutex::new(control_send)),
+        welcome: std::sync::Mutex::new(Some(welcome)),
+    };
+    room_tx
+        .send(RoomCommand::Attach {
+            peer_id: peer_id.clone(),
+            sender: Arc::new(sender),
+            metadata,
+            features,
+        })
+        .await
+        .map_err(|_| anyhow!("Room closed"))?;
+
+    let mut control_task = tokio::spawn(forward_control(
+        control_recv,
+        peer_id.clone(),
+        room_tx.clone(),
+    ));
+    let streams_task = tokio::spawn(forward_streams(
+        connection.clone(),
+        peer_id.clone(),
+        room_tx.clone(),
+    ));
+    let datagrams_task = tokio::spawn(forward_datagrams(
+        connection.clone(),
+        peer_id.clone(),
+        room_tx.clone(),
+    ));
+
+    tokio::select! {
+        _ = &mut control_task => {},
+        _ = connection.closed() => control_task.abort(),
+    }
+    streams_task.abort();
+    datagrams_task.abort();
+
+    info!("QUIC peer {:?} disconnected", peer_id);
+    let _ = room_tx.send(RoomCommand::Disconnect { peer_id }).await;
+    Ok(())
+}
+
+async fn forward_control(
+    mut control: RecvStream,
+    peer_id: PeerId,
+    room_tx: mpsc::Sender<RoomCommand>,
+) {
+    while let Ok(Some(data)) = read_frame(&mut control).await {
+        let cmd = RoomCommand::Data {
+            peer_id: peer_id.clone(),
+            data,
+        };
+        if room_tx.send(cmd).await.is_err() {
+            break;
+        }
+    }
+}
+
+async fn forward_streams(
+    connection: Connection,
+    peer_id: PeerId,
+    room_tx: mpsc::Sender<RoomCommand>,
+) {
+    let permits = Arc::new(Semaphore::new(MAX_CONCURRENT_STREAMS));
+
+    loop {
+        // Stop accepting while the peer already has the maximum of streams in flight
+        let Ok(permit) = permits.clone().acquire_owned().await else {
+            break;
+        };
+        let Ok(mut stream) = connection.accept_uni().await else {
+            break;
+        };
+        let peer_id = peer_id.clone();
+        let room_tx = room_tx.clone();
+
+        tokio::spawn(async move {
+            let _permit = permit;
+            match read_to_end(&mut stream).await {
+                Ok(data) => {
+                    let _ = room_tx.send(RoomCommand::Data { peer_id, data }).await;
+                }
+                Err(e) => warn!("Failed to read QUIC stream of {:?}: {:?}", peer_id, e),
+            }
+        });
+    }
+}
+
+async fn forward_datagrams(
+    connection: Connection,
+    peer_id: PeerId,
+    room_tx: mpsc::Sender<RoomCommand>,
+) {
+    while let Ok(datagram) = connection.receive_datagram().await {
+        let cmd = RoomCommand::Data {
+            peer_id: peer_id.clone(),
+            data: datagram.payload(),
+        };
+        if room_tx.send(cmd).await.is_err() {
+            break;
+        }
+    }
+}
+
+struct QuicSender {
+    connection: Connection,
+    control: Arc<Mutex<SendStream>>,
+    /// Answers the `Join` once the room admitted the peer
+    welcome: std::sync::Mutex<Option<SignalMessage>>,
+}
+
+#[async_trait]
+impl PeerSender for QuicSender {
+    async fn send(&self, data: &Bytes) -> Result<()> {
+        self.send_on(Channel::Reliable, data).await
+    }
+
+    async fn send_on(&self, channel: Channel, data: &Bytes) -> Result<()> {
+        match channel {
+            Channel::Unreliable
+                if self
+                    .connection
+                    .max_datagram_size()
+                    .is_some_and(|max| data.len() <= max) =>
+            {
+                self.connection.send_datagram(data)?;
+            }
+            Channel::ReliableUnordered => {
+                let mut stream = self.connection.open_uni().await?.await?;
+                stream.write_all(data).await?;
+                stream.finish().await?;
+            }
+            // Datagrams too large for the path fall back to the reliable stream
+            Channel::Reliable | Channel::Unreliable => {
+                let mut control = self.control.lock().await;
+                write_frame(&mut control, data).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn close(&self) {
+        self.connection.close(0u32.into(), b"leave");
+    }
+
+    /// Admission results are JSON frames on the control stream, like the handshake; `Welcome`
+    /// stands for `Admitted` and ends the handshake.
+    async fn signal(&self, msg: &SignalMessage) -> bool {
+        let msg = match msg {
+            SignalMessage::Admitted { .. } => {
+                let Some(welcome) = self.welcome.lock().unwrap().take() else {
+                    return true;
+                };
+                welcome
+            }
+            other => other.clone(),
+        };
+        let json = match serde_json::to_vec(&msg) {
+            Ok(json) => json,
+            Err(e) => {
+                warn!("Failed to encode {:?}: {:?}", msg, e);
+                return true;
+            }
+        };
+
+        let mut control = self.control.lock().await;
+        if let Err(e) = write_frame(&mut control, &json).await {
+            warn!("Failed to send {:?} to QUIC peer: {:?}", msg, e);
+        }
+        drop(control);
+
+        if matches!(msg, SignalMessage::JoinRejected { .. }) {
+            // Close once the rejection got through, without holding up the room
+            let control = self.control.clone();
+            let connection = self.connection.clone();
+            tokio::spawn(async move {
+                let mut control = control.lock().await;
+                let _ = tokio::time::timeout(REJECT_LINGER, control.finish()).await;
+                connection.close(0u32.into(), b"rejected");
+            });
+        }
+        true
+    }
+
+    async fn redirect(&self, owner: &NodeInfo) -> bool {
+        match &owner.quic_url {
+            Some(url) => self
+                .connection
+                .close(QUIC_REDIRECT_CODE.into(), url.as_bytes()),
+            None => {
+                warn!("Node {} has no QUIC endpoint to redirect to", owner.id);
+                self.connection.close(0u32.into(), b"leave");
+            }
+        }
+        true
+    }
+}
+
+async fn write_frame(stream: &mut SendStream, data: &[u8]) -> Result<()> {
+    if data.len() > MAX_FRAME_SIZE {
+        bail!("Frame of {} bytes exceeds the maximum size", data.len());
+    }
+    stream.write_all(&(data.len() as u32).to_be_bytes()).await?;
+    stream.write_all(data).await?;
+    Ok(())
+}
+
+async fn read_frame(stream: &mut RecvStream) -> Result<Option<Bytes>> {
+    let mut len = [0u8; 4];
+    match stream.read_exact(&mut len).await {
+        Ok(()) => {}
+        Err(StreamReadExactError::FinishedEarly(0)) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_FRAME_SIZE {
+        bail!("Frame of {} bytes exceeds the maximum size", len);
+    }
+
+    let mut data = vec![0u8; len];
+    stream.read_exact(&mut data).await?;
+    Ok(Some(Bytes::from(data)))
+}
+
+async fn read_to_end(stream: &mut RecvStream) -> Result<Bytes> {
+    let mut data = Vec::new();
+    let mut chunk = [0u8; 4096];
+
+    while let Some(n) = stream.read(&mut chunk).await? {
+        data.extend_from_slice(&chunk[..n]);
+        if data.len() > MAX_FRAME_SIZE {
+            bail!("Stream exceeds the maximum message size");
+        }
+    }
+    Ok(Bytes::from(data))
+}
@@ -0,0 +1,39 @@
+use crate::signaling::SignalingService;
+use crate::transport::peer_transport::PeerSender;
+use antenna_core::{PeerId, encode_ws_frame};
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use bytes::Bytes;
+
+/// Fallback data path for peers without a working WebRTC connection.
+/// Packets are carried as binary frames over the peer's signaling WebSocket, prefixed with
+/// `room` (`antenna_core::encode_ws_frame`) since the connection may serve several rooms.
+pub struct WebSocketSender {
+    peer_id: PeerId,
+    room: String,
+    signaling_service: SignalingService,
+}
+
+impl WebSocketSender {
+    pub fn new(
+        peer_id: PeerId,
+        room: impl Into<String>,
+        signaling_service: SignalingService,
+    ) -> Self {
+        Self {
+            peer_id,
+            room: room.into(),
+            signaling_service,
+        }
+    }
+}
+
+#[async_trait]
+impl PeerSender for WebSocketSender {
+    async fn send(&self, data: &Bytes) -> Result<()> {
+        let frame = encode_ws_frame(&self.room, data)
+            .ok_or_else(|| anyhow!("Room name '{}' is too long for a frame", self.room))?;
+        self.signaling_service
+            .send_data(&self.peer_id, Bytes::from(frame))
+    }
+}
@@ -0,0 +1,16 @@
+mod test_memory_echo;
+mod test_memory_fragmentation;
+mod test_memory_input_acks;
+mod test_memory_interest_broadcast;
+mod test_memory_latency;
+mod test_memory_peer_leave_command;
+mod test_memory_peer_leaves;
+mod test_memory_room_presence;
+mod test_memory_room_shutdown;
+mod test_memory_shared_state;
+mod test_memory_stream_relay;
+mod test_shared_state_encode_error;
+mod test_shared_state_slow_peer;
+mod test_stream_relay_slow_receiver;
+mod test_unnegotiated_features;
+mod test_zero_interest_cell_size;
@@ -0,0 +1,25 @@
+use crate::integration::create_memory_room;
+use crate::utils::{TestRoomBehavior, join_memory_peer};
+use antenna_server::MemoryTransportFactory;
+
+#[tokio::test(start_paused = true)]
+async fn test_memory_echo() {
+    let transport = MemoryTransportFactory::new();
+    let behavior = TestRoomBehavior::new().with_echo();
+    let room_cmd_tx = create_memory_room(behavior.clone(), transport.clone());
+
+    let mut client = join_memory_peer(&room_cmd_tx, &transport)
+        .await
+        .expect("Failed to join peer");
+
+    assert!(behavior.wait_for_events(1, 1000).await);
+    assert!(behavior.has_join(&client.peer_id).await);
+
+    client.send(&b"ping"[..]);
+
+    let echoed = client.recv().await.expect("Room closed the link");
+    assert_eq!(echoed.as_ref(), b"ping");
+
+    let messages = behavior.messages_from(&client.peer_id).await;
+    assert_eq!(messages.len(), 1);
+}
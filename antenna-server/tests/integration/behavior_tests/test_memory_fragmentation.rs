@@ -0,0 +1,26 @@
+use crate::integration::create_memory_room;
+use crate::utils::{TestRoomBehavior, join_memory_peer};
+use antenna_server::MemoryTransportFactory;
+
+#[tokio::test(start_paused = true)]
+async fn test_memory_fragmentation() {
+    // Frames of 64 bytes force the payload through fragmentation in both directions
+    let transport = MemoryTransportFactory::new().with_fragment_size(64);
+    let behavior = TestRoomBehavior::new().with_echo();
+    let room_cmd_tx = create_memory_room(behavior.clone(), transport.clone());
+
+    let mut client = join_memory_peer(&room_cmd_tx, &transport)
+        .await
+        .expect("Failed to join peer");
+    assert!(behavior.wait_for_events(1, 1000).await);
+
+    let payload: Vec<u8> = (0..1000u32).map(|i| (i % 251) as u8).collect();
+    client.send(payload.clone());
+
+    let echoed = client.recv().await.expect("Room closed the link");
+    assert_eq!(echoed.as_ref(), payload.as_slice());
+
+    let messages = behavior.messages_from(&client.peer_id).await;
+    assert_eq!(messages.len(), 1, "Behavior sees one reassembled message");
+    assert_eq!(messages[0].as_ref(), payload.as_slice());
+}
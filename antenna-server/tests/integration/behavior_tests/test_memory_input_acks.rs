@@ -0,0 +1,100 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicI32, Ordering};
+
+use antenna_core::{AckTracker, Packet, PeerId};
+use antenna_server::{
+    MemoryClient, MemoryTransportFactory, Room, RoomBehavior, RoomCommand, RoomContext,
+};
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::sync::mpsc;
+
+use crate::utils::{MockSignalingOutput, join_memory_peer};
+
+/// Answers every input with the total of all steps, acknowledging the processed input.
+#[derive(Default)]
+struct MovementBehavior {
+    position: AtomicI32,
+}
+
+#[async_trait]
+impl RoomBehavior for MovementBehavior {
+    async fn on_join(&self, _ctx: &RoomContext, _peer_id: PeerId) {}
+
+    async fn on_message(&self, ctx: &RoomContext, peer_id: PeerId, data: Bytes) {
+        let Ok(Packet::Sequenced { payload: step, .. }) =
+            postcard::from_bytes::<Packet<i32>>(&data)
+        else {
+            return;
+        };
+        let position = self.position.fetch_add(step, Ordering::SeqCst) + step;
+        let reply = Packet::Sequenced {
+            header: ctx.next_header(&peer_id),
+            payload: position,
+        };
+        let _ = ctx
+            .send(
+                &peer_id,
+                Bytes::from(postcard::to_allocvec(&reply).unwrap()),
+            )
+            .await;
+    }
+
+    async fn on_leave(&self, _ctx: &RoomContext, _peer_id: PeerId) {}
+}
+
+fn input(received: &AckTracker, seq: u32, step: i32) -> Bytes {
+    let packet = Packet::Sequenced {
+        header: received.header(seq),
+        payload: step,
+    };
+    Bytes::from(postcard::to_allocvec(&packet).unwrap())
+}
+
+async fn recv_ack(client: &mut MemoryClient, received: &mut AckTracker) -> (Option<u32>, i32) {
+    let data = client.recv().await.expect("Room closed the link");
+    match postcard::from_bytes::<Packet<i32>>(&data) {
+        Ok(Packet::Sequenced { header, payload }) => {
+            assert!(received.record(header.seq), "Room reused a sequence number");
+            (header.ack, payload)
+        }
+        other => panic!("Expected a sequenced packet, got {:?}", other),
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_memory_input_acks() {
+    let transport = MemoryTransportFactory::new();
+    let (cmd_tx, cmd_rx) = mpsc::channel::<RoomCommand>(100);
+    let signaling = MockSignalingOutput::new_stored_only();
+    let room = Room::new(
+        Box::new(MovementBehavior::default()),
+        cmd_rx,
+        signaling.service.clone(),
+    )
+    .with_transport_factory(Arc::new(transport.clone()));
+    tokio::spawn(room.run());
+
+    let mut client = join_memory_peer(&cmd_tx, &transport)
+        .await
+        .expect("Failed to join peer");
+    let mut received = AckTracker::default();
+
+    client.send(input(&received, 0, 1));
+    assert_eq!(recv_ack(&mut client, &mut received).await, (Some(0), 1));
+
+    client.send(input(&received, 1, 1));
+    assert_eq!(recv_ack(&mut client, &mut received).await, (Some(1), 2));
+
+    // A reordered input older than the last processed one is dropped.
+    client.send(input(&received, 0, 5));
+    client.send(input(&received, 3, 1));
+    assert_eq!(recv_ack(&mut client, &mut received).await, (Some(3), 3));
+
+    assert_eq!(client.try_recv(), None);
+
+    // Acks cover the packets received before the newest one.
+    let header = received.header(4);
+    assert_eq!(header.ack, Some(2));
+    assert_eq!(header.acked().collect::<Vec<_>>(), vec![2, 1, 0]);
+}
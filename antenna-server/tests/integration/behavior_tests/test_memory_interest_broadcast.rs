@@ -0,0 +1,108 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use antenna_core::PeerId;
+use antenna_server::{
+    MemoryClient, MemoryTransportFactory, Room, RoomBehavior, RoomCommand, RoomContext,
+};
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::sync::mpsc;
+
+use crate::utils::{MockSignalingOutput, join_memory_peer};
+
+/// Teams and spawn points handed out in join order.
+const SPAWNS: [(&str, (f32, f32)); 3] = [
+    ("team-red", (0.0, 0.0)),
+    ("team-blue", (30.0, 40.0)),
+    ("team-red", (500.0, -500.0)),
+];
+
+/// Relays every message to the audience it names.
+#[derive(Default)]
+struct ArenaBehavior {
+    joined: AtomicUsize,
+}
+
+#[async_trait]
+impl RoomBehavior for ArenaBehavior {
+    async fn on_join(&self, ctx: &RoomContext, peer_id: PeerId) {
+        let (team, spawn) = SPAWNS[self.joined.fetch_add(1, Ordering::SeqCst)];
+        ctx.set_groups(&peer_id, [team]);
+        ctx.set_position(&peer_id, spawn);
+        let _ = ctx.send(&peer_id, Bytes::from_static(b"spawned")).await;
+    }
+
+    async fn on_message(&self, ctx: &RoomContext, peer_id: PeerId, data: Bytes) {
+        match &data[..] {
+            b"team" => {
+                let team = ctx.groups(&peer_id).into_iter().next().unwrap();
+                ctx.broadcast_to_group(&team, data).await;
+            }
+            b"near" => {
+                let position = ctx.position(&peer_id).unwrap();
+                ctx.broadcast_near(position, 50.0, data).await;
+            }
+            _ => ctx.broadcast_except(&peer_id, data).await,
+        }
+    }
+
+    async fn on_leave(&self, _ctx: &RoomContext, _peer_id: PeerId) {}
+}
+
+async fn join(
+    cmd_tx: &mpsc::Sender<RoomCommand>,
+    transport: &MemoryTransportFactory,
+) -> MemoryClient {
+    let mut client = join_memory_peer(cmd_tx, transport)
+        .await
+        .expect("Failed to join peer");
+    assert_eq!(client.recv().await.unwrap(), Bytes::from_static(b"spawned"));
+    client
+}
+
+async fn assert_received(clients: &mut [MemoryClient], expected: [bool; 3], message: &[u8]) {
+    // Let the room deliver before checking who did not receive anything
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    for (i, client) in clients.iter_mut().enumerate() {
+        let received = client.try_recv();
+        assert_eq!(
+            received.as_deref() == Some(message),
+            expected[i],
+            "Peer {} received {:?}",
+            i,
+            received
+        );
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_memory_interest_broadcast() {
+    let transport = MemoryTransportFactory::new();
+    let (cmd_tx, cmd_rx) = mpsc::channel::<RoomCommand>(100);
+    let signaling = MockSignalingOutput::new_stored_only();
+    let room = Room::new(
+        Box::new(ArenaBehavior::default()),
+        cmd_rx,
+        signaling.service.clone(),
+    )
+    .with_transport_factory(Arc::new(transport.clone()))
+    .with_interest_cell_size(32.0);
+    tokio::spawn(room.run());
+
+    let mut clients = [
+        join(&cmd_tx, &transport).await,
+        join(&cmd_tx, &transport).await,
+        join(&cmd_tx, &transport).await,
+    ];
+
+    clients[0].send(&b"team"[..]);
+    assert_received(&mut clients, [true, false, true], b"team").await;
+
+    // Peer 1 stands exactly 50 units away, peer 2 far off.
+    clients[0].send(&b"near"[..]);
+    assert_received(&mut clients, [true, true, false], b"near").await;
+
+    clients[2].send(&b"others"[..]);
+    assert_received(&mut clients, [true, true, false], b"others").await;
+}
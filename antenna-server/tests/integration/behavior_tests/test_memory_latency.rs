@@ -0,0 +1,31 @@
+use std::time::Duration;
+
+use crate::integration::create_memory_room;
+use crate::utils::{TestRoomBehavior, join_memory_peer};
+use antenna_server::MemoryTransportFactory;
+use tokio::time::Instant;
+
+#[tokio::test(start_paused = true)]
+async fn test_memory_latency() {
+    let latency = Duration::from_millis(50);
+    let transport = MemoryTransportFactory::new().with_latency(latency);
+    let behavior = TestRoomBehavior::new().with_echo();
+    let room_cmd_tx = create_memory_room(behavior.clone(), transport.clone());
+
+    let mut client = join_memory_peer(&room_cmd_tx, &transport)
+        .await
+        .expect("Failed to join peer");
+
+    let start = Instant::now();
+    for i in 0..10u8 {
+        client.send(vec![i]);
+    }
+
+    for i in 0..10u8 {
+        let data = client.recv().await.expect("Room closed the link");
+        assert_eq!(data.as_ref(), &[i], "Messages must keep their order");
+    }
+
+    // One hop to the room and one hop back, measured on the paused clock.
+    assert_eq!(start.elapsed(), latency * 2);
+}
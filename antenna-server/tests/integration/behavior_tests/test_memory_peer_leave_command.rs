@@ -0,0 +1,30 @@
+use crate::integration::create_memory_room;
+use crate::utils::{TestRoomBehavior, join_memory_peer};
+use antenna_server::{MemoryTransportFactory, RoomCommand};
+
+#[tokio::test(start_paused = true)]
+async fn test_memory_peer_leave_command() {
+    let transport = MemoryTransportFactory::new();
+    let behavior = TestRoomBehavior::new();
+    let room_cmd_tx = create_memory_room(behavior.clone(), transport.clone());
+
+    let mut leaving = join_memory_peer(&room_cmd_tx, &transport)
+        .await
+        .expect("Failed to join peer");
+    assert!(behavior.wait_for_events(1, 1000).await);
+
+    // An explicit Leave does not wait for the transport to notice anything.
+    room_cmd_tx
+        .send(RoomCommand::Leave {
+            peer_id: leaving.peer_id.clone(),
+        })
+        .await
+        .expect("Failed to send leave");
+
+    assert!(behavior.wait_for_events(2, 1000).await);
+    assert!(behavior.has_leave(&leaving.peer_id).await);
+    assert!(
+        leaving.recv().await.is_none(),
+        "Link should close once the peer left"
+    );
+}
@@ -0,0 +1,38 @@
+use crate::integration::create_memory_room;
+use crate::utils::{TestRoomBehavior, join_memory_peer};
+use antenna_server::{MemoryTransportFactory, RoomCommand};
+
+#[tokio::test(start_paused = true)]
+async fn test_memory_peer_leaves() {
+    let transport = MemoryTransportFactory::new();
+    let behavior = TestRoomBehavior::new();
+    let room_cmd_tx = create_memory_room(behavior.clone(), transport.clone());
+
+    let leaving = join_memory_peer(&room_cmd_tx, &transport)
+        .await
+        .expect("Failed to join peer 1");
+    let mut staying = join_memory_peer(&room_cmd_tx, &transport)
+        .await
+        .expect("Failed to join peer 2");
+    assert!(behavior.wait_for_events(2, 1000).await);
+
+    let leaving_id = leaving.peer_id.clone();
+    leaving.disconnect();
+    assert!(behavior.wait_for_events(3, 1000).await);
+    assert!(behavior.has_leave(&leaving_id).await);
+    assert!(!behavior.has_leave(&staying.peer_id).await);
+
+    room_cmd_tx
+        .send(RoomCommand::Disconnect {
+            peer_id: staying.peer_id.clone(),
+        })
+        .await
+        .expect("Failed to send disconnect");
+
+    assert!(
+        staying.recv().await.is_none(),
+        "Link should close once the room drops the peer"
+    );
+    assert!(behavior.wait_for_events(4, 1000).await);
+    assert!(behavior.has_leave(&staying.peer_id).await);
+}
@@ -0,0 +1,60 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use antenna_core::{Packet, PeerId, SystemMessage};
+use antenna_server::{MemoryClient, MemoryTransportFactory, Room, RoomCommand, RoomConfig};
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+
+use crate::utils::{MockSignalingOutput, TestRoomBehavior, join_memory_peer};
+
+async fn next_presence(client: &mut MemoryClient) -> SystemMessage {
+    let data = timeout(Duration::from_secs(5), client.recv())
+        .await
+        .expect("No presence message received")
+        .expect("Room dropped the peer");
+    match postcard::from_bytes::<Packet<()>>(&data) {
+        Ok(Packet::System(msg)) => msg,
+        other => panic!("expected a system message, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_memory_room_presence() {
+    let transport = MemoryTransportFactory::new();
+    let (cmd_tx, cmd_rx) = mpsc::channel::<RoomCommand>(100);
+    let signaling = MockSignalingOutput::new_stored_only();
+    let room = Room::new(
+        Box::new(TestRoomBehavior::new()),
+        cmd_rx,
+        signaling.service.clone(),
+    )
+    .with_transport_factory(Arc::new(transport.clone()))
+    .with_config(RoomConfig::new().with_presence());
+    tokio::spawn(room.run());
+
+    let mut first = join_memory_peer(&cmd_tx, &transport)
+        .await
+        .expect("Failed to join the first peer");
+    let mut second = join_memory_peer(&cmd_tx, &transport)
+        .await
+        .expect("Failed to join the second peer");
+    let second_id: PeerId = second.peer_id.clone();
+
+    match next_presence(&mut first).await {
+        SystemMessage::PeerJoined(peer_id) => assert_eq!(peer_id, second_id),
+        other => panic!("expected PeerJoined, got {:?}", other),
+    }
+    // Peers are not told about themselves.
+    assert!(
+        timeout(Duration::from_millis(200), second.recv())
+            .await
+            .is_err()
+    );
+
+    second.disconnect();
+    match next_presence(&mut first).await {
+        SystemMessage::PeerLeft(peer_id) => assert_eq!(peer_id, second_id),
+        other => panic!("expected PeerLeft, got {:?}", other),
+    }
+}
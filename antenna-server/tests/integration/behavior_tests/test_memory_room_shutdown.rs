@@ -0,0 +1,40 @@
+use tokio::sync::oneshot;
+
+use crate::integration::create_memory_room;
+use crate::utils::{RoomEvent, TestRoomBehavior, join_memory_peer};
+use antenna_server::{MemoryTransportFactory, RoomCommand};
+
+#[tokio::test(start_paused = true)]
+async fn test_memory_room_shutdown() {
+    let transport = MemoryTransportFactory::new();
+    let behavior = TestRoomBehavior::new().with_farewell(b"bye");
+    let room_cmd_tx = create_memory_room(behavior.clone(), transport.clone());
+
+    let mut first = join_memory_peer(&room_cmd_tx, &transport)
+        .await
+        .expect("Failed to join peer 1");
+    let mut second = join_memory_peer(&room_cmd_tx, &transport)
+        .await
+        .expect("Failed to join peer 2");
+    assert!(behavior.wait_for_events(2, 1000).await);
+
+    let (done, closed) = oneshot::channel();
+    room_cmd_tx
+        .send(RoomCommand::Shutdown { done })
+        .await
+        .expect("Failed to send shutdown");
+    closed
+        .await
+        .expect("Room stopped without confirming shutdown");
+
+    // Messages sent from on_close are delivered before the links go down.
+    for client in [&mut first, &mut second] {
+        assert_eq!(client.recv().await.as_deref(), Some(&b"bye"[..]));
+        assert!(client.recv().await.is_none(), "Link should be closed");
+    }
+
+    let events = behavior.get_events().await;
+    assert!(matches!(events.last(), Some(RoomEvent::Close)));
+    assert!(!behavior.has_leave(&first.peer_id).await);
+    assert!(room_cmd_tx.is_closed(), "Room should stop after shutdown");
+}
@@ -0,0 +1,102 @@
+use std::sync::Arc;
+
+use antenna_core::{Packet, PeerId, SystemMessage};
+use antenna_server::{
+    MemoryClient, MemoryTransportFactory, Room, RoomBehavior, RoomCommand, RoomContext, SharedState,
+};
+use async_trait::async_trait;
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use crate::utils::{MockSignalingOutput, join_memory_peer};
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct Board {
+    round: u32,
+    moves: Vec<String>,
+}
+
+/// Records every received message as a move.
+struct BoardBehavior;
+
+#[async_trait]
+impl RoomBehavior for BoardBehavior {
+    async fn on_join(&self, _ctx: &RoomContext, _peer_id: PeerId) {}
+
+    async fn on_message(&self, ctx: &RoomContext, _peer_id: PeerId, data: Bytes) {
+        let state = ctx.state::<Board>().expect("Room has no board");
+        state
+            .update(ctx, |board| {
+                board.round += 1;
+                board
+                    .moves
+                    .push(String::from_utf8_lossy(&data).into_owned());
+            })
+            .await
+            .expect("Update failed");
+    }
+
+    async fn on_leave(&self, _ctx: &RoomContext, _peer_id: PeerId) {}
+}
+
+async fn recv_state(client: &mut MemoryClient) -> SystemMessage {
+    let data = client.recv().await.expect("Room closed the link");
+    match postcard::from_bytes::<Packet<()>>(&data) {
+        Ok(Packet::System(msg)) => msg,
+        other => panic!("Expected a system message, got {:?}", other),
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_memory_shared_state() {
+    let transport = MemoryTransportFactory::new();
+    let (cmd_tx, cmd_rx) = mpsc::channel::<RoomCommand>(100);
+    let signaling = MockSignalingOutput::new_stored_only();
+    let state = SharedState::new(Board::default()).expect("Failed to encode state");
+    let room = Room::new(Box::new(BoardBehavior), cmd_rx, signaling.service.clone())
+        .with_transport_factory(Arc::new(transport.clone()))
+        .with_shared_state(state.clone());
+    tokio::spawn(room.run());
+
+    // Joining peers start from a full snapshot.
+    let mut first = join_memory_peer(&cmd_tx, &transport)
+        .await
+        .expect("Failed to join peer 1");
+    let SystemMessage::StateSnapshot { version, mut data } = recv_state(&mut first).await else {
+        panic!("Expected StateSnapshot");
+    };
+    assert_eq!(version, 0);
+    assert_eq!(
+        postcard::from_bytes::<Board>(&data).unwrap(),
+        Board::default()
+    );
+
+    // Updates arrive as patches against the previous version.
+    first.send(&b"e4"[..]);
+    let SystemMessage::StatePatch { version, delta } = recv_state(&mut first).await else {
+        panic!("Expected StatePatch");
+    };
+    assert_eq!(version, 1);
+    delta.apply(&mut data).expect("Patch does not fit");
+    let expected = Board {
+        round: 1,
+        moves: vec!["e4".to_string()],
+    };
+    assert_eq!(postcard::from_bytes::<Board>(&data).unwrap(), expected);
+
+    // A late joiner gets the current state, equal to the patched one.
+    let mut second = join_memory_peer(&cmd_tx, &transport)
+        .await
+        .expect("Failed to join peer 2");
+    let SystemMessage::StateSnapshot {
+        version,
+        data: late,
+    } = recv_state(&mut second).await
+    else {
+        panic!("Expected StateSnapshot");
+    };
+    assert_eq!(version, 1);
+    assert_eq!(late, data);
+    assert_eq!(state.read(|board| board.round).await, 1);
+}
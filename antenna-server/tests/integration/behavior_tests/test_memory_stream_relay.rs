@@ -0,0 +1,89 @@
+use antenna_core::{Packet, SystemMessage};
+use bytes::Bytes;
+use uuid::Uuid;
+
+use crate::integration::create_memory_room;
+use crate::utils::{TestRoomBehavior, join_memory_peer};
+use antenna_server::MemoryTransportFactory;
+
+fn encode(msg: SystemMessage) -> Bytes {
+    Bytes::from(postcard::to_allocvec(&Packet::<()>::System(msg)).unwrap())
+}
+
+fn decode(data: &[u8]) -> SystemMessage {
+    match postcard::from_bytes::<Packet<()>>(data).expect("Invalid packet") {
+        Packet::System(msg) => msg,
+        other => panic!("Expected a system message, got {:?}", other),
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_memory_stream_relay() {
+    let transport = MemoryTransportFactory::new();
+    let behavior = TestRoomBehavior::new();
+    let room_cmd_tx = create_memory_room(behavior.clone(), transport.clone());
+
+    let sender = join_memory_peer(&room_cmd_tx, &transport)
+        .await
+        .expect("Failed to join sender");
+    let mut receiver = join_memory_peer(&room_cmd_tx, &transport)
+        .await
+        .expect("Failed to join receiver");
+    assert!(behavior.wait_for_events(2, 1000).await);
+
+    let stream_id = Uuid::new_v4();
+    sender.send(encode(SystemMessage::StreamOpen {
+        stream_id,
+        from: None,
+        to: Some(receiver.peer_id.clone()),
+        name: "file.bin".to_owned(),
+        size: 6,
+    }));
+    sender.send(encode(SystemMessage::StreamChunk {
+        stream_id,
+        data: b"abc".to_vec(),
+    }));
+    sender.send(encode(SystemMessage::StreamChunk {
+        stream_id,
+        data: b"def".to_vec(),
+    }));
+    sender.send(encode(SystemMessage::StreamClose { stream_id }));
+
+    let open = receiver.recv().await.expect("Room closed the link");
+    match decode(&open) {
+        SystemMessage::StreamOpen {
+            stream_id: id,
+            from,
+            name,
+            size,
+            ..
+        } => {
+            assert_eq!(id, stream_id);
+            assert_eq!(
+                from,
+                Some(sender.peer_id.clone()),
+                "Server fills in the author"
+            );
+            assert_eq!(name, "file.bin");
+            assert_eq!(size, 6);
+        }
+        other => panic!("Expected StreamOpen, got {:?}", other),
+    }
+
+    let mut received = Vec::new();
+    loop {
+        let data = receiver.recv().await.expect("Room closed the link");
+        match decode(&data) {
+            SystemMessage::StreamChunk { data, .. } => received.extend_from_slice(&data),
+            SystemMessage::StreamClose { stream_id: id } => {
+                assert_eq!(id, stream_id);
+                break;
+            }
+            other => panic!("Unexpected message {:?}", other),
+        }
+    }
+    assert_eq!(received, b"abcdef");
+
+    // Stream packets are relayed by the room and never reach the behavior
+    assert!(behavior.messages_from(&sender.peer_id).await.is_empty());
+}
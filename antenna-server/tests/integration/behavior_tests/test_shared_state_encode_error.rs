@@ -0,0 +1,104 @@
+use std::sync::Arc;
+
+use antenna_core::{Packet, PeerId, SystemMessage};
+use antenna_server::{
+    MemoryClient, MemoryTransportFactory, Room, RoomBehavior, RoomCommand, RoomContext, SharedState,
+};
+use async_trait::async_trait;
+use bytes::Bytes;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use tokio::sync::mpsc;
+
+use crate::utils::{MockSignalingOutput, join_memory_peer};
+
+/// Counter that refuses to encode once poisoned.
+#[derive(Debug, Default)]
+struct Counter {
+    value: u32,
+    poisoned: bool,
+}
+
+impl Serialize for Counter {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.poisoned {
+            return Err(serde::ser::Error::custom("poisoned counter"));
+        }
+        self.value.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Counter {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self {
+            value: u32::deserialize(deserializer)?,
+            poisoned: false,
+        })
+    }
+}
+
+/// Sets the counter to the received byte, poisoning it on `0xff`. A failed update answers with
+/// the value the counter holds afterwards.
+struct CounterBehavior;
+
+#[async_trait]
+impl RoomBehavior for CounterBehavior {
+    async fn on_join(&self, _ctx: &RoomContext, _peer_id: PeerId) {}
+
+    async fn on_message(&self, ctx: &RoomContext, _peer_id: PeerId, data: Bytes) {
+        let state = ctx.state::<Counter>().expect("Room has no counter");
+        let updated = state
+            .update(ctx, |counter| {
+                counter.value = u32::from(data[0]);
+                counter.poisoned = data[0] == 0xff;
+            })
+            .await;
+        if updated.is_err() {
+            let value = state.read(|counter| counter.value).await;
+            ctx.broadcast(Bytes::copy_from_slice(&value.to_le_bytes()))
+                .await;
+        }
+    }
+
+    async fn on_leave(&self, _ctx: &RoomContext, _peer_id: PeerId) {}
+}
+
+async fn recv_state(client: &mut MemoryClient) -> SystemMessage {
+    let data = client.recv().await.expect("Room closed the link");
+    match postcard::from_bytes::<Packet<()>>(&data) {
+        Ok(Packet::System(msg)) => msg,
+        other => panic!("Expected a system message, got {:?}", other),
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_shared_state_encode_error() {
+    let transport = MemoryTransportFactory::new();
+    let (cmd_tx, cmd_rx) = mpsc::channel::<RoomCommand>(100);
+    let signaling = MockSignalingOutput::new_stored_only();
+    let state = SharedState::new(Counter::default()).expect("Failed to encode state");
+    let room = Room::new(Box::new(CounterBehavior), cmd_rx, signaling.service.clone())
+        .with_transport_factory(Arc::new(transport.clone()))
+        .with_shared_state(state.clone());
+    tokio::spawn(room.run());
+
+    let mut client = join_memory_peer(&cmd_tx, &transport)
+        .await
+        .expect("Failed to join peer");
+    let SystemMessage::StateSnapshot { mut data, .. } = recv_state(&mut client).await else {
+        panic!("Expected StateSnapshot");
+    };
+
+    // The failed update leaves the state as it was last sent
+    client.send(&[0xff][..]);
+    let value = client.recv().await.expect("Room closed the link");
+    assert_eq!(value.as_ref(), 0u32.to_le_bytes());
+
+    client.send(&[7][..]);
+    let SystemMessage::StatePatch { version, delta } = recv_state(&mut client).await else {
+        panic!("Expected StatePatch");
+    };
+    assert_eq!(version, 1);
+    delta.apply(&mut data).expect("Patch does not fit");
+    assert_eq!(postcard::from_bytes::<u32>(&data).unwrap(), 7);
+    assert_eq!(state.read(|counter| counter.value).await, 7);
+}
@@ -0,0 +1,150 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use antenna_core::{Packet, PeerId, SystemMessage};
+use antenna_server::{Room, RoomBehavior, RoomCommand, RoomContext, SendQueueConfig, SharedState};
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::sync::mpsc;
+
+use crate::integration::init_tracing;
+use crate::utils::{GatedSender, MockSignalingOutput, all_features};
+
+/// Appends every received message to the shared log.
+struct LogBehavior {
+    joins: mpsc::UnboundedSender<PeerId>,
+}
+
+#[async_trait]
+impl RoomBehavior for LogBehavior {
+    async fn on_join(&self, _ctx: &RoomContext, peer_id: PeerId) {
+        let _ = self.joins.send(peer_id);
+    }
+
+    async fn on_message(&self, ctx: &RoomContext, _peer_id: PeerId, data: Bytes) {
+        let state = ctx.state::<Vec<String>>().expect("Room has no log");
+        state
+            .update(ctx, |log| {
+                log.push(String::from_utf8_lossy(&data).into_owned())
+            })
+            .await
+            .expect("Update failed");
+    }
+
+    async fn on_leave(&self, _ctx: &RoomContext, _peer_id: PeerId) {}
+}
+
+/// Applies the state messages a peer received the way the client engine does.
+fn replay(sent: &[Bytes]) -> Option<(u64, Vec<u8>)> {
+    let mut state: Option<(u64, Vec<u8>)> = None;
+    for data in sent {
+        match postcard::from_bytes::<Packet<()>>(data).expect("Invalid packet") {
+            Packet::System(SystemMessage::StateSnapshot { version, data }) => {
+                state = Some((version, data));
+            }
+            Packet::System(SystemMessage::StatePatch { version, delta }) => {
+                if let Some((current, data)) = &mut state
+                    && version == *current + 1
+                    && delta.apply(data).is_ok()
+                {
+                    *current = version;
+                    continue;
+                }
+                state = None;
+            }
+            other => panic!("Unexpected packet {:?}", other),
+        }
+    }
+    state
+}
+
+async fn wait_for_version(state: &SharedState<Vec<String>>, version: u64) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while state.version().await < version {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("Updates stalled");
+}
+
+#[tokio::test]
+async fn test_shared_state_slow_peer() {
+    init_tracing();
+
+    let config = SendQueueConfig {
+        high_watermark: 32,
+        low_watermark: 0,
+        broadcast_timeout: Duration::from_millis(100),
+        ..SendQueueConfig::default()
+    };
+    let (cmd_tx, cmd_rx) = mpsc::channel::<RoomCommand>(100);
+    let (joins_tx, mut joins_rx) = mpsc::unbounded_channel();
+    let signaling = MockSignalingOutput::new_stored_only();
+    let state = SharedState::new(Vec::<String>::new()).expect("Failed to encode state");
+    let room = Room::new(
+        Box::new(LogBehavior { joins: joins_tx }),
+        cmd_rx,
+        signaling.service.clone(),
+    )
+    .with_send_queue_config(config)
+    .with_shared_state(state.clone());
+    tokio::spawn(room.run());
+
+    let slow = GatedSender::new();
+    let fast = GatedSender::new();
+    fast.open();
+    let fast_id = PeerId::new();
+    for (peer_id, sender) in [(PeerId::new(), &slow), (fast_id.clone(), &fast)] {
+        cmd_tx
+            .send(RoomCommand::Attach {
+                peer_id,
+                sender: Arc::new(sender.clone()),
+                metadata: Default::default(),
+                features: all_features(),
+            })
+            .await
+            .expect("Failed to attach peer");
+        joins_rx.recv().await.expect("Room closed");
+    }
+
+    // The slow peer's queue overflows and it misses some of the patches
+    for version in 1..=6 {
+        cmd_tx
+            .send(RoomCommand::Data {
+                peer_id: fast_id.clone(),
+                data: Bytes::from(format!("move {version}")),
+            })
+            .await
+            .expect("Failed to send data");
+        wait_for_version(&state, version).await;
+    }
+
+    // Once it catches up it receives a snapshot and follows the patches again
+    slow.open();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    cmd_tx
+        .send(RoomCommand::Data {
+            peer_id: fast_id.clone(),
+            data: Bytes::from_static(b"move 7"),
+        })
+        .await
+        .expect("Failed to send data");
+    wait_for_version(&state, 7).await;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let expected = state.read(|log| postcard::to_allocvec(log).unwrap()).await;
+    assert_eq!(replay(&fast.sent().await), Some((7, expected.clone())));
+    let slow_sent = slow.sent().await;
+    let snapshots = slow_sent
+        .iter()
+        .filter(|data| {
+            matches!(
+                postcard::from_bytes::<Packet<()>>(data),
+                Ok(Packet::System(SystemMessage::StateSnapshot { .. }))
+            )
+        })
+        .count();
+    assert_eq!(snapshots, 2, "Lagging peer was not resynced");
+    assert_eq!(replay(&slow_sent), Some((7, expected)));
+}
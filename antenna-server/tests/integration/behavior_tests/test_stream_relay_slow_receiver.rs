@@ -0,0 +1,109 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use antenna_core::{Packet, PeerId, SystemMessage};
+use antenna_server::{Room, RoomCommand, SendQueueConfig};
+use bytes::Bytes;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use crate::integration::init_tracing;
+use crate::utils::{GatedSender, MockSignalingOutput, TestRoomBehavior, all_features};
+
+fn encode(msg: SystemMessage) -> Bytes {
+    Bytes::from(postcard::to_allocvec(&Packet::<()>::System(msg)).unwrap())
+}
+
+#[tokio::test]
+async fn test_stream_relay_slow_receiver() {
+    init_tracing();
+
+    let config = SendQueueConfig {
+        high_watermark: 64,
+        low_watermark: 0,
+        broadcast_timeout: Duration::from_millis(100),
+        ..SendQueueConfig::default()
+    };
+    let (cmd_tx, cmd_rx) = mpsc::channel::<RoomCommand>(100);
+    let signaling = MockSignalingOutput::new_stored_only();
+    let behavior = TestRoomBehavior::new();
+    let room = Room::new(
+        Box::new(behavior.clone()),
+        cmd_rx,
+        signaling.service.clone(),
+    )
+    .with_send_queue_config(config);
+    tokio::spawn(room.run());
+
+    let sender = GatedSender::new();
+    sender.open();
+    let receiver = GatedSender::new();
+    let sender_id = PeerId::new();
+    let receiver_id = PeerId::new();
+    for (peer_id, peer) in [
+        (sender_id.clone(), &sender),
+        (receiver_id.clone(), &receiver),
+    ] {
+        cmd_tx
+            .send(RoomCommand::Attach {
+                peer_id,
+                sender: Arc::new(peer.clone()),
+                metadata: Default::default(),
+                features: all_features(),
+            })
+            .await
+            .expect("Failed to attach peer");
+    }
+    assert!(behavior.wait_for_events(2, 1000).await);
+
+    let data = |data: Bytes| RoomCommand::Data {
+        peer_id: sender_id.clone(),
+        data,
+    };
+    let stream_id = Uuid::new_v4();
+    cmd_tx
+        .send(data(encode(SystemMessage::StreamOpen {
+            stream_id,
+            from: None,
+            to: Some(receiver_id.clone()),
+            name: "file.bin".to_owned(),
+            size: 1024,
+        })))
+        .await
+        .expect("Failed to send data");
+    for _ in 0..16 {
+        cmd_tx
+            .send(data(encode(SystemMessage::StreamChunk {
+                stream_id,
+                data: vec![0; 64],
+            })))
+            .await
+            .expect("Failed to send data");
+    }
+
+    // The receiver never reads, so the relay gives up on the stream and tells its author
+    let aborted = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let data = sender.next_started().await.expect("Writer stopped");
+            if let Ok(Packet::System(SystemMessage::StreamAbort { stream_id: id })) =
+                postcard::from_bytes::<Packet<()>>(&data)
+            {
+                return id;
+            }
+        }
+    })
+    .await
+    .expect("Stream was not aborted");
+    assert_eq!(aborted, stream_id);
+
+    // The room keeps serving its peers
+    cmd_tx
+        .send(data(Bytes::from_static(b"still here")))
+        .await
+        .expect("Failed to send data");
+    assert!(behavior.wait_for_events(3, 5000).await);
+    assert_eq!(
+        behavior.messages_from(&sender_id).await,
+        vec![Bytes::from_static(b"still here")]
+    );
+}
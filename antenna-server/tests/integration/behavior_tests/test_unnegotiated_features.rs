@@ -0,0 +1,93 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use antenna_core::{AckTracker, Packet, PeerId, SystemMessage};
+use antenna_server::{Room, RoomCommand};
+use bytes::Bytes;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use crate::integration::init_tracing;
+use crate::utils::{GatedSender, MockSignalingOutput, TestRoomBehavior, all_features};
+
+fn encode<T: serde::Serialize>(packet: &Packet<T>) -> Bytes {
+    Bytes::from(postcard::to_allocvec(packet).unwrap())
+}
+
+#[tokio::test]
+async fn test_unnegotiated_features() {
+    init_tracing();
+
+    let (cmd_tx, cmd_rx) = mpsc::channel::<RoomCommand>(100);
+    let signaling = MockSignalingOutput::new_stored_only();
+    let behavior = TestRoomBehavior::new();
+    let room = Room::new(
+        Box::new(behavior.clone()),
+        cmd_rx,
+        signaling.service.clone(),
+    );
+    tokio::spawn(room.run());
+
+    // An old client announcing no features next to a current one
+    let legacy = GatedSender::new();
+    legacy.open();
+    let current = GatedSender::new();
+    current.open();
+    let legacy_id = PeerId::new();
+    let current_id = PeerId::new();
+    for (peer_id, sender, features) in [
+        (legacy_id.clone(), &legacy, vec![]),
+        (current_id.clone(), &current, all_features()),
+    ] {
+        cmd_tx
+            .send(RoomCommand::Attach {
+                peer_id,
+                sender: Arc::new(sender.clone()),
+                metadata: Default::default(),
+                features,
+            })
+            .await
+            .expect("Failed to attach peer");
+    }
+    assert!(behavior.wait_for_events(2, 1000).await);
+
+    let data = |peer_id: &PeerId, data: Bytes| RoomCommand::Data {
+        peer_id: peer_id.clone(),
+        data,
+    };
+    let open = |stream_id| {
+        encode(&Packet::<()>::System(SystemMessage::StreamOpen {
+            stream_id,
+            from: None,
+            to: None,
+            name: "file.bin".to_owned(),
+            size: 16,
+        }))
+    };
+    let sequenced = encode(&Packet::Sequenced {
+        header: AckTracker::default().header(0),
+        payload: 7u32,
+    });
+
+    // Streams and sequenced inputs of the legacy peer are neither relayed nor handed over
+    for packet in [open(Uuid::new_v4()), sequenced.clone()] {
+        cmd_tx
+            .send(data(&legacy_id, packet))
+            .await
+            .expect("Failed to send data");
+    }
+    // Streams of the current peer skip the legacy peer, its sequenced inputs go through
+    for packet in [open(Uuid::new_v4()), sequenced.clone()] {
+        cmd_tx
+            .send(data(&current_id, packet))
+            .await
+            .expect("Failed to send data");
+    }
+    assert!(behavior.wait_for_events(3, 1000).await);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    assert!(behavior.messages_from(&legacy_id).await.is_empty());
+    assert_eq!(behavior.messages_from(&current_id).await, vec![sequenced]);
+    assert!(legacy.sent().await.is_empty(), "Legacy peer got a stream");
+    assert!(current.sent().await.is_empty(), "Current peer got a stream");
+}
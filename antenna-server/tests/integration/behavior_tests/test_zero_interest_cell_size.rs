@@ -0,0 +1,8 @@
+use antenna_server::AntennaServer;
+
+#[test]
+#[should_panic(expected = "interest cell size must be positive and finite")]
+fn test_zero_interest_cell_size() {
+    // Would otherwise divide positions by zero when rooms assign grid cells
+    let _ = AntennaServer::new().with_interest_cell_size(0.0);
+}
@@ -0,0 +1,10 @@
+mod test_directory_lease_takeover;
+mod test_directory_server_line_limit;
+mod test_lost_lease_redirects_peers;
+#[cfg(feature = "quic")]
+mod test_lost_lease_redirects_quic_peers;
+mod test_memory_directory_routes_rooms;
+#[cfg(feature = "quic")]
+mod test_quic_redirect_to_owner;
+mod test_tcp_directory_concurrent_requests;
+mod test_tcp_directory_requests;
@@ -0,0 +1,50 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use antenna_server::{
+    BehaviorFactory, MemoryRoomDirectory, NodeInfo, RoomBehavior, RoomManager, RoomRoute,
+    SignalingService,
+};
+
+use crate::utils::TestRoomBehavior;
+
+fn create_node(directory: &MemoryRoomDirectory, node: NodeInfo) -> RoomManager {
+    let factory: BehaviorFactory =
+        Arc::new(|| Box::new(TestRoomBehavior::new()) as Box<dyn RoomBehavior>);
+    RoomManager::new(factory, Arc::new(SignalingService::new(vec![])))
+        .with_directory(Arc::new(directory.clone()), node)
+        .with_lease_renewal(Duration::from_secs(3))
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_directory_lease_takeover() {
+    let directory = MemoryRoomDirectory::new().with_lease(Duration::from_secs(10));
+    let node_a = NodeInfo::new("a", "ws://127.0.0.1:3001/ws");
+    let node_b = NodeInfo::new("b", "ws://127.0.0.1:3002/ws");
+    let manager_a = create_node(&directory, node_a.clone());
+    let manager_b = create_node(&directory, node_b.clone());
+
+    let route = manager_a.route("lobby").await.expect("Routing failed");
+    assert!(matches!(route, RoomRoute::Local(_)));
+
+    // A running node keeps renewing its lease.
+    tokio::time::sleep(Duration::from_secs(25)).await;
+    match manager_b.route("lobby").await.expect("Routing failed") {
+        RoomRoute::Remote(owner) => assert_eq!(owner, node_a),
+        other => panic!("Expected Remote, got {:?}", other),
+    }
+
+    // A crashed node neither renews nor releases: its rooms move once the lease ran out.
+    drop(route);
+    drop(manager_a);
+    tokio::time::sleep(Duration::from_secs(5)).await;
+    assert!(matches!(
+        manager_b.route("lobby").await.expect("Routing failed"),
+        RoomRoute::Remote(_)
+    ));
+    tokio::time::sleep(Duration::from_secs(6)).await;
+    assert!(matches!(
+        manager_b.route("lobby").await.expect("Routing failed"),
+        RoomRoute::Local(_)
+    ));
+}
@@ -0,0 +1,38 @@
+use std::time::Duration;
+
+use antenna_server::{DirectoryServer, NodeInfo, RoomDirectory, TcpRoomDirectory};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+#[tokio::test]
+async fn test_directory_server_line_limit() {
+    let server = DirectoryServer::bind("127.0.0.1:0".parse().unwrap())
+        .await
+        .expect("Failed to bind directory");
+    let addr = server.local_addr().unwrap();
+    tokio::spawn(server.run());
+
+    // A client that never ends its line is cut off instead of growing the server's buffer
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let chunk = vec![b'x'; 64 * 1024];
+    let mut closed = false;
+    for _ in 0..64 {
+        if stream.write_all(&chunk).await.is_err() {
+            closed = true;
+            break;
+        }
+    }
+    if !closed {
+        let mut buf = [0u8; 64];
+        let read = tokio::time::timeout(Duration::from_secs(5), stream.read(&mut buf))
+            .await
+            .expect("Server kept the connection open");
+        closed = matches!(read, Ok(0) | Err(_));
+    }
+    assert!(closed);
+
+    // Other nodes are still served
+    let node = NodeInfo::new("a", "ws://127.0.0.1:3001/ws");
+    let directory = TcpRoomDirectory::new(addr);
+    assert_eq!(directory.claim("lobby", &node).await.unwrap(), node);
+}
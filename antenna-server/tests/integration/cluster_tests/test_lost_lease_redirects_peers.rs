@@ -0,0 +1,140 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use antenna_core::{PeerId, SignalMessage};
+use antenna_server::{
+    BehaviorFactory, MemoryRoomDirectory, NodeInfo, RoomBehavior, RoomCommand, RoomContext,
+    RoomDirectory, RoomManager, RoomRoute, RoomStore,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::utils::{GatedSender, MockSignalingOutput, RoomEvent, TestRoomBehavior, all_features};
+
+/// `TestRoomBehavior` that always has a snapshot to persist.
+#[derive(Clone)]
+struct SnapshotBehavior(TestRoomBehavior);
+
+#[async_trait]
+impl RoomBehavior for SnapshotBehavior {
+    async fn on_join(&self, ctx: &RoomContext, peer_id: PeerId) {
+        self.0.on_join(ctx, peer_id).await;
+    }
+
+    async fn on_message(&self, ctx: &RoomContext, peer_id: PeerId, data: Bytes) {
+        self.0.on_message(ctx, peer_id, data).await;
+    }
+
+    async fn on_leave(&self, ctx: &RoomContext, peer_id: PeerId) {
+        self.0.on_leave(ctx, peer_id).await;
+    }
+
+    async fn on_close(&self, ctx: &RoomContext) {
+        self.0.on_close(ctx).await;
+    }
+
+    async fn snapshot(&self) -> Option<Vec<u8>> {
+        Some(b"stale".to_vec())
+    }
+}
+
+/// Counts saved snapshots.
+#[derive(Clone, Default)]
+struct CountingStore {
+    saves: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl RoomStore for CountingStore {
+    async fn save(&self, _room_id: &str, _snapshot: &[u8]) -> Result<()> {
+        self.saves.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn load(&self, _room_id: &str) -> Result<Option<Vec<u8>>> {
+        Ok(None)
+    }
+
+    async fn delete(&self, _room_id: &str) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_lost_lease_redirects_peers() {
+    let directory = MemoryRoomDirectory::new().with_lease(Duration::from_secs(10));
+    let node_a = NodeInfo::new("a", "ws://127.0.0.1:3001/ws");
+    let node_b = NodeInfo::new("b", "ws://127.0.0.1:3002/ws");
+    let (signaling, mut signal_rx) = MockSignalingOutput::new();
+    let behavior = TestRoomBehavior::new();
+    let factory: BehaviorFactory = {
+        let behavior = behavior.clone();
+        Arc::new(move || Box::new(SnapshotBehavior(behavior.clone())) as Box<dyn RoomBehavior>)
+    };
+    let store = CountingStore::default();
+    let manager = RoomManager::new(factory, signaling.service.clone())
+        .with_directory(Arc::new(directory.clone()), node_a.clone())
+        .with_room_store(Arc::new(store.clone()))
+        .with_lease_renewal(Duration::from_secs(3));
+
+    let RoomRoute::Local(tx) = manager.route("lobby").await.expect("Routing failed") else {
+        panic!("Expected the room to be local");
+    };
+    let peer_id = PeerId::new();
+    signaling.register_peer(peer_id.clone());
+    let sender = GatedSender::new();
+    sender.open();
+    tx.send(RoomCommand::Attach {
+        peer_id: peer_id.clone(),
+        sender: Arc::new(sender),
+        metadata: Default::default(),
+        features: all_features(),
+    })
+    .await
+    .expect("Failed to attach peer");
+    match signal_rx.recv().await.expect("Signaling closed") {
+        SignalMessage::Admitted { room } => assert_eq!(room.as_deref(), Some("lobby")),
+        other => panic!("Expected Admitted, got {:?}", other),
+    }
+    assert!(behavior.wait_for_events(1, 1000).await);
+
+    // Another node got hold of the room, e.g. after this node stalled past its lease
+    directory
+        .release("lobby", &node_a)
+        .await
+        .expect("Release failed");
+    directory
+        .claim("lobby", &node_b)
+        .await
+        .expect("Claim failed");
+
+    let redirect = tokio::time::timeout(Duration::from_secs(10), signal_rx.recv())
+        .await
+        .expect("Peer was not redirected")
+        .expect("Signaling closed");
+    match redirect {
+        SignalMessage::Redirect { url, room } => {
+            assert_eq!(url, format!("ws://127.0.0.1:3002/ws/{}", peer_id));
+            assert_eq!(room.as_deref(), Some("lobby"));
+        }
+        other => panic!("Expected Redirect, got {:?}", other),
+    }
+
+    // The local copy is closed instead of running next to the new owner's, and neither says
+    // goodbye nor overwrites the state the new owner persists
+    assert!(!manager.has_room("lobby"));
+    assert!(
+        !behavior
+            .get_events()
+            .await
+            .iter()
+            .any(|event| matches!(event, RoomEvent::Close))
+    );
+    assert_eq!(store.saves.load(Ordering::SeqCst), 0);
+    match manager.route("lobby").await.expect("Routing failed") {
+        RoomRoute::Remote(owner) => assert_eq!(owner, node_b),
+        other => panic!("Expected Remote, got {:?}", other),
+    }
+}
@@ -0,0 +1,60 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use antenna_server::{
+    BehaviorFactory, MemoryRoomDirectory, NodeInfo, QUIC_REDIRECT_CODE, QuicServer, RoomBehavior,
+    RoomDirectory, RoomManager, SignalingService,
+};
+use wtransport::error::ConnectionError;
+
+use crate::integration::init_tracing;
+use crate::utils::{QuicTestClient, TestRoomBehavior};
+
+#[tokio::test]
+async fn test_lost_lease_redirects_quic_peers() {
+    init_tracing();
+
+    let directory = MemoryRoomDirectory::new();
+    let node_a = NodeInfo::new("a", "ws://node-a/ws").with_quic_url("https://node-a");
+    let node_b = NodeInfo::new("b", "ws://node-b/ws").with_quic_url("https://node-b");
+    let behavior = TestRoomBehavior::new();
+    let factory: BehaviorFactory = {
+        let behavior = behavior.clone();
+        Arc::new(move || Box::new(behavior.clone()) as Box<dyn RoomBehavior>)
+    };
+    let room_manager = RoomManager::new(factory, Arc::new(SignalingService::new(vec![])))
+        .with_directory(Arc::new(directory.clone()), node_a.clone())
+        .with_lease_renewal(Duration::from_millis(100));
+
+    let server = QuicServer::bind_self_signed("127.0.0.1:0".parse().unwrap(), room_manager)
+        .expect("Failed to bind QUIC server");
+    let addr = server.local_addr().expect("No local address");
+    let hashes = server.certificate_hashes().to_vec();
+    tokio::spawn(server.run());
+
+    let client = QuicTestClient::connect(addr, &hashes, "lobby")
+        .await
+        .expect("Failed to connect");
+    assert!(behavior.wait_for_events(1, 2000).await);
+
+    directory
+        .release("lobby", &node_a)
+        .await
+        .expect("Release failed");
+    directory
+        .claim("lobby", &node_b)
+        .await
+        .expect("Claim failed");
+
+    // The QUIC peer has no signaling connection, the session itself carries the new owner
+    let error = tokio::time::timeout(Duration::from_secs(5), client.closed())
+        .await
+        .expect("Peer was not redirected");
+    match error {
+        ConnectionError::ApplicationClosed(close) => {
+            assert_eq!(close.code(), QUIC_REDIRECT_CODE.into());
+            assert_eq!(close.reason(), b"https://node-b");
+        }
+        other => panic!("Expected an application close, got {:?}", other),
+    }
+}
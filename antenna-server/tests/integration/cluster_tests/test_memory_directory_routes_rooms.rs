@@ -0,0 +1,46 @@
+use std::sync::Arc;
+
+use antenna_server::{
+    BehaviorFactory, MemoryRoomDirectory, NodeInfo, RoomBehavior, RoomDirectory, RoomManager,
+    RoomRoute, SignalingService,
+};
+
+use crate::integration::init_tracing;
+use crate::utils::TestRoomBehavior;
+
+fn create_node(directory: &MemoryRoomDirectory, node: NodeInfo) -> RoomManager {
+    let factory: BehaviorFactory =
+        Arc::new(|| Box::new(TestRoomBehavior::new()) as Box<dyn RoomBehavior>);
+    RoomManager::new(factory, Arc::new(SignalingService::new(vec![])))
+        .with_directory(Arc::new(directory.clone()), node)
+}
+
+#[tokio::test]
+async fn test_memory_directory_routes_rooms() {
+    init_tracing();
+
+    let directory = MemoryRoomDirectory::new();
+    let node_a = NodeInfo::new("a", "ws://127.0.0.1:3001/ws");
+    let node_b = NodeInfo::new("b", "ws://127.0.0.1:3002/ws");
+    let manager_a = create_node(&directory, node_a.clone());
+    let manager_b = create_node(&directory, node_b.clone());
+
+    // The first node asked for a room becomes its owner.
+    let route = manager_a.route("lobby").await.expect("Routing failed");
+    assert!(matches!(route, RoomRoute::Local(_)));
+
+    match manager_b.route("lobby").await.expect("Routing failed") {
+        RoomRoute::Remote(owner) => assert_eq!(owner, node_a),
+        other => panic!("Expected Remote, got {:?}", other),
+    }
+    let route = manager_b.route("arena").await.expect("Routing failed");
+    assert!(matches!(route, RoomRoute::Local(_)));
+
+    // A node shutting down hands its rooms back.
+    manager_a.shutdown().await;
+    assert_eq!(directory.lookup("lobby").await.unwrap(), None);
+    assert_eq!(directory.lookup("arena").await.unwrap(), Some(node_b));
+
+    let route = manager_b.route("lobby").await.expect("Routing failed");
+    assert!(matches!(route, RoomRoute::Local(_)));
+}
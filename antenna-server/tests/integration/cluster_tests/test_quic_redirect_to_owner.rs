@@ -0,0 +1,82 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use antenna_core::{PROTOCOL_VERSION, SignalMessage};
+use antenna_server::{
+    BehaviorFactory, DirectoryServer, NodeInfo, QuicServer, RoomBehavior, RoomDirectory,
+    RoomManager, SignalingService, TcpRoomDirectory,
+};
+use wtransport::tls::Sha256Digest;
+
+use crate::integration::init_tracing;
+use crate::utils::{QuicTestClient, TestRoomBehavior};
+
+fn spawn_node(
+    directory: Arc<dyn RoomDirectory>,
+    node: NodeInfo,
+) -> (SocketAddr, Vec<Sha256Digest>) {
+    let factory: BehaviorFactory =
+        Arc::new(|| Box::new(TestRoomBehavior::new()) as Box<dyn RoomBehavior>);
+    let room_manager = RoomManager::new(factory, Arc::new(SignalingService::new(vec![])))
+        .with_directory(directory, node);
+
+    let server = QuicServer::bind_self_signed("127.0.0.1:0".parse().unwrap(), room_manager)
+        .expect("Failed to bind QUIC server");
+    let addr = server.local_addr().expect("No local address");
+    let hashes = server.certificate_hashes().to_vec();
+    tokio::spawn(server.run());
+
+    (addr, hashes)
+}
+
+fn join(room: &str) -> SignalMessage {
+    SignalMessage::Join {
+        room: room.to_string(),
+        version: PROTOCOL_VERSION,
+        features: vec![],
+        schema: None,
+        metadata: Default::default(),
+    }
+}
+
+#[tokio::test]
+async fn test_quic_redirect_to_owner() {
+    init_tracing();
+
+    let directory_server = DirectoryServer::bind("127.0.0.1:0".parse().unwrap())
+        .await
+        .expect("Failed to bind room directory");
+    let directory: Arc<dyn RoomDirectory> = Arc::new(TcpRoomDirectory::new(
+        directory_server.local_addr().expect("No local address"),
+    ));
+    tokio::spawn(directory_server.run());
+
+    let node_a = NodeInfo::new("a", "ws://node-a/ws").with_quic_url("https://node-a");
+    let node_b = NodeInfo::new("b", "ws://node-b/ws").with_quic_url("https://node-b");
+    let (addr_a, hashes_a) = spawn_node(directory.clone(), node_a);
+    let (addr_b, hashes_b) = spawn_node(directory.clone(), node_b);
+
+    let reply = QuicTestClient::join_raw(addr_a, &hashes_a, &join("lobby"))
+        .await
+        .expect("Handshake with node a failed");
+    assert!(
+        matches!(reply, SignalMessage::Welcome { .. }),
+        "{:?}",
+        reply
+    );
+
+    // Node b does not run the room itself but points the client to its owner.
+    let reply = QuicTestClient::join_raw(addr_b, &hashes_b, &join("lobby"))
+        .await
+        .expect("Handshake with node b failed");
+    match reply {
+        SignalMessage::Redirect { url, room } => {
+            assert_eq!(url, "https://node-a");
+            assert_eq!(room.as_deref(), Some("lobby"));
+        }
+        other => panic!("Expected Redirect, got {:?}", other),
+    }
+
+    let owner = directory.lookup("lobby").await.expect("Lookup failed");
+    assert_eq!(owner.map(|node| node.id).as_deref(), Some("a"));
+}
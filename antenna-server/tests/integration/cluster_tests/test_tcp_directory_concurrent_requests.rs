@@ -0,0 +1,47 @@
+use std::time::{Duration, Instant};
+
+use antenna_server::{RoomDirectory, TcpRoomDirectory};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+const ANSWER_DELAY: Duration = Duration::from_millis(300);
+
+/// Directory that takes `ANSWER_DELAY` to answer each request on a connection.
+async fn serve_slowly(listener: TcpListener) {
+    loop {
+        let (stream, _) = listener.accept().await.unwrap();
+        tokio::spawn(async move {
+            let (reader, mut writer) = stream.into_split();
+            let mut lines = BufReader::new(reader).lines();
+            while let Ok(Some(_)) = lines.next_line().await {
+                tokio::time::sleep(ANSWER_DELAY).await;
+                let answer = b"{\"op\":\"Owner\",\"node\":null}\n";
+                if writer.write_all(answer).await.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+}
+
+#[tokio::test]
+async fn test_tcp_directory_concurrent_requests() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let directory = TcpRoomDirectory::new(listener.local_addr().unwrap());
+    tokio::spawn(serve_slowly(listener));
+
+    // Renewals of many rooms run at once instead of queueing behind one connection
+    let started = Instant::now();
+    let lookups = (0..4).map(|i| {
+        let directory = directory.clone();
+        async move { directory.lookup(&format!("room-{i}")).await }
+    });
+    for result in futures::future::join_all(lookups).await {
+        assert_eq!(result.unwrap(), None);
+    }
+    assert!(
+        started.elapsed() < ANSWER_DELAY * 2,
+        "{:?}",
+        started.elapsed()
+    );
+}
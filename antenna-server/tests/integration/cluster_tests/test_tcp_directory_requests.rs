@@ -0,0 +1,31 @@
+use std::time::{Duration, Instant};
+
+use antenna_server::{DirectoryServer, NodeInfo, RoomDirectory, TcpRoomDirectory};
+use tokio::net::TcpListener;
+
+#[tokio::test]
+async fn test_tcp_directory_requests() {
+    let server = DirectoryServer::bind("127.0.0.1:0".parse().unwrap())
+        .await
+        .expect("Failed to bind directory");
+    let addr = server.local_addr().unwrap();
+    let server = tokio::spawn(server.run());
+
+    let node = NodeInfo::new("a", "ws://127.0.0.1:3001/ws");
+    let directory = TcpRoomDirectory::new(addr);
+    assert_eq!(directory.claim("lobby", &node).await.unwrap(), node);
+    assert_eq!(directory.lookup("lobby").await.unwrap(), Some(node.clone()));
+
+    // Requests reuse the open connection, so they keep working once no new one is accepted.
+    server.abort();
+    let _ = server.await;
+    assert_eq!(directory.lookup("lobby").await.unwrap(), Some(node.clone()));
+
+    // A directory that never answers fails the request instead of stalling the node.
+    let silent = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let directory = TcpRoomDirectory::new(silent.local_addr().unwrap())
+        .with_timeout(Duration::from_millis(200));
+    let started = Instant::now();
+    assert!(directory.claim("lobby", &node).await.is_err());
+    assert!(started.elapsed() < Duration::from_secs(2));
+}
@@ -1,3 +1,17 @@
 mod test_ice_candidate_exchange;
+mod test_invalid_offer_reports_error;
 mod test_peer_disconnect_triggers_leave;
+#[cfg(feature = "quic")]
+mod test_quic_admission;
+#[cfg(feature = "quic")]
+mod test_quic_peer_joins_room;
+#[cfg(feature = "quic")]
+mod test_quic_version_mismatch;
+mod test_room_tags_signals;
 mod test_single_peer_joins_room;
+mod test_websocket_fallback;
+mod test_websocket_handshake_gates_features;
+mod test_websocket_leave_falls_back;
+mod test_websocket_schema_mismatch;
+mod test_websocket_transport_admission;
+mod test_websocket_transport_rooms;
@@ -4,7 +4,7 @@ use antenna_core::PeerId;
 use antenna_server::RoomCommand;
 
 use crate::integration::{create_test_room, init_tracing};
-use crate::utils::{TestClient, TestClientConfig, wait_for_client_ready};
+use crate::utils::{TestClient, TestClientConfig, all_features, wait_for_client_ready};
 use antenna_core::SignalMessage;
 
 #[tokio::test]
@@ -29,6 +29,8 @@ async fn test_ice_candidate_exchange() {
         .send(RoomCommand::JoinRequest {
             peer_id: peer_id.clone(),
             offer,
+            metadata: Default::default(),
+            features: all_features(),
         })
         .await
         .expect("Failed to send join request");
@@ -63,14 +65,14 @@ async fn test_ice_candidate_exchange() {
             tokio::time::timeout(Duration::from_millis(100), signal_rx.recv()).await
         {
             match msg {
-                SignalMessage::Answer { sdp } => {
+                SignalMessage::Answer { sdp, .. } => {
                     client
                         .set_remote_answer(sdp)
                         .await
                         .expect("Failed to set answer");
                     answer_received = true;
                 }
-                SignalMessage::IceCandidate { candidate } => {
+                SignalMessage::IceCandidate { candidate, .. } => {
                     let _ = client.add_ice_candidate(candidate).await;
                 }
                 _ => {}
@@ -0,0 +1,43 @@
+use std::time::Duration;
+
+use antenna_core::{ErrorCode, PeerId, SignalMessage};
+use antenna_server::RoomCommand;
+
+use crate::integration::{create_test_room, init_tracing};
+use crate::utils::all_features;
+
+#[tokio::test]
+async fn test_invalid_offer_reports_error() {
+    init_tracing();
+
+    let (room_cmd_tx, signaling, behavior) = create_test_room();
+    let mut signal_rx = signaling.1;
+    let signaling = signaling.0;
+
+    let peer_id = PeerId::new();
+    signaling.register_peer(peer_id.clone());
+
+    room_cmd_tx
+        .send(RoomCommand::JoinRequest {
+            peer_id: peer_id.clone(),
+            offer: "not an sdp".to_string(),
+            metadata: Default::default(),
+            features: all_features(),
+        })
+        .await
+        .expect("Failed to send JoinRequest");
+
+    let signal = tokio::time::timeout(Duration::from_secs(5), signal_rx.recv())
+        .await
+        .expect("Timeout waiting for error")
+        .expect("Signaling channel closed");
+
+    match signal {
+        SignalMessage::Error { code, fatal, .. } => {
+            assert_eq!(code, ErrorCode::SdpFailed);
+            assert!(fatal, "A failed offer leaves the peer without a connection");
+        }
+        other => panic!("Expected Error, got {:?}", other),
+    }
+    assert!(behavior.get_events().await.is_empty());
+}
@@ -0,0 +1,86 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use antenna_core::{JoinRejection, PROTOCOL_VERSION, SignalMessage};
+use antenna_server::{
+    BehaviorFactory, QuicServer, RoomBehavior, RoomConfig, RoomManager, SignalingService,
+};
+
+use crate::integration::init_tracing;
+use crate::utils::{QuicJoin, QuicTestClient, TestRoomBehavior};
+
+fn join() -> SignalMessage {
+    SignalMessage::Join {
+        room: "quic-room".to_string(),
+        version: PROTOCOL_VERSION,
+        features: vec![],
+        schema: None,
+        metadata: Default::default(),
+    }
+}
+
+#[tokio::test]
+async fn test_quic_admission() {
+    init_tracing();
+
+    let behavior = TestRoomBehavior::new();
+    let factory: BehaviorFactory = {
+        let behavior = behavior.clone();
+        Arc::new(move || Box::new(behavior.clone()) as Box<dyn RoomBehavior>)
+    };
+    let room_manager = RoomManager::new(factory, Arc::new(SignalingService::new(vec![])))
+        .with_room_config(RoomConfig::new().with_max_peers(1).with_wait_list(1));
+
+    let server = QuicServer::bind_self_signed("127.0.0.1:0".parse().unwrap(), room_manager)
+        .expect("Failed to bind QUIC server");
+    let addr = server.local_addr().expect("No local address");
+    let hashes = server.certificate_hashes().to_vec();
+    tokio::spawn(server.run());
+    let timeout = Duration::from_secs(5);
+
+    let first = QuicTestClient::connect(addr, &hashes, "quic-room")
+        .await
+        .expect("Failed to connect");
+
+    // The full room answers on the control stream, without a Welcome yet
+    let mut waiting = QuicJoin::start(addr, &hashes, &join())
+        .await
+        .expect("Failed to connect");
+    let signal = tokio::time::timeout(timeout, waiting.next_signal())
+        .await
+        .expect("No admission result")
+        .expect("Control stream closed");
+    assert!(
+        matches!(signal, SignalMessage::WaitListed { position: 1, .. }),
+        "Expected WaitListed, got {:?}",
+        signal
+    );
+
+    // With the wait-list taken as well, the next peer is turned away
+    let reply = tokio::time::timeout(timeout, QuicTestClient::join_raw(addr, &hashes, &join()))
+        .await
+        .expect("No admission result")
+        .expect("Handshake failed");
+    assert!(
+        matches!(
+            reply,
+            SignalMessage::JoinRejected {
+                reason: JoinRejection::RoomFull { max_peers: 1 },
+                ..
+            }
+        ),
+        "Expected RoomFull, got {:?}",
+        reply
+    );
+
+    // The waiting peer is welcomed once the first one leaves
+    first.close();
+    let signal = tokio::time::timeout(timeout, waiting.next_signal())
+        .await
+        .expect("Peer was not admitted")
+        .expect("Control stream closed");
+    match signal {
+        SignalMessage::Welcome { room, .. } => assert_eq!(room.as_deref(), Some("quic-room")),
+        other => panic!("Expected Welcome, got {:?}", other),
+    }
+}
@@ -0,0 +1,82 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use antenna_server::{BehaviorFactory, QuicServer, RoomBehavior, RoomManager, SignalingService};
+
+use crate::integration::init_tracing;
+use crate::utils::{QuicTestClient, TestRoomBehavior};
+
+#[tokio::test]
+async fn test_quic_peer_joins_room() {
+    init_tracing();
+
+    let behavior = TestRoomBehavior::new().with_echo();
+    let factory: BehaviorFactory = {
+        let behavior = behavior.clone();
+        Arc::new(move || Box::new(behavior.clone()) as Box<dyn RoomBehavior>)
+    };
+    let room_manager = RoomManager::new(factory, Arc::new(SignalingService::new(vec![])));
+
+    let server = QuicServer::bind_self_signed("127.0.0.1:0".parse().unwrap(), room_manager)
+        .expect("Failed to bind QUIC server");
+    let addr = server.local_addr().expect("No local address");
+    let hashes = server.certificate_hashes().to_vec();
+    tokio::spawn(server.run());
+
+    let mut client = QuicTestClient::connect(addr, &hashes, "quic-room")
+        .await
+        .expect("Failed to connect QUIC client");
+
+    assert!(
+        behavior.wait_for_events(1, 5000).await,
+        "Expected join event"
+    );
+    assert!(behavior.has_join(&client.peer_id).await);
+
+    // Every channel ends up in the same on_message path.
+    client
+        .send_reliable(b"reliable")
+        .await
+        .expect("Reliable send failed");
+    client
+        .send_unordered(b"unordered")
+        .await
+        .expect("Unordered send failed");
+    client
+        .send_datagram(b"datagram")
+        .expect("Datagram send failed");
+
+    assert!(
+        behavior.wait_for_events(4, 5000).await,
+        "Expected 3 messages"
+    );
+    let mut messages: Vec<Vec<u8>> = behavior
+        .messages_from(&client.peer_id)
+        .await
+        .into_iter()
+        .map(|m| m.to_vec())
+        .collect();
+    messages.sort();
+    assert_eq!(
+        messages,
+        vec![
+            b"datagram".to_vec(),
+            b"reliable".to_vec(),
+            b"unordered".to_vec()
+        ]
+    );
+
+    // RoomContext::send answers on the reliable control stream.
+    let echoed = tokio::time::timeout(Duration::from_secs(5), client.recv_reliable())
+        .await
+        .expect("Timeout waiting for echo")
+        .expect("Failed to read echo");
+    assert!(messages.contains(&echoed.to_vec()));
+
+    client.close();
+    assert!(
+        behavior.wait_for_events(5, 5000).await,
+        "Expected leave event"
+    );
+    assert!(behavior.has_leave(&client.peer_id).await);
+}
@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use antenna_core::{PROTOCOL_VERSION, RejectReason, SignalMessage};
+use antenna_server::{
+    BehaviorFactory, QuicServer, RoomBehavior, RoomConfig, RoomManager, SignalingService,
+};
+
+use crate::integration::init_tracing;
+use crate::utils::{QuicTestClient, TestRoomBehavior};
+
+#[tokio::test]
+async fn test_quic_version_mismatch() {
+    init_tracing();
+
+    let behavior = TestRoomBehavior::new();
+    let factory: BehaviorFactory = {
+        let behavior = behavior.clone();
+        Arc::new(move || Box::new(behavior.clone()) as Box<dyn RoomBehavior>)
+    };
+    let room_manager = RoomManager::new(factory, Arc::new(SignalingService::new(vec![])))
+        .with_room_config(RoomConfig {
+            schema: Some("0000000000000001".to_string()),
+            ..RoomConfig::default()
+        });
+
+    // The schema comes from the room config, as on the WebSocket signaling
+    let server = QuicServer::bind_self_signed("127.0.0.1:0".parse().unwrap(), room_manager)
+        .expect("Failed to bind QUIC server");
+    let addr = server.local_addr().expect("No local address");
+    let hashes = server.certificate_hashes().to_vec();
+    tokio::spawn(server.run());
+
+    // Clients predating the handshake send no version at all.
+    let reply = QuicTestClient::join_raw(
+        addr,
+        &hashes,
+        &SignalMessage::Join {
+            room: "quic-room".to_string(),
+            version: 0,
+            features: vec![],
+            schema: None,
+            metadata: Default::default(),
+        },
+    )
+    .await
+    .expect("Handshake failed");
+
+    match reply {
+        SignalMessage::Reject {
+            reason: RejectReason::VersionMismatch { server, client },
+        } => {
+            assert_eq!(server, PROTOCOL_VERSION);
+            assert_eq!(client, 0);
+        }
+        other => panic!("Expected VersionMismatch, got {:?}", other),
+    }
+
+    // A matching version with a different message schema is refused as well.
+    let reply = QuicTestClient::join_raw(
+        addr,
+        &hashes,
+        &SignalMessage::Join {
+            room: "quic-room".to_string(),
+            version: PROTOCOL_VERSION,
+            features: vec![],
+            schema: Some("0000000000000002".to_string()),
+            metadata: Default::default(),
+        },
+    )
+    .await
+    .expect("Handshake failed");
+
+    assert!(
+        matches!(
+            reply,
+            SignalMessage::Reject {
+                reason: RejectReason::SchemaMismatch { .. }
+            }
+        ),
+        "Expected SchemaMismatch, got {:?}",
+        reply
+    );
+    assert!(
+        behavior.get_events().await.is_empty(),
+        "Rejected peers must not reach the room"
+    );
+}
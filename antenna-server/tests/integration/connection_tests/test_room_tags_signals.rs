@@ -0,0 +1,49 @@
+use std::time::Duration;
+
+use antenna_core::{ErrorCode, PeerId, SignalMessage};
+use antenna_server::{Room, RoomCommand};
+use tokio::sync::mpsc;
+
+use crate::integration::init_tracing;
+use crate::utils::{MockSignalingOutput, TestRoomBehavior, all_features};
+
+#[tokio::test]
+async fn test_room_tags_signals() {
+    init_tracing();
+
+    let (cmd_tx, cmd_rx) = mpsc::channel::<RoomCommand>(100);
+    let (signaling, mut signal_rx) = MockSignalingOutput::new();
+    let room = Room::new(
+        Box::new(TestRoomBehavior::new()),
+        cmd_rx,
+        signaling.service.clone(),
+    )
+    .with_room_id("lobby");
+    tokio::spawn(room.run());
+
+    let peer_id = PeerId::new();
+    signaling.register_peer(peer_id.clone());
+
+    cmd_tx
+        .send(RoomCommand::JoinRequest {
+            peer_id: peer_id.clone(),
+            offer: "not an sdp".to_string(),
+            metadata: Default::default(),
+            features: all_features(),
+        })
+        .await
+        .expect("Failed to send JoinRequest");
+
+    let signal = tokio::time::timeout(Duration::from_secs(5), signal_rx.recv())
+        .await
+        .expect("Timeout waiting for error")
+        .expect("Signaling channel closed");
+
+    match signal {
+        SignalMessage::Error { code, room, .. } => {
+            assert_eq!(code, ErrorCode::SdpFailed);
+            assert_eq!(room.as_deref(), Some("lobby"));
+        }
+        other => panic!("Expected Error, got {:?}", other),
+    }
+}
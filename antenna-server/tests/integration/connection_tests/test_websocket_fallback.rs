@@ -0,0 +1,90 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use antenna_core::{PeerId, decode_ws_frame};
+use antenna_server::{Room, RoomCommand, WebSocketSender};
+use bytes::Bytes;
+use tokio::sync::mpsc;
+
+use crate::integration::init_tracing;
+use crate::utils::{MockSignalingOutput, TestRoomBehavior, all_features};
+
+#[tokio::test]
+async fn test_websocket_fallback() {
+    init_tracing();
+
+    let (room_cmd_tx, cmd_rx) = mpsc::channel::<RoomCommand>(100);
+    let signaling = MockSignalingOutput::new_stored_only();
+    let behavior = TestRoomBehavior::new().with_echo();
+    let room = Room::new(
+        Box::new(behavior.clone()),
+        cmd_rx,
+        signaling.service.clone(),
+    );
+    tokio::spawn(room.run());
+
+    let peer_id = PeerId::new();
+    let mut data_rx = signaling.register_peer_with_data(peer_id.clone());
+
+    room_cmd_tx
+        .send(RoomCommand::Attach {
+            peer_id: peer_id.clone(),
+            sender: Arc::new(WebSocketSender::new(
+                peer_id.clone(),
+                "lobby",
+                signaling.service.as_ref().clone(),
+            )),
+            metadata: Default::default(),
+            features: all_features(),
+        })
+        .await
+        .expect("Failed to send WebSocket join");
+
+    assert!(
+        behavior.wait_for_events(1, 5000).await,
+        "Expected join event"
+    );
+    assert!(behavior.has_join(&peer_id).await);
+
+    room_cmd_tx
+        .send(RoomCommand::Data {
+            peer_id: peer_id.clone(),
+            data: Bytes::from_static(b"over websocket"),
+        })
+        .await
+        .expect("Failed to send WebSocket message");
+
+    // RoomContext::send reaches the peer as a binary WebSocket frame naming its room.
+    let echoed = tokio::time::timeout(Duration::from_secs(5), data_rx.recv())
+        .await
+        .expect("Timeout waiting for echo")
+        .expect("Signaling channel closed");
+    assert_eq!(
+        decode_ws_frame(&echoed),
+        Some(("lobby", &b"over websocket"[..]))
+    );
+
+    // Data from a peer that was never attached is ignored.
+    let stranger = PeerId::new();
+    room_cmd_tx
+        .send(RoomCommand::Data {
+            peer_id: stranger.clone(),
+            data: Bytes::from_static(b"ignored"),
+        })
+        .await
+        .expect("Failed to send WebSocket message");
+
+    room_cmd_tx
+        .send(RoomCommand::Disconnect {
+            peer_id: peer_id.clone(),
+        })
+        .await
+        .expect("Failed to send disconnect");
+
+    assert!(
+        behavior.wait_for_events(3, 5000).await,
+        "Expected leave event"
+    );
+    assert!(behavior.has_leave(&peer_id).await);
+    assert!(behavior.messages_from(&stranger).await.is_empty());
+}
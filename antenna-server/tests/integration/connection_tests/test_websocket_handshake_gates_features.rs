@@ -0,0 +1,101 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use antenna_core::{ErrorCode, PROTOCOL_VERSION, PeerId, SignalMessage};
+use antenna_server::{
+    AppState, BehaviorFactory, MatchRules, Matchmaker, RoomBehavior, RoomManager, SignalingService,
+};
+
+use crate::integration::init_tracing;
+use crate::utils::{TestRoomBehavior, WsTestClient, serve_ws};
+
+#[tokio::test]
+async fn test_websocket_handshake_gates_features() {
+    init_tracing();
+
+    let behavior = TestRoomBehavior::new();
+    let factory: BehaviorFactory = {
+        let behavior = behavior.clone();
+        Arc::new(move || Box::new(behavior.clone()) as Box<dyn RoomBehavior>)
+    };
+    let signaling_service = SignalingService::new(vec![]);
+    let room_manager = RoomManager::new(factory, Arc::new(signaling_service.clone()));
+    let matchmaker = Matchmaker::new(room_manager.clone(), signaling_service.clone())
+        .with_queue("duel", MatchRules::new(2));
+    let state = Arc::new(AppState {
+        room_manager,
+        signaling_service,
+        schema: None,
+        matchmaker: Some(matchmaker.clone()),
+    });
+    let addr = serve_ws(state).await.expect("Failed to serve");
+    let timeout = Duration::from_secs(5);
+
+    let mut client = WsTestClient::connect(addr, &PeerId::new())
+        .await
+        .expect("Failed to connect");
+
+    // Nothing is queued before the handshake
+    client
+        .send(&SignalMessage::Matchmake {
+            queue: "duel".to_string(),
+            attributes: Default::default(),
+        })
+        .await
+        .expect("Failed to send Matchmake");
+    match client.recv(timeout).await.expect("No reply") {
+        Some(SignalMessage::Error { code, .. }) => assert_eq!(code, ErrorCode::NotJoined),
+        other => panic!("Expected NotJoined, got {:?}", other),
+    }
+    assert_eq!(matchmaker.waiting("duel"), 0);
+
+    // A client announcing no features gets none
+    client
+        .send(&SignalMessage::Join {
+            room: "lobby".to_string(),
+            version: PROTOCOL_VERSION,
+            features: vec![],
+            schema: None,
+            metadata: Default::default(),
+        })
+        .await
+        .expect("Failed to send Join");
+    match client.recv(timeout).await.expect("No reply") {
+        Some(SignalMessage::Welcome { features, .. }) => assert!(features.is_empty()),
+        other => panic!("Expected Welcome, got {:?}", other),
+    }
+
+    // So it cannot switch to the WebSocket transport
+    client
+        .send(&SignalMessage::UseWebSocketTransport { room: None })
+        .await
+        .expect("Failed to send UseWebSocketTransport");
+    match client.recv(timeout).await.expect("No reply") {
+        Some(SignalMessage::Error {
+            code, room, fatal, ..
+        }) => {
+            assert_eq!(code, ErrorCode::TransportFailed);
+            assert_eq!(room.as_deref(), Some("lobby"));
+            assert!(fatal);
+        }
+        other => panic!("Expected TransportFailed, got {:?}", other),
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(behavior.get_events().await.is_empty());
+
+    // After the handshake matchmaking works
+    client
+        .send(&SignalMessage::Matchmake {
+            queue: "duel".to_string(),
+            attributes: Default::default(),
+        })
+        .await
+        .expect("Failed to send Matchmake");
+    tokio::time::timeout(timeout, async {
+        while matchmaker.waiting("duel") == 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("Peer was not queued");
+}
@@ -0,0 +1,73 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use antenna_core::{ErrorCode, PROTOCOL_VERSION, PeerId, SignalMessage};
+use antenna_server::{AppState, BehaviorFactory, RoomBehavior, RoomManager, SignalingService};
+
+use crate::integration::init_tracing;
+use crate::utils::{TestRoomBehavior, WsTestClient, serve_ws};
+
+fn join(room: &str) -> SignalMessage {
+    SignalMessage::Join {
+        room: room.to_string(),
+        version: PROTOCOL_VERSION,
+        features: vec![],
+        schema: None,
+        metadata: Default::default(),
+    }
+}
+
+#[tokio::test]
+async fn test_websocket_leave_falls_back() {
+    init_tracing();
+
+    let factory: BehaviorFactory = {
+        let behavior = TestRoomBehavior::new();
+        Arc::new(move || Box::new(behavior.clone()) as Box<dyn RoomBehavior>)
+    };
+    let signaling_service = SignalingService::new(vec![]);
+    let state = Arc::new(AppState {
+        room_manager: RoomManager::new(factory, Arc::new(signaling_service.clone())),
+        signaling_service,
+        schema: None,
+        matchmaker: None,
+    });
+    let addr = serve_ws(state).await.expect("Failed to serve");
+    let timeout = Duration::from_secs(5);
+
+    let mut client = WsTestClient::connect(addr, &PeerId::new())
+        .await
+        .expect("Failed to connect");
+    for room in ["first", "second"] {
+        client.send(&join(room)).await.expect("Failed to send Join");
+        match client.recv(timeout).await.expect("No reply") {
+            Some(SignalMessage::Welcome { room: welcomed, .. }) => {
+                assert_eq!(welcomed.as_deref(), Some(room));
+            }
+            other => panic!("Expected Welcome, got {:?}", other),
+        }
+    }
+
+    client
+        .send(&SignalMessage::Leave {
+            room: Some("second".to_string()),
+        })
+        .await
+        .expect("Failed to send Leave");
+
+    // A signal naming no room now addresses the room that is still joined
+    client
+        .send(&SignalMessage::Offer {
+            sdp: "not an sdp".to_string(),
+            room: None,
+        })
+        .await
+        .expect("Failed to send Offer");
+    match client.recv(timeout).await.expect("No reply") {
+        Some(SignalMessage::Error { code, room, .. }) => {
+            assert_eq!(code, ErrorCode::SdpFailed);
+            assert_eq!(room.as_deref(), Some("first"));
+        }
+        other => panic!("Expected SdpFailed from the first room, got {:?}", other),
+    }
+}
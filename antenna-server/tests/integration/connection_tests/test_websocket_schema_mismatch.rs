@@ -0,0 +1,79 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use antenna_core::{PROTOCOL_VERSION, PeerId, RejectReason, SignalMessage};
+use antenna_server::{AppState, BehaviorFactory, RoomBehavior, RoomManager, SignalingService};
+
+use crate::integration::init_tracing;
+use crate::utils::{TestRoomBehavior, WsTestClient, serve_ws};
+
+fn join(version: u32, schema: &str) -> SignalMessage {
+    SignalMessage::Join {
+        room: "ws-room".to_string(),
+        version,
+        features: vec![],
+        schema: Some(schema.to_string()),
+        metadata: Default::default(),
+    }
+}
+
+#[tokio::test]
+async fn test_websocket_schema_mismatch() {
+    init_tracing();
+
+    let behavior = TestRoomBehavior::new();
+    let factory: BehaviorFactory = {
+        let behavior = behavior.clone();
+        Arc::new(move || Box::new(behavior.clone()) as Box<dyn RoomBehavior>)
+    };
+    let signaling_service = SignalingService::new(vec![]);
+    let state = Arc::new(AppState {
+        room_manager: RoomManager::new(factory, Arc::new(signaling_service.clone())),
+        signaling_service,
+        schema: Some("0000000000000001".to_string()),
+        matchmaker: None,
+    });
+    let addr = serve_ws(state).await.expect("Failed to serve");
+    let timeout = Duration::from_secs(5);
+
+    let mut client = WsTestClient::connect(addr, &PeerId::new())
+        .await
+        .expect("Failed to connect");
+    client
+        .send(&join(PROTOCOL_VERSION, "0000000000000002"))
+        .await
+        .expect("Failed to send Join");
+    match client.recv(timeout).await.expect("No reply") {
+        Some(SignalMessage::Reject {
+            reason: RejectReason::SchemaMismatch { server, client },
+        }) => {
+            assert_eq!(server, "0000000000000001");
+            assert_eq!(client, "0000000000000002");
+        }
+        other => panic!("Expected SchemaMismatch, got {:?}", other),
+    }
+    // The server closes the connection after rejecting it.
+    assert!(client.recv(timeout).await.expect("No close").is_none());
+
+    let mut client = WsTestClient::connect(addr, &PeerId::new())
+        .await
+        .expect("Failed to connect");
+    client
+        .send(&join(0, "0000000000000001"))
+        .await
+        .expect("Failed to send Join");
+    match client.recv(timeout).await.expect("No reply") {
+        Some(SignalMessage::Reject {
+            reason: RejectReason::VersionMismatch { server, client },
+        }) => {
+            assert_eq!(server, PROTOCOL_VERSION);
+            assert_eq!(client, 0);
+        }
+        other => panic!("Expected VersionMismatch, got {:?}", other),
+    }
+
+    assert!(
+        behavior.get_events().await.is_empty(),
+        "Rejected peers must not reach the room"
+    );
+}
@@ -0,0 +1,105 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use antenna_core::{PeerId, SignalMessage};
+use antenna_server::{Room, RoomCommand, RoomConfig, WebSocketSender};
+use bytes::Bytes;
+use tokio::sync::mpsc;
+
+use crate::integration::init_tracing;
+use crate::utils::{GatedSender, MockSignalingOutput, TestRoomBehavior, all_features};
+
+#[tokio::test]
+async fn test_websocket_transport_admission() {
+    init_tracing();
+
+    let (cmd_tx, cmd_rx) = mpsc::channel::<RoomCommand>(100);
+    let (signaling, mut signals) = MockSignalingOutput::new();
+    let behavior = TestRoomBehavior::new();
+    let room = Room::new(
+        Box::new(behavior.clone()),
+        cmd_rx,
+        signaling.service.clone(),
+    )
+    .with_config(RoomConfig::new().with_max_peers(1).with_wait_list(1));
+    tokio::spawn(room.run());
+    let timeout = Duration::from_secs(5);
+
+    let first = PeerId::new();
+    let second = PeerId::new();
+    let sender = GatedSender::new();
+    sender.open();
+    cmd_tx
+        .send(RoomCommand::Attach {
+            peer_id: first.clone(),
+            sender: Arc::new(sender),
+            metadata: Default::default(),
+            features: all_features(),
+        })
+        .await
+        .expect("Failed to attach peer");
+    assert!(behavior.wait_for_events(1, 5000).await);
+
+    // A peer switching to the WebSocket transport of a full room waits instead of being admitted
+    signaling.register_peer(second.clone());
+    cmd_tx
+        .send(RoomCommand::Attach {
+            peer_id: second.clone(),
+            sender: Arc::new(WebSocketSender::new(
+                second.clone(),
+                "lobby",
+                signaling.service.as_ref().clone(),
+            )),
+            metadata: Default::default(),
+            features: all_features(),
+        })
+        .await
+        .expect("Failed to attach peer");
+    let signal = tokio::time::timeout(timeout, signals.recv())
+        .await
+        .expect("No admission result")
+        .expect("Signaling closed");
+    assert!(
+        matches!(signal, SignalMessage::WaitListed { position: 1, .. }),
+        "Expected WaitListed, got {:?}",
+        signal
+    );
+
+    // What it sends meanwhile is dropped
+    cmd_tx
+        .send(RoomCommand::Data {
+            peer_id: second.clone(),
+            data: Bytes::from_static(b"too early"),
+        })
+        .await
+        .expect("Failed to send data");
+
+    cmd_tx
+        .send(RoomCommand::Leave {
+            peer_id: first.clone(),
+        })
+        .await
+        .expect("Failed to leave");
+    let signal = tokio::time::timeout(timeout, signals.recv())
+        .await
+        .expect("Peer was not admitted")
+        .expect("Signaling closed");
+    assert!(
+        matches!(signal, SignalMessage::Admitted { .. }),
+        "Expected Admitted, got {:?}",
+        signal
+    );
+
+    cmd_tx
+        .send(RoomCommand::Data {
+            peer_id: second.clone(),
+            data: Bytes::from_static(b"admitted"),
+        })
+        .await
+        .expect("Failed to send data");
+    assert!(behavior.wait_for_events(4, 5000).await);
+    assert_eq!(
+        behavior.messages_from(&second).await,
+        vec![Bytes::from_static(b"admitted")]
+    );
+}
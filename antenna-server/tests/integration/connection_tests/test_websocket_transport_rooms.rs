@@ -0,0 +1,89 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use antenna_core::{PROTOCOL_VERSION, PeerId, SignalMessage, decode_ws_frame, encode_ws_frame};
+use antenna_server::{AppState, BehaviorFactory, RoomBehavior, RoomManager, SignalingService};
+
+use crate::integration::init_tracing;
+use crate::utils::{TestRoomBehavior, WsTestClient, all_features, serve_ws};
+
+#[tokio::test]
+async fn test_websocket_transport_rooms() {
+    init_tracing();
+
+    let behavior = TestRoomBehavior::new().with_echo();
+    let factory: BehaviorFactory = {
+        let behavior = behavior.clone();
+        Arc::new(move || Box::new(behavior.clone()) as Box<dyn RoomBehavior>)
+    };
+    let signaling_service = SignalingService::new(vec![]);
+    let room_manager = RoomManager::new(factory, Arc::new(signaling_service.clone()));
+    let state = Arc::new(AppState {
+        room_manager,
+        signaling_service,
+        schema: None,
+        matchmaker: None,
+    });
+    let addr = serve_ws(state).await.expect("Failed to serve");
+    let timeout = Duration::from_secs(5);
+
+    let peer_id = PeerId::new();
+    let mut client = WsTestClient::connect(addr, &peer_id)
+        .await
+        .expect("Failed to connect");
+    for room in ["a", "b"] {
+        client
+            .send(&SignalMessage::Join {
+                room: room.to_string(),
+                version: PROTOCOL_VERSION,
+                features: all_features(),
+                schema: None,
+                metadata: Default::default(),
+            })
+            .await
+            .expect("Failed to send Join");
+        match client.recv(timeout).await.expect("No reply") {
+            Some(SignalMessage::Welcome { .. }) => {}
+            other => panic!("Expected Welcome, got {:?}", other),
+        }
+        client
+            .send(&SignalMessage::UseWebSocketTransport {
+                room: Some(room.to_string()),
+            })
+            .await
+            .expect("Failed to send UseWebSocketTransport");
+        match client.recv(timeout).await.expect("No reply") {
+            Some(SignalMessage::Admitted { room: admitted }) => {
+                assert_eq!(admitted.as_deref(), Some(room))
+            }
+            other => panic!("Expected Admitted, got {:?}", other),
+        }
+    }
+    assert!(behavior.wait_for_events(2, 5000).await, "Rooms not joined");
+
+    // Both rooms share the WebSocket, every frame reaches the room it names
+    for room in ["a", "b"] {
+        let frame = encode_ws_frame(room, format!("to {room}").as_bytes()).unwrap();
+        client.send_binary(frame).await.expect("Failed to send");
+    }
+    let mut echoed = HashSet::new();
+    for _ in 0..2 {
+        let frame = client.recv_binary(timeout).await.expect("No echo");
+        let (room, data) = decode_ws_frame(&frame).expect("Malformed frame");
+        echoed.insert((room.to_string(), data.to_vec()));
+    }
+    assert_eq!(
+        echoed,
+        HashSet::from([
+            ("a".to_string(), b"to a".to_vec()),
+            ("b".to_string(), b"to b".to_vec()),
+        ])
+    );
+
+    // Frames of rooms the connection does not use the transport for are dropped
+    let frame = encode_ws_frame("c", b"to c").unwrap();
+    client.send_binary(frame).await.expect("Failed to send");
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(behavior.messages_from(&peer_id).await.len(), 2);
+}
@@ -0,0 +1,3 @@
+mod test_match_rules_zero_size;
+mod test_matchmaking_closes_unjoined_rooms;
+mod test_matchmaking_widens_skill_range;
@@ -0,0 +1,7 @@
+use antenna_server::MatchRules;
+
+#[test]
+#[should_panic(expected = "match size must not be zero")]
+fn test_match_rules_zero_size() {
+    let _ = MatchRules::new(0);
+}
@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use antenna_core::{PeerId, SignalMessage};
+use antenna_server::{
+    BehaviorFactory, MatchRules, Matchmaker, RoomBehavior, RoomCommand, RoomManager,
+    SignalingService,
+};
+use axum::extract::ws::Message;
+use tokio::sync::mpsc;
+
+use crate::utils::{GatedSender, TestRoomBehavior, all_features};
+
+/// Connects `count` players and returns their ids with the signals they receive.
+fn connect(
+    signaling: &SignalingService,
+    count: usize,
+) -> Vec<(PeerId, mpsc::UnboundedReceiver<Message>)> {
+    (0..count)
+        .map(|_| {
+            let (tx, signals) = mpsc::unbounded_channel();
+            let peer_id = PeerId::new();
+            signaling.add_peer(peer_id.clone(), tx);
+            (peer_id, signals)
+        })
+        .collect()
+}
+
+fn matched_room(signals: &mut mpsc::UnboundedReceiver<Message>) -> String {
+    while let Ok(Message::Text(text)) = signals.try_recv() {
+        if let Ok(SignalMessage::MatchFound { room, .. }) = serde_json::from_str(&text) {
+            return room;
+        }
+    }
+    panic!("No MatchFound received");
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_matchmaking_closes_unjoined_rooms() {
+    let signaling = SignalingService::new(vec![]);
+    let factory: BehaviorFactory =
+        Arc::new(|| Box::new(TestRoomBehavior::new()) as Box<dyn RoomBehavior>);
+    let room_manager = RoomManager::new(factory, Arc::new(signaling.clone()));
+    let matchmaker = Matchmaker::new(room_manager.clone(), signaling.clone())
+        .with_queue("duel", MatchRules::new(2))
+        .with_join_timeout(Duration::from_secs(10));
+
+    let mut players = connect(&signaling, 4);
+    for (peer_id, _) in &players {
+        matchmaker
+            .enqueue(peer_id.clone(), "duel", HashMap::new())
+            .await
+            .unwrap();
+    }
+    let joined = matched_room(&mut players[0].1);
+    let abandoned = matched_room(&mut players[2].1);
+    assert_ne!(joined, abandoned);
+
+    room_manager
+        .get_room_sender(&joined)
+        .send(RoomCommand::Attach {
+            peer_id: players[0].0.clone(),
+            sender: Arc::new(GatedSender::new()),
+            metadata: Default::default(),
+            features: all_features(),
+        })
+        .await
+        .expect("Room closed");
+
+    tokio::time::sleep(Duration::from_secs(11)).await;
+    for _ in 0..100 {
+        if !room_manager.has_room(&abandoned) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(!room_manager.has_room(&abandoned));
+    assert!(room_manager.has_room(&joined));
+}
@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use antenna_core::{PeerId, SignalMessage};
+use antenna_server::{
+    BehaviorFactory, MatchRules, Matchmaker, RoomBehavior, RoomManager, RoomRoute, SignalingService,
+};
+use axum::extract::ws::Message;
+use tokio::sync::mpsc;
+
+use crate::utils::TestRoomBehavior;
+
+struct Player {
+    peer_id: PeerId,
+    signals: mpsc::UnboundedReceiver<Message>,
+}
+
+impl Player {
+    fn connect(signaling: &SignalingService) -> Self {
+        let (tx, signals) = mpsc::unbounded_channel();
+        let peer_id = PeerId::new();
+        signaling.add_peer(peer_id.clone(), tx);
+        Self { peer_id, signals }
+    }
+
+    /// Room of the `MatchFound` received so far, if any.
+    fn matched_room(&mut self) -> Option<String> {
+        while let Ok(Message::Text(text)) = self.signals.try_recv() {
+            if let Ok(SignalMessage::MatchFound { queue, room }) = serde_json::from_str(&text) {
+                assert_eq!(queue, "duel");
+                return Some(room);
+            }
+        }
+        None
+    }
+}
+
+fn skill(value: f64) -> HashMap<String, f64> {
+    HashMap::from([("skill".to_string(), value)])
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_matchmaking_widens_skill_range() {
+    let signaling = SignalingService::new(vec![]);
+    let factory: BehaviorFactory =
+        Arc::new(|| Box::new(TestRoomBehavior::new()) as Box<dyn RoomBehavior>);
+    let room_manager = RoomManager::new(factory, Arc::new(signaling.clone()));
+    let matchmaker = Matchmaker::new(room_manager.clone(), signaling.clone()).with_queue(
+        "duel",
+        MatchRules::new(2).with_skill_range("skill", 100.0, 50.0),
+    );
+
+    let mut veteran = Player::connect(&signaling);
+    let mut rookie = Player::connect(&signaling);
+    let mut close = Player::connect(&signaling);
+    let mut far = Player::connect(&signaling);
+
+    assert!(
+        matchmaker
+            .enqueue(veteran.peer_id.clone(), "ranked", skill(0.0))
+            .await
+            .is_err()
+    );
+
+    matchmaker
+        .enqueue(veteran.peer_id.clone(), "duel", skill(1000.0))
+        .await
+        .unwrap();
+    matchmaker
+        .enqueue(far.peer_id.clone(), "duel", skill(1500.0))
+        .await
+        .unwrap();
+    assert_eq!(veteran.matched_room(), None);
+
+    // Within the initial range: matched right away.
+    matchmaker
+        .enqueue(close.peer_id.clone(), "duel", skill(1050.0))
+        .await
+        .unwrap();
+    let room = veteran.matched_room().expect("Veteran was not matched");
+    assert_eq!(close.matched_room(), Some(room.clone()));
+    assert!(matches!(
+        room_manager.route(&room).await,
+        Ok(RoomRoute::Local(_))
+    ));
+    assert_eq!(matchmaker.waiting("duel"), 1);
+
+    // 300 apart: the range of the older ticket reaches that after 4 seconds.
+    matchmaker
+        .enqueue(rookie.peer_id.clone(), "duel", skill(1800.0))
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(2500)).await;
+    assert_eq!(far.matched_room(), None);
+
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    let room = far.matched_room().expect("Range did not widen");
+    assert_eq!(rookie.matched_room(), Some(room));
+    assert_eq!(matchmaker.waiting("duel"), 0);
+}
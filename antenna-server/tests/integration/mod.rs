@@ -1,13 +1,22 @@
+pub mod behavior_tests;
+pub mod cluster_tests;
 pub mod connection_tests;
+pub mod matchmaking_tests;
 pub mod messaging_tests;
 pub mod multi_peer_tests;
+pub mod send_queue_tests;
+pub mod store_tests;
 
+use std::sync::Arc;
 use tokio::sync::mpsc;
 use tracing::Level;
 
-use antenna_server::{Room, RoomCommand};
+use antenna_core::{Channel, PeerId};
+use antenna_server::{MemoryTransportFactory, Room, RoomCommand, SendQueueConfig};
 
-use crate::utils::{MockSignalingOutput, TestRoomBehavior};
+use crate::utils::{
+    GatedSender, MockSignalingOutput, RelayBehavior, RelayOutput, TestRoomBehavior, all_features,
+};
 use antenna_core::SignalMessage;
 
 pub fn init_tracing() {
@@ -38,3 +47,51 @@ pub fn create_test_room() -> (
 
     (cmd_tx, (signaling, signal_rx), behavior)
 }
+
+/// Spawn a room whose peers connect through the in-memory transport instead of WebRTC.
+pub fn create_memory_room(
+    behavior: TestRoomBehavior,
+    transport: MemoryTransportFactory,
+) -> mpsc::Sender<RoomCommand> {
+    let (cmd_tx, cmd_rx) = mpsc::channel::<RoomCommand>(100);
+    let signaling = MockSignalingOutput::new_stored_only();
+
+    let room = Room::new(Box::new(behavior), cmd_rx, signaling.service.clone())
+        .with_transport_factory(Arc::new(transport));
+
+    tokio::spawn(async move {
+        room.run().await;
+    });
+
+    cmd_tx
+}
+
+/// Spawn a room with a single peer attached through a `GatedSender`, relaying its messages back
+/// on `channel` through a send queue configured by `config`.
+pub async fn create_gated_room(
+    channel: Channel,
+    config: SendQueueConfig,
+) -> (mpsc::Sender<RoomCommand>, PeerId, GatedSender, RelayOutput) {
+    let (cmd_tx, cmd_rx) = mpsc::channel::<RoomCommand>(100);
+    let signaling = MockSignalingOutput::new_stored_only();
+    let (behavior, mut output) = RelayBehavior::new(channel);
+
+    let room = Room::new(Box::new(behavior), cmd_rx, signaling.service.clone())
+        .with_send_queue_config(config);
+    tokio::spawn(room.run());
+
+    let peer_id = PeerId::new();
+    let sender = GatedSender::new();
+    cmd_tx
+        .send(RoomCommand::Attach {
+            peer_id: peer_id.clone(),
+            sender: Arc::new(sender.clone()),
+            metadata: Default::default(),
+            features: all_features(),
+        })
+        .await
+        .expect("Failed to attach peer");
+    output.joins.recv().await.expect("Room closed");
+
+    (cmd_tx, peer_id, sender, output)
+}
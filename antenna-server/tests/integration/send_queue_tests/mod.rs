@@ -0,0 +1,6 @@
+mod test_broadcast_timeout;
+mod test_message_too_large;
+mod test_reliable_backpressure;
+mod test_reliable_queue_full;
+mod test_send_timeout;
+mod test_unreliable_drop_oldest;
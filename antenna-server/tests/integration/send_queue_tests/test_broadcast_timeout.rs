@@ -0,0 +1,101 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use antenna_core::PeerId;
+use antenna_server::{Room, RoomBehavior, RoomCommand, RoomContext, SendQueueConfig};
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::sync::mpsc;
+
+use crate::integration::init_tracing;
+use crate::utils::{GatedSender, MockSignalingOutput, all_features};
+
+/// Broadcasts every received message and reports joins and returned broadcasts.
+struct BroadcastBehavior {
+    joins: mpsc::UnboundedSender<PeerId>,
+    done: mpsc::UnboundedSender<()>,
+}
+
+#[async_trait]
+impl RoomBehavior for BroadcastBehavior {
+    async fn on_join(&self, _ctx: &RoomContext, peer_id: PeerId) {
+        let _ = self.joins.send(peer_id);
+    }
+
+    async fn on_message(&self, ctx: &RoomContext, _peer_id: PeerId, data: Bytes) {
+        ctx.broadcast(data).await;
+        let _ = self.done.send(());
+    }
+
+    async fn on_leave(&self, _ctx: &RoomContext, _peer_id: PeerId) {}
+}
+
+#[tokio::test]
+async fn test_broadcast_timeout() {
+    init_tracing();
+
+    let config = SendQueueConfig {
+        high_watermark: 8,
+        low_watermark: 0,
+        broadcast_timeout: Duration::from_millis(100),
+        ..SendQueueConfig::default()
+    };
+    let (cmd_tx, cmd_rx) = mpsc::channel::<RoomCommand>(100);
+    let (joins_tx, mut joins_rx) = mpsc::unbounded_channel();
+    let (done_tx, mut done_rx) = mpsc::unbounded_channel();
+    let signaling = MockSignalingOutput::new_stored_only();
+    let room = Room::new(
+        Box::new(BroadcastBehavior {
+            joins: joins_tx,
+            done: done_tx,
+        }),
+        cmd_rx,
+        signaling.service.clone(),
+    )
+    .with_send_queue_config(config);
+    tokio::spawn(room.run());
+
+    let slow = GatedSender::new();
+    let fast = GatedSender::new();
+    fast.open();
+    let fast_id = PeerId::new();
+    for (peer_id, sender) in [(PeerId::new(), &slow), (fast_id.clone(), &fast)] {
+        cmd_tx
+            .send(RoomCommand::Attach {
+                peer_id,
+                sender: Arc::new(sender.clone()),
+                metadata: Default::default(),
+                features: all_features(),
+            })
+            .await
+            .expect("Failed to attach peer");
+        joins_rx.recv().await.expect("Room closed");
+    }
+
+    let payloads = [b"aaaaaaaa", b"bbbbbbbb", b"cccccccc"];
+    for payload in payloads {
+        cmd_tx
+            .send(RoomCommand::Data {
+                peer_id: fast_id.clone(),
+                data: Bytes::from_static(payload),
+            })
+            .await
+            .expect("Failed to send data");
+
+        // The third broadcast finds the slow peer's queue full and gives up on it
+        tokio::time::timeout(Duration::from_secs(5), done_rx.recv())
+            .await
+            .expect("Broadcast blocked on the slow peer")
+            .expect("Room closed");
+        if payload == b"aaaaaaaa" {
+            slow.next_started().await.expect("Writer stopped");
+        }
+    }
+
+    let expected: Vec<Bytes> = payloads.iter().map(|p| Bytes::from_static(*p)).collect();
+    assert_eq!(fast.sent().await, expected);
+
+    slow.open();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(slow.sent().await, expected[..2]);
+}
@@ -0,0 +1,40 @@
+use std::time::Duration;
+
+use antenna_core::Channel;
+use antenna_server::{RoomCommand, SendError, SendQueueConfig};
+use bytes::Bytes;
+
+use crate::integration::{create_gated_room, init_tracing};
+
+#[tokio::test]
+async fn test_message_too_large() {
+    init_tracing();
+
+    let config = SendQueueConfig {
+        max_message_size: 4,
+        ..SendQueueConfig::default()
+    };
+    let (room_cmd_tx, peer_id, sender, mut output) =
+        create_gated_room(Channel::Reliable, config).await;
+
+    room_cmd_tx
+        .send(RoomCommand::Data {
+            peer_id: peer_id.clone(),
+            data: Bytes::from_static(b"too large"),
+        })
+        .await
+        .expect("Failed to send data");
+
+    let result = tokio::time::timeout(Duration::from_secs(5), output.results.recv())
+        .await
+        .expect("Timeout waiting for send result")
+        .expect("Room closed");
+    assert!(matches!(
+        result,
+        Err(SendError::MessageTooLarge { size: 9, max: 4 })
+    ));
+
+    sender.open();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(sender.sent().await.is_empty());
+}
@@ -0,0 +1,66 @@
+use std::time::Duration;
+
+use antenna_core::Channel;
+use antenna_server::{RoomCommand, SendQueueConfig};
+use bytes::Bytes;
+
+use crate::integration::{create_gated_room, init_tracing};
+
+#[tokio::test]
+async fn test_reliable_backpressure() {
+    init_tracing();
+
+    let config = SendQueueConfig {
+        high_watermark: 8,
+        low_watermark: 0,
+        ..SendQueueConfig::default()
+    };
+    let (room_cmd_tx, peer_id, sender, mut output) =
+        create_gated_room(Channel::Reliable, config).await;
+
+    for payload in [b"aaaaaaaa", b"bbbbbbbb", b"cccccccc"] {
+        room_cmd_tx
+            .send(RoomCommand::Data {
+                peer_id: peer_id.clone(),
+                data: Bytes::from_static(payload),
+            })
+            .await
+            .expect("Failed to send data");
+
+        if payload == b"cccccccc" {
+            break;
+        }
+
+        let result = tokio::time::timeout(Duration::from_secs(5), output.results.recv())
+            .await
+            .expect("Timeout waiting for send result")
+            .expect("Room closed");
+        assert!(result.is_ok());
+
+        if payload == b"aaaaaaaa" {
+            sender.next_started().await.expect("Writer stopped");
+        }
+    }
+
+    // The third send waits for the queue to drain
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(output.results.try_recv().is_err());
+
+    sender.open();
+    let result = tokio::time::timeout(Duration::from_secs(5), output.results.recv())
+        .await
+        .expect("Backpressured send never resolved")
+        .expect("Room closed");
+    assert!(result.is_ok());
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let sent = sender.sent().await;
+    assert_eq!(
+        sent,
+        vec![
+            Bytes::from_static(b"aaaaaaaa"),
+            Bytes::from_static(b"bbbbbbbb"),
+            Bytes::from_static(b"cccccccc"),
+        ]
+    );
+}
@@ -0,0 +1,54 @@
+use std::time::Duration;
+
+use antenna_core::Channel;
+use antenna_server::{OverflowPolicy, RoomCommand, SendError, SendQueueConfig};
+use bytes::Bytes;
+
+use crate::integration::{create_gated_room, init_tracing};
+
+#[tokio::test]
+async fn test_reliable_queue_full() {
+    init_tracing();
+
+    let config = SendQueueConfig {
+        high_watermark: 8,
+        low_watermark: 0,
+        reliable: OverflowPolicy::Fail,
+        ..SendQueueConfig::default()
+    };
+    let (room_cmd_tx, peer_id, sender, mut output) =
+        create_gated_room(Channel::Reliable, config).await;
+
+    for (i, payload) in [b"aaaaaaaa", b"bbbbbbbb", b"cccccccc"]
+        .into_iter()
+        .enumerate()
+    {
+        room_cmd_tx
+            .send(RoomCommand::Data {
+                peer_id: peer_id.clone(),
+                data: Bytes::from_static(payload),
+            })
+            .await
+            .expect("Failed to send data");
+
+        let result = tokio::time::timeout(Duration::from_secs(5), output.results.recv())
+            .await
+            .expect("Timeout waiting for send result")
+            .expect("Room closed");
+
+        match i {
+            // The first message is in flight, the second one fills the queue
+            0 => {
+                assert!(result.is_ok());
+                let started = sender.next_started().await.expect("Writer stopped");
+                assert_eq!(started.as_ref(), b"aaaaaaaa");
+            }
+            1 => assert!(result.is_ok()),
+            _ => assert!(matches!(result, Err(SendError::QueueFull(id)) if id == peer_id)),
+        }
+    }
+
+    sender.open();
+    let started = sender.next_started().await.expect("Writer stopped");
+    assert_eq!(started.as_ref(), b"bbbbbbbb");
+}
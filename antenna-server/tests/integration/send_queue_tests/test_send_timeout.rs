@@ -0,0 +1,59 @@
+use std::time::Duration;
+
+use antenna_core::Channel;
+use antenna_server::{RoomCommand, SendError, SendQueueConfig};
+use bytes::Bytes;
+
+use crate::integration::{create_gated_room, init_tracing};
+
+#[tokio::test]
+async fn test_send_timeout() {
+    init_tracing();
+
+    let config = SendQueueConfig {
+        high_watermark: 8,
+        low_watermark: 0,
+        send_timeout: Duration::from_millis(100),
+        ..SendQueueConfig::default()
+    };
+    let (room_cmd_tx, peer_id, sender, mut output) =
+        create_gated_room(Channel::Reliable, config).await;
+
+    for (i, payload) in [b"aaaaaaaa", b"bbbbbbbb", b"cccccccc", b"dddddddd"]
+        .into_iter()
+        .enumerate()
+    {
+        room_cmd_tx
+            .send(RoomCommand::Data {
+                peer_id: peer_id.clone(),
+                data: Bytes::from_static(payload),
+            })
+            .await
+            .expect("Failed to send data");
+
+        // The room gives up on the full queue instead of waiting for the peer forever
+        let result = tokio::time::timeout(Duration::from_secs(5), output.results.recv())
+            .await
+            .expect("Send blocked the room")
+            .expect("Room closed");
+
+        match i {
+            0 => {
+                assert!(result.is_ok());
+                sender.next_started().await.expect("Writer stopped");
+            }
+            1 => assert!(result.is_ok()),
+            _ => assert!(matches!(result, Err(SendError::Timeout(id)) if id == peer_id)),
+        }
+    }
+
+    sender.open();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(
+        sender.sent().await,
+        vec![
+            Bytes::from_static(b"aaaaaaaa"),
+            Bytes::from_static(b"bbbbbbbb"),
+        ]
+    );
+}
@@ -0,0 +1,55 @@
+use std::time::Duration;
+
+use antenna_core::Channel;
+use antenna_server::{RoomCommand, SendQueueConfig};
+use bytes::Bytes;
+
+use crate::integration::{create_gated_room, init_tracing};
+
+#[tokio::test]
+async fn test_unreliable_drop_oldest() {
+    init_tracing();
+
+    let config = SendQueueConfig {
+        high_watermark: 8,
+        low_watermark: 0,
+        ..SendQueueConfig::default()
+    };
+    let (room_cmd_tx, peer_id, sender, mut output) =
+        create_gated_room(Channel::Unreliable, config).await;
+
+    for payload in [b"aaaaaaaa", b"bbbbbbbb", b"cccccccc"] {
+        room_cmd_tx
+            .send(RoomCommand::Data {
+                peer_id: peer_id.clone(),
+                data: Bytes::from_static(payload),
+            })
+            .await
+            .expect("Failed to send data");
+
+        let result = tokio::time::timeout(Duration::from_secs(5), output.results.recv())
+            .await
+            .expect("Timeout waiting for send result")
+            .expect("Room closed");
+        assert!(result.is_ok(), "Unreliable sends never fail on overflow");
+
+        if payload == b"aaaaaaaa" {
+            sender.next_started().await.expect("Writer stopped");
+        }
+    }
+
+    // "bbbbbbbb" was evicted in favour of the newer message
+    sender.open();
+    let started = sender.next_started().await.expect("Writer stopped");
+    assert_eq!(started.as_ref(), b"cccccccc");
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let sent = sender.sent().await;
+    assert_eq!(
+        sent,
+        vec![
+            Bytes::from_static(b"aaaaaaaa"),
+            Bytes::from_static(b"cccccccc")
+        ]
+    );
+}
@@ -0,0 +1,3 @@
+mod test_file_room_store;
+mod test_room_snapshot_restore;
+mod test_zero_snapshot_interval;
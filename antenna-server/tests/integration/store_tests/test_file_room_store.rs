@@ -0,0 +1,31 @@
+use antenna_server::{FileRoomStore, RoomStore};
+
+#[tokio::test]
+async fn test_file_room_store() {
+    let dir = std::env::temp_dir().join(format!("antenna-store-{}", uuid::Uuid::new_v4()));
+    let store = FileRoomStore::new(&dir);
+
+    // Room ids are not restricted to file name characters.
+    let room_id = "lobby/../eu west";
+    assert_eq!(store.load(room_id).await.unwrap(), None);
+
+    store.save(room_id, b"first").await.expect("Save failed");
+    store
+        .save(room_id, b"second")
+        .await
+        .expect("Overwrite failed");
+    assert_eq!(store.load(room_id).await.unwrap(), Some(b"second".to_vec()));
+
+    // A store over the same directory sees the snapshot, like a restarted server.
+    let reopened = FileRoomStore::new(&dir);
+    assert_eq!(
+        reopened.load(room_id).await.unwrap(),
+        Some(b"second".to_vec())
+    );
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+    reopened.delete(room_id).await.expect("Delete failed");
+    assert_eq!(store.load(room_id).await.unwrap(), None);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,111 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use antenna_core::PeerId;
+use antenna_server::{
+    BehaviorFactory, MemoryRoomStore, RoomBehavior, RoomCommand, RoomContext, RoomManager,
+    RoomStore, SignalingService,
+};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::sync::Mutex;
+
+use crate::integration::init_tracing;
+use crate::utils::{GatedSender, all_features};
+
+/// Counts received messages and persists the count. Greets joining peers.
+#[derive(Default)]
+struct CounterBehavior {
+    count: Arc<AtomicU64>,
+}
+
+#[async_trait]
+impl RoomBehavior for CounterBehavior {
+    async fn on_join(&self, ctx: &RoomContext, peer_id: PeerId) {
+        let _ = ctx.send(&peer_id, Bytes::from_static(b"hello")).await;
+    }
+
+    async fn on_message(&self, _ctx: &RoomContext, _peer_id: PeerId, _data: Bytes) {
+        self.count.fetch_add(1, Ordering::SeqCst);
+    }
+
+    async fn on_leave(&self, _ctx: &RoomContext, _peer_id: PeerId) {}
+
+    async fn snapshot(&self) -> Option<Vec<u8>> {
+        Some(self.count.load(Ordering::SeqCst).to_be_bytes().to_vec())
+    }
+
+    async fn restore(&self, snapshot: &[u8]) -> Result<()> {
+        let bytes = snapshot.try_into().context("Snapshot is not a u64")?;
+        self.count
+            .store(u64::from_be_bytes(bytes), Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+async fn wait_for_count(count: &AtomicU64, expected: u64) -> bool {
+    for _ in 0..500 {
+        if count.load(Ordering::SeqCst) == expected {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    false
+}
+
+#[tokio::test]
+async fn test_room_snapshot_restore() {
+    init_tracing();
+
+    // Counters of every behavior instance the manager created, in order.
+    let counters: Arc<Mutex<Vec<Arc<AtomicU64>>>> = Arc::default();
+    let factory: BehaviorFactory = {
+        let counters = counters.clone();
+        Arc::new(move || {
+            let behavior = CounterBehavior::default();
+            counters.try_lock().unwrap().push(behavior.count.clone());
+            Box::new(behavior) as Box<dyn RoomBehavior>
+        })
+    };
+    let store = MemoryRoomStore::new();
+    let manager = RoomManager::new(factory, Arc::new(SignalingService::new(vec![])))
+        .with_room_store(Arc::new(store.clone()));
+
+    let room_tx = manager.get_room_sender("counter");
+    let peer_id = PeerId::new();
+    let sender = GatedSender::new();
+    sender.open();
+    room_tx
+        .send(RoomCommand::Attach {
+            peer_id: peer_id.clone(),
+            sender: Arc::new(sender.clone()),
+            metadata: Default::default(),
+            features: all_features(),
+        })
+        .await
+        .expect("Failed to attach peer");
+    sender.next_started().await.expect("Peer was not greeted");
+    for _ in 0..3 {
+        room_tx
+            .send(RoomCommand::Data {
+                peer_id: peer_id.clone(),
+                data: Bytes::from_static(b"tick"),
+            })
+            .await
+            .expect("Failed to send data");
+    }
+    let first = counters.lock().await[0].clone();
+    assert!(wait_for_count(&first, 3).await, "Messages not counted");
+
+    // Shutting down saves the snapshot...
+    manager.shutdown().await;
+    let snapshot = store.load("counter").await.expect("Load failed");
+    assert_eq!(snapshot, Some(3u64.to_be_bytes().to_vec()));
+
+    // ...and the re-created room starts from it.
+    let _room_tx = manager.get_room_sender("counter");
+    let second = counters.lock().await[1].clone();
+    assert!(wait_for_count(&second, 3).await, "Room was not restored");
+}
@@ -0,0 +1,10 @@
+use std::time::Duration;
+
+use antenna_server::AntennaServer;
+
+#[test]
+#[should_panic(expected = "snapshot interval must not be zero")]
+fn test_zero_snapshot_interval() {
+    // Would otherwise panic later inside the room's event loop
+    let _ = AntennaServer::new().with_snapshot_interval(Duration::ZERO);
+}
@@ -0,0 +1,61 @@
+use antenna_server::PeerSender;
+use anyhow::Result;
+use async_trait::async_trait;
+use bytes::Bytes;
+use std::sync::Arc;
+use tokio::sync::{Mutex, mpsc, watch};
+
+/// A PeerSender whose sends block until the gate is opened, simulating a congested link.
+#[derive(Clone)]
+pub struct GatedSender {
+    gate: watch::Sender<bool>,
+    started_tx: mpsc::UnboundedSender<Bytes>,
+    started_rx: Arc<Mutex<mpsc::UnboundedReceiver<Bytes>>>,
+    sent: Arc<Mutex<Vec<Bytes>>>,
+}
+
+impl GatedSender {
+    pub fn new() -> Self {
+        let (started_tx, started_rx) = mpsc::unbounded_channel();
+        Self {
+            gate: watch::Sender::new(false),
+            started_tx,
+            started_rx: Arc::new(Mutex::new(started_rx)),
+            sent: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Lets every pending and future send complete.
+    pub fn open(&self) {
+        self.gate.send_replace(true);
+    }
+
+    /// Waits until the writer hands the next message to the transport.
+    pub async fn next_started(&self) -> Option<Bytes> {
+        self.started_rx.lock().await.recv().await
+    }
+
+    /// Messages that passed the gate, in order.
+    pub async fn sent(&self) -> Vec<Bytes> {
+        self.sent.lock().await.clone()
+    }
+}
+
+impl Default for GatedSender {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl PeerSender for GatedSender {
+    async fn send(&self, data: &Bytes) -> Result<()> {
+        let _ = self.started_tx.send(data.clone());
+
+        let mut gate = self.gate.subscribe();
+        gate.wait_for(|open| *open).await?;
+
+        self.sent.lock().await.push(data.clone());
+        Ok(())
+    }
+}
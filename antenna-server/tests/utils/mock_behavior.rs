@@ -14,6 +14,8 @@ pub enum RoomEvent {
     Message { peer_id: PeerId, data: Bytes },
     /// A peer left the room.
     Leave { peer_id: PeerId },
+    /// The room shut down.
+    Close,
 }
 
 /// A test implementation of RoomBehavior that records all events.
@@ -24,6 +26,10 @@ pub struct TestRoomBehavior {
     events: Arc<Mutex<Vec<RoomEvent>>>,
     /// Optional callback to execute on join
     on_join_callback: Option<OnJoinCallback>,
+    /// Whether received messages are sent back to their author
+    echo: bool,
+    /// Message broadcast to every peer when the room shuts down
+    farewell: Option<Bytes>,
 }
 
 impl TestRoomBehavior {
@@ -32,9 +38,23 @@ impl TestRoomBehavior {
         Self {
             events: Arc::new(Mutex::new(Vec::new())),
             on_join_callback: None,
+            echo: false,
+            farewell: None,
         }
     }
 
+    /// Send every received message back to the peer it came from.
+    pub fn with_echo(mut self) -> Self {
+        self.echo = true;
+        self
+    }
+
+    /// Broadcast `data` to every peer from `on_close`.
+    pub fn with_farewell(mut self, data: &'static [u8]) -> Self {
+        self.farewell = Some(Bytes::from_static(data));
+        self
+    }
+
     /// Get all recorded events (convenience method).
     pub async fn get_events(&self) -> Vec<RoomEvent> {
         self.events.lock().await.clone()
@@ -109,13 +129,17 @@ impl RoomBehavior for TestRoomBehavior {
         }
     }
 
-    async fn on_message(&self, _ctx: &RoomContext, peer_id: PeerId, data: Bytes) {
+    async fn on_message(&self, ctx: &RoomContext, peer_id: PeerId, data: Bytes) {
         tracing::info!(
             "[TestBehavior] on_message from {:?}: {} bytes",
             peer_id,
             data.len()
         );
 
+        if self.echo {
+            let _ = ctx.send(&peer_id, data.clone()).await;
+        }
+
         self.events
             .lock()
             .await
@@ -127,6 +151,16 @@ impl RoomBehavior for TestRoomBehavior {
 
         self.events.lock().await.push(RoomEvent::Leave { peer_id });
     }
+
+    async fn on_close(&self, ctx: &RoomContext) {
+        tracing::info!("[TestBehavior] on_close");
+
+        if let Some(farewell) = &self.farewell {
+            ctx.broadcast(farewell.clone()).await;
+        }
+
+        self.events.lock().await.push(RoomEvent::Close);
+    }
 }
 
 #[cfg(test)]
@@ -1,6 +1,7 @@
 use antenna_core::{PeerId, SignalMessage};
 use antenna_server::SignalingService;
 use axum::extract::ws::Message;
+use bytes::Bytes;
 use std::sync::Arc;
 use tokio::sync::{Mutex, mpsc};
 
@@ -61,11 +62,40 @@ impl MockSignalingOutput {
         });
     }
 
+    /// Register a peer whose binary WebSocket frames (fallback transport data) are captured too.
+    pub fn register_peer_with_data(&self, peer_id: PeerId) -> mpsc::UnboundedReceiver<Bytes> {
+        let (ws_tx, mut ws_rx) = mpsc::unbounded_channel();
+        let (data_tx, data_rx) = mpsc::unbounded_channel();
+        self.service.add_peer(peer_id, ws_tx);
+
+        let tx = self.tx.clone();
+        let signals = self.signals.clone();
+
+        tokio::spawn(async move {
+            while let Some(msg) = ws_rx.recv().await {
+                match msg {
+                    Message::Text(text) => {
+                        if let Ok(signal) = serde_json::from_str::<SignalMessage>(&text) {
+                            signals.lock().await.push(signal.clone());
+                            let _ = tx.send(signal);
+                        }
+                    }
+                    Message::Binary(data) => {
+                        let _ = data_tx.send(data);
+                    }
+                    _ => {}
+                }
+            }
+        });
+
+        data_rx
+    }
+
     /// Get the SDP answer for a specific peer (if any).
     #[allow(dead_code)]
     pub async fn get_answer_for(&self, _peer_id: &PeerId) -> Option<String> {
         self.signals.lock().await.iter().find_map(|s| match s {
-            SignalMessage::Answer { sdp } => Some(sdp.clone()),
+            SignalMessage::Answer { sdp, .. } => Some(sdp.clone()),
             _ => None,
         })
     }
@@ -78,7 +108,7 @@ impl MockSignalingOutput {
             .await
             .iter()
             .filter_map(|s| match s {
-                SignalMessage::IceCandidate { candidate } => Some(candidate.clone()),
+                SignalMessage::IceCandidate { candidate, .. } => Some(candidate.clone()),
                 _ => None,
             })
             .collect()
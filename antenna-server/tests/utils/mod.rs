@@ -1,9 +1,19 @@
+pub mod gated_sender;
 pub mod mock_behavior;
 pub mod mock_signaling;
+#[cfg(feature = "quic")]
+pub mod quic_client;
+pub mod relay_behavior;
 pub mod signal_helpers;
 pub mod test_client;
+pub mod ws_client;
 
+pub use gated_sender::*;
 pub use mock_behavior::*;
 pub use mock_signaling::*;
+#[cfg(feature = "quic")]
+pub use quic_client::*;
+pub use relay_behavior::*;
 pub use signal_helpers::*;
 pub use test_client::*;
+pub use ws_client::*;
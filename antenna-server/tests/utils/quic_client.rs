@@ -0,0 +1,169 @@
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use std::net::SocketAddr;
+use wtransport::error::ConnectionError;
+use wtransport::tls::Sha256Digest;
+use wtransport::{ClientConfig, Connection, Endpoint, RecvStream, SendStream};
+
+use antenna_core::{PROTOCOL_VERSION, PeerId, SUPPORTED_FEATURES, SignalMessage};
+
+/// Native WebTransport client speaking the `QuicServer` protocol.
+pub struct QuicTestClient {
+    /// The peer ID assigned by the server in `Welcome`.
+    pub peer_id: PeerId,
+    connection: Connection,
+    control_send: SendStream,
+    control_recv: RecvStream,
+}
+
+impl QuicTestClient {
+    /// Connect to the server, trusting its self-signed certificate by hash, and join `room`.
+    pub async fn connect(
+        addr: SocketAddr,
+        certificate_hashes: &[Sha256Digest],
+        room: &str,
+    ) -> Result<Self> {
+        let join = SignalMessage::Join {
+            room: room.to_string(),
+            version: PROTOCOL_VERSION,
+            features: SUPPORTED_FEATURES.iter().map(|f| f.to_string()).collect(),
+            schema: None,
+            metadata: Default::default(),
+        };
+        let (connection, control_send, control_recv, reply) =
+            handshake(addr, certificate_hashes, &join).await?;
+
+        let peer_id = match reply {
+            SignalMessage::Welcome { peer_id, .. } => peer_id,
+            other => anyhow::bail!("Expected Welcome, got {:?}", other),
+        };
+
+        Ok(Self {
+            peer_id,
+            connection,
+            control_send,
+            control_recv,
+        })
+    }
+
+    /// Send an arbitrary `join` message and return the server's first reply.
+    pub async fn join_raw(
+        addr: SocketAddr,
+        certificate_hashes: &[Sha256Digest],
+        join: &SignalMessage,
+    ) -> Result<SignalMessage> {
+        let (_, _, _, reply) = handshake(addr, certificate_hashes, join).await?;
+        Ok(reply)
+    }
+
+    /// Send a reliable, ordered message over the control stream.
+    pub async fn send_reliable(&mut self, data: &[u8]) -> Result<()> {
+        write_frame(&mut self.control_send, data).await
+    }
+
+    /// Send a reliable message on its own unidirectional stream.
+    pub async fn send_unordered(&self, data: &[u8]) -> Result<()> {
+        let mut stream = self.connection.open_uni().await?.await?;
+        stream.write_all(data).await?;
+        stream.finish().await?;
+        Ok(())
+    }
+
+    /// Send an unreliable datagram.
+    pub fn send_datagram(&self, data: &[u8]) -> Result<()> {
+        self.connection.send_datagram(data)?;
+        Ok(())
+    }
+
+    /// Receive the next reliable message from the server.
+    pub async fn recv_reliable(&mut self) -> Result<Bytes> {
+        read_frame(&mut self.control_recv).await
+    }
+
+    /// Wait until the server closes the connection.
+    pub async fn closed(&self) -> ConnectionError {
+        self.connection.closed().await
+    }
+
+    /// Close the connection.
+    pub fn close(&self) {
+        self.connection.close(0u32.into(), b"bye");
+    }
+}
+
+/// A session that sent its `Join` and waits for the room's admission.
+pub struct QuicJoin {
+    _connection: Connection,
+    _control_send: SendStream,
+    control_recv: RecvStream,
+}
+
+impl QuicJoin {
+    pub async fn start(
+        addr: SocketAddr,
+        certificate_hashes: &[Sha256Digest],
+        join: &SignalMessage,
+    ) -> Result<Self> {
+        let (connection, control_send, control_recv) = open(addr, certificate_hashes, join).await?;
+        Ok(Self {
+            _connection: connection,
+            _control_send: control_send,
+            control_recv,
+        })
+    }
+
+    /// The next JSON signal on the control stream.
+    pub async fn next_signal(&mut self) -> Result<SignalMessage> {
+        let frame = read_frame(&mut self.control_recv).await?;
+        Ok(serde_json::from_slice(&frame)?)
+    }
+}
+
+async fn handshake(
+    addr: SocketAddr,
+    certificate_hashes: &[Sha256Digest],
+    join: &SignalMessage,
+) -> Result<(Connection, SendStream, RecvStream, SignalMessage)> {
+    let (connection, control_send, mut control_recv) = open(addr, certificate_hashes, join).await?;
+
+    let reply = read_frame(&mut control_recv).await?;
+    let reply = serde_json::from_slice::<SignalMessage>(&reply)?;
+
+    Ok((connection, control_send, control_recv, reply))
+}
+
+async fn open(
+    addr: SocketAddr,
+    certificate_hashes: &[Sha256Digest],
+    join: &SignalMessage,
+) -> Result<(Connection, SendStream, RecvStream)> {
+    let config = ClientConfig::builder()
+        .with_bind_default()
+        .with_server_certificate_hashes(certificate_hashes.to_vec())
+        .build();
+
+    let connection = Endpoint::client(config)?
+        .connect(format!("https://{}", addr))
+        .await
+        .context("Failed to connect")?;
+
+    let (mut control_send, control_recv) = connection.open_bi().await?.await?;
+
+    write_frame(&mut control_send, &serde_json::to_vec(join)?).await?;
+
+    Ok((connection, control_send, control_recv))
+}
+
+async fn write_frame(stream: &mut SendStream, data: &[u8]) -> Result<()> {
+    stream.write_all(&(data.len() as u32).to_be_bytes()).await?;
+    stream.write_all(data).await?;
+    Ok(())
+}
+
+async fn read_frame(stream: &mut RecvStream) -> Result<Bytes> {
+    let mut len = [0u8; 4];
+    stream.read_exact(&mut len).await?;
+    let mut data = vec![0u8; u32::from_be_bytes(len) as usize];
+    stream.read_exact(&mut data).await?;
+    Ok(Bytes::from(data))
+}
@@ -0,0 +1,50 @@
+use antenna_core::{Channel, PeerId};
+use antenna_server::{RoomBehavior, RoomContext, SendError};
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::sync::mpsc;
+
+/// A RoomBehavior that sends every received message back on a fixed channel and reports
+/// the outcome of each send.
+pub struct RelayBehavior {
+    channel: Channel,
+    joins: mpsc::UnboundedSender<PeerId>,
+    results: mpsc::UnboundedSender<Result<(), SendError>>,
+}
+
+/// Observations of a running RelayBehavior.
+pub struct RelayOutput {
+    pub joins: mpsc::UnboundedReceiver<PeerId>,
+    pub results: mpsc::UnboundedReceiver<Result<(), SendError>>,
+}
+
+impl RelayBehavior {
+    pub fn new(channel: Channel) -> (Self, RelayOutput) {
+        let (joins, joins_rx) = mpsc::unbounded_channel();
+        let (results, results_rx) = mpsc::unbounded_channel();
+        let behavior = Self {
+            channel,
+            joins,
+            results,
+        };
+        let output = RelayOutput {
+            joins: joins_rx,
+            results: results_rx,
+        };
+        (behavior, output)
+    }
+}
+
+#[async_trait]
+impl RoomBehavior for RelayBehavior {
+    async fn on_join(&self, _ctx: &RoomContext, peer_id: PeerId) {
+        let _ = self.joins.send(peer_id);
+    }
+
+    async fn on_message(&self, ctx: &RoomContext, peer_id: PeerId, data: Bytes) {
+        let result = ctx.send_on_channel(&peer_id, self.channel, data).await;
+        let _ = self.results.send(result);
+    }
+
+    async fn on_leave(&self, _ctx: &RoomContext, _peer_id: PeerId) {}
+}
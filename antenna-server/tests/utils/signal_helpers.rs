@@ -1,8 +1,8 @@
 use anyhow::{Context, Result};
 use tokio::sync::mpsc;
 
-use antenna_core::PeerId;
-use antenna_server::RoomCommand;
+use antenna_core::{PeerId, SUPPORTED_FEATURES};
+use antenna_server::{MemoryClient, MemoryTransportFactory, RoomCommand};
 
 use super::test_client::TestClient;
 use antenna_core::SignalMessage;
@@ -19,6 +19,11 @@ pub const CONNECTION_TIMEOUT_MS: u64 = 10000;
 /// Timeout for data channel opening (ms).
 pub const DATA_CHANNEL_TIMEOUT_MS: u64 = 5000;
 
+/// Every optional protocol feature, as negotiated by a current client.
+pub fn all_features() -> Vec<String> {
+    SUPPORTED_FEATURES.iter().map(|f| f.to_string()).collect()
+}
+
 /// Helper to perform full signaling exchange between a TestClient and Room.
 pub async fn perform_signaling(
     client: &TestClient,
@@ -37,6 +42,8 @@ pub async fn perform_signaling(
         .send(RoomCommand::JoinRequest {
             peer_id: peer_id.clone(),
             offer,
+            metadata: Default::default(),
+            features: all_features(),
         })
         .await
         .context("Failed to send JoinRequest")?;
@@ -72,7 +79,7 @@ async fn wait_for_answer(
             tokio::time::timeout(std::time::Duration::from_millis(100), signal_rx.recv());
 
         match recv_timeout.await {
-            Ok(Some(SignalMessage::Answer { sdp })) => {
+            Ok(Some(SignalMessage::Answer { sdp, .. })) => {
                 return Ok(sdp);
             }
             Ok(Some(SignalMessage::IceCandidate { .. })) => {
@@ -121,7 +128,7 @@ async fn exchange_ice_candidates(
             tokio::time::timeout(std::time::Duration::from_millis(100), signal_rx.recv());
 
         match recv_timeout.await {
-            Ok(Some(SignalMessage::IceCandidate { candidate })) => {
+            Ok(Some(SignalMessage::IceCandidate { candidate, .. })) => {
                 if let Err(e) = client.add_ice_candidate(candidate).await {
                     tracing::warn!("[SignalHelper] Failed to add ICE candidate: {}", e);
                 }
@@ -149,3 +156,24 @@ pub async fn wait_for_client_ready(client: &TestClient) -> Result<()> {
 
     Ok(())
 }
+
+/// Join a new peer to a room running on the in-memory transport and return its client side.
+pub async fn join_memory_peer(
+    room_cmd_tx: &mpsc::Sender<RoomCommand>,
+    transport: &MemoryTransportFactory,
+) -> Result<MemoryClient> {
+    room_cmd_tx
+        .send(RoomCommand::JoinRequest {
+            peer_id: PeerId::new(),
+            offer: String::new(),
+            metadata: Default::default(),
+            features: all_features(),
+        })
+        .await
+        .context("Failed to send JoinRequest")?;
+
+    transport
+        .accept()
+        .await
+        .context("Memory transport closed before peer was accepted")
+}
@@ -1,6 +1,7 @@
 use anyhow::{Context, Result};
 use bytes::Bytes;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
 use tokio::sync::{Mutex, mpsc};
 use webrtc::api::APIBuilder;
 use webrtc::api::interceptor_registry::register_default_interceptors;
@@ -13,7 +14,7 @@ use webrtc::peer_connection::RTCPeerConnection;
 use webrtc::peer_connection::configuration::RTCConfiguration;
 use webrtc::peer_connection::peer_connection_state::RTCPeerConnectionState;
 
-use antenna_core::PeerId;
+use antenna_core::{DEFAULT_FRAGMENT_SIZE, PeerId, split_frames};
 
 /// Configuration for TestClient.
 #[derive(Clone, Default)]
@@ -38,6 +39,8 @@ pub struct TestClient {
     connection_state: Arc<Mutex<RTCPeerConnectionState>>,
     /// Generated ICE candidates (to be sent to the server).
     ice_candidates: Arc<Mutex<Vec<String>>>,
+    /// Id of the next outgoing message, used by the framing header.
+    next_message_id: AtomicU32,
 }
 
 impl TestClient {
@@ -145,6 +148,7 @@ impl TestClient {
             dc_open_rx: Arc::new(Mutex::new(dc_open_rx)),
             connection_state,
             ice_candidates,
+            next_message_id: AtomicU32::new(0),
         })
     }
 
@@ -284,7 +288,7 @@ impl TestClient {
         }
     }
 
-    /// Send a binary message through the data channel.
+    /// Send a binary message through the data channel, fragmenting it like the wasm engine.
     pub async fn send_message(&self, data: &[u8]) -> Result<()> {
         let dc = self
             .data_channel
@@ -293,9 +297,12 @@ impl TestClient {
             .clone()
             .context("Data channel not available")?;
 
-        dc.send(&Bytes::from(data.to_vec()))
-            .await
-            .context("Failed to send message")?;
+        let message_id = self.next_message_id.fetch_add(1, Ordering::Relaxed);
+        for frame in split_frames(message_id, data, DEFAULT_FRAGMENT_SIZE) {
+            dc.send(&Bytes::from(frame))
+                .await
+                .context("Failed to send message")?;
+        }
 
         Ok(())
     }
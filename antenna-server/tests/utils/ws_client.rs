@@ -0,0 +1,85 @@
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use futures::{SinkExt, StreamExt};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::error::{Error as WsError, ProtocolError};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, connect_async};
+
+use antenna_core::{PeerId, SignalMessage};
+use antenna_server::{AppState, ws_axum_handler};
+
+/// Serves `ws_axum_handler` on `/ws/{peer_id}` of a local port.
+pub async fn serve_ws(state: Arc<AppState>) -> Result<SocketAddr> {
+    let app = axum::Router::new()
+        .route("/ws/{peer_id}", axum::routing::get(ws_axum_handler))
+        .with_state(state);
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    Ok(addr)
+}
+
+/// Signaling client speaking JSON over a WebSocket, like the browser engine.
+pub struct WsTestClient {
+    stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
+}
+
+impl WsTestClient {
+    pub async fn connect(addr: SocketAddr, peer_id: &PeerId) -> Result<Self> {
+        let (stream, _) = connect_async(format!("ws://{}/ws/{}", addr, peer_id))
+            .await
+            .context("Failed to connect")?;
+        Ok(Self { stream })
+    }
+
+    pub async fn send(&mut self, msg: &SignalMessage) -> Result<()> {
+        let text = serde_json::to_string(msg)?;
+        self.stream.send(Message::text(text)).await?;
+        Ok(())
+    }
+
+    /// Sends a binary frame of the WebSocket transport.
+    pub async fn send_binary(&mut self, frame: Vec<u8>) -> Result<()> {
+        self.stream.send(Message::binary(frame)).await?;
+        Ok(())
+    }
+
+    /// The next binary frame, skipping signals.
+    pub async fn recv_binary(&mut self, timeout: Duration) -> Result<Bytes> {
+        loop {
+            let msg = tokio::time::timeout(timeout, self.stream.next())
+                .await
+                .context("Timeout waiting for a frame")?
+                .context("Connection closed")??;
+            if let Message::Binary(frame) = msg {
+                return Ok(frame);
+            }
+        }
+    }
+
+    /// The next signal, skipping `IceConfig`; `None` once the server closed the connection.
+    pub async fn recv(&mut self, timeout: Duration) -> Result<Option<SignalMessage>> {
+        loop {
+            let msg = tokio::time::timeout(timeout, self.stream.next())
+                .await
+                .context("Timeout waiting for a signal")?;
+            match msg {
+                Some(Ok(Message::Text(text))) => match serde_json::from_str(&text)? {
+                    SignalMessage::IceConfig { .. } => continue,
+                    signal => return Ok(Some(signal)),
+                },
+                Some(Ok(Message::Close(_))) | None => return Ok(None),
+                // The handler drops rejected sockets without a closing handshake.
+                Some(Err(WsError::Protocol(ProtocolError::ResetWithoutClosingHandshake))) => {
+                    return Ok(None);
+                }
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => return Err(e.into()),
+            }
+        }
+    }
+}
@@ -7,7 +7,7 @@ use wasm_bindgen::prelude::Closure;
 use wasm_bindgen::prelude::*;
 
 use crate::AntennaEngine;
-use crate::engine::EngineService;
+use crate::engine::{AntennaError, EngineService};
 use crate::logger::Logger;
 
 impl<T, E> AntennaEngine<T, E>
@@ -17,7 +17,8 @@ where
 {
     pub(super) fn create_pc(
         service: &Rc<RefCell<EngineService>>,
-    ) -> Result<web_sys::RtcPeerConnection, JsValue> {
+        room: &str,
+    ) -> Result<web_sys::RtcPeerConnection, AntennaError> {
         let rtc_config = web_sys::RtcConfiguration::new();
         let ice_servers_arr = js_sys::Array::new();
 
@@ -46,13 +47,16 @@ where
 
         rtc_config.set_ice_servers(&ice_servers_arr);
 
-        let pc = web_sys::RtcPeerConnection::new_with_configuration(&rtc_config)?;
+        let pc = web_sys::RtcPeerConnection::new_with_configuration(&rtc_config)
+            .map_err(AntennaError::peer_connection)?;
 
         let service_for_ice = service.clone();
+        let room_for_ice = room.to_string();
         let onice = Closure::wrap(Box::new(move |ev: web_sys::RtcPeerConnectionIceEvent| {
             if let Some(candidate) = ev.candidate() {
                 let msg = SignalMessage::IceCandidate {
                     candidate: candidate.candidate(),
+                    room: Some(room_for_ice.clone()),
                 };
                 if let Ok(json) = serde_json::to_string(&msg)
                     && let Some(ws) = &service_for_ice.borrow().ws
@@ -67,6 +71,7 @@ where
         onice.forget();
         let pc_clone = pc.clone();
         let service_clone = service.clone();
+        let room_clone = room.to_string();
 
         let oniceconnectionstatechange = Closure::wrap(Box::new(move || {
             let state = pc_clone.ice_connection_state();
@@ -80,59 +85,31 @@ where
                 return;
             }
 
+            let restart_attempted = match service_clone.borrow_mut().rooms.get_mut(&room_clone) {
+                Some(connection) => std::mem::replace(&mut connection.ice_restart_attempted, true),
+                // The room was left meanwhile
+                None => return,
+            };
+
+            if restart_attempted {
+                Logger::error(&JsValue::from_str(
+                    "ICE Connection Failed after restart! Switching to WebSocket.",
+                ));
+                Self::fall_back_to_websocket(&service_clone, &room_clone);
+                return;
+            }
+
             Logger::error(&JsValue::from_str(
                 "ICE Connection Failed! Need to restart ICE.",
             ));
 
             let service = service_clone.clone();
+            let room = room_clone.clone();
             let pc = pc_clone.clone();
 
             wasm_bindgen_futures::spawn_local(async move {
-                Logger::info("Attempting ICE restart...");
-
-                let options = web_sys::RtcOfferOptions::new();
-                options.set_ice_restart(true);
-
-                let offer_val = match wasm_bindgen_futures::JsFuture::from(
-                    pc.create_offer_with_rtc_offer_options(&options),
-                )
-                .await
-                {
-                    Ok(v) => v,
-                    Err(e) => {
-                        Logger::error(&e);
-                        return;
-                    }
-                };
-
-                let offer: web_sys::RtcSessionDescription = offer_val.unchecked_into();
-                let offer_sdp = offer.sdp();
-
-                let desc = web_sys::RtcSessionDescriptionInit::new(web_sys::RtcSdpType::Offer);
-                desc.set_sdp(&offer_sdp);
-
-                if let Err(e) =
-                    wasm_bindgen_futures::JsFuture::from(pc.set_local_description(&desc)).await
-                {
-                    Logger::error(&e);
-                    return;
-                }
-
-                Logger::info("Sending ICE restart OFFER to server...");
-
-                let msg = SignalMessage::Offer { sdp: offer_sdp };
-                let json = match serde_json::to_string(&msg) {
-                    Ok(j) => j,
-                    Err(e) => {
-                        Logger::error(&JsValue::from_str(&format!("JSON error: {}", e)));
-                        return;
-                    }
-                };
-
-                if let Some(ws) = &service.borrow().ws
-                    && let Err(e) = ws.send_with_str(&json)
-                {
-                    Logger::error(&e);
+                if let Err(e) = Self::restart_ice(&service, &room, &pc).await {
+                    Self::recover(&service, &room, e);
                 }
             });
         }) as Box<dyn FnMut()>);
@@ -152,9 +129,10 @@ where
         onconnectionstatechange.forget();
 
         let service_clone = service.clone();
+        let room_clone = room.to_string();
         let ontrack = Closure::wrap(Box::new(move |ev: web_sys::RtcTrackEvent| {
             if let Some(cb) = &service_clone.borrow().track_callback {
-                let _ = cb.call1(&JsValue::NULL, &ev);
+                let _ = cb.call2(&JsValue::NULL, &ev, &JsValue::from_str(&room_clone));
             }
         }) as Box<dyn FnMut(web_sys::RtcTrackEvent)>);
 
@@ -163,4 +141,37 @@ where
 
         Ok(pc)
     }
+
+    /// Renegotiates with `iceRestart` after the ICE connection failed once.
+    async fn restart_ice(
+        service: &Rc<RefCell<EngineService>>,
+        room: &str,
+        pc: &web_sys::RtcPeerConnection,
+    ) -> Result<(), AntennaError> {
+        Logger::info("Attempting ICE restart...");
+
+        let options = web_sys::RtcOfferOptions::new();
+        options.set_ice_restart(true);
+
+        let offer_val =
+            wasm_bindgen_futures::JsFuture::from(pc.create_offer_with_rtc_offer_options(&options))
+                .await
+                .map_err(AntennaError::negotiation)?;
+
+        let offer: web_sys::RtcSessionDescription = offer_val.unchecked_into();
+        let offer_sdp = offer.sdp();
+
+        let desc = web_sys::RtcSessionDescriptionInit::new(web_sys::RtcSdpType::Offer);
+        desc.set_sdp(&offer_sdp);
+
+        wasm_bindgen_futures::JsFuture::from(pc.set_local_description(&desc))
+            .await
+            .map_err(AntennaError::negotiation)?;
+
+        Logger::info("Sending ICE restart OFFER to server...");
+        service.borrow().send_signal(&SignalMessage::Offer {
+            sdp: offer_sdp,
+            room: Some(room.to_string()),
+        })
+    }
 }
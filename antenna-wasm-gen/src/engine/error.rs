@@ -0,0 +1,189 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use antenna_core::{ErrorCode, JoinRejection, RejectReason};
+use serde::Serialize;
+use wasm_bindgen::JsValue;
+
+use crate::engine::EngineService;
+use crate::logger::Logger;
+
+/// Everything that can go wrong inside the engine. Passed to the `on_error` callback as
+/// `{ kind, description, fatal, room, ...fields }`.
+#[derive(Debug, Clone, PartialEq, Serialize, thiserror::Error)]
+#[serde(tag = "kind")]
+pub enum AntennaError {
+    /// The browser refused to create an `RTCPeerConnection`.
+    #[error("failed to create peer connection: {message}")]
+    PeerConnection { message: String },
+    /// Creating or applying an SDP offer/answer failed.
+    #[error("SDP negotiation failed: {message}")]
+    Negotiation { message: String },
+    /// The signaling WebSocket could not be opened or written to.
+    #[error("signaling failed: {message}")]
+    Signaling { message: String },
+    /// A message could not be encoded.
+    #[error("serialization failed: {message}")]
+    Serialization { message: String },
+    /// A message exceeds `SendBufferConfig::max_message_size`.
+    #[error("message of {size} bytes exceeds the maximum message size of {max}")]
+    MessageTooLarge { size: usize, max: usize },
+    /// A reliable message does not fit into the send buffer.
+    #[error("send buffer is full")]
+    SendBufferFull,
+    /// The data channel or WebSocket refused a message.
+    #[error("transport failed: {message}")]
+    Transport { message: String },
+    /// The server reported an error via `SignalMessage::Error`.
+    #[error("server error ({code}): {message}")]
+    Server {
+        code: ErrorCode,
+        message: String,
+        #[serde(skip)]
+        fatal: bool,
+    },
+    /// The server did not enable the protocol feature an operation needs, see
+    /// `AntennaEngine::features`.
+    #[error("the server did not enable `{feature}`")]
+    Unsupported { feature: String },
+    /// A message was sent to a room the engine has not joined.
+    #[error("room '{room}' is not joined")]
+    NotJoined { room: String },
+    /// A shared state patch did not fit the local copy; it is dropped until the next snapshot.
+    #[error("shared state out of sync: {message}")]
+    StateDesync { message: String },
+    /// The server ended the session, e.g. because it is shutting down.
+    #[error("the server closed the session")]
+    Closed,
+    /// The room runs on another node of the cluster, but the engine cannot follow the redirect
+    /// because it takes part in other rooms. Join the room with a separate engine connected to `url`.
+    #[error("room '{room}' runs on another node: {url}")]
+    Redirected { room: String, url: String },
+    /// The room refused this client, e.g. because it is full.
+    #[error("join rejected by the room: {reason}")]
+    JoinRejected { reason: JoinRejection },
+    /// The server refused the join handshake.
+    #[error("join rejected: {reason}")]
+    Rejected { reason: RejectReason },
+}
+
+impl AntennaError {
+    pub(crate) fn peer_connection(err: JsValue) -> Self {
+        AntennaError::PeerConnection {
+            message: js_message(&err),
+        }
+    }
+
+    pub(crate) fn negotiation(err: JsValue) -> Self {
+        AntennaError::Negotiation {
+            message: js_message(&err),
+        }
+    }
+
+    pub(crate) fn signaling(err: JsValue) -> Self {
+        AntennaError::Signaling {
+            message: js_message(&err),
+        }
+    }
+
+    pub(crate) fn transport(err: JsValue) -> Self {
+        AntennaError::Transport {
+            message: js_message(&err),
+        }
+    }
+
+    pub(crate) fn unsupported(feature: &str) -> Self {
+        AntennaError::Unsupported {
+            feature: feature.to_string(),
+        }
+    }
+
+    pub(crate) fn serialization(err: impl std::fmt::Display) -> Self {
+        AntennaError::Serialization {
+            message: err.to_string(),
+        }
+    }
+
+    /// Fatal errors leave the affected room, or the whole engine, `Disconnected`. Failed WebRTC negotiation is not fatal,
+    /// the engine continues over the WebSocket transport.
+    pub fn is_fatal(&self) -> bool {
+        match self {
+            AntennaError::Server { fatal, .. } => *fatal,
+            AntennaError::Signaling { .. }
+            | AntennaError::Closed
+            | AntennaError::Redirected { .. }
+            | AntennaError::Rejected { .. }
+            | AntennaError::JoinRejected { .. } => true,
+            AntennaError::PeerConnection { .. }
+            | AntennaError::Negotiation { .. }
+            | AntennaError::Serialization { .. }
+            | AntennaError::MessageTooLarge { .. }
+            | AntennaError::SendBufferFull
+            | AntennaError::NotJoined { .. }
+            | AntennaError::Unsupported { .. }
+            | AntennaError::StateDesync { .. }
+            | AntennaError::Transport { .. } => false,
+        }
+    }
+}
+
+impl From<AntennaError> for JsValue {
+    fn from(err: AntennaError) -> Self {
+        js_sys::Error::new(&err.to_string()).into()
+    }
+}
+
+fn js_message(err: &JsValue) -> String {
+    if let Some(text) = err.as_string() {
+        return text;
+    }
+    if let Some(error) = wasm_bindgen::JsCast::dyn_ref::<js_sys::Error>(err) {
+        return error.message().into();
+    }
+    format!("{:?}", err)
+}
+
+#[derive(Serialize)]
+struct ErrorEvent<'a> {
+    #[serde(flatten)]
+    error: &'a AntennaError,
+    description: String,
+    fatal: bool,
+    room: Option<&'a str>,
+}
+
+impl EngineService {
+    /// Logs `error`, tears down `room` (or every room and the WebSocket when `None`) if the error
+    /// is fatal and notifies `on_error`.
+    pub(super) fn report_error(
+        service: &Rc<RefCell<EngineService>>,
+        room: Option<&str>,
+        error: AntennaError,
+    ) {
+        Logger::error(&JsValue::from_str(&error.to_string()));
+
+        let fatal = error.is_fatal();
+        if fatal {
+            let mut service = service.borrow_mut();
+            match room {
+                Some(room) => service.close_room(room),
+                None => service.close_all(),
+            }
+        }
+
+        let handler = service.borrow().error_handler.clone();
+        let event = ErrorEvent {
+            error: &error,
+            description: error.to_string(),
+            fatal,
+            room,
+        };
+        if let Some(cb) = handler
+            && let Ok(js_val) = serde_wasm_bindgen::to_value(&event)
+        {
+            let _ = cb.call1(&JsValue::NULL, &js_val);
+        }
+
+        service.borrow_mut().last_error = Some(error);
+    }
+}
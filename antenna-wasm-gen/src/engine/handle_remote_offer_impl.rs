@@ -1,5 +1,5 @@
 use crate::AntennaEngine;
-use crate::engine::EngineService;
+use crate::engine::{AntennaError, EngineService};
 use crate::logger::Logger;
 use antenna_core::Message;
 
@@ -16,16 +16,25 @@ where
 {
     pub(super) async fn handle_remote_offer(
         service: Rc<RefCell<EngineService>>,
+        room: String,
         remote_sdp: String,
-    ) {
-        let pc = Self::create_pc(&service).expect("Failed to create PC");
+    ) -> Result<(), AntennaError> {
+        let pc = Self::create_pc(&service, &room)?;
+        match service.borrow_mut().rooms.get_mut(&room) {
+            Some(connection) => connection.pc = Some(pc.clone()),
+            None => {
+                pc.close();
+                return Ok(());
+            }
+        }
 
         let service_clone = service.clone();
+        let room_clone = room.clone();
         let ondatachannel_callback =
             Closure::wrap(Box::new(move |ev: web_sys::RtcDataChannelEvent| {
                 let dc = ev.channel();
                 Logger::info(&format!("Received DataChannel: {}", dc.label()));
-                Self::setup_data_channel(&service_clone, dc);
+                Self::setup_data_channel(&service_clone, &room_clone, dc);
             })
                 as Box<dyn FnMut(web_sys::RtcDataChannelEvent)>);
         pc.set_ondatachannel(Some(ondatachannel_callback.as_ref().unchecked_ref()));
@@ -35,30 +44,30 @@ where
         desc_init.set_sdp(&remote_sdp);
         wasm_bindgen_futures::JsFuture::from(pc.set_remote_description(&desc_init))
             .await
-            .unwrap();
+            .map_err(AntennaError::negotiation)?;
 
         let answer = wasm_bindgen_futures::JsFuture::from(pc.create_answer())
             .await
-            .unwrap();
+            .map_err(AntennaError::negotiation)?;
         let answer_sdp = js_sys::Reflect::get(&answer, &"sdp".into())
-            .unwrap()
+            .map_err(AntennaError::negotiation)?
             .as_string()
-            .unwrap();
+            .ok_or_else(|| AntennaError::Negotiation {
+                message: "Answer has no SDP".to_string(),
+            })?;
 
         // Set Local
         let answer_init = web_sys::RtcSessionDescriptionInit::new(web_sys::RtcSdpType::Answer);
         answer_init.set_sdp(&answer_sdp);
         wasm_bindgen_futures::JsFuture::from(pc.set_local_description(&answer_init))
             .await
-            .unwrap();
+            .map_err(AntennaError::negotiation)?;
 
         Logger::info("Sending ANSWER to server...");
-        let msg = SignalMessage::Answer { sdp: answer_sdp };
-        let json = serde_json::to_string(&msg).unwrap();
-
-        service.borrow_mut().pc = Some(pc);
-        if let Some(ws) = &service.borrow().ws {
-            ws.send_with_str(&json).unwrap();
-        }
+        let msg = SignalMessage::Answer {
+            sdp: answer_sdp,
+            room: Some(room),
+        };
+        service.borrow().send_signal(&msg)
     }
 }
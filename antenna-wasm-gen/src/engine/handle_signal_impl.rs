@@ -4,9 +4,12 @@ use std::rc::Rc;
 use antenna_core::{Message, SignalMessage};
 
 use crate::AntennaEngine;
-use crate::engine::{EngineService, IcePayload};
+use crate::engine::{AntennaError, EngineService, IcePayload, TransportMode};
 use crate::logger::Logger;
 
+/// Upper bound of consecutive `Redirect`s, guards against directories pointing back and forth.
+const MAX_REDIRECTS: u32 = 3;
+
 impl<T, E> AntennaEngine<T, E>
 where
     T: Message,
@@ -33,24 +36,128 @@ where
                 service.borrow_mut().ice_servers = Some(ice_servers);
             }
 
-            SignalMessage::Welcome { .. } => {
+            SignalMessage::Welcome {
+                room,
+                version,
+                features,
+                ..
+            } => {
+                let room = service.borrow().resolve_room(room);
+                service.borrow_mut().redirects = 0;
+                Logger::info(&format!(
+                    "Joined room '{}', server speaks protocol v{} with features {:?}",
+                    room, version, features
+                ));
+                service.borrow_mut().features = features;
+
+                if service.borrow().transport_mode == TransportMode::WebSocketOnly {
+                    Logger::info("Received Welcome. Using WebSocket transport...");
+                    Self::fall_back_to_websocket(&service, &room);
+                    return;
+                }
+
                 Logger::info("Received Welcome. Initiating connection...");
                 wasm_bindgen_futures::spawn_local(async move {
-                    Self::init_connection(service).await;
+                    if let Err(e) = Self::init_connection(service.clone(), room.clone()).await {
+                        Self::recover(&service, &room, e);
+                    }
                 });
             }
 
-            SignalMessage::Offer { sdp } => {
-                Logger::info("Received Offer from Server");
+            SignalMessage::Reject { reason } => {
+                service.borrow_mut().rejection = Some(reason.clone());
+                EngineService::report_error(&service, None, AntennaError::Rejected { reason });
+            }
+
+            SignalMessage::JoinRejected { reason, room } => {
+                let room = service.borrow().resolve_room(room);
+                EngineService::report_error(
+                    &service,
+                    Some(&room),
+                    AntennaError::JoinRejected { reason },
+                );
+            }
+
+            SignalMessage::WaitListed { position, room } => {
+                let room = service.borrow().resolve_room(room);
+                Logger::info(&format!(
+                    "Room '{}' is full, waiting at position {}",
+                    room, position
+                ));
+                if let Some(connection) = service.borrow_mut().rooms.get_mut(&room) {
+                    connection.wait_position = Some(position);
+                }
+            }
+
+            SignalMessage::Admitted { room } => {
+                let room = service.borrow().resolve_room(room);
+                Logger::info(&format!("Admitted to room '{}'", room));
+                let on_websocket = match service.borrow_mut().rooms.get_mut(&room) {
+                    Some(connection) => {
+                        connection.wait_position = None;
+                        connection.ws_fallback
+                    }
+                    None => false,
+                };
+                if on_websocket {
+                    EngineService::room_connected(&service, &room);
+                }
+            }
+
+            SignalMessage::MatchFound { queue, room } => {
+                Self::match_found(&service, queue, room);
+            }
+
+            SignalMessage::Redirect { url, room } => {
+                let room = service.borrow().resolve_room(room);
+                Self::follow_redirect(&service, room, url);
+            }
+
+            SignalMessage::Error {
+                code,
+                message,
+                fatal,
+                room,
+            } => {
+                EngineService::report_error(
+                    &service,
+                    room.as_deref(),
+                    AntennaError::Server {
+                        code,
+                        message,
+                        fatal,
+                    },
+                );
+            }
+
+            SignalMessage::Leave { room } => {
+                EngineService::report_error(&service, room.as_deref(), AntennaError::Closed);
+            }
+
+            SignalMessage::Offer { sdp, room } => {
+                let room = service.borrow().resolve_room(room);
+                Logger::info(&format!("Received Offer for room '{}' from Server", room));
                 wasm_bindgen_futures::spawn_local(async move {
-                    Self::handle_remote_offer(service, sdp).await;
+                    if let Err(e) =
+                        Self::handle_remote_offer(service.clone(), room.clone(), sdp).await
+                    {
+                        Self::recover(&service, &room, e);
+                    }
                 });
             }
 
-            SignalMessage::Answer { sdp } => {
-                Logger::info("Received Answer from Server");
+            SignalMessage::Answer { sdp, room } => {
+                let room = service.borrow().resolve_room(room);
+                Logger::info(&format!("Received Answer for room '{}' from Server", room));
+                if let Some(connection) = service.borrow_mut().rooms.get_mut(&room) {
+                    connection.wait_position = None;
+                }
                 wasm_bindgen_futures::spawn_local(async move {
-                    let pc = service.borrow().pc.clone();
+                    let pc = service
+                        .borrow()
+                        .rooms
+                        .get(&room)
+                        .and_then(|connection| connection.pc.clone());
                     if let Some(pc) = pc {
                         let desc =
                             web_sys::RtcSessionDescriptionInit::new(web_sys::RtcSdpType::Answer);
@@ -59,7 +166,7 @@ where
                             wasm_bindgen_futures::JsFuture::from(pc.set_remote_description(&desc))
                                 .await
                         {
-                            Logger::error(&e);
+                            Self::recover(&service, &room, AntennaError::negotiation(e));
                         } else {
                             Logger::info("Remote description set (Answer)");
                         }
@@ -67,8 +174,14 @@ where
                 });
             }
 
-            SignalMessage::IceCandidate { candidate } => {
-                if let Some(pc) = service.borrow().pc.clone() {
+            SignalMessage::IceCandidate { candidate, room } => {
+                let room = service.borrow().resolve_room(room);
+                let pc = service
+                    .borrow()
+                    .rooms
+                    .get(&room)
+                    .and_then(|connection| connection.pc.clone());
+                if let Some(pc) = pc {
                     let (real_candidate, real_mid, real_idx) = if candidate.trim().starts_with('{')
                     {
                         match serde_json::from_str::<IcePayload>(&candidate) {
@@ -108,4 +221,52 @@ where
             _ => {}
         }
     }
+
+    /// Reconnects to the node running `room`. Only possible while `room` is the only room of the
+    /// engine, since the WebSocket is shared by all rooms.
+    fn follow_redirect(service: &Rc<RefCell<EngineService>>, room: String, url: String) {
+        let only_room = {
+            let service = service.borrow();
+            service.rooms.len() == 1 && service.rooms.contains_key(&room)
+        };
+        if !only_room {
+            EngineService::report_error(
+                service,
+                Some(&room),
+                AntennaError::Redirected {
+                    room: room.clone(),
+                    url,
+                },
+            );
+            return;
+        }
+
+        let redirects = {
+            let mut service = service.borrow_mut();
+            service.redirects += 1;
+            service.redirects
+        };
+        if redirects > MAX_REDIRECTS {
+            EngineService::report_error(
+                service,
+                None,
+                AntennaError::Signaling {
+                    message: format!("Too many redirects, last to {}", url),
+                },
+            );
+            return;
+        }
+
+        Logger::info(&format!(
+            "Room '{}' runs on another node, reconnecting to {}",
+            room, url
+        ));
+        if let Some(ws) = service.borrow_mut().ws.take() {
+            ws.set_onmessage(None);
+            let _ = ws.close();
+        }
+        if let Err(e) = Self::ws_setup(service, &url) {
+            EngineService::report_error(service, None, e);
+        }
+    }
 }
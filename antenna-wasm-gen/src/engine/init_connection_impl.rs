@@ -1,5 +1,5 @@
 use crate::AntennaEngine;
-use crate::engine::EngineService;
+use crate::engine::{AntennaError, EngineService};
 use crate::logger::Logger;
 use antenna_core::Message;
 use antenna_core::SignalMessage;
@@ -11,49 +11,57 @@ where
     T: Message,
     E: Message,
 {
-    pub(super) async fn init_connection(service: Rc<RefCell<EngineService>>) {
-        let pc = Self::create_pc(&service).expect("Failed to create PC");
+    pub(super) async fn init_connection(
+        service: Rc<RefCell<EngineService>>,
+        room: String,
+    ) -> Result<(), AntennaError> {
+        let pc = Self::create_pc(&service, &room)?;
+        // Stored right away so a failed negotiation below gets closed by the WebSocket fallback
+        match service.borrow_mut().rooms.get_mut(&room) {
+            Some(connection) => connection.pc = Some(pc.clone()),
+            None => {
+                pc.close();
+                return Ok(());
+            }
+        }
 
         let dc = pc.create_data_channel("chat");
-        Self::setup_data_channel(&service, dc);
+        Self::setup_data_channel(&service, &room, dc);
 
         let offer_promise = pc.create_offer();
         let offer_val = wasm_bindgen_futures::JsFuture::from(offer_promise)
             .await
-            .unwrap();
+            .map_err(AntennaError::negotiation)?;
         let offer_sdp = js_sys::Reflect::get(&offer_val, &"sdp".into())
-            .unwrap()
+            .map_err(AntennaError::negotiation)?
             .as_string()
-            .unwrap();
+            .ok_or_else(|| AntennaError::Negotiation {
+                message: "Offer has no SDP".to_string(),
+            })?;
 
         let desc = web_sys::RtcSessionDescriptionInit::new(web_sys::RtcSdpType::Offer);
         desc.set_sdp(&offer_sdp);
         wasm_bindgen_futures::JsFuture::from(pc.set_local_description(&desc))
             .await
-            .unwrap();
+            .map_err(AntennaError::negotiation)?;
 
-        Logger::info("Sending OFFER to server...");
-        let msg = SignalMessage::Offer { sdp: offer_sdp };
-        let json = serde_json::to_string(&msg).unwrap();
-
-        service.borrow_mut().pc = Some(pc);
-        if let Some(ws) = &service.borrow().ws {
-            ws.send_with_str(&json).unwrap();
-        }
+        Logger::info(&format!("Sending OFFER for room '{}' to server...", room));
+        let msg = SignalMessage::Offer {
+            sdp: offer_sdp,
+            room: Some(room.clone()),
+        };
+        service.borrow().send_signal(&msg)?;
 
         let service_clone = service.clone();
         wasm_bindgen_futures::spawn_local(async move {
             loop {
-                let promise = js_sys::Promise::new(&mut |resolve, _| {
-                    web_sys::window()
-                        .unwrap()
-                        .set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, 5000)
-                        .unwrap();
-                });
-                wasm_bindgen_futures::JsFuture::from(promise).await.unwrap();
+                if let Err(e) = sleep(5000).await {
+                    Logger::error(&e);
+                    break;
+                }
 
                 let service = service_clone.borrow();
-                if let Some(dc) = &service.dc {
+                if let Some(dc) = service.rooms.get(&room).and_then(|c| c.dc.as_ref()) {
                     if dc.ready_state() == web_sys::RtcDataChannelState::Open {
                         let ping: antenna_core::Packet<T> =
                             antenna_core::Packet::System(antenna_core::SystemMessage::Ping {
@@ -70,5 +78,18 @@ where
                 }
             }
         });
+
+        Ok(())
     }
 }
+
+async fn sleep(ms: i32) -> Result<(), wasm_bindgen::JsValue> {
+    let window = web_sys::window().ok_or("No window available")?;
+    let mut scheduled = Ok(0);
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        scheduled = window.set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, ms);
+    });
+    scheduled?;
+    wasm_bindgen_futures::JsFuture::from(promise).await?;
+    Ok(())
+}
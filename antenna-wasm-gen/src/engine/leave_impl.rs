@@ -0,0 +1,22 @@
+use antenna_core::{Message, SignalMessage};
+
+use crate::AntennaEngine;
+use crate::logger::Logger;
+
+impl<T, E> AntennaEngine<T, E>
+where
+    T: Message,
+    E: Message,
+{
+    /// Leaves every room: tells the server to drop this peer right away and closes the data
+    /// channels, the peer connections and the WebSocket. Queued messages are discarded.
+    pub fn leave(&self) {
+        let mut service = self.service.borrow_mut();
+        if let Err(e) = service.send_signal(&SignalMessage::Leave { room: None }) {
+            Logger::warn(&format!("Failed to send Leave: {}", e));
+        }
+
+        service.close_all();
+        Logger::info("Left the room");
+    }
+}
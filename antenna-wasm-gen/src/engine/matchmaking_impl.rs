@@ -0,0 +1,72 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use antenna_core::{Message, SignalMessage};
+use wasm_bindgen::JsValue;
+
+use crate::AntennaEngine;
+use crate::engine::{AntennaError, EngineService};
+use crate::logger::Logger;
+
+impl<T, E> AntennaEngine<T, E>
+where
+    T: Message,
+    E: Message,
+{
+    /// Reports a `MatchFound` to `on_match`.
+    pub(super) fn match_found(service: &Rc<RefCell<EngineService>>, queue: String, room: String) {
+        Logger::info(&format!(
+            "Matched into room '{}' by queue '{}'",
+            room, queue
+        ));
+        let handler = service.borrow().match_handler.clone();
+        if let Some(cb) = handler {
+            let _ = cb.call2(
+                &JsValue::NULL,
+                &JsValue::from_str(&room),
+                &JsValue::from_str(&queue),
+            );
+        }
+    }
+
+    /// Asks the server to group this client with other players of `queue`. `attributes` are
+    /// compared by the queue's rules, e.g. `skill`. The room is reported to `on_match`; join it
+    /// with [`AntennaEngine::join_room`].
+    pub fn matchmake(
+        &self,
+        queue: &str,
+        attributes: HashMap<String, f64>,
+    ) -> Result<(), AntennaError> {
+        self.service
+            .borrow()
+            .send_signal(&SignalMessage::Matchmake {
+                queue: queue.to_string(),
+                attributes,
+            })
+    }
+
+    /// [`AntennaEngine::matchmake`] taking the attributes as an object of numbers.
+    pub fn matchmake_js(&self, queue: &str, attributes: JsValue) -> Result<(), AntennaError> {
+        let attributes = if attributes.is_undefined() || attributes.is_null() {
+            HashMap::new()
+        } else {
+            serde_wasm_bindgen::from_value(attributes).map_err(AntennaError::serialization)?
+        };
+        self.matchmake(queue, attributes)
+    }
+
+    /// Leaves `queue`, or every queue when `None`.
+    pub fn cancel_matchmake(&self, queue: Option<&str>) -> Result<(), AntennaError> {
+        self.service
+            .borrow()
+            .send_signal(&SignalMessage::CancelMatchmake {
+                queue: queue.map(str::to_string),
+            })
+    }
+
+    /// Calls `callback(room, queue)` when the server formed a match.
+    pub fn set_match_handler(&self, callback: js_sys::Function) {
+        self.service.borrow_mut().match_handler = Some(callback);
+    }
+}
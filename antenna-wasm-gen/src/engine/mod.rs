@@ -1,24 +1,79 @@
+use antenna_core::Channel;
 use antenna_core::Message;
 use antenna_core::Packet;
+use antenna_core::{
+    AckTracker, DEFAULT_MAX_MESSAGE_SIZE, Reassembler, RejectReason, SystemMessage,
+};
 
 use antenna_core::IceServerConfig;
 use postcard::to_allocvec;
 use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
 use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 
 mod create_pc_impl;
+mod error;
 mod handle_remote_offer_impl;
 mod handle_signal_impl;
 mod init_connection_impl;
+mod leave_impl;
+mod matchmaking_impl;
+mod presence_impl;
+mod room_impl;
+mod send_buffer_impl;
+mod sequence_impl;
 mod setup_data_channel_impl;
+mod state_impl;
+mod stream_impl;
+mod websocket_fallback_impl;
 mod ws_setup_impl;
 
+pub use error::AntennaError;
+
 #[derive(Clone)]
 pub struct EngineConfig {
     pub url: String,
     pub room_id: String,
     pub ice_servers: Option<Vec<IceServerConfig>>,
+    pub transport: TransportMode,
+    pub send_buffer: SendBufferConfig,
+}
+
+/// Selects how data messages reach the server.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TransportMode {
+    /// WebRTC data channel, falling back to the signaling WebSocket when ICE fails.
+    #[default]
+    Auto,
+    /// Binary frames over the signaling WebSocket only, for networks where WebRTC is blocked.
+    WebSocketOnly,
+}
+
+/// Bounds of the outgoing buffer, in bytes.
+#[derive(Debug, Clone, Copy)]
+pub struct SendBufferConfig {
+    /// Data channel `bufferedAmount` above which messages are kept in the local queue.
+    pub high_watermark: usize,
+    /// `bufferedAmount` at which the local queue is flushed again (`bufferedamountlow`).
+    pub low_watermark: usize,
+    /// Upper bound of the local queue. Unreliable messages evict older unreliable ones,
+    /// reliable messages are rejected once it is reached.
+    pub max_queued_bytes: usize,
+    /// Largest single message in both directions. Bigger messages are fragmented on the data
+    /// channel; larger ones are rejected by `send` and dropped on receive.
+    pub max_message_size: usize,
+}
+
+impl Default for SendBufferConfig {
+    fn default() -> Self {
+        Self {
+            high_watermark: 1024 * 1024,
+            low_watermark: 256 * 1024,
+            max_queued_bytes: 4 * 1024 * 1024,
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -36,15 +91,63 @@ struct IcePayload {
     sdp_m_line_index: Option<u16>,
 }
 
-struct EngineService {
+/// Decodes the shared state for the `on_state_patch` callback.
+pub(super) type StateDecoder = Box<dyn Fn(&[u8]) -> Option<JsValue>>;
+
+/// Connection to a single room: its own peer connection, data channel and send queue.
+struct RoomConnection {
     state: ConnectionState,
-    ws: Option<web_sys::WebSocket>,
     pc: Option<web_sys::RtcPeerConnection>,
     dc: Option<web_sys::RtcDataChannel>,
-    message_queue: Vec<Vec<u8>>,
+    message_queue: VecDeque<(Channel, Vec<u8>)>,
+    queued_bytes: usize,
+    send_buffer: SendBufferConfig,
+    next_message_id: u32,
+    reassembler: Reassembler,
+    ws_fallback: bool,
+    ice_restart_attempted: bool,
+    /// Postcard encoding of the room's shared state and its version
+    shared_state: Option<(u64, Vec<u8>)>,
+    /// Sequence number of the next `Packet::Sequenced` input
+    next_seq: u32,
+    /// Sequenced packets received from the room, acknowledged in outgoing inputs
+    received: AckTracker,
+    /// Sent with `Join` for the room's `on_join_request` hook
+    metadata: HashMap<String, String>,
+    /// Place on the room's wait-list while it is full (`SignalMessage::WaitListed`)
+    wait_position: Option<usize>,
+}
+
+struct EngineService {
+    ws: Option<web_sys::WebSocket>,
+    rooms: HashMap<String, RoomConnection>,
+    /// Room of `EngineConfig::room_id`, used by methods that take no room
+    default_room: String,
+    send_buffer: SendBufferConfig,
     event_handler: Option<js_sys::Function>,
     track_callback: Option<js_sys::Function>,
+    stream_handler: Option<js_sys::Function>,
+    error_handler: Option<js_sys::Function>,
+    state_handler: Option<(js_sys::Function, StateDecoder)>,
+    ack_handler: Option<js_sys::Function>,
+    match_handler: Option<js_sys::Function>,
+    presence_handler: Option<js_sys::Function>,
+    connect_handler: Option<js_sys::Function>,
     ice_servers: Option<Vec<IceServerConfig>>,
+    transport_mode: TransportMode,
+    features: Vec<String>,
+    rejection: Option<RejectReason>,
+    last_error: Option<AntennaError>,
+    /// `Redirect`s followed since the last `Welcome`
+    redirects: u32,
+}
+
+/// Outgoing stream opened by [`AntennaEngine::open_stream`]. Data written to it is relayed by
+/// the room in `STREAM_CHUNK_SIZE` chunks.
+pub struct OutgoingStream {
+    service: Rc<RefCell<EngineService>>,
+    room: String,
+    stream_id: uuid::Uuid,
 }
 
 pub struct AntennaEngine<T, E> {
@@ -58,16 +161,33 @@ where
     T: Message,
     E: Message,
 {
-    pub fn new(config: EngineConfig) -> Result<Self, JsValue> {
+    pub fn new(config: EngineConfig) -> Result<Self, AntennaError> {
+        let mut rooms = HashMap::new();
+        rooms.insert(
+            config.room_id.clone(),
+            RoomConnection::new(config.send_buffer),
+        );
+
         let service = Rc::new(RefCell::new(EngineService {
-            state: ConnectionState::Disconnected,
             ws: None,
-            pc: None,
-            dc: None,
-            message_queue: Vec::new(),
+            rooms,
+            default_room: config.room_id.clone(),
+            send_buffer: config.send_buffer,
             event_handler: None,
             track_callback: None,
+            stream_handler: None,
+            error_handler: None,
+            state_handler: None,
+            ack_handler: None,
+            match_handler: None,
+            presence_handler: None,
+            connect_handler: None,
             ice_servers: config.ice_servers.clone(),
+            transport_mode: config.transport,
+            features: Vec::new(),
+            rejection: None,
+            last_error: None,
+            redirects: 0,
         }));
 
         let engine = AntennaEngine {
@@ -76,30 +196,101 @@ where
             _phantom_out: std::marker::PhantomData,
         };
 
-        engine.ws_setup(config)?;
+        Self::ws_setup(&engine.service, &config.url)?;
         Ok(engine)
     }
 
-    fn dispatch_event(service: &Rc<RefCell<EngineService>>, packet: Packet<E>) {
-        if let Some(cb) = &service.borrow().event_handler
-            && let Packet::User(event) = packet
-            && let Ok(js_val) = serde_wasm_bindgen::to_value(&event)
+    /// Hands a packet received in `room` to the JS callbacks, which get the room as second argument.
+    fn dispatch_event(service: &Rc<RefCell<EngineService>>, room: &str, packet: Packet<E>) {
+        let (handler, js_val) = match packet {
+            Packet::System(
+                msg @ (SystemMessage::StateSnapshot { .. } | SystemMessage::StatePatch { .. }),
+            ) => {
+                Self::apply_state(service, room, msg);
+                return;
+            }
+            Packet::Sequenced { header, payload } => {
+                if !Self::receive_sequenced(service, room, header) {
+                    return;
+                }
+                (
+                    service.borrow().event_handler.clone(),
+                    serde_wasm_bindgen::to_value(&payload),
+                )
+            }
+            Packet::System(msg @ (SystemMessage::PeerJoined(_) | SystemMessage::PeerLeft(_))) => {
+                Self::presence_changed(service, room, msg);
+                return;
+            }
+            Packet::User(event) => (
+                service.borrow().event_handler.clone(),
+                serde_wasm_bindgen::to_value(&event),
+            ),
+            Packet::System(
+                msg @ (SystemMessage::StreamOpen { .. }
+                | SystemMessage::StreamChunk { .. }
+                | SystemMessage::StreamClose { .. }
+                | SystemMessage::StreamAbort { .. }),
+            ) => (
+                service.borrow().stream_handler.clone(),
+                serde_wasm_bindgen::to_value(&msg),
+            ),
+            _ => return,
+        };
+
+        if let Some(cb) = handler
+            && let Ok(js_val) = js_val
         {
-            let _ = cb.call1(&JsValue::NULL, &js_val);
+            let _ = cb.call2(&JsValue::NULL, &js_val, &JsValue::from_str(room));
         }
     }
 
-    pub fn send(&self, msg: T) {
-        let mut service = self.service.borrow_mut();
+    /// Sends `msg` to the room of `EngineConfig::room_id`, see [`AntennaEngine::send_in`].
+    pub fn send(&self, msg: T) -> Result<(), AntennaError> {
+        let room = self.service.borrow().default_room.clone();
+        self.send_in(&room, msg)
+    }
+
+    /// Sends `msg` to `room` right away or queues it until the connection can take more data.
+    /// Fails when a reliable message does not fit into the send buffer.
+    pub fn send_in(&self, room: &str, msg: T) -> Result<(), AntennaError> {
+        let channel = msg.channel();
         let packet = Packet::User(msg);
-        let bytes = to_allocvec(&packet).unwrap();
-        if let Some(dc) = &service.dc
-            && dc.ready_state() == web_sys::RtcDataChannelState::Open
-        {
-            let _ = dc.send_with_u8_array(&bytes);
-            return;
-        }
-        service.message_queue.push(bytes);
+        let bytes = to_allocvec(&packet).map_err(AntennaError::serialization)?;
+
+        self.service.borrow_mut().send_bytes(room, channel, bytes)
+    }
+
+    /// [`AntennaEngine::send`] taking the message as the JS value `serde_wasm_bindgen` makes of `T`.
+    pub fn send_js(&self, msg: JsValue) -> Result<(), AntennaError> {
+        let msg = serde_wasm_bindgen::from_value(msg).map_err(AntennaError::serialization)?;
+        self.send(msg)
+    }
+
+    /// Why the server refused to let this client join, e.g. `RejectReason::VersionMismatch`
+    /// when the client and server were built against different protocol versions.
+    pub fn rejection(&self) -> Option<RejectReason> {
+        self.service.borrow().rejection.clone()
+    }
+
+    /// The most recent error reported to `on_error`.
+    pub fn last_error(&self) -> Option<AntennaError> {
+        self.service.borrow().last_error.clone()
+    }
+
+    /// Optional protocol features enabled by the server in `Welcome`.
+    pub fn features(&self) -> Vec<String> {
+        self.service.borrow().features.clone()
+    }
+
+    /// Bytes waiting in the local send queues of all rooms.
+    pub fn queued_bytes(&self) -> usize {
+        self.service
+            .borrow()
+            .rooms
+            .values()
+            .map(|room| room.queued_bytes)
+            .sum()
     }
 
     pub fn set_event_handler(&self, event_handler: js_sys::Function) {
@@ -110,12 +301,31 @@ where
         self.service.borrow_mut().track_callback = Some(callback);
     }
 
+    /// Receives `StreamOpen`, `StreamChunk`, `StreamClose` and `StreamAbort` messages of streams
+    /// relayed to this peer, followed by the room.
+    pub fn set_stream_handler(&self, callback: js_sys::Function) {
+        self.service.borrow_mut().stream_handler = Some(callback);
+    }
+
+    /// Receives every [`AntennaError`] as `{ kind, description, fatal, room, ...fields }`. A fatal
+    /// error closes the affected room, or the whole session when `room` is `null`; other errors
+    /// leave the connection usable.
+    pub fn set_error_handler(&self, callback: js_sys::Function) {
+        self.service.borrow_mut().error_handler = Some(callback);
+    }
+
+    /// Adds a media track to the peer connection of the room of `EngineConfig::room_id`.
     pub fn add_track(
         &self,
         track: web_sys::MediaStreamTrack,
         stream: web_sys::MediaStream,
     ) -> Result<(), JsValue> {
-        if let Some(pc) = &self.service.borrow().pc {
+        let service = self.service.borrow();
+        if let Some(pc) = service
+            .rooms
+            .get(&service.default_room)
+            .and_then(|room| room.pc.as_ref())
+        {
             pc.add_track_0(&track, &stream);
         }
         Ok(())
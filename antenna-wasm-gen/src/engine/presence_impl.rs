@@ -0,0 +1,53 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use antenna_core::{Message, SystemMessage};
+use serde::Serialize;
+use wasm_bindgen::JsValue;
+
+use crate::AntennaEngine;
+use crate::engine::EngineService;
+
+/// Argument of the `on_presence` callback.
+#[derive(Serialize)]
+struct PresenceEvent {
+    kind: &'static str,
+    peer: String,
+}
+
+impl<T, E> AntennaEngine<T, E>
+where
+    T: Message,
+    E: Message,
+{
+    /// Reports a `PeerJoined` / `PeerLeft` of `room` to `on_presence`.
+    pub(super) fn presence_changed(
+        service: &Rc<RefCell<EngineService>>,
+        room: &str,
+        msg: SystemMessage,
+    ) {
+        let event = match msg {
+            SystemMessage::PeerJoined(peer_id) => PresenceEvent {
+                kind: "joined",
+                peer: peer_id.0.to_string(),
+            },
+            SystemMessage::PeerLeft(peer_id) => PresenceEvent {
+                kind: "left",
+                peer: peer_id.0.to_string(),
+            },
+            _ => return,
+        };
+        let handler = service.borrow().presence_handler.clone();
+        if let Some(cb) = handler
+            && let Ok(event) = serde_wasm_bindgen::to_value(&event)
+        {
+            let _ = cb.call2(&JsValue::NULL, &event, &JsValue::from_str(room));
+        }
+    }
+
+    /// Calls `callback({ kind: "joined" | "left", peer }, room)` when another peer joins or
+    /// leaves a room that announces presence (`RoomConfig::with_presence`).
+    pub fn set_presence_handler(&self, callback: js_sys::Function) {
+        self.service.borrow_mut().presence_handler = Some(callback);
+    }
+}
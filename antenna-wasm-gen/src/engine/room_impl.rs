@@ -0,0 +1,195 @@
+use antenna_core::{AckTracker, Message, Reassembler, SignalMessage};
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::rc::Rc;
+use wasm_bindgen::JsValue;
+
+use crate::AntennaEngine;
+use crate::engine::{
+    AntennaError, ConnectionState, EngineService, RoomConnection, SendBufferConfig,
+};
+use crate::logger::Logger;
+
+impl RoomConnection {
+    pub(super) fn new(send_buffer: SendBufferConfig) -> Self {
+        Self {
+            state: ConnectionState::Disconnected,
+            pc: None,
+            dc: None,
+            message_queue: VecDeque::new(),
+            queued_bytes: 0,
+            send_buffer,
+            next_message_id: 0,
+            reassembler: Reassembler::new(send_buffer.max_message_size),
+            ws_fallback: false,
+            ice_restart_attempted: false,
+            shared_state: None,
+            next_seq: 0,
+            received: AckTracker::default(),
+            metadata: HashMap::new(),
+            wait_position: None,
+        }
+    }
+
+    /// Closes the data channel and the peer connection and discards queued messages.
+    pub(super) fn close(&mut self) {
+        self.state = ConnectionState::Disconnected;
+        self.message_queue.clear();
+        self.queued_bytes = 0;
+        if let Some(dc) = self.dc.take() {
+            dc.close();
+        }
+        if let Some(pc) = self.pc.take() {
+            pc.close();
+        }
+    }
+}
+
+impl EngineService {
+    /// Signals without a room address the room of `EngineConfig::room_id`.
+    pub(super) fn resolve_room(&self, room: Option<String>) -> String {
+        room.unwrap_or_else(|| self.default_room.clone())
+    }
+
+    /// Whether the server enabled `feature` in its `Welcome`.
+    pub(super) fn supports(&self, feature: &str) -> bool {
+        self.features.iter().any(|f| f == feature)
+    }
+
+    /// Forgets `room` and closes its connection.
+    pub(super) fn close_room(&mut self, room: &str) {
+        if let Some(mut connection) = self.rooms.remove(room) {
+            connection.close();
+        }
+    }
+
+    /// Marks `room` connected once its data channel or the WebSocket fallback is open, sends the
+    /// messages queued meanwhile and notifies `on_connect`.
+    pub(super) fn room_connected(service: &Rc<RefCell<EngineService>>, room: &str) {
+        let handler = {
+            let mut service = service.borrow_mut();
+            if let Some(connection) = service.rooms.get_mut(room) {
+                connection.state = ConnectionState::Connected;
+            }
+            service.flush_queue(room);
+            service.connect_handler.clone()
+        };
+        if let Some(cb) = handler {
+            let _ = cb.call1(&JsValue::NULL, &JsValue::from_str(room));
+        }
+    }
+
+    /// Closes every room and the WebSocket.
+    pub(super) fn close_all(&mut self) {
+        for (_, mut connection) in self.rooms.drain() {
+            connection.close();
+        }
+        if let Some(ws) = self.ws.take() {
+            let _ = ws.close();
+        }
+    }
+}
+
+impl<T, E> AntennaEngine<T, E>
+where
+    T: Message,
+    E: Message,
+{
+    /// Joins another room over the same WebSocket. Every room gets its own peer connection;
+    /// messages are sent with [`AntennaEngine::send_in`] and callbacks receive the room as the
+    /// second argument.
+    pub fn join_room(&self, room: &str) -> Result<(), AntennaError> {
+        self.join_room_with_metadata(room, HashMap::new())
+    }
+
+    /// Joins `room` like [`AntennaEngine::join_room`], sending `metadata` (e.g. a ticket or a
+    /// display name) to the room's `on_join_request` hook.
+    pub fn join_room_with_metadata(
+        &self,
+        room: &str,
+        metadata: HashMap<String, String>,
+    ) -> Result<(), AntennaError> {
+        let mut service = self.service.borrow_mut();
+        if service.rooms.contains_key(room) {
+            return Ok(());
+        }
+
+        let mut connection = RoomConnection::new(service.send_buffer);
+        connection.metadata = metadata;
+        let ws_open = service
+            .ws
+            .as_ref()
+            .is_some_and(|ws| ws.ready_state() == web_sys::WebSocket::OPEN);
+        // Otherwise the Join is sent once the WebSocket opens
+        if ws_open {
+            service.send_signal(&Self::join_message(room, &connection.metadata))?;
+            connection.state = ConnectionState::Connecting;
+        }
+
+        service.rooms.insert(room.to_string(), connection);
+        Ok(())
+    }
+
+    /// [`AntennaEngine::join_room_with_metadata`] taking the metadata as an object of strings.
+    pub fn join_room_with_metadata_js(
+        &self,
+        room: &str,
+        metadata: JsValue,
+    ) -> Result<(), AntennaError> {
+        let metadata =
+            serde_wasm_bindgen::from_value(metadata).map_err(AntennaError::serialization)?;
+        self.join_room_with_metadata(room, metadata)
+    }
+
+    /// Leaves `room` and closes its peer connection. Other rooms stay connected.
+    pub fn leave_room(&self, room: &str) {
+        let mut service = self.service.borrow_mut();
+        let leave = SignalMessage::Leave {
+            room: Some(room.to_string()),
+        };
+        if let Err(e) = service.send_signal(&leave) {
+            Logger::warn(&format!("Failed to send Leave: {}", e));
+        }
+        service.close_room(room);
+        Logger::info(&format!("Left room '{}'", room));
+    }
+
+    /// Rooms this engine joined or is joining.
+    pub fn rooms(&self) -> Vec<String> {
+        self.service.borrow().rooms.keys().cloned().collect()
+    }
+
+    /// Metadata sent with the `Join` of the room of `EngineConfig::room_id`. Call it right after
+    /// [`AntennaEngine::new`], before the WebSocket opens.
+    pub fn set_join_metadata(&self, metadata: HashMap<String, String>) {
+        let mut service = self.service.borrow_mut();
+        let room = service.default_room.clone();
+        if let Some(connection) = service.rooms.get_mut(&room) {
+            connection.metadata = metadata;
+        }
+    }
+
+    /// Place of this client on the wait-list of the full `room`, `None` once admitted.
+    pub fn wait_position(&self, room: &str) -> Option<usize> {
+        self.service
+            .borrow()
+            .rooms
+            .get(room)
+            .and_then(|connection| connection.wait_position)
+    }
+
+    /// Calls `callback(room)` whenever a room becomes connected, over WebRTC or the WebSocket
+    /// fallback.
+    pub fn set_connect_handler(&self, callback: js_sys::Function) {
+        self.service.borrow_mut().connect_handler = Some(callback);
+    }
+
+    /// Connection state of `room`, `Disconnected` for rooms not joined.
+    pub fn room_state(&self, room: &str) -> ConnectionState {
+        self.service
+            .borrow()
+            .rooms
+            .get(room)
+            .map_or(ConnectionState::Disconnected, |connection| connection.state)
+    }
+}
@@ -0,0 +1,154 @@
+use crate::engine::{AntennaError, ConnectionState, EngineService, RoomConnection};
+use crate::logger::Logger;
+use antenna_core::{Channel, DEFAULT_FRAGMENT_SIZE, encode_ws_frame, split_frames};
+
+impl EngineService {
+    /// Sends a serialized packet to `room` right away or queues it until the connection can take
+    /// more data.
+    pub(super) fn send_bytes(
+        &mut self,
+        room: &str,
+        channel: Channel,
+        bytes: Vec<u8>,
+    ) -> Result<(), AntennaError> {
+        let ws = self.ws.clone();
+        let connection = self
+            .rooms
+            .get_mut(room)
+            .ok_or_else(|| AntennaError::NotJoined {
+                room: room.to_string(),
+            })?;
+        connection.send_bytes(ws.as_ref(), room, channel, bytes)
+    }
+
+    /// Hands the queued messages of `room` to its active transport.
+    pub(super) fn flush_queue(&mut self, room: &str) {
+        let ws = self.ws.clone();
+        if let Some(connection) = self.rooms.get_mut(room) {
+            connection.flush_queue(ws.as_ref(), room);
+        }
+    }
+}
+
+impl RoomConnection {
+    fn send_bytes(
+        &mut self,
+        ws: Option<&web_sys::WebSocket>,
+        room: &str,
+        channel: Channel,
+        bytes: Vec<u8>,
+    ) -> Result<(), AntennaError> {
+        if bytes.len() > self.send_buffer.max_message_size {
+            return Err(AntennaError::MessageTooLarge {
+                size: bytes.len(),
+                max: self.send_buffer.max_message_size,
+            });
+        }
+
+        // Until the room admitted the peer on the WebSocket it would drop the data
+        if self.ws_fallback
+            && self.state == ConnectionState::Connected
+            && let Some(ws) = ws
+            && ws.ready_state() == web_sys::WebSocket::OPEN
+        {
+            return send_ws_frame(ws, room, &bytes);
+        }
+        if self.message_queue.is_empty()
+            && let Some(dc) = self.dc.clone()
+            && dc.ready_state() == web_sys::RtcDataChannelState::Open
+            && (dc.buffered_amount() as usize) <= self.send_buffer.high_watermark
+        {
+            return self.send_on_data_channel(&dc, &bytes);
+        }
+        self.enqueue(channel, bytes)
+    }
+
+    /// Writes one message as a sequence of data channel frames.
+    fn send_on_data_channel(
+        &mut self,
+        dc: &web_sys::RtcDataChannel,
+        bytes: &[u8],
+    ) -> Result<(), AntennaError> {
+        let message_id = self.next_message_id;
+        self.next_message_id = self.next_message_id.wrapping_add(1);
+
+        for frame in split_frames(message_id, bytes, DEFAULT_FRAGMENT_SIZE) {
+            dc.send_with_u8_array(&frame)
+                .map_err(AntennaError::transport)?;
+        }
+        Ok(())
+    }
+
+    /// Queues a message until the connection is open and below the high watermark.
+    fn enqueue(&mut self, channel: Channel, bytes: Vec<u8>) -> Result<(), AntennaError> {
+        let max = self.send_buffer.max_queued_bytes;
+
+        if self.queued_bytes + bytes.len() > max {
+            if channel != Channel::Unreliable {
+                return Err(AntennaError::SendBufferFull);
+            }
+
+            while self.queued_bytes + bytes.len() > max {
+                let Some(index) = self
+                    .message_queue
+                    .iter()
+                    .position(|(c, _)| *c == Channel::Unreliable)
+                else {
+                    Logger::warn("Send buffer is full, dropping unreliable message");
+                    return Ok(());
+                };
+                if let Some((_, evicted)) = self.message_queue.remove(index) {
+                    self.queued_bytes -= evicted.len();
+                }
+            }
+        }
+
+        self.queued_bytes += bytes.len();
+        self.message_queue.push_back((channel, bytes));
+        Ok(())
+    }
+
+    /// Hands queued messages to the active transport. Stops at the data channel high watermark,
+    /// the rest is sent from `bufferedamountlow`.
+    fn flush_queue(&mut self, ws: Option<&web_sys::WebSocket>, room: &str) {
+        if self.ws_fallback {
+            let Some(ws) = ws.filter(|_| self.state == ConnectionState::Connected) else {
+                return;
+            };
+            while let Some((_, msg)) = self.message_queue.pop_front() {
+                self.queued_bytes -= msg.len();
+                if let Err(e) = send_ws_frame(ws, room, &msg) {
+                    Logger::warn(&format!("Failed to send buffered message: {}", e));
+                }
+            }
+            return;
+        }
+
+        let Some(dc) = self.dc.clone() else {
+            return;
+        };
+        while dc.ready_state() == web_sys::RtcDataChannelState::Open
+            && (dc.buffered_amount() as usize) <= self.send_buffer.high_watermark
+        {
+            let Some((_, msg)) = self.message_queue.pop_front() else {
+                break;
+            };
+            self.queued_bytes -= msg.len();
+            if let Err(e) = self.send_on_data_channel(&dc, &msg) {
+                Logger::warn(&format!("Failed to send buffered message: {}", e));
+            }
+        }
+    }
+}
+
+/// Sends a packet of `room` as a binary frame of the shared signaling WebSocket.
+fn send_ws_frame(ws: &web_sys::WebSocket, room: &str, bytes: &[u8]) -> Result<(), AntennaError> {
+    let frame = encode_ws_frame(room, bytes).ok_or_else(|| AntennaError::Transport {
+        message: format!(
+            "Room name '{}' is too long for the WebSocket transport",
+            room
+        ),
+    })?;
+    ws.send_with_u8_array(&frame)
+        .map_err(AntennaError::transport)
+}
@@ -0,0 +1,84 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use antenna_core::{FEATURE_SEQUENCING, Message, Packet, SequenceHeader};
+use postcard::to_allocvec;
+use wasm_bindgen::JsValue;
+
+use crate::AntennaEngine;
+use crate::engine::{AntennaError, EngineService};
+
+impl<T, E> AntennaEngine<T, E>
+where
+    T: Message,
+    E: Message,
+{
+    /// Records a `Packet::Sequenced` received in `room` and reports its acks to `on_ack`.
+    /// Returns `false` for packets older than one already received, which are dropped.
+    pub(super) fn receive_sequenced(
+        service: &Rc<RefCell<EngineService>>,
+        room: &str,
+        header: SequenceHeader,
+    ) -> bool {
+        let newest = match service.borrow_mut().rooms.get_mut(room) {
+            Some(connection) => connection.received.record(header.seq),
+            None => return false,
+        };
+        if !newest {
+            return false;
+        }
+
+        let handler = service.borrow().ack_handler.clone();
+        if let Some(cb) = handler
+            && let Ok(js_val) = serde_wasm_bindgen::to_value(&header)
+        {
+            let _ = cb.call2(&JsValue::NULL, &js_val, &JsValue::from_str(room));
+        }
+        true
+    }
+
+    /// Sends `msg` as a sequenced input to the room of `EngineConfig::room_id`, see
+    /// [`AntennaEngine::send_sequenced_in`].
+    pub fn send_sequenced(&self, msg: T) -> Result<u32, AntennaError> {
+        let room = self.service.borrow().default_room.clone();
+        self.send_sequenced_in(&room, msg)
+    }
+
+    /// Sends `msg` to `room` as a `Packet::Sequenced` and returns its sequence number. Keep the
+    /// input until the room acknowledges it in `on_ack`, then replay the newer ones on top of
+    /// the authoritative state. Fails unless the server enabled `FEATURE_SEQUENCING`.
+    pub fn send_sequenced_in(&self, room: &str, msg: T) -> Result<u32, AntennaError> {
+        let channel = msg.channel();
+        let mut service = self.service.borrow_mut();
+        if !service.supports(FEATURE_SEQUENCING) {
+            return Err(AntennaError::unsupported(FEATURE_SEQUENCING));
+        }
+        let connection = service
+            .rooms
+            .get_mut(room)
+            .ok_or_else(|| AntennaError::NotJoined {
+                room: room.to_string(),
+            })?;
+
+        let seq = connection.next_seq;
+        let header = connection.received.header(seq);
+        let bytes = to_allocvec(&Packet::Sequenced {
+            header,
+            payload: msg,
+        })
+        .map_err(AntennaError::serialization)?;
+
+        service.send_bytes(room, channel, bytes)?;
+        if let Some(connection) = service.rooms.get_mut(room) {
+            connection.next_seq = seq.wrapping_add(1);
+        }
+        Ok(seq)
+    }
+
+    /// Calls `callback(header, room)` for every sequenced packet from the room with its
+    /// `{ seq, ack, ack_bits }` header: `ack` is the newest input the room has processed, and
+    /// `undefined` before the first one.
+    pub fn set_ack_handler(&self, callback: js_sys::Function) {
+        self.service.borrow_mut().ack_handler = Some(callback);
+    }
+}
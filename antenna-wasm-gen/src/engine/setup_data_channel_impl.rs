@@ -1,5 +1,4 @@
 use crate::AntennaEngine;
-use crate::ConnectionState;
 use crate::engine::EngineService;
 use crate::logger::Logger;
 use antenna_core::Message;
@@ -18,19 +17,35 @@ where
 {
     pub(super) fn setup_data_channel(
         inner: &Rc<RefCell<EngineService>>,
+        room: &str,
         dc: web_sys::RtcDataChannel,
     ) {
         dc.set_binary_type(web_sys::RtcDataChannelType::Arraybuffer);
 
         let on_msg = {
             let inner = inner.clone();
+            let room = room.to_string();
             Closure::<dyn FnMut(web_sys::MessageEvent)>::wrap(Box::new(
                 move |ev: web_sys::MessageEvent| {
-                    if let Ok(ab) = ev.data().dyn_into::<js_sys::ArrayBuffer>() {
-                        let bytes = js_sys::Uint8Array::new(&ab).to_vec();
-                        if let Ok(packet) = from_bytes::<Packet<E>>(&bytes) {
-                            Self::dispatch_event(&inner, packet);
+                    let Ok(ab) = ev.data().dyn_into::<js_sys::ArrayBuffer>() else {
+                        return;
+                    };
+                    let frame = js_sys::Uint8Array::new(&ab).to_vec();
+
+                    let pushed = match inner.borrow_mut().rooms.get_mut(&room) {
+                        Some(connection) => connection.reassembler.push(&frame),
+                        None => return,
+                    };
+                    let bytes = match pushed {
+                        Ok(Some(bytes)) => bytes,
+                        Ok(None) => return,
+                        Err(e) => {
+                            Logger::warn(&format!("Dropping message: {}", e));
+                            return;
                         }
+                    };
+                    if let Ok(packet) = from_bytes::<Packet<E>>(&bytes) {
+                        Self::dispatch_event(&inner, &room, packet);
                     }
                 },
             ))
@@ -41,29 +56,32 @@ where
 
         let on_open = {
             let inner = inner.clone();
+            let room = room.to_string();
             Closure::<dyn FnMut(JsValue)>::wrap(Box::new(move |_| {
-                Logger::info("DataChannel OPEN");
-
-                let (dc, messages) = {
-                    let mut inner_mut = inner.borrow_mut();
-                    inner_mut.state = ConnectionState::Connected;
-                    let dc = inner_mut.dc.clone();
-                    let msgs: Vec<Vec<u8>> = inner_mut.message_queue.drain(..).collect();
-                    (dc, msgs)
-                };
+                Logger::info(&format!("DataChannel OPEN in room '{}'", room));
 
-                if let Some(dc) = dc {
-                    for msg in messages {
-                        if let Err(e) = dc.send_with_u8_array(&msg) {
-                            Logger::warn(&format!("Failed to send buffered message: {:?}", e));
-                        }
-                    }
-                }
+                EngineService::room_connected(&inner, &room);
             }))
         };
         dc.set_onopen(Some(on_open.as_ref().unchecked_ref()));
         on_open.forget();
 
-        inner.borrow_mut().dc = Some(dc);
+        let on_buffered_amount_low = {
+            let inner = inner.clone();
+            let room = room.to_string();
+            Closure::<dyn FnMut(JsValue)>::wrap(Box::new(move |_| {
+                inner.borrow_mut().flush_queue(&room);
+            }))
+        };
+        dc.set_onbufferedamountlow(Some(on_buffered_amount_low.as_ref().unchecked_ref()));
+        on_buffered_amount_low.forget();
+
+        let low_watermark = inner.borrow().send_buffer.low_watermark;
+        dc.set_buffered_amount_low_threshold(low_watermark as u32);
+
+        match inner.borrow_mut().rooms.get_mut(room) {
+            Some(connection) => connection.dc = Some(dc),
+            None => dc.close(),
+        }
     }
 }
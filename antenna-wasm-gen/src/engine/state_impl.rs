@@ -0,0 +1,130 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use antenna_core::{Message, StateError, SystemMessage};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use wasm_bindgen::JsValue;
+
+use crate::AntennaEngine;
+use crate::engine::{AntennaError, EngineService, StateDecoder};
+use crate::logger::Logger;
+
+impl<T, E> AntennaEngine<T, E>
+where
+    T: Message,
+    E: Message,
+{
+    /// Applies a `StateSnapshot` or `StatePatch` of `room` and notifies `on_state_patch`.
+    pub(super) fn apply_state(
+        service: &Rc<RefCell<EngineService>>,
+        room: &str,
+        msg: SystemMessage,
+    ) {
+        let applied = {
+            let mut service = service.borrow_mut();
+            let Some(connection) = service.rooms.get_mut(room) else {
+                return;
+            };
+
+            match (msg, &mut connection.shared_state) {
+                (SystemMessage::StateSnapshot { version, data }, state) => {
+                    *state = Some((version, data));
+                    Ok(version)
+                }
+                (SystemMessage::StatePatch { version, delta }, Some((current, data))) => {
+                    if version != *current + 1 {
+                        Err(StateError::VersionGap {
+                            expected: *current + 1,
+                            got: version,
+                        })
+                    } else {
+                        delta.apply(data).map(|()| {
+                            *current = version;
+                            version
+                        })
+                    }
+                }
+                (SystemMessage::StatePatch { version, .. }, None) => Err(StateError::VersionGap {
+                    expected: 0,
+                    got: version,
+                }),
+                _ => return,
+            }
+        };
+
+        let version = match applied {
+            Ok(version) => version,
+            Err(e) => {
+                // The local copy is unusable until the next snapshot
+                if let Some(connection) = service.borrow_mut().rooms.get_mut(room) {
+                    connection.shared_state = None;
+                }
+                EngineService::report_error(
+                    service,
+                    Some(room),
+                    AntennaError::StateDesync {
+                        message: e.to_string(),
+                    },
+                );
+                return;
+            }
+        };
+
+        let service = service.borrow();
+        let (Some((cb, decode)), Some((_, data))) = (
+            &service.state_handler,
+            service
+                .rooms
+                .get(room)
+                .and_then(|c| c.shared_state.as_ref()),
+        ) else {
+            return;
+        };
+        match decode(data) {
+            Some(state) => {
+                let _ = cb.call3(
+                    &JsValue::NULL,
+                    &state,
+                    &JsValue::from_str(room),
+                    &JsValue::from_f64(version as f64),
+                );
+            }
+            None => Logger::warn("Failed to decode shared state"),
+        }
+    }
+
+    /// Shared state of the room of `EngineConfig::room_id`, see `SharedState` on the server.
+    /// `None` until the first snapshot arrived or when it does not decode as `S`.
+    pub fn state_snapshot<S: DeserializeOwned>(&self) -> Option<S> {
+        let room = self.service.borrow().default_room.clone();
+        self.state_snapshot_in(&room)
+    }
+
+    /// Shared state of `room`.
+    pub fn state_snapshot_in<S: DeserializeOwned>(&self, room: &str) -> Option<S> {
+        let service = self.service.borrow();
+        let (_, data) = service.rooms.get(room)?.shared_state.as_ref()?;
+        postcard::from_bytes(data).ok()
+    }
+
+    /// [`AntennaEngine::state_snapshot`] converted to a JS value, `undefined` when missing.
+    pub fn state_snapshot_js<S: DeserializeOwned + Serialize>(&self) -> JsValue {
+        self.state_snapshot::<S>()
+            .and_then(|state| serde_wasm_bindgen::to_value(&state).ok())
+            .unwrap_or(JsValue::UNDEFINED)
+    }
+
+    /// Calls `callback(state, room, version)` with the whole state, decoded as `S`, after every
+    /// snapshot and patch.
+    pub fn set_state_patch_handler<S: DeserializeOwned + Serialize>(
+        &self,
+        callback: js_sys::Function,
+    ) {
+        let decode: StateDecoder = Box::new(|data| {
+            let state = postcard::from_bytes::<S>(data).ok()?;
+            serde_wasm_bindgen::to_value(&state).ok()
+        });
+        self.service.borrow_mut().state_handler = Some((callback, decode));
+    }
+}
@@ -0,0 +1,98 @@
+use crate::AntennaEngine;
+use crate::engine::{AntennaError, OutgoingStream};
+use antenna_core::{
+    Channel, FEATURE_STREAMS, Message, Packet, PeerId, STREAM_CHUNK_SIZE, SystemMessage,
+};
+use postcard::to_allocvec;
+
+impl<T, E> AntennaEngine<T, E>
+where
+    T: Message,
+    E: Message,
+{
+    /// Starts a transfer of `size` bytes to `to`, or to every other peer in the room when `None`.
+    /// Receivers get it through `set_stream_handler`.
+    pub fn open_stream(
+        &self,
+        to: Option<PeerId>,
+        name: String,
+        size: u64,
+    ) -> Result<OutgoingStream, AntennaError> {
+        let room = self.service.borrow().default_room.clone();
+        self.open_stream_in(&room, to, name, size)
+    }
+
+    /// Like [`AntennaEngine::open_stream`], in another joined room. Fails unless the server
+    /// enabled `FEATURE_STREAMS`.
+    pub fn open_stream_in(
+        &self,
+        room: &str,
+        to: Option<PeerId>,
+        name: String,
+        size: u64,
+    ) -> Result<OutgoingStream, AntennaError> {
+        if !self.service.borrow().supports(FEATURE_STREAMS) {
+            return Err(AntennaError::unsupported(FEATURE_STREAMS));
+        }
+        let stream = OutgoingStream {
+            service: self.service.clone(),
+            room: room.to_string(),
+            stream_id: uuid::Uuid::new_v4(),
+        };
+
+        stream.send(SystemMessage::StreamOpen {
+            stream_id: stream.stream_id,
+            from: None,
+            to,
+            name,
+            size,
+        })?;
+        Ok(stream)
+    }
+}
+
+impl OutgoingStream {
+    pub fn stream_id(&self) -> uuid::Uuid {
+        self.stream_id
+    }
+
+    /// Queues `data` for sending and returns how many bytes were accepted. A short count means
+    /// the send buffer is full; the caller writes the rest once `AntennaEngine::queued_bytes`
+    /// went down. Fails only when nothing could be accepted.
+    pub fn write(&self, data: &[u8]) -> Result<usize, AntennaError> {
+        let mut written = 0;
+        for chunk in data.chunks(STREAM_CHUNK_SIZE) {
+            let result = self.send(SystemMessage::StreamChunk {
+                stream_id: self.stream_id,
+                data: chunk.to_vec(),
+            });
+            match result {
+                Ok(()) => written += chunk.len(),
+                Err(e) if written == 0 => return Err(e),
+                Err(_) => break,
+            }
+        }
+        Ok(written)
+    }
+
+    /// Marks the transfer as complete.
+    pub fn close(self) -> Result<(), AntennaError> {
+        self.send(SystemMessage::StreamClose {
+            stream_id: self.stream_id,
+        })
+    }
+
+    /// Cancels the transfer; receivers get `StreamAbort`.
+    pub fn abort(self) -> Result<(), AntennaError> {
+        self.send(SystemMessage::StreamAbort {
+            stream_id: self.stream_id,
+        })
+    }
+
+    fn send(&self, msg: SystemMessage) -> Result<(), AntennaError> {
+        let bytes = to_allocvec(&Packet::<()>::System(msg)).map_err(AntennaError::serialization)?;
+        self.service
+            .borrow_mut()
+            .send_bytes(&self.room, Channel::Reliable, bytes)
+    }
+}
@@ -0,0 +1,84 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use antenna_core::{FEATURE_WEBSOCKET_TRANSPORT, Message, SignalMessage};
+
+use crate::AntennaEngine;
+use crate::engine::{AntennaError, ConnectionState, EngineService};
+use crate::logger::Logger;
+
+impl<T, E> AntennaEngine<T, E>
+where
+    T: Message,
+    E: Message,
+{
+    /// Reports a failed negotiation step of `room`. Unless the error is fatal the room keeps
+    /// going over the WebSocket transport instead of WebRTC.
+    pub(super) fn recover(service: &Rc<RefCell<EngineService>>, room: &str, error: AntennaError) {
+        let fatal = error.is_fatal();
+        EngineService::report_error(service, Some(room), error);
+        if !fatal {
+            Self::fall_back_to_websocket(service, room);
+        }
+    }
+
+    /// Switches data messages of `room` to binary frames over the signaling WebSocket.
+    /// Used when WebRTC cannot be established or the client opted in via `TransportMode`.
+    /// Any number of rooms can share the WebSocket, their frames name the room. Rooms are left
+    /// instead when the server did not enable `FEATURE_WEBSOCKET_TRANSPORT`.
+    pub(super) fn fall_back_to_websocket(service: &Rc<RefCell<EngineService>>, room: &str) {
+        Logger::warn(&format!(
+            "Falling back to WebSocket transport in room '{}'",
+            room
+        ));
+
+        if !service.borrow().supports(FEATURE_WEBSOCKET_TRANSPORT) {
+            let message = "the server did not enable the WebSocket transport".to_string();
+            EngineService::report_error(service, Some(room), AntennaError::Transport { message });
+            let mut service = service.borrow_mut();
+            let leave = SignalMessage::Leave {
+                room: Some(room.to_string()),
+            };
+            if let Err(e) = service.send_signal(&leave) {
+                Logger::warn(&format!("Failed to send Leave: {}", e));
+            }
+            service.close_room(room);
+            return;
+        }
+
+        let has_ws = {
+            let mut service = service.borrow_mut();
+            let has_ws = service.ws.is_some();
+            let Some(connection) = service.rooms.get_mut(room) else {
+                return;
+            };
+            if connection.ws_fallback {
+                return;
+            }
+            connection.ws_fallback = true;
+            connection.state = ConnectionState::Connecting;
+
+            if let Some(dc) = connection.dc.take() {
+                dc.close();
+            }
+            if let Some(pc) = connection.pc.take() {
+                pc.close();
+            }
+
+            has_ws
+        };
+
+        if !has_ws {
+            return;
+        }
+
+        let msg = SignalMessage::UseWebSocketTransport {
+            room: Some(room.to_string()),
+        };
+        // The room answers with `Admitted` once the peer holds its place, possibly after waiting
+        let sent = service.borrow().send_signal(&msg);
+        if let Err(e) = sent {
+            EngineService::report_error(service, None, e);
+        }
+    }
+}
@@ -1,48 +1,98 @@
-use antenna_core::{Message, SignalMessage};
+use antenna_core::{
+    Message, PROTOCOL_VERSION, Packet, SUPPORTED_FEATURES, SignalMessage, decode_ws_frame,
+    protocol_schema,
+};
+use postcard::from_bytes;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::{JsValue, prelude::Closure};
 use web_sys::WebSocket;
 
 use crate::AntennaEngine;
-use crate::{ConnectionState, EngineConfig, logger::Logger};
+use crate::engine::EngineService;
+use crate::{AntennaError, ConnectionState, logger::Logger};
 
 impl<T, E> AntennaEngine<T, E>
 where
     T: Message,
     E: Message,
 {
-    pub(crate) fn ws_setup(&self, config: EngineConfig) -> Result<(), JsValue> {
-        let ws: WebSocket = web_sys::WebSocket::new(&config.url)?;
+    /// `Join` announcing the protocol version, features and message schema of this client.
+    pub(super) fn join_message(room: &str, metadata: &HashMap<String, String>) -> SignalMessage {
+        SignalMessage::Join {
+            room: room.to_string(),
+            version: PROTOCOL_VERSION,
+            features: SUPPORTED_FEATURES.iter().map(|f| f.to_string()).collect(),
+            schema: Some(protocol_schema(T::schema_hash(), E::schema_hash())),
+            metadata: metadata.clone(),
+        }
+    }
+
+    /// Opens the signaling WebSocket to `url`. Every room gets joined once it is open.
+    pub(super) fn ws_setup(
+        service: &Rc<RefCell<EngineService>>,
+        url: &str,
+    ) -> Result<(), AntennaError> {
+        let ws: WebSocket = web_sys::WebSocket::new(url).map_err(AntennaError::signaling)?;
         ws.set_binary_type(web_sys::BinaryType::Arraybuffer);
 
         let onopen_callback = {
-            let service = self.service.clone();
-            let room_id = config.room_id.clone();
+            let service = service.clone();
             Closure::<dyn FnMut(JsValue)>::wrap(Box::new(move |_| {
                 Logger::info("WS Open");
 
-                let join_msg = SignalMessage::Join {
-                    room: room_id.clone(),
-                };
-
-                let json = serde_json::to_string(&join_msg).unwrap();
-                if let Some(ws) = &service.borrow().ws {
-                    ws.send_with_str(&json).unwrap();
+                let joins: Vec<(String, SignalMessage)> = service
+                    .borrow()
+                    .rooms
+                    .iter()
+                    .map(|(room, connection)| {
+                        (room.clone(), Self::join_message(room, &connection.metadata))
+                    })
+                    .collect();
+                for (room, join) in joins {
+                    let sent = service.borrow().send_signal(&join);
+                    match sent {
+                        Ok(()) => {
+                            if let Some(connection) = service.borrow_mut().rooms.get_mut(&room) {
+                                connection.state = ConnectionState::Connecting;
+                            }
+                        }
+                        Err(e) => {
+                            EngineService::report_error(&service, None, e);
+                            return;
+                        }
+                    }
                 }
-                service.borrow_mut().state = ConnectionState::Connecting;
             }))
         };
         ws.set_onopen(Some(onopen_callback.as_ref().unchecked_ref()));
         onopen_callback.forget();
 
         let onmessage_callback = {
-            let service = self.service.clone();
+            let service = service.clone();
             Closure::<dyn FnMut(web_sys::MessageEvent)>::wrap(Box::new(
                 move |e: web_sys::MessageEvent| {
                     if let Ok(text) = e.data().dyn_into::<js_sys::JsString>() {
                         let text: String = text.into();
                         Logger::info(&format!("WS IN: {}", text));
                         Self::handle_signal(&service, text);
+                    } else if let Ok(ab) = e.data().dyn_into::<js_sys::ArrayBuffer>() {
+                        let frame = js_sys::Uint8Array::new(&ab).to_vec();
+                        // Binary frames name the room using the WebSocket transport
+                        let Some((room, bytes)) = decode_ws_frame(&frame) else {
+                            Logger::warn("Malformed WebSocket data frame");
+                            return;
+                        };
+                        let on_websocket = service
+                            .borrow()
+                            .rooms
+                            .get(room)
+                            .is_some_and(|connection| connection.ws_fallback);
+                        if on_websocket && let Ok(packet) = from_bytes::<Packet<E>>(bytes) {
+                            Self::dispatch_event(&service, room, packet);
+                        }
                     }
                 },
             ))
@@ -50,7 +100,18 @@ where
         ws.set_onmessage(Some(onmessage_callback.as_ref().unchecked_ref()));
         onmessage_callback.forget();
 
-        self.service.borrow_mut().ws = Some(ws);
+        service.borrow_mut().ws = Some(ws);
         Ok(())
     }
 }
+
+impl EngineService {
+    /// Writes a signaling message to the WebSocket.
+    pub(super) fn send_signal(&self, msg: &SignalMessage) -> Result<(), AntennaError> {
+        let json = serde_json::to_string(msg).map_err(AntennaError::serialization)?;
+        let ws = self.ws.as_ref().ok_or_else(|| AntennaError::Signaling {
+            message: "WebSocket is closed".to_string(),
+        })?;
+        ws.send_with_str(&json).map_err(AntennaError::signaling)
+    }
+}
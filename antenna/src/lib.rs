@@ -4,21 +4,40 @@ pub mod utils {
 
 #[cfg(feature = "server")]
 pub mod server {
-    /// Marks a struct as an Antenna room.
+    /// Marks a struct as an Antenna room and describes how the server creates it.
     ///
-    /// This attribute is currently a marker and does not modify the struct definition.
-    /// It is used in conjunction with `#[antenna_logic]` to define the behavior of the room.
+    /// The attribute implements `RoomDefinition`, which `AntennaServer::build` reads to create
+    /// rooms and configure them. It is used in conjunction with `#[antenna_logic]` to define the
+    /// behavior of the room.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_peers = N` - Upper bound of peers in one room, see `RoomConfig::max_peers`.
+    /// * `wait_list = N` - How many peers may wait for a place in a full room.
+    /// * `tick_rate = N` - Calls `on_tick` N times per second.
+    /// * `presence = true` - Announces joining and leaving peers to the room, see
+    ///   `RoomConfig::with_presence`.
+    /// * `client = T`, `server = T` - The message types; together they set the schema checked
+    ///   during the handshake, as `AntennaServer::with_schema` does.
+    /// * `factory = path` - Function creating the room instead of its `Default` implementation,
+    ///   which the struct needs otherwise.
     ///
     /// # Example
     ///
     /// ```rust,ignore
     /// use antenna::server::antenna_room;
     ///
-    /// #[antenna_room]
+    /// #[antenna_room(max_peers = 8, tick_rate = 30, client = ChatClientMsg, server = ChatServerMsg)]
+    /// #[derive(Default)]
     /// struct ChatRoom {
     ///     // Room state fields
     /// }
     /// ```
+    ///
+    /// The macro generates:
+    /// * Implementation of `RoomDefinition` trait.
+    /// * `Self::send_server_message(ctx, peer_id, msg)` and `Self::broadcast_server_message(ctx, msg)`
+    ///   taking the `server` message type, if it is given.
     pub use antenna_codegen::antenna_room;
 
     /// Implements the business logic for an Antenna room.
@@ -30,7 +49,16 @@ pub mod server {
     /// # Arguments
     ///
     /// * `handle_user_message(MessageType)` - Attribute placed on methods to specify which user message type they handle.
+    /// * `handle_variant(MessageType::Variant)` - Attribute placed on methods handling a single variant of a message enum.
+    ///   Variants without such a handler go to the `handle_user_message` handler.
     /// * `handle_system_message` - Attribute placed on methods to specify they handle system messages.
+    /// * `fallback` - Attribute placed on a method `(&self, ctx, peer_id, data: Bytes)` receiving input that does
+    ///   not decode as the message type or has no handler. Without it such input is logged and dropped.
+    ///
+    /// Every message is decoded once as a `Packet` of a single message type, so all handlers must refer to the
+    /// same type; the macro rejects handlers for different types and duplicate handlers at compile time.
+    /// Handler and hook signatures are checked as well (`async`, `&self`, argument types), as are methods
+    /// with the `on_join` / `on_leave` signature whose names look like a misspelling of either.
     ///
     /// # Example
     ///
@@ -41,9 +69,14 @@ pub mod server {
     ///
     /// #[antenna_logic]
     /// impl ChatRoom {
+    ///     #[handle_variant(ChatClientMsg::Typing)]
+    ///     async fn handle_typing(&self, ctx: &RoomContext, peer_id: PeerId, msg: ChatClientMsg) {
+    ///         // Handle a single variant
+    ///     }
+    ///
     ///     #[handle_user_message(ChatClientMsg)]
     ///     async fn handle_message(&self, ctx: &RoomContext, peer_id: PeerId, msg: ChatClientMsg) {
-    ///         // Handle the user message
+    ///         // Handle the remaining variants
     ///     }
     ///
     ///     #[handle_system_message]
@@ -64,7 +97,12 @@ pub mod server {
     /// The macro generates:
     /// * Implementation of `RoomBehavior` trait.
     /// * `on_message` method that deserializes incoming packets and dispatches them to the appropriate handler.
-    /// * `on_join` and `on_leave` methods if they are defined in the `impl` block.
+    /// * `on_join`, `on_leave`, `on_tick` and `on_close` methods if they are defined in the `impl` block.
+    /// * `on_join_request` and `on_stream_open` methods if they are defined in the `impl` block,
+    ///   see `Admission`.
+    /// * `snapshot` and `restore` methods if they are defined in the `impl` block, see `RoomStore`.
+    ///
+    /// Hooks are checked against their `RoomBehavior` signature, return type included.
     pub use antenna_codegen::antenna_logic;
 
     /// The main entry point for the Antenna server.
@@ -92,9 +130,47 @@ pub mod server {
     ///     axum::serve(listener, app).await.unwrap();
     /// }
     /// ```
+    pub use antenna_server::Admission;
     pub use antenna_server::AntennaServer;
+    pub use antenna_server::MatchRules;
+    pub use antenna_server::Matchmaker;
+    pub use antenna_server::Position;
+    /// WebTransport (QUIC) endpoint, an alternative transport to WebRTC data channels. Requires the
+    /// `quic` feature.
+    ///
+    /// Peers connected through it are attached to the same rooms as WebRTC peers, so
+    /// `RoomBehavior` implementations do not depend on how a peer connected.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let app_state = AntennaServer::new().build::<MyRoomBehavior>();
+    ///
+    /// let quic = QuicServer::bind_self_signed(
+    ///     "0.0.0.0:4433".parse().unwrap(),
+    ///     app_state.room_manager.clone(),
+    /// )?;
+    /// tokio::spawn(quic.run());
+    /// ```
+    #[cfg(feature = "quic")]
+    pub use antenna_server::QuicServer;
     pub use antenna_server::RoomBehavior;
+    pub use antenna_server::RoomConfig;
     pub use antenna_server::RoomContext;
+    pub use antenna_server::RoomDefinition;
+    pub use antenna_server::SendError;
+    pub use antenna_server::SharedState;
+    #[doc(hidden)]
+    pub use antenna_server::log_unhandled_message;
+    /// Several server processes sharing rooms, see `AntennaServer::with_directory`.
+    pub use antenna_server::{
+        DirectoryServer, MemoryRoomDirectory, NodeInfo, RoomDirectory, TcpRoomDirectory,
+    };
+    /// Persistence of room snapshots across restarts, see `AntennaServer::with_room_store`.
+    pub use antenna_server::{FileRoomStore, MemoryRoomStore, RoomStore};
+    /// `RoomBehavior::restore` returns an `anyhow::Result`.
+    #[doc(hidden)]
+    pub use anyhow;
     pub mod signaling {
         /// WebSocket handler for Axum.
         ///
@@ -127,6 +203,7 @@ pub mod client {
     ///
     /// * `ClientMsg` - The type of messages sent by the client.
     /// * `ServerMsg` - The type of messages received from the server.
+    /// * `State` (optional) - The type of the room's shared state, see `SharedState` on the server.
     ///
     /// # Example
     ///
@@ -143,9 +220,21 @@ pub mod client {
     /// The macro generates:
     /// * `on_event` method to register a callback for server messages.
     /// * `on_track` method to register a callback for new media tracks.
+    /// * `on_stream` method to register a callback for streams relayed to this peer.
+    /// * `on_error` method to register a callback for engine and server errors (`AntennaError`).
+    /// * `on_ack` method to register a callback for the acks of sequenced inputs (`send_sequenced`).
+    /// * `leave` method to leave all rooms and close the connection.
+    /// * `join_room`, `leave_room` and `rooms` methods to take part in several rooms over one connection.
+    /// * `join_room_with_metadata` and `wait_position` methods for rooms with admission control.
+    /// * `matchmake`, `cancel_matchmake` and `on_match` methods to be grouped into a room by a matchmaking queue.
     /// * `add_track` method to add a media track to the connection.
+    /// * `state_snapshot` and `on_state_patch` methods when a state type is given.
     /// * TypeScript definitions for the callback types.
     pub use antenna_codegen::antenna_client;
     pub use antenna_wasm_gen::AntennaEngine;
+    pub use antenna_wasm_gen::AntennaError;
     pub use antenna_wasm_gen::EngineConfig;
+    pub use antenna_wasm_gen::OutgoingStream;
+    pub use antenna_wasm_gen::SendBufferConfig;
+    pub use antenna_wasm_gen::TransportMode;
 }
@@ -0,0 +1,4 @@
+mod test_memory_join_admission;
+mod test_memory_logic_dispatch;
+mod test_memory_room_tick;
+mod test_memory_wait_list_candidates;
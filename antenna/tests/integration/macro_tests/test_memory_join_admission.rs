@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use antenna::server::{Admission, antenna_logic, antenna_room};
+use antenna_core::{JoinRejection, PeerId, SignalMessage};
+use antenna_server::{
+    MemoryTransportFactory, Room, RoomBehavior, RoomCommand, RoomContext, RoomDefinition,
+};
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+
+use crate::utils::{MockSignalingOutput, all_features};
+
+/// Holds one peer with one more waiting and turns away banned peers.
+#[antenna_room(max_peers = 1, wait_list = 1)]
+#[derive(Default)]
+struct LobbyRoom;
+
+#[antenna_logic]
+impl LobbyRoom {
+    async fn on_join_request(
+        &self,
+        _ctx: &RoomContext,
+        _peer_id: &PeerId,
+        metadata: &HashMap<String, String>,
+    ) -> Admission {
+        match metadata.get("name").map(String::as_str) {
+            Some("mallory") => Admission::deny("banned"),
+            _ => Admission::Accept,
+        }
+    }
+}
+
+async fn join(cmd_tx: &mpsc::Sender<RoomCommand>, peer_id: &PeerId, name: &str) {
+    cmd_tx
+        .send(RoomCommand::JoinRequest {
+            peer_id: peer_id.clone(),
+            offer: String::new(),
+            metadata: HashMap::from([("name".to_string(), name.to_string())]),
+            features: all_features(),
+        })
+        .await
+        .expect("Room closed");
+}
+
+/// Next signal that is not an `Answer`.
+async fn next_signal(signals: &mut mpsc::UnboundedReceiver<SignalMessage>) -> SignalMessage {
+    loop {
+        let signal = timeout(Duration::from_secs(5), signals.recv())
+            .await
+            .expect("No signal received")
+            .expect("Signaling closed");
+        if !matches!(signal, SignalMessage::Answer { .. }) {
+            return signal;
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_memory_join_admission() {
+    let transport = MemoryTransportFactory::new();
+    let (cmd_tx, cmd_rx) = mpsc::channel::<RoomCommand>(100);
+    let (signaling, mut signals) = MockSignalingOutput::new();
+    let room = Room::new(
+        Box::new(LobbyRoom::create()),
+        cmd_rx,
+        signaling.service.clone(),
+    )
+    .with_transport_factory(Arc::new(transport.clone()))
+    .with_config(LobbyRoom::config());
+    tokio::spawn(room.run());
+
+    let [alice, bob, carol, mallory] = [(); 4].map(|_| PeerId::new());
+    for peer_id in [&alice, &bob, &carol, &mallory] {
+        signaling.register_peer(peer_id.clone());
+    }
+
+    join(&cmd_tx, &alice, "alice").await;
+    let _alice_client = transport.accept().await.expect("Alice was not admitted");
+
+    // The hook runs before the capacity check.
+    join(&cmd_tx, &mallory, "mallory").await;
+    match next_signal(&mut signals).await {
+        SignalMessage::JoinRejected {
+            reason: JoinRejection::Denied { reason },
+            ..
+        } => assert_eq!(reason, "banned"),
+        other => panic!("expected a denial, got {:?}", other),
+    }
+
+    join(&cmd_tx, &bob, "bob").await;
+    assert!(matches!(
+        next_signal(&mut signals).await,
+        SignalMessage::WaitListed { position: 1, .. }
+    ));
+
+    join(&cmd_tx, &carol, "carol").await;
+    assert!(matches!(
+        next_signal(&mut signals).await,
+        SignalMessage::JoinRejected {
+            reason: JoinRejection::RoomFull { max_peers: 1 },
+            ..
+        }
+    ));
+
+    // Alice leaving frees the place for Bob.
+    cmd_tx
+        .send(RoomCommand::Leave {
+            peer_id: alice.clone(),
+        })
+        .await
+        .expect("Room closed");
+    timeout(Duration::from_secs(5), transport.accept())
+        .await
+        .expect("Bob was not admitted")
+        .expect("Memory transport closed");
+}
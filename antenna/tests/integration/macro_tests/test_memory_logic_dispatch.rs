@@ -0,0 +1,108 @@
+use std::sync::Arc;
+
+use antenna::server::{antenna_logic, antenna_room};
+use antenna_core::{Packet, PeerId, SystemMessage};
+use antenna_server::{
+    MemoryClient, MemoryTransportFactory, Room, RoomBehavior, RoomCommand, RoomContext,
+};
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use crate::utils::{MockSignalingOutput, join_memory_peer};
+
+#[derive(Debug, Serialize, Deserialize)]
+enum GameMsg {
+    Move { x: i32, y: i32 },
+    Chat(String),
+    Ready,
+}
+
+/// Answers with the name of the handler that got the message.
+#[antenna_room]
+#[derive(Default)]
+struct GameRoom;
+
+#[antenna_logic]
+impl GameRoom {
+    #[handle_variant(GameMsg::Move)]
+    async fn handle_move(&self, ctx: &RoomContext, peer_id: PeerId, msg: GameMsg) {
+        let GameMsg::Move { x, y } = msg else {
+            unreachable!()
+        };
+        reply(ctx, &peer_id, format!("move {} {}", x, y)).await;
+    }
+
+    #[handle_variant(GameMsg::Chat)]
+    async fn handle_chat(&self, ctx: &RoomContext, peer_id: PeerId, _msg: GameMsg) {
+        reply(ctx, &peer_id, "chat".to_string()).await;
+    }
+
+    #[handle_user_message(GameMsg)]
+    async fn handle_other(&self, ctx: &RoomContext, peer_id: PeerId, msg: GameMsg) {
+        reply(ctx, &peer_id, format!("other {:?}", msg)).await;
+    }
+
+    #[handle_system_message]
+    async fn handle_system(&self, ctx: &RoomContext, peer_id: PeerId, _msg: SystemMessage) {
+        reply(ctx, &peer_id, "system".to_string()).await;
+    }
+
+    #[fallback]
+    async fn handle_garbage(&self, ctx: &RoomContext, peer_id: PeerId, data: Bytes) {
+        reply(ctx, &peer_id, format!("fallback {}", data.len())).await;
+    }
+}
+
+async fn reply(ctx: &RoomContext, peer_id: &PeerId, text: String) {
+    let _ = ctx.send(peer_id, Bytes::from(text)).await;
+}
+
+fn encode<T: Serialize>(packet: &Packet<T>) -> Bytes {
+    Bytes::from(postcard::to_allocvec(packet).unwrap())
+}
+
+async fn roundtrip(client: &mut MemoryClient, data: Bytes) -> String {
+    client.send(data);
+    let reply = client.recv().await.expect("Room closed the link");
+    String::from_utf8(reply.to_vec()).unwrap()
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_memory_logic_dispatch() {
+    let transport = MemoryTransportFactory::new();
+    let (cmd_tx, cmd_rx) = mpsc::channel::<RoomCommand>(100);
+    let signaling = MockSignalingOutput::new_stored_only();
+    let room = Room::new(Box::new(GameRoom), cmd_rx, signaling.service.clone())
+        .with_transport_factory(Arc::new(transport.clone()));
+    tokio::spawn(room.run());
+
+    let mut client = join_memory_peer(&cmd_tx, &transport)
+        .await
+        .expect("Failed to join peer");
+
+    let moved = encode(&Packet::User(GameMsg::Move { x: 3, y: -4 }));
+    assert_eq!(roundtrip(&mut client, moved).await, "move 3 -4");
+
+    let chat = encode(&Packet::User(GameMsg::Chat("gg".to_string())));
+    assert_eq!(roundtrip(&mut client, chat).await, "chat");
+
+    // Variants without a handler of their own go to the whole-type handler.
+    let ready = encode(&Packet::User(GameMsg::Ready));
+    assert_eq!(roundtrip(&mut client, ready).await, "other Ready");
+
+    let ping = encode(&Packet::<GameMsg>::System(SystemMessage::Ping {
+        timestamp: 1,
+    }));
+    assert_eq!(roundtrip(&mut client, ping).await, "system");
+
+    // A different type whose bytes would partially decode as `GameMsg` is not misrouted.
+    let foreign = encode(&Packet::User((0u8, "hello".to_string(), 7u64)));
+    assert_eq!(
+        roundtrip(&mut client, foreign.clone()).await,
+        format!("fallback {}", foreign.len())
+    );
+
+    let garbage = Bytes::from_static(&[0xff, 0xff, 0xff]);
+    assert_eq!(roundtrip(&mut client, garbage).await, "fallback 3");
+}
@@ -0,0 +1,73 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use antenna::server::{antenna_logic, antenna_room};
+use antenna_core::{Channel, Message, Packet, PeerId};
+use antenna_server::{
+    MemoryTransportFactory, Room, RoomBehavior, RoomCommand, RoomContext, RoomDefinition,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use crate::utils::{MockSignalingOutput, join_memory_peer};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct TickMsg(u32);
+
+impl Message for TickMsg {
+    fn channel(&self) -> Channel {
+        Channel::Reliable
+    }
+}
+
+/// Broadcasts the number of the tick on every tick.
+#[antenna_room(max_peers = 4, tick_rate = 20, server = TickMsg)]
+#[derive(Default)]
+struct GameRoom {
+    ticks: AtomicU32,
+}
+
+#[antenna_logic]
+impl GameRoom {
+    async fn on_tick(&self, ctx: &RoomContext) {
+        let tick = self.ticks.fetch_add(1, Ordering::SeqCst) + 1;
+        Self::broadcast_server_message(ctx, TickMsg(tick))
+            .await
+            .expect("Failed to encode tick");
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_memory_room_tick() {
+    let config = GameRoom::config();
+    assert_eq!(config.max_peers, Some(4));
+    assert_eq!(config.tick_rate, Some(20));
+    assert_eq!(config.schema, None);
+
+    let transport = MemoryTransportFactory::new();
+    let (cmd_tx, cmd_rx) = mpsc::channel::<RoomCommand>(100);
+    let signaling = MockSignalingOutput::new_stored_only();
+    let room = Room::new(
+        Box::new(GameRoom::create()),
+        cmd_rx,
+        signaling.service.clone(),
+    )
+    .with_transport_factory(Arc::new(transport.clone()))
+    .with_config(config);
+    tokio::spawn(room.run());
+
+    let mut client = join_memory_peer(&cmd_tx, &transport)
+        .await
+        .expect("Failed to join peer");
+
+    // Ticks before the peer joined went to nobody, so only check that they keep counting up.
+    let mut last = 0;
+    for _ in 0..3 {
+        let data = client.recv().await.expect("Room closed the link");
+        let Packet::User(TickMsg(tick)) = postcard::from_bytes(&data).unwrap() else {
+            panic!("expected a user packet");
+        };
+        assert!(tick > last, "tick {} after {}", tick, last);
+        last = tick;
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use antenna::server::{antenna_logic, antenna_room};
+use antenna_core::{JoinRejection, PeerId, SignalMessage};
+use antenna_server::{Room, RoomBehavior, RoomCommand, RoomContext, RoomDefinition};
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+
+use crate::utils::{MockSignalingOutput, RecordingTransportFactory, all_features};
+
+#[antenna_room(max_peers = 1, wait_list = 1)]
+#[derive(Default)]
+struct SingleSeatRoom;
+
+#[antenna_logic]
+impl SingleSeatRoom {}
+
+async fn join(
+    cmd_tx: &mpsc::Sender<RoomCommand>,
+    peer_id: &PeerId,
+    metadata: HashMap<String, String>,
+) {
+    cmd_tx
+        .send(RoomCommand::JoinRequest {
+            peer_id: peer_id.clone(),
+            offer: String::new(),
+            metadata,
+            features: all_features(),
+        })
+        .await
+        .expect("Room closed");
+}
+
+/// Next signal that is not an `Answer`.
+async fn next_signal(signals: &mut mpsc::UnboundedReceiver<SignalMessage>) -> SignalMessage {
+    loop {
+        let signal = timeout(Duration::from_secs(5), signals.recv())
+            .await
+            .expect("No signal received")
+            .expect("Signaling closed");
+        if !matches!(signal, SignalMessage::Answer { .. }) {
+            return signal;
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_memory_wait_list_candidates() {
+    let transport = RecordingTransportFactory::default();
+    let (cmd_tx, cmd_rx) = mpsc::channel::<RoomCommand>(100);
+    let (signaling, mut signals) = MockSignalingOutput::new();
+    let room = Room::new(
+        Box::new(SingleSeatRoom::create()),
+        cmd_rx,
+        signaling.service.clone(),
+    )
+    .with_transport_factory(Arc::new(transport.clone()))
+    .with_config(SingleSeatRoom::config());
+    tokio::spawn(room.run());
+
+    let [alice, bob, mallory] = [(); 3].map(|_| PeerId::new());
+    for peer_id in [&alice, &bob, &mallory] {
+        signaling.register_peer(peer_id.clone());
+    }
+
+    join(&cmd_tx, &alice, HashMap::new()).await;
+    let _alice_client = transport
+        .memory
+        .accept()
+        .await
+        .expect("Alice was not admitted");
+
+    // Oversized metadata is refused before it can sit on the wait-list.
+    let metadata = HashMap::from([("name".to_string(), "m".repeat(8192))]);
+    join(&cmd_tx, &mallory, metadata).await;
+    assert!(matches!(
+        next_signal(&mut signals).await,
+        SignalMessage::JoinRejected {
+            reason: JoinRejection::Denied { .. },
+            ..
+        }
+    ));
+
+    join(&cmd_tx, &bob, HashMap::new()).await;
+    assert!(matches!(
+        next_signal(&mut signals).await,
+        SignalMessage::WaitListed { position: 1, .. }
+    ));
+
+    // Trickled right after the offer, while Bob has no transport yet.
+    let candidates = vec!["candidate:1".to_string(), "candidate:2".to_string()];
+    for candidate in &candidates {
+        cmd_tx
+            .send(RoomCommand::IceCandidate {
+                peer_id: bob.clone(),
+                candidate: candidate.clone(),
+            })
+            .await
+            .expect("Room closed");
+    }
+
+    cmd_tx
+        .send(RoomCommand::Leave {
+            peer_id: alice.clone(),
+        })
+        .await
+        .expect("Room closed");
+    timeout(Duration::from_secs(5), transport.memory.accept())
+        .await
+        .expect("Bob was not admitted")
+        .expect("Memory transport closed");
+
+    assert_eq!(transport.candidates(&bob), candidates);
+}
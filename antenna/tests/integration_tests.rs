@@ -0,0 +1,2 @@
+mod integration;
+mod utils;
@@ -0,0 +1,45 @@
+use antenna_core::{PeerId, SignalMessage};
+use antenna_server::SignalingService;
+use axum::extract::ws::Message;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// Mock SignalingOutput that captures all outgoing signals.
+#[derive(Clone)]
+pub struct MockSignalingOutput {
+    /// Channel to send captured signals.
+    tx: mpsc::UnboundedSender<SignalMessage>,
+    /// The actual SignalingService instance
+    pub service: Arc<SignalingService>,
+}
+
+impl MockSignalingOutput {
+    /// Create a new MockSignalingOutput and its receiver channel.
+    pub fn new() -> (Self, mpsc::UnboundedReceiver<SignalMessage>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let service = Arc::new(SignalingService::new(vec![]));
+        (Self { tx, service }, rx)
+    }
+
+    /// Create a MockSignalingOutput whose signals are dropped.
+    pub fn new_stored_only() -> Self {
+        Self::new().0
+    }
+
+    /// Register a peer to capture its messages
+    pub fn register_peer(&self, peer_id: PeerId) {
+        let (ws_tx, mut ws_rx) = mpsc::unbounded_channel();
+        self.service.add_peer(peer_id, ws_tx);
+
+        let tx = self.tx.clone();
+        tokio::spawn(async move {
+            while let Some(msg) = ws_rx.recv().await {
+                if let Message::Text(text) = msg
+                    && let Ok(signal) = serde_json::from_str::<SignalMessage>(&text)
+                {
+                    let _ = tx.send(signal);
+                }
+            }
+        });
+    }
+}
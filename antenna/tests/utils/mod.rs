@@ -0,0 +1,7 @@
+pub mod mock_signaling;
+pub mod recording_transport;
+pub mod signal_helpers;
+
+pub use mock_signaling::*;
+pub use recording_transport::*;
+pub use signal_helpers::*;
@@ -0,0 +1,82 @@
+use antenna_core::PeerId;
+use antenna_server::{
+    MemoryTransportFactory, Transport, TransportConfig, TransportEvent, TransportFactory,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use webrtc::track::track_local::track_local_static_rtp::TrackLocalStaticRTP;
+
+/// Memory transports that remember the ICE candidates the room adds to them.
+#[derive(Clone, Default)]
+pub struct RecordingTransportFactory {
+    pub memory: MemoryTransportFactory,
+    candidates: Arc<Mutex<Vec<(PeerId, String)>>>,
+}
+
+impl RecordingTransportFactory {
+    /// Candidates added to the transport of `peer_id`, in order.
+    pub fn candidates(&self, peer_id: &PeerId) -> Vec<String> {
+        self.candidates
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(id, _)| id == peer_id)
+            .map(|(_, candidate)| candidate.clone())
+            .collect()
+    }
+}
+
+#[async_trait]
+impl TransportFactory for RecordingTransportFactory {
+    async fn create(
+        &self,
+        peer_id: PeerId,
+        config: TransportConfig,
+        event_tx: mpsc::Sender<TransportEvent>,
+    ) -> Result<Box<dyn Transport>> {
+        let inner = self
+            .memory
+            .create(peer_id.clone(), config, event_tx)
+            .await?;
+        Ok(Box::new(RecordingTransport {
+            peer_id,
+            inner,
+            candidates: self.candidates.clone(),
+        }))
+    }
+}
+
+struct RecordingTransport {
+    peer_id: PeerId,
+    inner: Box<dyn Transport>,
+    candidates: Arc<Mutex<Vec<(PeerId, String)>>>,
+}
+
+#[async_trait]
+impl Transport for RecordingTransport {
+    async fn set_remote_description(&self, sdp: String) -> Result<()> {
+        self.inner.set_remote_description(sdp).await
+    }
+
+    async fn create_answer(&self) -> Result<String> {
+        self.inner.create_answer().await
+    }
+
+    async fn add_ice_candidate(&self, candidate: String) -> Result<()> {
+        self.candidates
+            .lock()
+            .unwrap()
+            .push((self.peer_id.clone(), candidate.clone()));
+        self.inner.add_ice_candidate(candidate).await
+    }
+
+    async fn add_track(&self, track: Arc<TrackLocalStaticRTP>) -> Result<()> {
+        self.inner.add_track(track).await
+    }
+
+    async fn close(&self) -> Result<()> {
+        self.inner.close().await
+    }
+}
@@ -0,0 +1,30 @@
+use antenna_core::{PeerId, SUPPORTED_FEATURES};
+use antenna_server::{MemoryClient, MemoryTransportFactory, RoomCommand};
+use anyhow::{Context, Result};
+use tokio::sync::mpsc;
+
+/// Every optional protocol feature, as negotiated by a current client.
+pub fn all_features() -> Vec<String> {
+    SUPPORTED_FEATURES.iter().map(|f| f.to_string()).collect()
+}
+
+/// Join a new peer to a room running on the in-memory transport and return its client side.
+pub async fn join_memory_peer(
+    room_cmd_tx: &mpsc::Sender<RoomCommand>,
+    transport: &MemoryTransportFactory,
+) -> Result<MemoryClient> {
+    room_cmd_tx
+        .send(RoomCommand::JoinRequest {
+            peer_id: PeerId::new(),
+            offer: String::new(),
+            metadata: Default::default(),
+            features: all_features(),
+        })
+        .await
+        .context("Failed to send JoinRequest")?;
+
+    transport
+        .accept()
+        .await
+        .context("Memory transport closed before peer was accepted")
+}
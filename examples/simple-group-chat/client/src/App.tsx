@@ -1,9 +1,6 @@
 import React, {useEffect, useRef, useState} from 'react';
 
-import init, {ChatWrapper} from './generated/wasm';
-
-import type {ChatClientMsg} from "./generated/types/ChatClientMsg";
-import type {ChatServerMsg} from "./generated/types/ChatServerMsg";
+import {ChatClient, type ChatClientMsg, type ChatServerMsg} from './generated';
 
 const protocol = window.location.protocol === 'https:' ? 'wss:' : 'ws:';
 const SERVER_URL = import.meta.env.VITE_SERVER_URL || `${protocol}//${window.location.host}/ws`;
@@ -18,7 +15,7 @@ function App() {
     const [messages, setMessages] = useState<ChatServerMsg[]>([]);
     const [isInCall, setIsInCall] = useState(false);
 
-    const chatRef = useRef<ChatWrapper | null>(null);
+    const chatRef = useRef<ChatClient | null>(null);
     const runOnce = useRef(false);
     const audioElements = useRef<Map<string, HTMLAudioElement>>(new Map());
 
@@ -34,49 +31,41 @@ function App() {
         if (!roomId || runOnce.current) return;
         runOnce.current = true;
 
-        const startWasm = async () => {
-            try {
-                await init();
-                const userId = self.crypto.randomUUID();
-
-                const url = `${SERVER_URL}/${userId}`;
-
-                console.log(`Connecting to ${url} in room ${roomId}...`);
-
-                const client = new ChatWrapper(url, roomId);
-
-                client.on_event(event => {
-                    console.log("Received:", event);
-                    setMessages((prev) => [...prev, event]);
-                });
-
-                client.on_track((event: RTCTrackEvent) => {
-                    console.log("Received track:", event);
-                    const stream = event.streams[0];
-                    if (stream) {
-                        const audio = new Audio();
-                        audio.srcObject = stream;
-                        audio.autoplay = true;
-                        audio.controls = false; 
-                        audioElements.current.set(stream.id, audio);
-                        event.track.onended = () => {
-                            audioElements.current.delete(stream.id);
-                        };
-                    }
-                });
-
-                chatRef.current = client;
-                setIsReady(true);
-            } catch (err: any) {
-                console.error("WASM Error:", err);
-                setError(err.toString());
+        const userId = self.crypto.randomUUID();
+        const url = `${SERVER_URL}/${userId}`;
+        const client = new ChatClient(url, roomId);
+
+        client.on('event', event => {
+            console.log("Received:", event);
+            setMessages((prev) => [...prev, event]);
+        });
+
+        client.on('track', event => {
+            console.log("Received track:", event);
+            const stream = event.streams[0];
+            if (stream) {
+                const audio = new Audio();
+                audio.srcObject = stream;
+                audio.autoplay = true;
+                audio.controls = false; 
+                audioElements.current.set(stream.id, audio);
+                event.track.onended = () => {
+                    audioElements.current.delete(stream.id);
+                };
             }
-        };
+        });
 
-        startWasm();
+        console.log(`Connecting to ${url} in room ${roomId}...`);
+        chatRef.current = client;
+        client.connect()
+            .then(() => setIsReady(true))
+            .catch((err) => {
+                console.error("Connection error:", err);
+                setError(err.description ?? String(err));
+            });
 
         return () => {
-            chatRef.current?.free();
+            chatRef.current?.close();
         };
     }, [roomId]);
 
@@ -126,7 +115,7 @@ function App() {
         };
 
         try {
-            chatRef.current.send_message(payload.text);
+            chatRef.current.send(payload);
             setInputText("");
         } catch (e) {
             console.error("Send error:", e);
@@ -145,7 +134,7 @@ function App() {
             const track = stream.getAudioTracks()[0];
             
             if (track) {
-                chatRef.current.add_track(track, stream);
+                chatRef.current.addTrack(track, stream);
                 setIsInCall(true);
                 console.log("Added local audio track");
             }
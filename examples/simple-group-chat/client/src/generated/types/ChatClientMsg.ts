@@ -1,3 +1,4 @@
-// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
-
-export type ChatClientMsg = { text: string, };
+// Generated by `cargo antenna build`, do not edit.
+export type ChatClientMsg = {
+    text: string;
+};
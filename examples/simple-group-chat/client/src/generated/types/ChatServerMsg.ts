@@ -1,3 +1,6 @@
-// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
-
-export type ChatServerMsg = { author_id: string, text: string, timestamp: bigint, };
+// Generated by `cargo antenna build`, do not edit.
+export type ChatServerMsg = {
+    author_id: string;
+    text: string;
+    timestamp: number;
+};
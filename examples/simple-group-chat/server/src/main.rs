@@ -1,16 +1,15 @@
 use axum::{Router, routing::get};
 use bytes::Bytes;
-use postcard::to_allocvec;
 use std::net::SocketAddr;
 use tower_http::cors::{Any, CorsLayer};
-use tracing::{Level, info};
+use tracing::{Level, info, warn};
 
 use antenna::server::{AntennaServer, RoomBehavior, RoomContext, signaling::ws_axum_handler, antenna_room, antenna_logic};
-use antenna::utils::{Packet, PeerId};
+use antenna::utils::PeerId;
 use shared::{ChatClientMsg, ChatServerMsg};
 use std::env;
 
-#[antenna_room]
+#[antenna_room(client = ChatClientMsg, server = ChatServerMsg)]
 #[derive(Default)]
 struct ChatRoom;
 
@@ -20,7 +19,9 @@ impl ChatRoom {
         info!(">>> User joined the chat: {:?}", user_id);
 
         let welcome = format!("Welcome to Antenna Chat, {:?}!", user_id);
-        ctx.send(&user_id, Bytes::from(welcome)).await;
+        if let Err(e) = ctx.send(&user_id, Bytes::from(welcome)).await {
+            warn!("Failed to greet {:?}: {}", user_id, e);
+        }
 
         let announcement = format!("System: User {:?} has joined.", user_id);
         ctx.broadcast(Bytes::from(announcement)).await;
@@ -36,10 +37,8 @@ impl ChatRoom {
             timestamp: 123456789,
         };
 
-        let response_packet = Packet::User(response);
-
-        if let Ok(response_bytes) = to_allocvec(&response_packet) {
-            ctx.broadcast(Bytes::from(response_bytes)).await;
+        if let Err(e) = Self::broadcast_server_message(ctx, response).await {
+            warn!("Failed to relay message from {:?}: {}", user_id, e);
         }
     }
 
@@ -70,11 +69,17 @@ async fn main() {
     let app = Router::new()
         .route("/ws/{user_id}", get(ws_axum_handler))
         .layer(cors)
-        .with_state(state);
+        .with_state(state.clone());
 
     let addr = SocketAddr::from(([0, 0, 0, 0], 3000));
     info!("Server listening on http://{}", addr);
 
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move {
+            let _ = tokio::signal::ctrl_c().await;
+            state.shutdown().await;
+        })
+        .await
+        .unwrap();
 }
@@ -1,9 +1,7 @@
-use antenna::utils::{Channel, Message};
+use antenna::utils::{Channel, Message, export_types};
 use serde::{Deserialize, Serialize};
-use ts_rs::TS;
 
-#[derive(Serialize, Deserialize, Debug, Clone, TS)]
-#[ts(export)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ChatClientMsg {
     pub text: String,
 }
@@ -14,8 +12,7 @@ impl Message for ChatClientMsg {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, TS)]
-#[ts(export)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ChatServerMsg {
     pub author_id: String,
     pub text: String,
@@ -27,3 +24,6 @@ impl Message for ChatServerMsg {
         Channel::Reliable
     }
 }
+
+// Exported to TypeScript by `cargo antenna build`, with the types they contain
+export_types!(ChatClientMsg, ChatServerMsg);
@@ -1,4 +1,4 @@
-use antenna::client::{antenna_client, AntennaEngine, EngineConfig};
+use antenna::client::{antenna_client, AntennaEngine, EngineConfig, SendBufferConfig, TransportMode};
 use shared::{ChatClientMsg, ChatServerMsg};
 use wasm_bindgen::prelude::*;
 use web_sys::js_sys;
@@ -17,13 +17,16 @@ impl ChatWrapper {
             url,
             room_id,
             ice_servers: None,
+            transport: TransportMode::Auto,
+            send_buffer: SendBufferConfig::default(),
         };
         let engine = AntennaEngine::new(config)?;
         Ok(ChatWrapper { engine })
     }
 
-    pub fn send_message(&self, text: String) {
+    pub fn send_message(&self, text: String) -> Result<(), JsValue> {
         let msg = ChatClientMsg { text };
-        self.engine.send(msg);
+        self.engine.send(msg)?;
+        Ok(())
     }
 }